@@ -1,21 +1,37 @@
-use reqwest;
+use reqwest::Client;
 use serde_json::Value;
 use serde::Deserialize;
-use crate::akka::model::ActorTreeNode;
+use crate::akka::model::{ActorTreeNode, AkkaHealth, HealthProbe, ShardingSettings, ShardingStats, ShardPlacement};
+use crate::config::Proxy;
+use crate::http;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-pub fn get_actors(url: &String, timeout: u64) -> Result<Vec<ActorTreeNode>, String> {
-    get_actors_async(url, timeout)
+pub fn get_actors(url: &String, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<ActorTreeNode>, String> {
+    get_actors_async(url, timeout, proxy)
 }
 
-pub fn get_actor_count(url: &String, timeout: u64) -> Result<u64, String> {
-    get_actor_count_async(url, timeout)
+pub fn get_actor_count(url: &String, timeout: u64, proxy: Option<&Proxy>) -> Result<u64, String> {
+    get_actor_count_async(url, timeout, proxy)
+}
+
+pub fn get_health(management_address: &str, timeout: u64, proxy: Option<&Proxy>) -> AkkaHealth {
+    get_health_async(management_address, timeout, proxy)
+}
+
+pub fn get_sharding(settings: &ShardingSettings, timeout: u64, proxy: Option<&Proxy>) -> Result<ShardingStats, String> {
+    get_sharding_async(settings, timeout, proxy)
+}
+
+/// Whether Akka Management serves the health check routes, which may fail without being missing
+pub fn has_health_checks(management_address: &str, timeout: u64, proxy: Option<&Proxy>) -> Result<(), String> {
+    has_health_checks_async(management_address, timeout, proxy)
 }
 
 #[tokio::main]
-async fn get_actors_async(url: &String, timeout: u64) -> Result<Vec<ActorTreeNode>, String> {
+async fn get_actors_async(url: &String, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<ActorTreeNode>, String> {
     let url = format!("{}?timeout={}", url, timeout);
-    let response = reqwest::get(&url).await.map_err(|e| e.to_string())?;
+    let response = http::client(proxy)?.get(&url).send().await.map_err(|e| e.to_string())?;
     if !response.status().is_success() {
         return Err(format!("Request to get actor tree failed with status: {}", response.status()));
     }
@@ -58,12 +74,90 @@ struct CountResult {
 }
 
 #[tokio::main]
-async fn get_actor_count_async(url: &String, timeout: u64) -> Result<u64, String> {
+async fn get_actor_count_async(url: &String, timeout: u64, proxy: Option<&Proxy>) -> Result<u64, String> {
     let url = format!("{}?timeout={}", url, timeout);
-    let response = reqwest::get(&url).await.map_err(|e| e.to_string())?;
+    let response = http::client(proxy)?.get(&url).send().await.map_err(|e| e.to_string())?;
     if !response.status().is_success() {
         return Err(format!("Request to get actor count failed with status {}", response.status()));
     }
     let body: CountResult = response.json().await.map_err(|e| e.to_string())?;
     Ok(body.result)
 }
+
+#[tokio::main]
+async fn get_health_async(management_address: &str, timeout: u64, proxy: Option<&Proxy>) -> AkkaHealth {
+    let address = management_address.trim_end_matches('/');
+    let client = match http::client(proxy) {
+        Ok(c) => c,
+        Err(_) => {
+            let failed = HealthProbe { ok: false, latency_ms: 0 };
+            return AkkaHealth { alive: failed.clone(), ready: failed };
+        }
+    };
+    AkkaHealth {
+        alive: probe(&client, &format!("{}/health/alive", address), timeout).await,
+        ready: probe(&client, &format!("{}/health/ready", address), timeout).await,
+    }
+}
+
+#[tokio::main]
+async fn has_health_checks_async(management_address: &str, timeout: u64, proxy: Option<&Proxy>) -> Result<(), String> {
+    let url = format!("{}/health/alive", management_address.trim_end_matches('/'));
+    let response = http::client(proxy)?.get(&url)
+        .timeout(Duration::from_millis(timeout))
+        .send().await
+        .map_err(|e| format!("{}: {}", url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("{} not found, are the health check routes enabled?", url));
+    }
+    Ok(())
+}
+
+/// Any non-2xx status or a failed request means the check didn't pass
+async fn probe(client: &Client, url: &str, timeout: u64) -> HealthProbe {
+    let start = Instant::now();
+    let ok = match client.get(url).timeout(Duration::from_millis(timeout)).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    };
+    HealthProbe { ok, latency_ms: start.elapsed().as_millis() as u64 }
+}
+
+/// Shards of an entity type hosted by a node, as returned by `/cluster/shards/<entity type>`
+#[derive(Deserialize)]
+pub(crate) struct ShardDetails {
+    pub regions: Vec<ShardRegionInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShardRegionInfo {
+    pub shard_id: String,
+    pub num_entities: u64,
+}
+
+#[tokio::main]
+async fn get_sharding_async(settings: &ShardingSettings, timeout: u64, proxy: Option<&Proxy>) -> Result<ShardingStats, String> {
+    let client = http::client(proxy)?;
+    let mut shards = vec![];
+    for node in settings.nodes.iter() {
+        for entity_type in settings.entity_types.iter() {
+            let url = format!("{}/cluster/shards/{}", node.trim_end_matches('/'), entity_type);
+            let response = client.get(&url)
+                .timeout(Duration::from_millis(timeout))
+                .send().await
+                .map_err(|e| format!("{}: {}", node, e))?;
+            if !response.status().is_success() {
+                return Err(format!("Request to {} failed with status {}", url, response.status()));
+            }
+            let details: ShardDetails = response.json().await.map_err(|e| format!("{}: {}", url, e))?;
+            shards.extend(details.regions.into_iter().map(|r| ShardPlacement {
+                entity_type: entity_type.to_owned(),
+                shard_id: r.shard_id,
+                node: node.to_owned(),
+                entities: r.num_entities,
+            }));
+        }
+    }
+    Ok(ShardingStats { shards })
+}
@@ -1,14 +1,294 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::Downsample;
+
 #[derive(Clone)]
 pub struct AkkaSettings {
     pub tree_address: String,
     pub count_address: String,
     pub tree_timeout: u64,
     pub count_timeout: u64,
+    pub management_address: Option<String>,
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
 pub struct ActorTreeNode {
     pub name: String,
     pub parent: Option<usize>,
     pub id: usize,
 }
+
+/// An actor created or terminated between two actor tree refreshes
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ActorEvent {
+    /// e.g. `user/orders/order-42`
+    pub path: String,
+    pub created: bool,
+}
+
+impl ActorEvent {
+    ///
+    /// Actors that appear in only one of the trees, by their path.
+    /// Node ids aren't stable between refreshes, so they can't be compared.
+    pub fn between(prev: &[ActorTreeNode], current: &[ActorTreeNode]) -> Vec<ActorEvent> {
+        let prev_paths = actor_paths(prev);
+        let current_paths = actor_paths(current);
+        let before: HashSet<&String> = prev_paths.iter().collect();
+        let after: HashSet<&String> = current_paths.iter().collect();
+        let created = current_paths.iter()
+            .filter(|p| !before.contains(p))
+            .map(|p| ActorEvent { path: p.to_owned(), created: true });
+        let terminated = prev_paths.iter()
+            .filter(|p| !after.contains(p))
+            .map(|p| ActorEvent { path: p.to_owned(), created: false });
+        created.chain(terminated).collect()
+    }
+}
+
+/// An actor of a saved actor tree, see `ActorTreeSnapshot`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ActorSnapshotEntry {
+    /// e.g. `user/orders/order-42`
+    pub path: String,
+    pub parent: Option<String>,
+    pub children: usize,
+    /// Actors below this one, its children included
+    pub descendants: usize,
+}
+
+///
+/// The whole actor tree, saved as a JSON file to audit changes of the actor system's topology between releases.
+/// Actors are sorted by path, as node ids aren't stable, so that snapshots can be diffed.
+#[derive(Debug, Serialize)]
+pub struct ActorTreeSnapshot {
+    /// Seconds since the Unix epoch
+    pub exported_at: u64,
+    pub actor_count: usize,
+    pub actors: Vec<ActorSnapshotEntry>,
+}
+
+impl ActorTreeSnapshot {
+    pub fn of(actors: &[ActorTreeNode], exported_at: u64) -> ActorTreeSnapshot {
+        let paths = actor_paths(actors);
+        let path_of: HashMap<usize, &String> = actors.iter().map(|a| a.id).zip(paths.iter()).collect();
+        let mut children: HashMap<usize, usize> = HashMap::new();
+        for p in actors.iter().filter_map(|a| a.parent) {
+            *children.entry(p).or_insert(0) += 1;
+        }
+        // the paths below an actor's are next to each other once sorted
+        let mut sorted: Vec<&String> = paths.iter().collect();
+        sorted.sort();
+        let mut entries: Vec<ActorSnapshotEntry> = actors.iter().zip(paths.iter())
+            .map(|(a, path)| {
+                let below = format!("{}/", path);
+                let from = sorted.binary_search(&&below).unwrap_or_else(|i| i);
+                ActorSnapshotEntry {
+                    path: path.to_owned(),
+                    parent: a.parent.and_then(|p| path_of.get(&p)).map(|p| p.to_string()),
+                    children: children.get(&a.id).copied().unwrap_or(0),
+                    descendants: sorted[from..].iter().take_while(|p| p.starts_with(&below)).count(),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        ActorTreeSnapshot { exported_at, actor_count: entries.len(), actors: entries }
+    }
+
+    pub fn to_json(&self) -> String {
+        // serializing plain strings and numbers can't fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+///
+/// Subtree of the actor tree whose actor count is charted on the Akka tab and available to hooks
+/// and thresholds as `actors_<name>`, set with `actor_watches` in the config file.
+///
+/// Path segments of the pattern may contain `*`, matching any part of a name, and a `**` segment
+/// matches any number of segments, e.g. `/user/payment/**` counts `payment` and all its descendants.
+///
+/// eg. `{ "name": "payments", "pattern": "/user/payment/**" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ActorWatch {
+    pub name: String,
+    pub pattern: String,
+}
+
+impl ActorWatch {
+    /// Number of actors of the tree matching the pattern
+    pub fn count(&self, actors: &[ActorTreeNode]) -> u64 {
+        actor_paths(actors).iter().filter(|p| self.matches(p)).count() as u64
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        let pattern: Vec<&str> = self.pattern.split('/').filter(|s| !s.is_empty()).collect();
+        let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        matches_segments(&pattern, &path)
+    }
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| matches_segments(rest, &path[skipped..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => matches_name(segment, name) && matches_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// Whether an actor name matches a pattern segment, where `*` matches any characters
+fn matches_name(segment: &str, name: &str) -> bool {
+    let mut parts = segment.split('*');
+    let first = parts.next().unwrap_or("");
+    if !name.starts_with(first) {
+        return false;
+    }
+    let mut rest = &name[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    match parts.split_last() {
+        // no `*` in the segment
+        None => rest.is_empty(),
+        Some((last, middle)) => {
+            for part in middle {
+                match rest.find(part) {
+                    Some(i) => rest = &rest[i + part.len()..],
+                    None => return false,
+                }
+            }
+            rest.len() >= last.len() && rest.ends_with(last)
+        }
+    }
+}
+
+/// Path of each actor of the tree, in the tree's order
+fn actor_paths(actors: &[ActorTreeNode]) -> Vec<String> {
+    let by_id: HashMap<usize, &ActorTreeNode> = actors.iter().map(|a| (a.id, a)).collect();
+    actors.iter()
+        .map(|a| {
+            let mut names = vec![a.name.as_str()];
+            let mut parent = a.parent;
+            while let Some(p) = parent.and_then(|id| by_id.get(&id)) {
+                names.push(&p.name);
+                parent = p.parent;
+            }
+            names.reverse();
+            names.join("/")
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthProbe {
+    pub ok: bool,
+    pub latency_ms: u64,
+}
+
+/// Result of polling Akka Management `/health/alive` and `/health/ready` endpoints
+#[derive(Clone, Debug, PartialEq)]
+pub struct AkkaHealth {
+    pub alive: HealthProbe,
+    pub ready: HealthProbe,
+}
+
+/// Failing if any probe of the bucket failed, with the slowest latency
+impl Downsample for HealthProbe {
+    fn merge(&mut self, later: HealthProbe) {
+        self.ok &= later.ok;
+        self.latency_ms = self.latency_ms.max(later.latency_ms);
+    }
+}
+
+impl Downsample for AkkaHealth {
+    fn merge(&mut self, later: AkkaHealth) {
+        self.alive.merge(later.alive);
+        self.ready.merge(later.ready);
+    }
+}
+
+///
+/// Cluster Sharding shown in the Akka tab, read from Akka Management's `/cluster/shards/<entity type>`
+/// of every node.
+///
+/// eg. `{ "entity_types": ["Cart"], "nodes": ["http://node1:8558", "http://node2:8558"] }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ShardingSettings {
+    pub entity_types: Vec<String>,
+    /// Akka Management addresses of the cluster nodes, only `--akka-management` if empty
+    #[serde(default)]
+    pub nodes: Vec<String>,
+}
+
+/// A shard of an entity type hosted on a node
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ShardPlacement {
+    pub entity_type: String,
+    pub shard_id: String,
+    pub node: String,
+    pub entities: u64,
+}
+
+/// A shard that moved to another node between two polls, i.e. it was rebalanced
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ShardMove {
+    pub entity_type: String,
+    pub shard_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Shards of all configured entity types on all nodes
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ShardingStats {
+    pub shards: Vec<ShardPlacement>,
+}
+
+/// Shards and entities of an entity type on a node
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShardLoad {
+    pub shards: usize,
+    pub entities: u64,
+}
+
+impl ShardingStats {
+    /// Entities of each entity type across all nodes
+    pub fn entities_per_region(&self) -> BTreeMap<&str, u64> {
+        self.shards.iter().fold(BTreeMap::new(), |mut acc, s| {
+            *acc.entry(s.entity_type.as_str()).or_insert(0) += s.entities;
+            acc
+        })
+    }
+
+    /// Load of each (entity type, node) pair
+    pub fn distribution(&self) -> BTreeMap<(&str, &str), ShardLoad> {
+        self.shards.iter().fold(BTreeMap::new(), |mut acc, s| {
+            let load = acc.entry((s.entity_type.as_str(), s.node.as_str())).or_insert_with(ShardLoad::default);
+            load.shards += 1;
+            load.entities += s.entities;
+            acc
+        })
+    }
+
+    /// Shards hosted on a different node than in the previous poll
+    pub fn moves_since(&self, prev: &ShardingStats) -> Vec<ShardMove> {
+        self.shards.iter()
+            .filter_map(|s| {
+                let before = prev.shards.iter().find(|p| p.entity_type == s.entity_type && p.shard_id == s.shard_id)?;
+                if before.node == s.node {
+                    None
+                } else {
+                    Some(ShardMove {
+                        entity_type: s.entity_type.to_owned(),
+                        shard_id: s.shard_id.to_owned(),
+                        from: before.node.to_owned(),
+                        to: s.node.to_owned(),
+                    })
+                }
+            })
+            .collect()
+    }
+}
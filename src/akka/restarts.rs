@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Deserialize;
+
+///
+/// Application log tailed for restarted actors, whose supervisors' restart counts are shown on the Akka tab.
+/// The first capture group of the pattern is the path of the restarted actor. The default pattern matches
+/// the lines logged with `akka.actor.debug.lifecycle = on`, e.g. `[akka://orders/user/cart/cart-42] restarted`.
+///
+/// eg. `{ "path": "/var/log/orders/app.log" }`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "ActorRestartLogDef")]
+pub struct ActorRestartLog {
+    pub path: String,
+    pub pattern: Regex,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActorRestartLogDef {
+    path: String,
+    #[serde(default = "default_pattern")]
+    pattern: String,
+}
+
+fn default_pattern() -> String {
+    r"(akka(?:\.tcp|\.ssl)?://[^\s\]]+)\]?.*\brestarted\b".to_owned()
+}
+
+impl TryFrom<ActorRestartLogDef> for ActorRestartLog {
+    type Error = String;
+
+    fn try_from(def: ActorRestartLogDef) -> Result<ActorRestartLog, String> {
+        let pattern = Regex::new(&def.pattern).map_err(|e| format!("invalid actor restart pattern: {}", e))?;
+        if pattern.captures_len() < 2 {
+            return Err("actor restart pattern needs a capture group for the path of the actor".to_owned());
+        }
+        Ok(ActorRestartLog { path: def.path, pattern })
+    }
+}
+
+impl PartialEq for ActorRestartLog {
+    fn eq(&self, other: &ActorRestartLog) -> bool {
+        self.path == other.path && self.pattern.as_str() == other.pattern.as_str()
+    }
+}
+
+impl ActorRestartLog {
+    /// Paths of the actors restarted according to the lines
+    pub fn restarted_actors(&self, lines: &[String]) -> Vec<String> {
+        lines.iter()
+            .filter_map(|l| self.pattern.captures(l))
+            .filter_map(|c| c.get(1).map(|m| m.as_str().to_owned()))
+            .collect()
+    }
+}
+
+///
+/// Restarts of the children of each supervisor since the start, along with the ones of the last minute.
+///
+/// eg. a restart of `akka://orders/user/cart/cart-42` is counted for the supervisor `/user/cart`
+#[derive(Default)]
+pub struct RestartCounts {
+    pub totals: BTreeMap<String, u64>,
+    recent: VecDeque<(Instant, String)>,
+}
+
+impl RestartCounts {
+    pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+    pub fn record(&mut self, actor: &str, now: Instant) {
+        let supervisor = supervisor(actor);
+        *self.totals.entry(supervisor.clone()).or_insert(0) += 1;
+        self.recent.push_back((now, supervisor));
+        while matches!(self.recent.front(), Some((t, _)) if now.duration_since(*t) > RestartCounts::RATE_WINDOW) {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Restarts of the last minute, per supervisor or all of them
+    pub fn last_minute(&self, supervisor: Option<&str>, now: Instant) -> usize {
+        self.recent.iter()
+            .filter(|(t, _)| now.duration_since(*t) <= RestartCounts::RATE_WINDOW)
+            .filter(|(_, s)| supervisor.map_or(true, |x| s == x))
+            .count()
+    }
+}
+
+/// Path of the parent of an actor within its actor system, without the address
+fn supervisor(actor: &str) -> String {
+    let path = match actor.find("://") {
+        Some(i) => actor[i + 3..].find('/').map_or("", |j| &actor[i + 3 + j..]),
+        None => actor,
+    };
+    let path = path.split('#').next().unwrap_or(path).trim_end_matches('/');
+    match path.rfind('/') {
+        Some(0) | None => "/".to_owned(),
+        Some(i) => path[..i].to_owned(),
+    }
+}
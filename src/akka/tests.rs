@@ -0,0 +1,161 @@
+#[cfg(test)]
+use crate::akka::model::{ShardingStats, ShardPlacement};
+
+#[cfg(test)]
+fn shard(entity_type: &str, shard_id: &str, node: &str, entities: u64) -> ShardPlacement {
+    ShardPlacement {
+        entity_type: entity_type.to_owned(),
+        shard_id: shard_id.to_owned(),
+        node: node.to_owned(),
+        entities,
+    }
+}
+
+#[test]
+fn sharding_stats_per_region_and_node() {
+    use crate::akka::model::ShardLoad;
+
+    let stats = ShardingStats {
+        shards: vec![
+            shard("Cart", "1", "node1", 10),
+            shard("Cart", "2", "node1", 5),
+            shard("Cart", "3", "node2", 1),
+            shard("User", "1", "node2", 7),
+        ]
+    };
+
+    let regions = stats.entities_per_region();
+    assert_eq!(regions.get("Cart"), Some(&16));
+    assert_eq!(regions.get("User"), Some(&7));
+
+    let distribution = stats.distribution();
+    assert_eq!(distribution.get(&("Cart", "node1")), Some(&ShardLoad { shards: 2, entities: 15 }));
+    assert_eq!(distribution.get(&("Cart", "node2")), Some(&ShardLoad { shards: 1, entities: 1 }));
+    assert_eq!(distribution.len(), 3);
+}
+
+#[test]
+fn sharding_stats_detect_rebalanced_shards() {
+    use crate::akka::model::ShardMove;
+
+    let before = ShardingStats { shards: vec![shard("Cart", "1", "node1", 10), shard("Cart", "2", "node1", 5)] };
+    let after = ShardingStats {
+        shards: vec![shard("Cart", "1", "node1", 11), shard("Cart", "2", "node2", 5), shard("Cart", "3", "node2", 1)]
+    };
+
+    assert_eq!(after.moves_since(&before), vec![ShardMove {
+        entity_type: "Cart".to_owned(),
+        shard_id: "2".to_owned(),
+        from: "node1".to_owned(),
+        to: "node2".to_owned(),
+    }]);
+    assert!(before.moves_since(&before).is_empty());
+}
+
+#[test]
+fn actor_events_compare_paths() {
+    use crate::akka::model::{ActorEvent, ActorTreeNode};
+
+    let node = |name: &str, parent: Option<usize>, id: usize| ActorTreeNode { name: name.to_owned(), parent, id };
+    let before = vec![node("user", None, 1), node("orders", Some(1), 2), node("order-1", Some(2), 3)];
+    let after = vec![node("user", None, 1), node("orders", Some(1), 2), node("order-2", Some(2), 3), node("order-3", Some(2), 4)];
+
+    assert_eq!(ActorEvent::between(&before, &after), vec![
+        ActorEvent { path: "user/orders/order-2".to_owned(), created: true },
+        ActorEvent { path: "user/orders/order-3".to_owned(), created: true },
+        ActorEvent { path: "user/orders/order-1".to_owned(), created: false },
+    ]);
+    assert!(ActorEvent::between(&after, &after).is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn snapshots_actor_tree_by_path() {
+    use crate::akka::model::{ActorSnapshotEntry, ActorTreeNode, ActorTreeSnapshot};
+
+    let node = |name: &str, parent: Option<usize>, id: usize| ActorTreeNode { name: name.to_owned(), parent, id };
+    let tree = vec![node("user", None, 1), node("orders", Some(1), 2), node("order-2", Some(2), 4), node("order-1", Some(2), 3)];
+    let snapshot = ActorTreeSnapshot::of(&tree, 1600000000);
+
+    let entry = |path: &str, parent: Option<&str>, children: usize, descendants: usize| ActorSnapshotEntry {
+        path: path.to_owned(),
+        parent: parent.map(str::to_owned),
+        children,
+        descendants,
+    };
+    assert_eq!(snapshot.actor_count, 4);
+    assert_eq!(snapshot.actors, vec![
+        entry("user", None, 1, 3),
+        entry("user/orders", Some("user"), 2, 2),
+        entry("user/orders/order-1", Some("user/orders"), 0, 0),
+        entry("user/orders/order-2", Some("user/orders"), 0, 0),
+    ]);
+    assert!(snapshot.to_json().starts_with("{\n  \"exported_at\": 1600000000,\n  \"actor_count\": 4,"));
+}
+
+#[test]
+fn actor_watches_match_subtrees() {
+    use crate::akka::model::{ActorTreeNode, ActorWatch};
+
+    let watch = |pattern: &str| ActorWatch { name: "w".to_owned(), pattern: pattern.to_owned() };
+    assert!(watch("/user/payment/**").matches("user/payment"));
+    assert!(watch("/user/payment/**").matches("user/payment/p-1/retry"));
+    assert!(!watch("/user/payment/**").matches("user/payments"));
+    assert!(watch("/user/*/worker-*").matches("user/orders/worker-12"));
+    assert!(!watch("/user/*/worker-*").matches("user/orders/worker-12/child"));
+    assert!(watch("/**/*-retry").matches("user/payment/p-1/p-1-retry"));
+    assert!(watch("user/o*s").matches("user/orders"));
+    assert!(!watch("user/o*s*x").matches("user/orders"));
+
+    let node = |name: &str, parent: Option<usize>, id: usize| ActorTreeNode { name: name.to_owned(), parent, id };
+    let actors = vec![
+        node("user", None, 1),
+        node("payment", Some(1), 2),
+        node("p-1", Some(2), 3),
+        node("orders", Some(1), 4),
+        node("order-1", Some(4), 5),
+    ];
+    assert_eq!(watch("/user/payment/**").count(&actors), 2);
+    assert_eq!(watch("/user/orders/*").count(&actors), 1);
+}
+
+#[test]
+fn parses_shard_details() {
+    use crate::akka::client::ShardDetails;
+
+    let details: ShardDetails = serde_json::from_str(
+        r#"{"regions":[{"shardId":"12","numEntities":4},{"shardId":"7","numEntities":0}]}"#
+    ).unwrap();
+
+    assert_eq!(details.regions.len(), 2);
+    assert_eq!(details.regions[0].shard_id, "12");
+    assert_eq!(details.regions[0].num_entities, 4);
+}
+
+#[test]
+fn counts_restarts_per_supervisor() {
+    use std::time::{Duration, Instant};
+
+    use crate::akka::restarts::{ActorRestartLog, RestartCounts};
+
+    let log: ActorRestartLog = serde_json::from_str(r#"{ "path": "app.log" }"#).unwrap();
+    let lines = vec![
+        "[DEBUG] [10/12/2020 10:00:00.000] [orders-akka.actor.default-dispatcher-3] [akka://orders/user/cart/cart-42] restarted".to_owned(),
+        "[ERROR] [10/12/2020 10:00:00.000] [orders-akka.actor.default-dispatcher-3] [akka://orders/user/cart/cart-42] boom".to_owned(),
+        "10:00:01.000 DEBUG akka.tcp://orders@10.0.0.1:2552/user/payments/p-1#1234 - restarted".to_owned(),
+    ];
+    let actors = log.restarted_actors(&lines);
+    assert_eq!(actors, vec!["akka://orders/user/cart/cart-42", "akka.tcp://orders@10.0.0.1:2552/user/payments/p-1#1234"]);
+
+    let start = Instant::now();
+    let mut counts = RestartCounts::default();
+    counts.record(&actors[0], start);
+    counts.record(&actors[1], start + Duration::from_secs(30));
+    counts.record(&actors[0], start + Duration::from_secs(90));
+    assert_eq!(counts.totals.get("/user/cart"), Some(&2));
+    assert_eq!(counts.totals.get("/user/payments"), Some(&1));
+    assert_eq!(counts.last_minute(Some("/user/cart"), start + Duration::from_secs(90)), 1);
+    assert_eq!(counts.last_minute(None, start + Duration::from_secs(90)), 2);
+
+    assert!(serde_json::from_str::<ActorRestartLog>(r#"{ "path": "app.log", "pattern": "restarted" }"#).is_err());
+}
@@ -0,0 +1,139 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+///
+/// Serves a state snapshot as JSON over HTTP while the TUI runs.
+///
+/// `GET /` and `GET /state` ask for a snapshot, which the UI thread passes to `publish` on its next tick,
+/// see `App::state_json` for its contents. The state is only serialized when it's asked for.
+/// If no snapshot comes within 2s, the previous one is served.
+pub struct ApiServer {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    /// Number of snapshots published so far, along with the last one
+    snapshot: Mutex<(u64, String)>,
+    published: Condvar,
+    requested: AtomicBool,
+}
+
+impl ApiServer {
+    const SNAPSHOT_WAIT: Duration = Duration::from_secs(2);
+
+    pub fn start(host: &str, port: u16) -> Result<ApiServer, String> {
+        let listener = TcpListener::bind((host, port))
+            .map_err(|e| format!("Couldn't start the API server on {}:{}: {}", host, port, e))?;
+        let shared = Arc::new(Shared {
+            snapshot: Mutex::new((0, "{}".to_owned())),
+            published: Condvar::new(),
+            requested: AtomicBool::new(false),
+        });
+        let server = shared.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // a misbehaving client only affects its own response
+                let _ = respond(stream, &server);
+            }
+        });
+        Ok(ApiServer { shared })
+    }
+
+    /// Whether a client waits for a snapshot
+    pub fn is_requested(&self) -> bool {
+        self.shared.requested.load(Ordering::SeqCst)
+    }
+
+    pub fn publish(&self, state: String) {
+        let mut snapshot = self.shared.snapshot.lock().unwrap();
+        *snapshot = (snapshot.0 + 1, state);
+        self.shared.requested.store(false, Ordering::SeqCst);
+        self.shared.published.notify_all();
+    }
+}
+
+/// Snapshot published after the request, or the last one if none is within the wait
+fn next_snapshot(shared: &Shared, wait: Duration) -> String {
+    let snapshot = shared.snapshot.lock().unwrap();
+    let requested_at = snapshot.0;
+    shared.requested.store(true, Ordering::SeqCst);
+    let (snapshot, _) = shared.published.wait_timeout_while(snapshot, wait, |s| s.0 == requested_at).unwrap();
+    snapshot.1.clone()
+}
+
+fn respond(mut stream: TcpStream, shared: &Shared) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let (status, body) = match route(&request_line) {
+        200 => (200, next_snapshot(shared, ApiServer::SNAPSHOT_WAIT)),
+        code => (code, format!("{{\"error\":\"{}\"}}", reason(code))),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason(status), body.len(), body
+    )?;
+    stream.flush()
+}
+
+/// Status code for a request line like `GET /state HTTP/1.1`
+fn route(request_line: &str) -> u16 {
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => match path.split('?').next() {
+            Some("/") | Some("/state") => 200,
+            _ => 404,
+        },
+        (Some(_), Some(_)) => 405,
+        _ => 400,
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Bad Request",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::api::{next_snapshot, route, ApiServer};
+
+    #[test]
+    fn routes_requests() {
+        assert_eq!(route("GET /state HTTP/1.1\r\n"), 200);
+        assert_eq!(route("GET /state?source=zmx HTTP/1.1\r\n"), 200);
+        assert_eq!(route("GET /fibers HTTP/1.1\r\n"), 404);
+        assert_eq!(route("POST /state HTTP/1.1\r\n"), 405);
+        assert_eq!(route(""), 400);
+    }
+
+    #[test]
+    fn serves_snapshot_published_after_request() {
+        let api = ApiServer::start("127.0.0.1", 0).unwrap();
+        assert!(!api.is_requested());
+        let shared = api.shared.clone();
+        let client = thread::spawn(move || next_snapshot(&shared, Duration::from_secs(5)));
+        while !api.is_requested() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        api.publish("{\"fibers\":[]}".to_owned());
+        assert_eq!(client.join().unwrap(), "{\"fibers\":[]}");
+        assert!(!api.is_requested());
+
+        // nothing published within the wait, the last snapshot is served
+        assert_eq!(next_snapshot(&api.shared, Duration::from_millis(10)), "{\"fibers\":[]}");
+        assert!(api.is_requested());
+    }
+}
@@ -1,29 +1,213 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs;
 use std::iter::Iterator;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use serde_json::json;
 use tui::widgets::ListState;
 
-use crate::akka::model::{ActorTreeNode, AkkaSettings};
-use crate::jmx::model::{HikariMetrics, JMXConnectionSettings, SlickConfig, SlickMetrics};
-use crate::widgets::tree;
-use crate::zio::model::{Fiber, FiberCount, FiberStatus};
+use crate::akka::model::{ActorEvent, ActorTreeNode, ActorTreeSnapshot, ActorWatch, AkkaHealth, AkkaSettings, ShardingStats, ShardMove};
+use crate::akka::restarts::RestartCounts;
+use crate::capabilities::{Capabilities, Capability, Feature};
+use crate::clock::ClockSkew;
+use crate::config::{ListColumns, SourceLabel, SourceLabels, StartupView};
+use crate::connect::Connection;
+use crate::consul::model::{HealthStatus, ServiceHealth};
+use crate::dashboard::Dashboard;
+use crate::diagnostics::Diagnostics;
+use crate::gclog::model::GcPause;
+use crate::history::{Downsample, History, Retention};
+use crate::incident::IncidentSettings;
+use crate::jmx::model::{HikariMetrics, JMXConnectionSettings, JMXValue, JVMRuntimeMetrics, PoolErrorCounts, PoolErrorRates, SlickConfig, SlickMetrics, StatementStats, WatchedAttribute};
+use crate::logging::{self, Logger, LogLevel};
+use crate::paste::PasteSettings;
+use crate::plot::{self, ChartSeries};
+use crate::prometheus::explorer::{self, PinnedMetrics};
+use crate::prometheus::model::PrometheusSample;
+use crate::quick_actions::Step;
+use crate::redis::model::{RedisInfo, RedisStats, SlowlogEntry};
+use crate::script::derived::{DerivedMetric, DerivedSeries};
+use crate::script::hooks::{HookKind, Hooks};
+use crate::scrub::Scrubber;
+use crate::series::{self, ChartViews, PanelView, SeriesKind, SeriesView};
+use crate::source::{DataSource, PanelSpec, Sample};
+use crate::session::SessionSample;
+use crate::widgets::table::TableSort;
+use crate::widgets::tree::{self, Column};
+use crate::zio::burst::BurstCapture;
+use crate::zio::diff::{self, LineChange};
+use crate::zio::label::{FiberLabel, NamedFiber};
+use crate::zio::model::{self, Fiber, FiberCount, FiberDumpArchive, FiberGroup, FiberLifecycle, FiberSort, FiberStatus, FiberTreeShape};
+use crate::zio::outline::DumpOutline;
+use crate::zookeeper::model::{config_drift, KafkaBroker, ZkCommand, ZkNodeState, ZkNodeStatus};
 
 pub struct UIFiber {
     pub label: String,
     pub dump: String,
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum TabKind {
     ZMX,
     Slick,
+    JVM,
+    Custom,
     AkkaActorTree,
+    Redis,
+    Consul,
+    GcLog,
+    Prometheus,
+    /// Data source registered in `source::registered`, by its index
+    Source(usize),
+    Compare,
+    Derived,
+    Hooks,
+    Zookeeper,
+    Latency,
+    Log,
+    /// Panels of the other tabs laid out in the `dashboard` of the config file
+    Dashboard,
+}
+
+impl TabKind {
+    /// Name of the data source shown on the tab, as used in logs
+    pub fn source(&self) -> Option<&'static str> {
+        match self {
+            TabKind::ZMX => Some("zmx"),
+            TabKind::Slick | TabKind::JVM | TabKind::Custom => Some("jmx"),
+            TabKind::AkkaActorTree => Some("akka"),
+            TabKind::Redis => Some("redis"),
+            TabKind::Consul => Some("consul"),
+            TabKind::GcLog => Some("gclog"),
+            TabKind::Prometheus => Some("prometheus"),
+            TabKind::Zookeeper => Some("zookeeper"),
+            _ => None,
+        }
+    }
+
+    /// Panels shown on the tab, in focus order, the ones of the dashboard being configured
+    pub fn panels(&self) -> Vec<Panel> {
+        match self {
+            TabKind::ZMX => vec![Panel::Fibers, Panel::FiberCounts, Panel::FiberDump, Panel::FiberTree],
+            TabKind::Slick => vec![Panel::SlickThreads, Panel::SlickUtilization, Panel::SlickQueue, Panel::Hikari, Panel::PoolErrors, Panel::Statements],
+            TabKind::JVM => vec![Panel::JVMAttributes, Panel::JVMClasses, Panel::JVMThreads, Panel::JVMDaemonThreads],
+            TabKind::Custom => vec![Panel::Watchlist],
+            TabKind::AkkaActorTree => vec![Panel::Actors, Panel::ActorCount, Panel::ActorEvents, Panel::ActorRestarts, Panel::Sharding],
+            TabKind::Redis => vec![
+                Panel::RedisMemory,
+                Panel::RedisClients,
+                Panel::RedisOps,
+                Panel::RedisHitRate,
+                Panel::RedisSlowlog,
+            ],
+            TabKind::Consul => vec![Panel::ConsulServices],
+            TabKind::GcLog => vec![Panel::GcPauses, Panel::GcAllocationRate],
+            TabKind::Prometheus => vec![Panel::PrometheusSeries],
+            TabKind::Source(_) => vec![Panel::SourcePanels],
+            TabKind::Compare => vec![Panel::CompareFibers, Panel::CompareHikari, Panel::CompareActors],
+            TabKind::Derived => vec![Panel::Derived],
+            TabKind::Hooks => vec![Panel::Hooks, Panel::HookLog],
+            TabKind::Zookeeper => vec![Panel::ZookeeperNodes, Panel::ZookeeperConsole, Panel::ZookeeperChart],
+            TabKind::Latency => vec![Panel::Latency],
+            TabKind::Log => vec![Panel::Log],
+            TabKind::Dashboard => vec![],
+        }
+    }
+}
+
+/// JMX operation run on the monitored JVM once the user confirms it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum JmxAction {
+    HeapDump,
+    ThreadDump,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Panel {
+    Fibers,
+    FiberCounts,
+    FiberDump,
+    FiberTree,
+    SlickThreads,
+    SlickUtilization,
+    SlickQueue,
+    Hikari,
+    PoolErrors,
+    Statements,
+    JVMAttributes,
+    JVMClasses,
+    JVMThreads,
+    JVMDaemonThreads,
+    Watchlist,
+    Actors,
+    ActorCount,
+    ActorEvents,
+    ActorRestarts,
+    Sharding,
+    RedisMemory,
+    RedisClients,
+    RedisOps,
+    RedisHitRate,
+    RedisSlowlog,
+    ConsulServices,
+    GcPauses,
+    GcAllocationRate,
+    PrometheusSeries,
+    SourcePanels,
+    CompareFibers,
+    CompareHikari,
+    CompareActors,
+    Derived,
+    Hooks,
+    HookLog,
+    ZookeeperNodes,
+    ZookeeperConsole,
+    ZookeeperChart,
+    Latency,
+    Log,
+}
+
+impl Panel {
+    /// Header of the panel's table, sortable with `s` and `S`, or nothing if the panel isn't a table
+    pub fn table_columns(&self) -> &'static [&'static str] {
+        match self {
+            Panel::JVMAttributes => &["Key", "Value"],
+            Panel::Statements => &["Statement", "Executions", "Mean ms"],
+            Panel::ActorRestarts => &["Supervisor", "Restarts", "Last minute"],
+            Panel::Sharding => &["Entity type", "Node", "Shards", "Entities"],
+            Panel::RedisSlowlog => &["Id", "Duration (µs)", "Command"],
+            _ => &[],
+        }
+    }
+
+    /// Whether the panel charts series over time, which can have their own time range and be frozen
+    pub fn is_time_chart(&self) -> bool {
+        matches!(
+            self,
+            Panel::FiberCounts | Panel::Hikari | Panel::PoolErrors | Panel::CompareFibers | Panel::CompareHikari | Panel::CompareActors | Panel::ZookeeperChart
+        )
+    }
 }
 
 #[derive(Clone)]
 pub struct Tab<'a> {
     pub kind: TabKind,
     pub title: &'a str,
+    /// Index of the focused panel
+    pub focus: usize,
+    /// Whether the focused panel takes the whole tab
+    pub zoomed: bool,
+    /// Whether an alert on the tab's metrics fired since it was last visited
+    pub alert: bool,
+    /// Sort of the tab's table, tabs have one at most
+    pub sort: Option<TableSort>,
+}
+
+impl<'a> Tab<'a> {
+    pub fn new(kind: TabKind, title: &'a str) -> Tab<'a> {
+        Tab { kind, title, focus: 0, zoomed: false, alert: false, sort: None }
+    }
 }
 
 #[derive(Clone)]
@@ -38,6 +222,7 @@ impl<'a> TabsState<'a> {
     }
     pub fn next(&mut self) {
         self.index = (self.index + 1) % self.tabs.len();
+        self.tabs[self.index].alert = false;
     }
 
     pub fn previous(&mut self) {
@@ -46,43 +231,262 @@ impl<'a> TabsState<'a> {
         } else {
             self.index = self.tabs.len() - 1;
         }
+        self.tabs[self.index].alert = false;
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.index = index;
+        self.tabs[self.index].alert = false;
     }
 
     pub fn current(&self) -> &Tab<'a> {
         &self.tabs[self.index]
     }
 
-    pub fn titles(&self) -> Vec<&'a str> {
-        self.tabs.iter().map(|x| x.title).collect()
+    pub fn current_mut(&mut self) -> &mut Tab<'a> {
+        &mut self.tabs[self.index]
+    }
+}
+
+/// Keeps track of successful and failed polls of a data source over a sliding time window.
+pub struct Availability {
+    pub window: Duration,
+    polls: VecDeque<(Instant, bool)>,
+    succeeded_once: bool,
+    last_success: Option<Instant>,
+}
+
+impl Availability {
+    /// Amount of poll intervals without a sample after which the source's charts are marked as stale
+    pub const STALE_AFTER_POLLS: u32 = 3;
+
+    pub fn new(window: Duration) -> Availability {
+        Availability { window, polls: VecDeque::new(), succeeded_once: false, last_success: None }
+    }
+
+    pub fn record(&mut self, success: bool) {
+        self.record_at(Instant::now(), success)
+    }
+
+    fn record_at(&mut self, at: Instant, success: bool) {
+        self.polls.push_back((at, success));
+        self.succeeded_once = self.succeeded_once || success;
+        if success {
+            self.last_success = Some(at);
+        }
+        while let Some((t, _)) = self.polls.front() {
+            if at.duration_since(*t) > self.window {
+                self.polls.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Whether the source has ever responded successfully
+    pub fn succeeded_once(&self) -> bool {
+        self.succeeded_once
+    }
+
+    /// Percentage of successful polls within the window, if there were any polls at all
+    pub fn percentage(&self) -> Option<f64> {
+        if self.polls.is_empty() {
+            None
+        } else {
+            let successes = self.polls.iter().filter(|(_, s)| *s).count();
+            Some(successes as f64 * 100.0 / self.polls.len() as f64)
+        }
+    }
+
+    ///
+    /// Time since the last sample, if the source hasn't delivered one for `STALE_AFTER_POLLS` poll intervals.
+    /// The intervals are the effective ones, so a backed off source isn't stale for being polled less often.
+    pub fn stale_for(&self, backoff: &Backoff) -> Option<Duration> {
+        self.stale_at(Instant::now(), backoff)
+    }
+
+    fn stale_at(&self, now: Instant, backoff: &Backoff) -> Option<Duration> {
+        self.last_success
+            .map(|t| now.duration_since(t))
+            .filter(|age| *age > backoff.interval() * Availability::STALE_AFTER_POLLS)
+    }
+
+    /// eg. `ZMX 98.5% last 15m, every 2s` or `ZMX 40.0% last 15m, every 16s, STALE 31s`
+    pub fn label(&self, source: &str, backoff: &Backoff) -> String {
+        let pct = self.percentage().map_or("n/a".to_owned(), |p| format!("{:.1}%", p));
+        let interval = backoff.interval().as_secs_f64();
+        let label = format!("{} {} last {}m, every {}s", source, pct, self.window.as_secs() / 60, series::axis_label(interval));
+        match self.stale_for(backoff) {
+            Some(age) => format!("{}, STALE {}s", label, age.as_secs()),
+            None => label,
+        }
+    }
+}
+
+///
+/// Polling schedule of a data source.
+///
+/// A source is polled on every tick while it's healthy, each failed poll doubles the interval
+/// (up to `MAX_SLOWDOWN` times the tick rate) and each successful one halves it again.
+/// A new poll isn't sent until the previous one has been answered, so slow sources don't pile up requests.
+pub struct Backoff {
+    tick_rate: Duration,
+    slowdown: u32,
+    ticks_to_wait: u32,
+    in_flight: bool,
+}
+
+impl Backoff {
+    pub const MAX_SLOWDOWN: u32 = 32;
+
+    pub fn new(tick_rate: Duration) -> Backoff {
+        Backoff { tick_rate, slowdown: 1, ticks_to_wait: 0, in_flight: false }
+    }
+
+    /// Called on each tick, returns true if the source should be polled now
+    pub fn due(&mut self) -> bool {
+        if self.in_flight {
+            return false;
+        }
+        if self.ticks_to_wait > 0 {
+            self.ticks_to_wait -= 1;
+            return false;
+        }
+        self.ticks_to_wait = self.slowdown - 1;
+        self.in_flight = true;
+        true
+    }
+
+    pub fn on_response(&mut self, ok: bool) {
+        self.in_flight = false;
+        self.slowdown = if ok {
+            (self.slowdown / 2).max(1)
+        } else {
+            (self.slowdown * 2).min(Backoff::MAX_SLOWDOWN)
+        };
+        self.ticks_to_wait = self.ticks_to_wait.min(self.slowdown - 1);
+    }
+
+    /// Polls the source on the next tick again, e.g. when the user retries an unreachable source
+    pub fn reset(&mut self) {
+        self.slowdown = 1;
+        self.ticks_to_wait = 0;
+        self.in_flight = false;
+    }
+
+    /// Current effective interval between polls
+    pub fn interval(&self) -> Duration {
+        self.tick_rate * self.slowdown
+    }
+}
+
+/// Round-trip times of recent fetches, in milliseconds, for each source in the order they first responded
+pub struct PollLatency {
+    pub sources: Vec<&'static str>,
+    pub values: Vec<History<u64>>,
+    retention: Retention,
+}
+
+impl PollLatency {
+    pub fn new(retention: Retention) -> PollLatency {
+        PollLatency { sources: vec![], values: vec![], retention }
+    }
+
+    pub fn record(&mut self, source: &'static str, latency: Duration, at: Instant) {
+        let i = match self.sources.iter().position(|s| *s == source) {
+            Some(i) => i,
+            None => {
+                self.sources.push(source);
+                self.values.push(History::new(self.retention));
+                self.sources.len() - 1
+            }
+        };
+        self.values[i].push_back_at(latency.as_millis() as u64, at);
     }
 }
 
 pub struct ZMXTab {
     pub fibers: StatefulList<String>,
+    /// Selected fiber dump as shown, with folded sections left out, and its line count
     pub selected_fiber_dump: (String, u16),
+    pub dump_outline: DumpOutline,
+    /// Section of the dump outline shown on each line of `selected_fiber_dump`
+    dump_line_sections: Vec<Option<usize>>,
+    /// Change of each line of `selected_fiber_dump` since the previous snapshot, removed lines included
+    pub dump_changes: Vec<LineChange>,
+    /// Dumps of the previous snapshot by fiber id
+    previous_dumps: HashMap<usize, String>,
     pub fiber_dump_all: Vec<String>,
+    /// Fibers in the tree order, their dumps are kept in `fiber_dump_all` only
+    pub fiber_nodes: Vec<Fiber>,
+    /// Labels of the fibers in the order of `fiber_nodes`, the list only shows the ones of `status_filter`
+    fiber_labels: Vec<String>,
+    /// Position in `fiber_nodes` of each fiber of the list
+    shown: Vec<usize>,
+    /// Fibers listed one by one at most, snapshots with more of them being listed as groups, see `FiberGroup`
+    pub fiber_limit: usize,
+    /// Size of each group of `fiber_nodes`, whose fibers are the samples of the groups, while the snapshot is listed as groups
+    group_sizes: Option<Vec<usize>>,
+    /// Every fiber of the snapshot and their dumps while it's listed as groups, as `fiber_nodes` and `fiber_dump_all` are
+    /// for the listed fibers, so that exports and analysis still cover the whole snapshot
+    ungrouped: Option<(Vec<Fiber>, Vec<String>)>,
+    /// Status the fiber list is narrowed to, cycled with `g`
+    pub status_filter: Option<FiberStatus>,
+    pub fiber_sort: FiberSort,
+    /// Id of the fiber to jump to as it's being typed, while the prompt opened with `:` is shown
+    pub fiber_prompt: Option<String>,
+    /// Columns shown after the tree in the fiber list
+    pub columns: Vec<Column>,
+    pub fiber_label: Option<FiberLabel>,
     pub scroll: u16,
-    pub fiber_counts: VecDeque<FiberCount>,
+    pub wrap: bool,
+    pub x_offset: u16,
+    pub fiber_counts: History<FiberCount>,
+    /// Statuses left out of the fiber count chart
+    pub hidden_statuses: Vec<FiberStatus>,
+    pub fiber_lifecycle: History<FiberLifecycle>,
+    /// Structure of the fiber tree of the last polled dump
+    pub tree_shape: Option<FiberTreeShape>,
+    last_regular_dump: Option<Vec<Fiber>>,
+    /// Burst capture in progress, see `App::start_burst`
+    pub burst: Option<BurstCapture>,
+    pub availability: Availability,
+    pub backoff: Backoff,
 }
 
 impl ZMXTab {
-    pub const MAX_FIBER_COUNT_MEASURES: usize = 100;
-
-    pub fn new() -> ZMXTab {
+    pub fn new(columns: Vec<Column>, fiber_label: Option<FiberLabel>, retention: Retention, availability_window: Duration, tick_rate: Duration) -> ZMXTab {
         ZMXTab {
             fibers: StatefulList::with_items(vec![]),
             selected_fiber_dump: ("".to_string(), 1),
+            dump_outline: DumpOutline::default(),
+            dump_line_sections: vec![],
+            dump_changes: vec![],
+            previous_dumps: HashMap::new(),
             fiber_dump_all: vec![],
+            fiber_nodes: vec![],
+            fiber_labels: vec![],
+            shown: vec![],
+            fiber_limit: ZMXTab::DEFAULT_FIBER_LIMIT,
+            group_sizes: None,
+            ungrouped: None,
+            status_filter: None,
+            fiber_sort: FiberSort::Tree,
+            fiber_prompt: None,
+            columns,
+            fiber_label,
             scroll: 0,
-            fiber_counts: VecDeque::new(),
-        }
-    }
-
-    fn append_fiber_count(&mut self, c: FiberCount) {
-        if self.fiber_counts.len() > ZMXTab::MAX_FIBER_COUNT_MEASURES {
-            self.fiber_counts.pop_front();
+            wrap: true,
+            x_offset: 0,
+            fiber_counts: History::new(retention),
+            hidden_statuses: vec![],
+            fiber_lifecycle: History::new(retention),
+            tree_shape: None,
+            last_regular_dump: None,
+            burst: None,
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
         }
-        self.fiber_counts.push_back(c);
     }
 
     pub fn select_prev_fiber(&mut self) {
@@ -99,26 +503,215 @@ impl ZMXTab {
         }
     }
 
+    pub fn select_parent_fiber(&mut self) {
+        self.select_fiber_by(tree::parent_index)
+    }
+
+    pub fn select_first_child_fiber(&mut self) {
+        self.select_fiber_by(tree::first_child_index)
+    }
+
+    pub fn select_next_sibling_fiber(&mut self) {
+        self.select_fiber_by(tree::next_sibling_index)
+    }
+
+    /// Selects the fiber found relative to the selected one, if there's any and it isn't filtered out
+    fn select_fiber_by(&mut self, find: fn(&[Fiber], usize) -> Option<usize>) {
+        let selected = match self.selected_fiber() {
+            Some(s) => s,
+            None => return,
+        };
+        if let Some(i) = find(&self.fiber_nodes, selected).and_then(|i| self.shown.iter().position(|s| *s == i)) {
+            self.fibers.state.select(Some(i));
+            self.on_fiber_change()
+        }
+    }
+
+    /// Selects the fiber with the given id, showing every fiber again if the list is narrowed to another status
+    pub fn jump_to_fiber(&mut self, id: usize) -> Result<(), String> {
+        let n = match self.fiber_nodes.iter().position(|f| f.id == id) {
+            Some(n) => n,
+            None if self.group_sizes.is_some() => return Err(format!("Fiber #{} isn't the sample of a group of the last snapshot", id)),
+            None => return Err(format!("Fiber #{} isn't in the last snapshot, press <Enter> to take a new one", id)),
+        };
+        if !self.shown.contains(&n) {
+            self.status_filter = None;
+            self.apply_status_filter();
+        }
+        self.fibers.state.select(self.shown.iter().position(|s| *s == n));
+        self.on_fiber_change();
+        Ok(())
+    }
+
+    pub fn open_fiber_prompt(&mut self) {
+        self.fiber_prompt = Some(String::new());
+    }
+
+    pub fn close_fiber_prompt(&mut self) {
+        self.fiber_prompt = None;
+    }
+
+    /// Only digits are typed, ids being copied from logs as `#4812` as often as not
+    pub fn on_fiber_prompt_input(&mut self, c: char) {
+        if let Some(input) = self.fiber_prompt.as_mut().filter(|_| c.is_ascii_digit()) {
+            input.push(c);
+        }
+    }
+
+    pub fn on_fiber_prompt_backspace(&mut self) {
+        if let Some(input) = self.fiber_prompt.as_mut() {
+            input.pop();
+        }
+    }
+
+    /// Closes the prompt and jumps to the fiber with the typed id
+    pub fn submit_fiber_prompt(&mut self) -> Result<(), String> {
+        match self.fiber_prompt.take().and_then(|input| input.parse().ok()) {
+            Some(id) => self.jump_to_fiber(id),
+            None => Ok(()),
+        }
+    }
+
+    /// Position in `fiber_nodes` of the fiber selected in the list
+    pub fn selected_fiber(&self) -> Option<usize> {
+        self.fibers.state.selected().and_then(|i| self.shown.get(i)).copied()
+    }
+
     pub fn on_fiber_change(&mut self) {
-        let n = self.fibers.state.selected().unwrap_or(0);
-        self.selected_fiber_dump = ZMXTab::prepare_dump(self.fiber_dump_all[n].clone());
+        self.show_selected_dump();
         self.scroll = 0;
+        self.x_offset = 0;
+    }
+
+    fn show_selected_dump(&mut self) {
+        let dump = self.selected_fiber().map_or(String::new(), |n| self.fiber_dump_all[n].clone());
+        self.show_dump(dump);
+    }
+
+    /// Narrows the fiber list to the next status of `status_counts`, and after the last one back to every fiber
+    pub fn cycle_status_filter(&mut self) {
+        let statuses = ZMXTab::FILTERED_STATUSES;
+        let next = match &self.status_filter {
+            None => Some(statuses[0].clone()),
+            Some(s) => statuses.iter().skip_while(|x| *x != s).nth(1).cloned(),
+        };
+        self.filter_by_status(next);
+    }
+
+    /// Narrows the fiber list to a status, or shows every fiber with `None`
+    pub fn filter_by_status(&mut self, status: Option<FiberStatus>) {
+        self.status_filter = status;
+        self.apply_status_filter();
+        self.on_fiber_change();
+    }
+
+    /// Orders the fiber list by the next sort mode, selecting the first fiber
+    pub fn cycle_fiber_sort(&mut self) {
+        self.fiber_sort = self.fiber_sort.next();
+        self.apply_status_filter();
+        self.on_fiber_change();
+    }
+
+    /// Number of fibers of the snapshot with each status, in the order they're filtered by
+    pub fn status_counts(&self) -> Vec<(FiberStatus, usize)> {
+        let sizes = self.group_sizes.as_ref();
+        ZMXTab::FILTERED_STATUSES.iter()
+            .map(|s| {
+                let count = self.fiber_nodes.iter()
+                    .enumerate()
+                    .filter(|(_, f)| f.status == *s)
+                    .map(|(i, _)| sizes.map_or(1, |g| g[i]))
+                    .sum();
+                (s.clone(), count)
+            })
+            .collect()
+    }
+
+    /// Number of fibers of the last snapshot, if there were too many of them to list one by one
+    pub fn grouped_fibers(&self) -> Option<usize> {
+        self.group_sizes.as_ref().map(|g| g.iter().sum())
+    }
+
+    /// Every fiber of the last snapshot without its dump, and their dumps, the fibers only counted in a group included
+    pub fn snapshot(&self) -> (&[Fiber], &[String]) {
+        match &self.ungrouped {
+            Some((nodes, dumps)) => (nodes.as_slice(), dumps.as_slice()),
+            None => (self.fiber_nodes.as_slice(), self.fiber_dump_all.as_slice()),
+        }
+    }
+
+    const FILTERED_STATUSES: [FiberStatus; 4] = [FiberStatus::Running, FiberStatus::Suspended, FiberStatus::Finishing, FiberStatus::Done];
+
+    fn apply_status_filter(&mut self) {
+        let filter = &self.status_filter;
+        self.shown = self.fiber_nodes.iter()
+            .enumerate()
+            .filter(|(_, f)| filter.as_ref().map_or(true, |s| f.status == *s))
+            .map(|(i, _)| i)
+            .collect();
+        let nodes = &self.fiber_nodes;
+        let dumps = &self.fiber_dump_all;
+        match self.fiber_sort {
+            FiberSort::Tree => {}
+            FiberSort::Id => self.shown.sort_by_key(|i| nodes[*i].id),
+            FiberSort::Status => self.shown.sort_by_key(|i| ZMXTab::FILTERED_STATUSES.iter().position(|s| *s == nodes[*i].status)),
+            FiberSort::SuspendedAge => self.shown.sort_by_key(|i| match nodes[*i].status {
+                FiberStatus::Suspended => (false, Reverse(model::lifetime_ms(&dumps[*i]).unwrap_or(0))),
+                _ => (true, Reverse(0)),
+            }),
+        }
+        self.fibers.items = self.shown.iter().map(|i| self.fiber_labels[*i].clone()).collect();
+        self.fibers.state.select(if self.shown.is_empty() { None } else { Some(0) });
     }
 
+    pub const DEFAULT_FIBER_LIMIT: usize = 100_000;
+
     pub fn replace_fiber_dump(&mut self, dump: Vec<Fiber>) {
-        let list: Vec<UIFiber> = tree::tree_list_widget(dump, true)
-            .iter()
-            .map(|(label, fb)| UIFiber { label: label.to_owned(), dump: fb.dump.to_owned() })
+        self.previous_dumps = self.fiber_nodes.iter().map(|f| f.id).zip(self.fiber_dump_all.drain(..)).collect();
+        if dump.len() > self.fiber_limit {
+            self.list_groups(dump);
+        } else {
+            self.list_fibers(dump);
+        }
+        self.apply_status_filter();
+        self.show_selected_dump();
+    }
+
+    fn list_fibers(&mut self, dump: Vec<Fiber>) {
+        self.group_sizes = None;
+        self.ungrouped = None;
+        let fiber_label = &self.fiber_label;
+        let named: Vec<NamedFiber> = dump.into_iter()
+            .map(|fiber| NamedFiber { name: fiber_label.as_ref().and_then(|l| l.name(&fiber.dump)), fiber })
             .collect();
-        let mut fib_labels: Vec<String> = list.iter().map(|f| f.label.clone()).collect();
-        let mut fib_dumps = list.iter().map(|f| f.dump.to_owned()).collect::<Vec<String>>();
+        let rows = tree::tree_rows(named, true);
+        self.fiber_nodes = rows.iter()
+            .map(|r| Fiber { dump: "".to_owned(), ..r.item.fiber.to_owned() })
+            .collect();
+        let list: Vec<UIFiber> = tree::format_rows(&rows, &self.columns)
+            .into_iter()
+            .zip(rows.iter())
+            .map(|(label, r)| UIFiber { label, dump: r.item.fiber.dump.to_owned() })
+            .collect();
+        self.fiber_labels = list.iter().map(|f| f.label.clone()).collect();
+        self.fiber_dump_all = list.into_iter().map(|f| f.dump).collect();
+    }
 
-        self.fibers.items.clear();
-        self.fibers.items.append(&mut fib_labels);
-        self.fibers.state.select(Some(0));
-        self.selected_fiber_dump = ZMXTab::prepare_dump(fib_dumps[0].clone());
-        self.fiber_dump_all.clear();
-        self.fiber_dump_all.append(&mut fib_dumps);
+    /// Lists a group per status line instead of each fiber, as building the tree of a huge dump would freeze the UI
+    fn list_groups(&mut self, dump: Vec<Fiber>) {
+        let groups = FiberGroup::of(&dump);
+        self.group_sizes = Some(groups.iter().map(|g| g.count).collect());
+        self.fiber_labels = groups.iter()
+            .map(|g| format!("{:>7} × {} (e.g. #{})", g.count, g.status_line, g.sample.id))
+            .collect();
+        self.fiber_nodes = groups.iter().map(|g| Fiber { dump: "".to_owned(), ..g.sample.clone() }).collect();
+        self.fiber_dump_all = groups.into_iter().map(|g| g.sample.dump).collect();
+        self.ungrouped = Some(dump.into_iter()
+            .map(|mut fiber| {
+                let dump = std::mem::take(&mut fiber.dump);
+                (fiber, dump)
+            })
+            .unzip());
     }
 
     pub fn scroll_up(&mut self) {
@@ -133,7 +726,69 @@ impl ZMXTab {
         }
     }
 
-    pub fn append_fiber_dump_for_counts(&mut self, dump: Vec<Fiber>) {
+    /// Shows or hides the series of fibers with the given status in the fiber count chart
+    pub fn toggle_status(&mut self, status: FiberStatus) {
+        match self.hidden_statuses.iter().position(|s| *s == status) {
+            Some(i) => {
+                self.hidden_statuses.remove(i);
+            }
+            None => self.hidden_statuses.push(status),
+        }
+    }
+
+    /// Highest number of fibers in the statuses shown in the fiber count chart, downsampled counts included
+    pub fn shown_fibers_max(&self) -> i32 {
+        self.fiber_counts.points().into_iter()
+            .map(|(_, c)| c.total() - self.hidden_statuses.iter().map(|s| c.count(s)).sum::<i32>())
+            .max()
+            .unwrap_or(0)
+    }
+
+    ///
+    /// Id, lifetime (in ms) and first stack frame of the fibers suspended in the last dump, the oldest first.
+    /// Dumps don't tell since when a fiber is suspended, so its lifetime is used as for `FiberSort::SuspendedAge`.
+    pub fn stuck_fibers(&self, top: usize) -> Vec<(usize, u64, &str)> {
+        let (nodes, dumps) = self.snapshot();
+        let mut stuck: Vec<(usize, u64, &str)> = nodes.iter()
+            .zip(dumps.iter())
+            .filter(|(f, _)| f.status == FiberStatus::Suspended)
+            .map(|(f, dump)| (f.id, model::lifetime_ms(dump).unwrap_or(0), dump.lines().nth(1).map_or("", str::trim)))
+            .collect();
+        stuck.sort_by_key(|(id, lifetime, _)| (Reverse(*lifetime), *id));
+        stuck.truncate(top);
+        stuck
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.x_offset = 0;
+    }
+
+    pub fn scroll_left(&mut self) {
+        if self.x_offset > 0 {
+            self.x_offset -= 1;
+        }
+    }
+
+    pub fn scroll_right(&mut self) {
+        let longest = self.selected_fiber_dump.0.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        if !self.wrap && (self.x_offset as usize) < longest {
+            self.x_offset += 1;
+        }
+    }
+
+    /// Selected fiber dump with each line shifted by the horizontal offset
+    pub fn visible_dump(&self) -> String {
+        if self.wrap {
+            return self.selected_fiber_dump.0.to_owned();
+        }
+        self.selected_fiber_dump.0.lines()
+            .map(|l| l.chars().skip(self.x_offset as usize).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn append_fiber_dump_for_counts(&mut self, dump: Vec<Fiber>, at: Instant) {
         let mut count = FiberCount { done: 0, suspended: 0, running: 0, finishing: 0 };
         for f in dump.iter() {
             match f.status {
@@ -143,31 +798,123 @@ impl ZMXTab {
                 FiberStatus::Suspended => { count.suspended += 1 }
             }
         }
-        self.append_fiber_count(count);
+        self.fiber_counts.push_back_at(count, at);
+
+        if let Some(prev) = &self.last_regular_dump {
+            self.fiber_lifecycle.push_back_at(FiberLifecycle::between(prev, &dump), at);
+        }
+        self.tree_shape = Some(FiberTreeShape::of(&dump));
+        // dumps are only needed to compare fiber ids and statuses
+        self.last_regular_dump = Some(dump.into_iter().map(|f| Fiber { dump: "".to_owned(), ..f }).collect());
+    }
+
+    fn show_dump(&mut self, dump: String) {
+        self.dump_outline = DumpOutline::parse(&dump);
+        self.refresh_dump();
+    }
+
+    ///
+    /// Renders the outline of the selected fiber's dump.
+    /// Lines that changed since the previous snapshot of the fiber are marked until the next one,
+    /// the removed ones being shown where they were.
+    fn refresh_dump(&mut self) {
+        let rendered = self.dump_outline.render();
+        let previous = self.selected_fiber()
+            .and_then(|n| self.fiber_nodes.get(n))
+            .and_then(|f| self.previous_dumps.get(&f.id));
+        let (text, line_sections, changes) = match previous {
+            Some(dump) => {
+                let mut outline = DumpOutline::parse(dump);
+                outline.fold_like(&self.dump_outline);
+                let mut lines = vec![];
+                let mut line_sections = vec![];
+                let mut changes = vec![];
+                let mut shown = 0;
+                for (change, line) in diff::diff_lines(&outline.render().text, &rendered.text) {
+                    // removed lines belong to the section of the line they're shown before
+                    let section = rendered.line_sections.get(shown).or_else(|| rendered.line_sections.last());
+                    line_sections.push(section.cloned().flatten());
+                    if change != LineChange::Removed {
+                        shown += 1;
+                    }
+                    changes.push(change);
+                    lines.push(line);
+                }
+                (lines.join("\n"), line_sections, changes)
+            }
+            None => {
+                let changes = vec![LineChange::Unchanged; rendered.line_sections.len()];
+                (rendered.text, rendered.line_sections, changes)
+            }
+        };
+        self.selected_fiber_dump = (text, line_sections.len() as u16);
+        self.dump_line_sections = line_sections;
+        self.dump_changes = changes;
+    }
+
+    /// Folds or unfolds the section of the dump shown at the top of the dump panel
+    pub fn toggle_dump_section(&mut self) {
+        if let Some(Some(i)) = self.dump_line_sections.get(self.scroll as usize).cloned() {
+            self.dump_outline.toggle(i);
+            self.refresh_dump();
+            let heading = self.dump_line_sections.iter().position(|s| *s == Some(i));
+            self.scroll = heading.unwrap_or(0) as u16;
+        }
     }
 
-    fn prepare_dump(s: String) -> (String, u16) {
-        (s.clone(), s.lines().collect::<Vec<&str>>().len() as u16)
+    /// Folds every section of the dump, or unfolds them all if they're already folded
+    pub fn toggle_dump_sections(&mut self) {
+        self.dump_outline.toggle_all();
+        self.refresh_dump();
+        self.scroll = 0;
     }
 }
 
 pub struct SlickTab {
     pub has_hikari: bool,
-    pub slick_metrics: VecDeque<SlickMetrics>,
+    /// Why the HikariCP pool can't be charted, shown in place of its charts
+    pub hikari_missing: Option<String>,
+    pub slick_metrics: History<SlickMetrics>,
     pub slick_config: SlickConfig,
-    pub hikari_metrics: VecDeque<HikariMetrics>,
+    /// Percentage of the executor threads that were active, once `max_threads` is known
+    pub thread_utilization: History<f64>,
+    pub hikari_metrics: History<HikariMetrics>,
+    pub has_statements: bool,
+    /// Why there are no statement timers, shown in place of the statement list until some get registered
+    pub statements_missing: Option<String>,
+    /// The slowest statements as of the last poll
+    pub statements: Vec<StatementStats>,
+    /// Whether failure counters of the pool are configured
+    pub has_pool_errors: bool,
+    pub pool_error_rates: History<PoolErrorRates>,
+    /// Why the failure counters couldn't be read on the last poll
+    pub pool_errors_error: Option<String>,
+    last_pool_error_counts: Option<(Instant, PoolErrorCounts)>,
+    pub availability: Availability,
+    pub backoff: Backoff,
 }
 
 impl SlickTab {
+    /// Most recent samples shown as bars
     pub const MAX_SLICK_MEASURES: usize = 25;
-    pub const MAX_HIKARI_MEASURES: usize = 100;
 
-    pub fn new() -> SlickTab {
+    pub fn new(retention: Retention, availability_window: Duration, tick_rate: Duration) -> SlickTab {
         SlickTab {
             has_hikari: false,
-            slick_metrics: VecDeque::new(),
+            hikari_missing: None,
+            slick_metrics: History::new(retention),
             slick_config: SlickConfig { max_threads: 0, max_queue_size: 0 },
-            hikari_metrics: VecDeque::new(),
+            thread_utilization: History::new(retention),
+            hikari_metrics: History::new(retention),
+            has_statements: false,
+            statements_missing: None,
+            statements: vec![],
+            has_pool_errors: false,
+            pool_error_rates: History::new(retention),
+            pool_errors_error: None,
+            last_pool_error_counts: None,
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
         }
     }
 
@@ -175,38 +922,172 @@ impl SlickTab {
         self.slick_config = m
     }
 
-    pub fn append_slick_metrics(&mut self, m: SlickMetrics) {
-        if self.slick_metrics.len() > SlickTab::MAX_SLICK_MEASURES {
-            self.slick_metrics.pop_front();
+    pub fn append_slick_metrics(&mut self, m: SlickMetrics, at: Instant) {
+        if self.slick_config.max_threads > 0 {
+            self.thread_utilization.push_back_at(m.active_threads as f64 * 100.0 / self.slick_config.max_threads as f64, at);
+        }
+        self.slick_metrics.push_back_at(m, at);
+    }
+
+    pub fn append_hikari_metrics(&mut self, m: HikariMetrics, at: Instant) {
+        self.hikari_metrics.push_back_at(m, at);
+    }
+
+    /// Charts the failures per second since the previous poll of the counters
+    pub fn append_pool_error_counts(&mut self, counts: PoolErrorCounts, now: Instant) {
+        if let Some((at, prev)) = &self.last_pool_error_counts {
+            let secs = now.duration_since(*at).as_secs_f64();
+            self.pool_error_rates.push_back_at(PoolErrorRates::between(prev, &counts, secs), now);
+        }
+        self.last_pool_error_counts = Some((now, counts));
+        self.pool_errors_error = None;
+    }
+}
+
+pub struct JVMTab {
+    pub attributes: Vec<(String, JMXValue)>,
+    pub runtime: History<JVMRuntimeMetrics>,
+    pub error: Option<String>,
+}
+
+impl JVMTab {
+    pub fn new(retention: Retention) -> JVMTab {
+        JVMTab { attributes: vec![], runtime: History::new(retention), error: None }
+    }
+
+    pub fn append_runtime_metrics(&mut self, m: JVMRuntimeMetrics, at: Instant) {
+        self.runtime.push_back_at(m, at);
+    }
+
+    pub fn replace_attributes(&mut self, attributes: Vec<(String, JMXValue)>) {
+        self.attributes = attributes;
+        self.error = None;
+    }
+}
+
+/// Series of the watched attributes, a pattern having one per matching bean
+pub struct CustomTab {
+    pub labels: Vec<String>,
+    pub values: Vec<History<f64>>,
+    pub latest: Vec<Option<f64>>,
+    pub error: Option<String>,
+    retention: Retention,
+}
+
+impl CustomTab {
+    /// Series of the attributes that aren't patterns, the beans matching patterns are only known once polled
+    pub fn new(watchlist: &[WatchedAttribute], retention: Retention) -> CustomTab {
+        let labels: Vec<String> = watchlist.iter().filter(|w| !w.is_pattern()).map(|w| w.label.to_owned()).collect();
+        CustomTab {
+            values: labels.iter().map(|_| History::new(retention)).collect(),
+            latest: labels.iter().map(|_| None).collect(),
+            labels,
+            error: None,
+            retention,
         }
-        self.slick_metrics.push_back(m);
     }
 
-    pub fn append_hikari_metrics(&mut self, m: HikariMetrics) {
-        if self.hikari_metrics.len() > SlickTab::MAX_HIKARI_MEASURES {
-            self.hikari_metrics.pop_front();
+    ///
+    /// Appends labelled values in the watchlist order, unavailable ones are left out of the history.
+    /// Series of beans that appeared since the last poll are added, and the ones of beans that are gone removed.
+    pub fn append_values(&mut self, values: Vec<(String, Option<f64>)>, at: Instant) {
+        let mut histories: HashMap<String, History<f64>> = self.labels.drain(..).zip(self.values.drain(..)).collect();
+        self.latest.clear();
+        for (label, v) in values {
+            let mut history = histories.remove(&label).unwrap_or_else(|| History::new(self.retention));
+            if let Some(x) = v {
+                history.push_back_at(x, at);
+            }
+            self.labels.push(label);
+            self.values.push(history);
+            self.latest.push(v);
         }
-        self.hikari_metrics.push_back(m);
+        self.error = None;
     }
 }
 
 pub struct AkkaActorTreeTab {
     pub actors: StatefulList<String>,
-    pub actor_counts: VecDeque<u64>,
+    pub actor_nodes: Vec<ActorTreeNode>,
+    /// Actors created or terminated between tree refreshes with the time they were noticed, the oldest first
+    pub actor_events: VecDeque<(String, ActorEvent)>,
+    /// Columns shown after the tree in the actor list
+    pub columns: Vec<Column>,
+    pub actor_counts: History<u64>,
+    pub watches: Vec<ActorWatch>,
+    /// Actor count of each watch on every tree refresh, in the order of `watches`
+    pub watch_counts: Vec<History<u64>>,
+    pub has_health_checks: bool,
+    /// Why Akka Management doesn't serve health checks, shown in place of them
+    pub health_missing: Option<String>,
+    pub health: History<AkkaHealth>,
+    pub has_sharding: bool,
+    /// Why Akka Management doesn't serve cluster sharding, shown in place of it
+    pub sharding_missing: Option<String>,
+    pub sharding: ShardingStats,
+    pub sharding_error: Option<String>,
+    /// Shards that moved between nodes with the time they were noticed, the oldest first
+    pub rebalances: VecDeque<(String, ShardMove)>,
+    /// Restarts per supervisor, when an application log is tailed for them
+    pub restarts: Option<RestartCounts>,
+    pub restarts_error: Option<String>,
+    pub availability: Availability,
+    pub backoff: Backoff,
 }
 
 impl AkkaActorTreeTab {
+    /// Most recent actor counts shown as bars
     pub const MAX_ACTOR_COUNT_MEASURES: usize = 25;
+    pub const MAX_REBALANCES: usize = 50;
+    pub const MAX_ACTOR_EVENTS: usize = 200;
 
-    pub fn new() -> AkkaActorTreeTab {
-        AkkaActorTreeTab { actors: StatefulList::with_items(vec![]), actor_counts: VecDeque::new() }
+    pub fn new(
+        columns: Vec<Column>,
+        has_health_checks: bool,
+        has_sharding: bool,
+        watches: Vec<ActorWatch>,
+        retention: Retention,
+        availability_window: Duration,
+        tick_rate: Duration) -> AkkaActorTreeTab {
+        AkkaActorTreeTab {
+            actors: StatefulList::with_items(vec![]),
+            actor_nodes: vec![],
+            actor_events: VecDeque::new(),
+            columns,
+            actor_counts: History::new(retention),
+            watch_counts: watches.iter().map(|_| History::new(retention)).collect(),
+            watches,
+            has_health_checks,
+            health_missing: None,
+            health: History::new(retention),
+            has_sharding,
+            sharding_missing: None,
+            sharding: ShardingStats::default(),
+            sharding_error: None,
+            rebalances: VecDeque::new(),
+            restarts: None,
+            restarts_error: None,
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
+        }
     }
 
-    pub fn update_actor_tree(&mut self, actors: Vec<ActorTreeNode>) {
-        let mut list: Vec<String> = tree::tree_list_widget(actors, false)
-            .iter()
-            .map(|x| x.0.to_owned())
-            .collect();
+    pub fn update_actor_tree(&mut self, actors: Vec<ActorTreeNode>, at: Instant) {
+        // the first tree is the baseline, not a burst of created actors
+        if !self.actor_nodes.is_empty() {
+            let time = logging::utc_time();
+            for e in ActorEvent::between(&self.actor_nodes, &actors) {
+                if self.actor_events.len() >= AkkaActorTreeTab::MAX_ACTOR_EVENTS {
+                    self.actor_events.pop_front();
+                }
+                self.actor_events.push_back((time.to_owned(), e));
+            }
+        }
+        for (watch, counts) in self.watches.iter().zip(self.watch_counts.iter_mut()) {
+            counts.push_back_at(watch.count(&actors), at);
+        }
+        self.actor_nodes = actors.clone();
+        let mut list: Vec<String> = tree::format_rows(&tree::tree_rows(actors, false), &self.columns);
 
         self.actors.items.clear();
         self.actors.items.append(&mut list);
@@ -220,126 +1101,2023 @@ impl AkkaActorTreeTab {
         self.actors.next();
     }
 
-    pub fn append_actor_count(&mut self, c: u64) {
-        if self.actor_counts.len() > AkkaActorTreeTab::MAX_ACTOR_COUNT_MEASURES {
-            self.actor_counts.pop_front();
-        }
-        self.actor_counts.push_back(c);
+    pub fn append_actor_count(&mut self, c: u64, at: Instant) {
+        self.actor_counts.push_back_at(c, at);
     }
-}
-
-pub struct StatefulList<T> {
-    pub state: ListState,
-    pub items: Vec<T>,
-}
 
-impl<T> StatefulList<T> {
-    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
-        StatefulList {
-            state: ListState::default(),
-            items,
-        }
+    pub fn append_health(&mut self, h: AkkaHealth, at: Instant) {
+        self.health.push_back_at(h, at);
     }
 
-    pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
+    pub fn append_sharding(&mut self, stats: ShardingStats) {
+        let time = logging::utc_time();
+        for m in stats.moves_since(&self.sharding) {
+            if self.rebalances.len() >= AkkaActorTreeTab::MAX_REBALANCES {
+                self.rebalances.pop_front();
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
+            self.rebalances.push_back((time.to_owned(), m));
+        }
+        self.sharding = stats;
+        self.sharding_error = None;
     }
 
-    pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
+    pub fn record_restarts(&mut self, actors: &[String], at: Instant) {
+        if let Some(restarts) = self.restarts.as_mut() {
+            for a in actors {
+                restarts.record(a, at);
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
+        }
+        self.restarts_error = None;
     }
 }
 
-pub struct App<'a> {
-    pub title: &'a str,
-    pub should_quit: bool,
-    pub exit_reason: Option<String>,
-    pub tabs: TabsState<'a>,
-    pub zmx: Option<ZMXTab>,
-    pub slick: Option<SlickTab>,
-    pub actor_tree: Option<AkkaActorTreeTab>,
+pub struct RedisTab {
+    pub info: History<RedisInfo>,
+    pub hit_rates: History<f64>,
+    pub slowlog: Vec<SlowlogEntry>,
+    pub availability: Availability,
+    pub backoff: Backoff,
 }
 
-impl<'a> App<'a> {
-    pub fn new(
-        title: &'a str,
-        zio_zmx_addr: Option<String>,
-        jmx: Option<JMXConnectionSettings>,
-        akka: Option<AkkaSettings>) -> App<'a> {
-        let mut tabs: Vec<Tab> = vec![];
-
-        if let Some(_) = zio_zmx_addr {
-            tabs.push(Tab { kind: TabKind::ZMX, title: "ZIO" })
+impl RedisTab {
+    pub fn new(retention: Retention, availability_window: Duration, tick_rate: Duration) -> RedisTab {
+        RedisTab {
+            info: History::new(retention),
+            hit_rates: History::new(retention),
+            slowlog: vec![],
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
         }
+    }
+
+    pub fn append_stats(&mut self, stats: RedisStats, at: Instant) {
+        if let Some(rate) = self.info.back().and_then(|prev| stats.info.hit_rate_since(prev)) {
+            self.hit_rates.push_back_at(rate, at);
+        }
+        self.info.push_back_at(stats.info, at);
+        self.slowlog = stats.slowlog;
+    }
+}
+
+pub struct ConsulTab {
+    pub services: Vec<ServiceHealth>,
+    /// Recent statuses of each service, the oldest first
+    pub history: HashMap<String, History<HealthStatus>>,
+    pub availability: Availability,
+    pub backoff: Backoff,
+    retention: Retention,
+}
+
+impl ConsulTab {
+    /// Most recent statuses shown next to each service
+    pub const MAX_CONSUL_MEASURES: usize = 50;
+
+    pub fn new(retention: Retention, availability_window: Duration, tick_rate: Duration) -> ConsulTab {
+        ConsulTab {
+            services: vec![],
+            history: HashMap::new(),
+            retention,
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
+        }
+    }
+
+    pub fn append_services(&mut self, services: Vec<ServiceHealth>, at: Instant) {
+        for s in services.iter() {
+            let retention = self.retention;
+            self.history.entry(s.name.to_owned()).or_insert_with(|| History::new(retention)).push_back_at(s.status, at);
+        }
+        self.services = services;
+    }
+
+    /// Number of services currently in the given status
+    pub fn count(&self, status: HealthStatus) -> usize {
+        self.services.iter().filter(|s| s.status == status).count()
+    }
+}
+
+/// State of the ensemble on a probe, charted on the Zookeeper tab
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZkEnsembleSample {
+    /// Voting members serving requests
+    pub serving: usize,
+    /// Highest average latency (in ms) of the nodes that answered `mntr`
+    pub max_latency: f64,
+    /// New leader, or none, if the leader changed since the previous probe
+    pub leader_change: Option<Option<String>>,
+}
+
+/// The fewest members serving and the highest latency of the bucket, along with its last leader change
+impl Downsample for ZkEnsembleSample {
+    fn merge(&mut self, later: ZkEnsembleSample) {
+        self.serving = self.serving.min(later.serving);
+        self.max_latency = self.max_latency.max(later.max_latency);
+        if later.leader_change.is_some() {
+            self.leader_change = later.leader_change;
+        }
+    }
+}
+
+/// Latest probe of the Zookeeper ensemble, shown in the status bar and on the Zookeeper tab
+pub struct ZookeeperStatus {
+    pub nodes: Vec<ZkNodeStatus>,
+    /// Leader changes with the time they were noticed and the new leader, the oldest first
+    pub leader_changes: VecDeque<(String, Option<String>)>,
+    /// Probes of the ensemble, leader changes marked on the one they were noticed on
+    pub samples: History<ZkEnsembleSample>,
+    /// Addresses of the nodes that told they're observers, still left out of the quorum while they don't respond
+    pub observers: BTreeSet<String>,
+    /// Brokers of the Kafka cluster registered in the ensemble, once read, with `zookeeper_kafka`
+    pub brokers: Option<Result<Vec<KafkaBroker>, String>>,
+    polled: bool,
+    pub backoff: Backoff,
+    pub console: ZookeeperConsole,
+}
+
+impl ZookeeperStatus {
+    pub const MAX_LEADER_CHANGES: usize = 50;
+
+    pub fn new(nodes: &[String], retention: Retention, tick_rate: Duration) -> ZookeeperStatus {
+        ZookeeperStatus {
+            nodes: nodes.iter()
+                .map(|address| ZkNodeStatus {
+                    address: address.to_owned(),
+                    state: ZkNodeState::NotResponding,
+                    mode: None,
+                    server_id: None,
+                    zxid: None,
+                    error: None,
+                    monitor: None,
+                    not_whitelisted: vec![],
+                    admin_fallback: false,
+                    config: None,
+                })
+                .collect(),
+            leader_changes: VecDeque::new(),
+            samples: History::new(retention),
+            observers: BTreeSet::new(),
+            brokers: None,
+            polled: false,
+            backoff: Backoff::new(tick_rate),
+            console: ZookeeperConsole::default(),
+        }
+    }
+
+    ///
+    /// Polling slows down only while the whole ensemble is down.
+    /// Returns whether the leader changed since the previous poll, which includes losing it.
+    pub fn replace_nodes(&mut self, nodes: Vec<ZkNodeStatus>, at: Instant) -> bool {
+        self.backoff.on_response(nodes.iter().any(|n| n.state != ZkNodeState::NotResponding));
+        let previous = self.leader().map(|l| l.to_owned());
+        for n in nodes.iter().filter(|n| n.mode.is_some()) {
+            if n.is_observer() {
+                self.observers.insert(n.address.to_owned());
+            } else {
+                self.observers.remove(&n.address);
+            }
+        }
+        self.nodes = nodes;
+        let leader = self.leader().map(|l| l.to_owned());
+        let changed = self.polled && leader != previous;
+        if changed {
+            if self.leader_changes.len() >= ZookeeperStatus::MAX_LEADER_CHANGES {
+                self.leader_changes.pop_front();
+            }
+            self.leader_changes.push_back((logging::utc_time(), leader.clone()));
+        }
+        self.samples.push_back_at(ZkEnsembleSample {
+            serving: self.voters().0,
+            max_latency: self.nodes.iter().filter_map(|n| n.monitor.as_ref()).map(|m| m.avg_latency).fold(0.0, f64::max),
+            leader_change: if changed { Some(leader) } else { None },
+        }, at);
+        self.polled = true;
+        changed
+    }
+
+    /// Address of the node in leader mode
+    pub fn leader(&self) -> Option<&str> {
+        self.nodes.iter()
+            .find(|n| n.mode.as_deref() == Some("leader"))
+            .map(|n| n.address.as_str())
+    }
+
+    pub fn count(&self, state: ZkNodeState) -> usize {
+        self.nodes.iter().filter(|n| n.state == state).count()
+    }
+
+    /// Voting members serving requests, and voting members of the ensemble, i.e. the nodes that aren't observers
+    pub fn voters(&self) -> (usize, usize) {
+        let voters: Vec<&ZkNodeStatus> = self.nodes.iter().filter(|n| !self.observers.contains(&n.address)).collect();
+        (voters.iter().filter(|n| n.state == ZkNodeState::Ok).count(), voters.len())
+    }
+
+    /// Whether a majority of the voting members serves requests, however many observers are down
+    pub fn has_quorum(&self) -> bool {
+        let (serving, voters) = self.voters();
+        serving > voters / 2
+    }
+
+    ///
+    /// e.g. `ZK: zk1:2181 imok follower, zk2:2181 imok leader, zk3:2181 not responding, last leader change 12:30:01 UTC`,
+    /// followed by the nodes whose view of the ensemble drifted, e.g. `, config drift on zk3:2181`
+    pub fn label(&self) -> String {
+        let nodes: Vec<String> = self.nodes.iter()
+            .map(|n| match &n.mode {
+                Some(mode) => format!("{} {} {}", n.address, n.state, mode),
+                None => format!("{} {}", n.address, n.state),
+            })
+            .collect();
+        let mut label = format!("ZK: {}", nodes.join(", "));
+        if let Some((time, _)) = self.leader_changes.back() {
+            label = format!("{}, last leader change {}", label, time);
+        }
+        let drift = config_drift(&self.nodes);
+        if !drift.is_empty() {
+            label = format!("{}, config drift on {}", label, drift.join(", "));
+        }
+        label
+    }
+}
+
+/// Four letter word commands typed on the Zookeeper tab against the selected node, and the raw response of the last one
+#[derive(Default)]
+pub struct ZookeeperConsole {
+    pub selected: usize,
+    /// Command being typed, while the prompt is open
+    pub input: Option<String>,
+    /// Address of the node, command and response of the last command sent
+    pub output: Option<(String, String, Result<String, String>)>,
+    pub scroll: u16,
+}
+
+impl ZookeeperConsole {
+    pub fn select_prev_node(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next_node(&mut self, nodes: usize) {
+        self.selected = (self.selected + 1).min(nodes.saturating_sub(1));
+    }
+
+    pub fn open_prompt(&mut self) {
+        self.input = Some(String::new());
+    }
+
+    pub fn close_prompt(&mut self) {
+        self.input = None;
+    }
+
+    pub fn on_input(&mut self, c: char) {
+        if let Some(input) = self.input.as_mut() {
+            if input.len() < 4 {
+                input.push(c);
+            }
+        }
+    }
+
+    pub fn on_backspace(&mut self) {
+        if let Some(input) = self.input.as_mut() {
+            input.pop();
+        }
+    }
+
+    ///
+    /// Closes the prompt, returning the typed command to send to the selected node.
+    /// A command that isn't a four letter word is shown as the output instead.
+    pub fn submit(&mut self, nodes: &[ZkNodeStatus]) -> Option<(usize, ZkCommand)> {
+        let input = self.input.take()?;
+        let address = nodes.get(self.selected)?.address.to_owned();
+        match ZkCommand::parse(&input) {
+            Ok(command) => Some((self.selected, command)),
+            Err(e) => {
+                self.output = Some((address, input, Err(e)));
+                self.scroll = 0;
+                None
+            }
+        }
+    }
+
+    pub fn show_response(&mut self, address: &str, command: ZkCommand, response: Result<String, String>) {
+        self.output = Some((address.to_owned(), command.as_str().to_owned(), response));
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        let lines = match &self.output {
+            Some((_, _, Ok(response))) => response.lines().count(),
+            _ => 0,
+        };
+        if (self.scroll as usize) < lines {
+            self.scroll += 1;
+        }
+    }
+}
+
+pub struct GcLogTab {
+    pub pauses: History<GcPause>,
+    /// Megabytes allocated per second between consecutive pauses
+    pub allocation_rates: History<f64>,
+    pub availability: Availability,
+    pub backoff: Backoff,
+}
+
+impl GcLogTab {
+    pub fn new(retention: Retention, availability_window: Duration, tick_rate: Duration) -> GcLogTab {
+        GcLogTab {
+            pauses: History::new(retention),
+            allocation_rates: History::new(retention),
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
+        }
+    }
+
+    ///
+    /// Appends the pauses logged since the previous read of the log, read at `at`. Each is placed
+    /// before the last one by the difference of their uptimes, or a ms apart if the log isn't decorated with them.
+    pub fn append_pauses(&mut self, pauses: Vec<GcPause>, at: Instant) {
+        let last_uptime = pauses.last().and_then(|p| p.uptime);
+        let count = pauses.len();
+        for (i, p) in pauses.into_iter().enumerate() {
+            let before = match (p.uptime, last_uptime) {
+                (Some(t), Some(last)) if last > t => Duration::from_secs_f64(last - t),
+                _ => Duration::from_millis((count - 1 - i) as u64),
+            };
+            let ended = at.checked_sub(before).unwrap_or(at);
+            if let Some(rate) = self.pauses.back().and_then(|prev| p.allocation_rate_since(prev)) {
+                self.allocation_rates.push_back_at(rate, ended);
+            }
+            self.pauses.push_back_at(p, ended);
+        }
+    }
+}
+
+pub struct PrometheusTab {
+    /// Metrics charted as set in the config file
+    pub metrics: Vec<String>,
+    /// Metrics charted after pinning them in the metric explorer
+    pub pinned: PinnedMetrics,
+    /// Names of all metrics of the last scrape
+    pub available: BTreeSet<String>,
+    /// Metric explorer, open while searching
+    pub search: Option<MetricSearch>,
+    pub series: Vec<String>,
+    /// Metric of each series
+    series_metrics: Vec<String>,
+    pub values: Vec<History<f64>>,
+    pub availability: Availability,
+    pub backoff: Backoff,
+    retention: Retention,
+}
+
+/// Query typed in the metric explorer and the metrics matching it
+pub struct MetricSearch {
+    pub query: String,
+    pub matches: StatefulList<String>,
+}
+
+impl PrometheusTab {
+    pub fn new(metrics: Vec<String>, pinned: PinnedMetrics, retention: Retention, availability_window: Duration, tick_rate: Duration) -> PrometheusTab {
+        PrometheusTab {
+            metrics,
+            pinned,
+            available: BTreeSet::new(),
+            search: None,
+            series: vec![],
+            series_metrics: vec![],
+            values: vec![],
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
+            retention,
+        }
+    }
+
+    ///
+    /// Appends samples of the charted metrics to their series, series seen for the first time are added at the end.
+    /// Names of the other metrics are kept for the metric explorer.
+    pub fn append_samples(&mut self, samples: Vec<PrometheusSample>, at: Instant) {
+        self.available = samples.iter().map(|s| s.metric.to_owned()).collect();
+        self.update_matches();
+        for s in samples {
+            if !self.is_charted(&s.metric) {
+                continue;
+            }
+            let i = match self.series.iter().position(|x| *x == s.series) {
+                Some(i) => i,
+                None => {
+                    self.series.push(s.series);
+                    self.series_metrics.push(s.metric);
+                    self.values.push(History::new(self.retention));
+                    self.series.len() - 1
+                }
+            };
+            self.values[i].push_back_at(s.value, at);
+        }
+    }
+
+    fn is_charted(&self, metric: &str) -> bool {
+        self.metrics.iter().chain(self.pinned.metrics.iter()).any(|m| m == metric)
+    }
+
+    pub fn open_search(&mut self) {
+        self.search = Some(MetricSearch { query: String::new(), matches: StatefulList::with_items(vec![]) });
+        self.update_matches();
+    }
+
+    pub fn close_search(&mut self) {
+        self.search = None;
+    }
+
+    pub fn on_search_input(&mut self, c: char) {
+        if let Some(s) = self.search.as_mut() {
+            s.query.push(c);
+        }
+        self.update_matches();
+    }
+
+    pub fn on_search_backspace(&mut self) {
+        if let Some(s) = self.search.as_mut() {
+            s.query.pop();
+        }
+        self.update_matches();
+    }
+
+    pub fn select_prev_match(&mut self) {
+        if let Some(s) = self.search.as_mut().filter(|s| !s.matches.items.is_empty()) {
+            s.matches.previous();
+        }
+    }
+
+    pub fn select_next_match(&mut self) {
+        if let Some(s) = self.search.as_mut().filter(|s| !s.matches.items.is_empty()) {
+            s.matches.next();
+        }
+    }
+
+    /// Re-runs the search, keeping the selected metric selected if it still matches
+    fn update_matches(&mut self) {
+        let available = &self.available;
+        if let Some(s) = self.search.as_mut() {
+            let selected = s.matches.state.selected().and_then(|i| s.matches.items.get(i)).cloned();
+            s.matches.items = explorer::search(&s.query, available.iter()).into_iter().map(|m| m.to_owned()).collect();
+            let i = selected.and_then(|m| s.matches.items.iter().position(|x| *x == m));
+            s.matches.state.select(if s.matches.items.is_empty() { None } else { Some(i.unwrap_or(0)) });
+        }
+    }
+
+    ///
+    /// Pins the metric selected in the metric explorer, or unpins it and drops its series if it's pinned already,
+    /// and saves the pinned metrics. Returns a notice for the status bar.
+    pub fn toggle_selected_pin(&mut self) -> Option<Result<String, String>> {
+        let s = self.search.as_ref()?;
+        let metric = s.matches.items.get(s.matches.state.selected()?)?.to_owned();
+        let pinned = self.pinned.toggle(&metric);
+        if !pinned && !self.metrics.contains(&metric) {
+            for i in (0..self.series.len()).rev() {
+                if self.series_metrics[i] == metric {
+                    self.series.remove(i);
+                    self.series_metrics.remove(i);
+                    self.values.remove(i);
+                }
+            }
+        }
+        Some(self.pinned.save().map(|_| format!("{} {}", if pinned { "Pinned" } else { "Unpinned" }, metric)))
+    }
+
+    pub fn is_pinned(&self, metric: &str) -> bool {
+        self.pinned.metrics.iter().any(|m| m == metric)
+    }
+}
+
+/// Series of a data source registered in `source::registered`
+pub struct SourceTab {
+    pub name: String,
+    pub panels: Vec<PanelSpec>,
+    pub series: Vec<String>,
+    pub values: Vec<History<f64>>,
+    pub availability: Availability,
+    pub backoff: Backoff,
+    retention: Retention,
+}
+
+impl SourceTab {
+    pub fn new(source: &dyn DataSource, retention: Retention, availability_window: Duration, tick_rate: Duration) -> SourceTab {
+        SourceTab {
+            name: source.name().to_owned(),
+            panels: source.panels(),
+            series: vec![],
+            values: vec![],
+            availability: Availability::new(availability_window),
+            backoff: Backoff::new(tick_rate),
+            retention,
+        }
+    }
+
+    /// Appends the samples to their series, series seen for the first time are added at the end
+    pub fn append_samples(&mut self, samples: Vec<Sample>, at: Instant) {
+        for s in samples {
+            let i = match self.series.iter().position(|x| *x == s.series) {
+                Some(i) => i,
+                None => {
+                    self.series.push(s.series);
+                    self.values.push(History::new(self.retention));
+                    self.series.len() - 1
+                }
+            };
+            self.values[i].push_back_at(s.value, at);
+        }
+    }
+
+    /// Series shown on the panel with their history, in the order they were first seen
+    pub fn panel_series(&self, panel: &PanelSpec) -> Vec<(&str, &History<f64>)> {
+        self.series.iter()
+            .zip(self.values.iter())
+            .filter(|(name, _)| panel.shows(name))
+            .map(|(name, values)| (name.as_str(), values))
+            .collect()
+    }
+}
+
+/// Key metrics of the environments passed with `--compare`
+pub struct CompareTab {
+    pub envs: Vec<CompareEnv>,
+}
+
+impl CompareTab {
+    pub fn new(profiles: &[String], retention: Retention) -> CompareTab {
+        CompareTab { envs: profiles.iter().map(|p| CompareEnv::new(p, retention)).collect() }
+    }
+}
+
+pub struct CompareEnv {
+    pub name: String,
+    pub fibers_total: History<f64>,
+    pub hikari_active: History<f64>,
+    pub actor_count: History<f64>,
+    /// Requests of the last poll that haven't been answered yet
+    pub pending: usize,
+    /// Why the environment isn't polled anymore, e.g. its JMX server couldn't be reached
+    pub error: Option<String>,
+}
+
+impl CompareEnv {
+    pub fn new(name: &str, retention: Retention) -> CompareEnv {
+        CompareEnv {
+            name: name.to_owned(),
+            fibers_total: History::new(retention),
+            hikari_active: History::new(retention),
+            actor_count: History::new(retention),
+            pending: 0,
+            error: None,
+        }
+    }
+
+    pub fn append_fibers_total(&mut self, total: usize, at: Instant) {
+        self.fibers_total.push_back_at(total as f64, at)
+    }
+
+    pub fn append_hikari_active(&mut self, active: i32, at: Instant) {
+        self.hikari_active.push_back_at(active as f64, at)
+    }
+
+    pub fn append_actor_count(&mut self, count: u64, at: Instant) {
+        self.actor_count.push_back_at(count as f64, at)
+    }
+}
+
+pub struct StatefulList<T> {
+    pub state: ListState,
+    pub items: Vec<T>,
+    /// Position of the first item shown, see `visible_items`
+    pub offset: usize,
+}
+
+impl<T> StatefulList<T> {
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        StatefulList {
+            state: ListState::default(),
+            items,
+            offset: 0,
+        }
+    }
+
+    ///
+    /// Items that fit in a list of the given height, scrolled so the selected item stays visible,
+    /// along with the position of the selected item among them.
+    /// Only these are rendered, so drawing doesn't slow down with the length of the list.
+    pub fn visible_items(&mut self, height: usize) -> (&[T], Option<usize>) {
+        let selected = self.state.selected();
+        if let Some(s) = selected {
+            if s < self.offset {
+                self.offset = s;
+            } else if height > 0 && s >= self.offset + height {
+                self.offset = s + 1 - height;
+            }
+        }
+        // the list may have shrunk since the last frame
+        self.offset = self.offset.min(self.items.len().saturating_sub(height));
+        let end = (self.offset + height).min(self.items.len());
+        (&self.items[self.offset..end], selected.filter(|s| *s < end).map(|s| s - self.offset))
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.items.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.items.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+}
+
+pub struct App<'a> {
+    pub title: &'a str,
+    pub should_quit: bool,
+    pub exit_reason: Option<String>,
+    /// Result of the last user action, shown in the status bar
+    pub notice: Option<String>,
+    /// Action waiting for the user to confirm it with `y`
+    pub pending_action: Option<JmxAction>,
+    /// Command being typed in the command line opened with `:`, e.g. `connect zmx localhost:6789`
+    pub command_line: Option<String>,
+    /// Tab index and panel of the startup view, until the panel is shown and focused, see `open_view`
+    startup_panel: Option<(usize, Panel)>,
+    /// Optional features of the sources, probed once they're connected
+    pub capabilities: Capabilities,
+    /// Display names and tags of the sources
+    pub labels: SourceLabels,
+    /// Sources that couldn't be reached since the start, shown instead of the tabs
+    pub diagnostics: Diagnostics,
+    /// Skew of the sources' clocks, the skewed ones being shown in the status bar
+    pub clock: ClockSkew,
+    /// Patterns scrubbed from exported and uploaded dumps, while it's turned on
+    pub scrub: Scrubber,
+    pub tabs: TabsState<'a>,
+    /// Layout of the Dashboard tab, if there's one
+    pub dashboard: Option<Dashboard>,
+    pub zmx: Option<ZMXTab>,
+    pub slick: Option<SlickTab>,
+    pub jvm: Option<JVMTab>,
+    pub custom: Option<CustomTab>,
+    pub actor_tree: Option<AkkaActorTreeTab>,
+    pub redis: Option<RedisTab>,
+    pub consul: Option<ConsulTab>,
+    pub gc_log: Option<GcLogTab>,
+    pub prometheus: Option<PrometheusTab>,
+    pub sources: Vec<SourceTab>,
+    pub compare: Option<CompareTab>,
+    pub zookeeper: Option<ZookeeperStatus>,
+    pub series_view: SeriesView,
+    /// Time range and freeze of the charts set apart from the shared view, with `t` and `P`
+    pub panel_views: HashMap<Panel, PanelView>,
+    pub latency: PollLatency,
+    pub derived: Option<DerivedSeries>,
+    pub hooks: Option<Hooks>,
+    pub logger: Logger,
+}
+
+impl<'a> App<'a> {
+    /// Fibers listed in the incident summary
+    pub const INCIDENT_STUCK_FIBERS: usize = 5;
+    /// Fired alerts listed in the incident summary, the most recent ones
+    pub const INCIDENT_RECENT_ALERTS: usize = 5;
+
+    pub fn new(
+        title: &'a str,
+        zio_zmx_addr: Option<String>,
+        jmx: Option<JMXConnectionSettings>,
+        watchlist: Vec<WatchedAttribute>,
+        list_columns: ListColumns,
+        fiber_label: Option<FiberLabel>,
+        akka: Option<AkkaSettings>,
+        has_sharding: bool,
+        actor_watches: Vec<ActorWatch>,
+        redis_addr: Option<String>,
+        consul_addr: Option<String>,
+        zookeeper_nodes: Vec<String>,
+        has_gc_log: bool,
+        prometheus: Option<(Vec<String>, PinnedMetrics)>,
+        sources: &'a [Box<dyn DataSource>],
+        compare: Vec<String>,
+        derived: Vec<DerivedMetric>,
+        retention: Retention,
+        labels: SourceLabels,
+        hooks: Option<Hooks>,
+        logger: Logger,
+        availability_window: Duration,
+        sample_interval: Duration) -> App<'a> {
+        let mut tabs: Vec<Tab> = vec![];
+
+        if let Some(_) = zio_zmx_addr {
+            tabs.push(Tab::new(TabKind::ZMX, "ZIO"))
+        }
+
+        if let Some(_) = jmx {
+            tabs.push(Tab::new(TabKind::Slick, "Slick"));
+            tabs.push(Tab::new(TabKind::JVM, "JVM"));
+            if !watchlist.is_empty() {
+                tabs.push(Tab::new(TabKind::Custom, "Custom"));
+            }
+        }
+
+        if let Some(_) = akka {
+            tabs.push(Tab::new(TabKind::AkkaActorTree, "Akka"))
+        }
+
+        if let Some(_) = redis_addr {
+            tabs.push(Tab::new(TabKind::Redis, "Redis"))
+        }
+
+        if let Some(_) = consul_addr {
+            tabs.push(Tab::new(TabKind::Consul, "Consul"))
+        }
+
+        if has_gc_log {
+            tabs.push(Tab::new(TabKind::GcLog, "GC"))
+        }
+
+        if prometheus.is_some() {
+            tabs.push(Tab::new(TabKind::Prometheus, "Prometheus"))
+        }
+
+        for (i, s) in sources.iter().enumerate() {
+            tabs.push(Tab::new(TabKind::Source(i), s.name()))
+        }
+
+        if !compare.is_empty() {
+            tabs.push(Tab::new(TabKind::Compare, "Compare"))
+        }
+
+        if !derived.is_empty() {
+            tabs.push(Tab::new(TabKind::Derived, "Derived"))
+        }
+
+        if let Some(_) = hooks {
+            tabs.push(Tab::new(TabKind::Hooks, "Hooks"))
+        }
+
+        if !zookeeper_nodes.is_empty() {
+            tabs.push(Tab::new(TabKind::Zookeeper, "Zookeeper"))
+        }
+
+        tabs.push(Tab::new(TabKind::Latency, "Latency"));
+        tabs.push(Tab::new(TabKind::Log, "Log"));
+
+        App {
+            title,
+            should_quit: false,
+            exit_reason: None,
+            notice: None,
+            pending_action: None,
+            command_line: None,
+            startup_panel: None,
+            capabilities: Capabilities::default(),
+            labels,
+            diagnostics: Diagnostics::default(),
+            clock: ClockSkew::new(vec![], ClockSkew::DEFAULT_THRESHOLD),
+            scrub: Scrubber::default(),
+            tabs: TabsState::new(tabs),
+            dashboard: None,
+            zmx: zio_zmx_addr.map(|_| ZMXTab::new(list_columns.fibers.clone(), fiber_label, retention, availability_window, sample_interval)),
+            slick: jmx.as_ref().map(|_| SlickTab::new(retention, availability_window, sample_interval)),
+            custom: jmx.as_ref().filter(|_| !watchlist.is_empty()).map(|_| CustomTab::new(&watchlist, retention)),
+            jvm: jmx.map(|_| JVMTab::new(retention)),
+            actor_tree: akka.map(|s| AkkaActorTreeTab::new(
+                list_columns.actors.clone(),
+                s.management_address.is_some(),
+                has_sharding,
+                actor_watches,
+                retention,
+                availability_window,
+                sample_interval,
+            )),
+            redis: redis_addr.map(|_| RedisTab::new(retention, availability_window, sample_interval)),
+            consul: consul_addr.map(|_| ConsulTab::new(retention, availability_window, sample_interval)),
+            gc_log: if has_gc_log { Some(GcLogTab::new(retention, availability_window, sample_interval)) } else { None },
+            prometheus: prometheus.map(|(metrics, pinned)| PrometheusTab::new(metrics, pinned, retention, availability_window, sample_interval)),
+            sources: sources.iter().map(|s| SourceTab::new(s.as_ref(), retention, availability_window, sample_interval)).collect(),
+            compare: if compare.is_empty() { None } else { Some(CompareTab::new(&compare, retention)) },
+            zookeeper: if zookeeper_nodes.is_empty() { None } else { Some(ZookeeperStatus::new(&zookeeper_nodes, retention, sample_interval)) },
+            series_view: SeriesView { history_span: retention.span(), ..SeriesView::new(sample_interval) },
+            panel_views: HashMap::new(),
+            latency: PollLatency::new(retention),
+            derived: if derived.is_empty() { None } else { Some(DerivedSeries::new(derived, retention)) },
+            hooks,
+            logger,
+        }
+    }
+
+    pub fn on_up(&mut self) {
+        match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_mut().unwrap().select_prev_fiber(),
+            TabKind::Slick => {}
+            TabKind::JVM => {}
+            TabKind::Custom => {}
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().select_prev_actor(),
+            TabKind::Redis => {}
+            TabKind::Consul => {}
+            TabKind::GcLog => {}
+            TabKind::Prometheus => self.prometheus.as_mut().unwrap().select_prev_match(),
+            TabKind::Source(_) => {}
+            TabKind::Compare => {}
+            TabKind::Derived => {}
+            TabKind::Hooks => {}
+            TabKind::Zookeeper => self.zookeeper.as_mut().unwrap().console.select_prev_node(),
+            TabKind::Latency => {}
+            TabKind::Log => {}
+            TabKind::Dashboard => {}
+        }
+    }
+
+    pub fn on_down(&mut self) {
+        match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_mut().unwrap().select_next_fiber(),
+            TabKind::Slick => {}
+            TabKind::JVM => {}
+            TabKind::Custom => {}
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().select_next_actor(),
+            TabKind::Redis => {}
+            TabKind::Consul => {}
+            TabKind::GcLog => {}
+            TabKind::Prometheus => self.prometheus.as_mut().unwrap().select_next_match(),
+            TabKind::Source(_) => {}
+            TabKind::Compare => {}
+            TabKind::Derived => {}
+            TabKind::Hooks => {}
+            TabKind::Zookeeper => {
+                let zookeeper = self.zookeeper.as_mut().unwrap();
+                zookeeper.console.select_next_node(zookeeper.nodes.len())
+            }
+            TabKind::Latency => {}
+            TabKind::Log => {}
+            TabKind::Dashboard => {}
+        }
+    }
+
+    /// Shows the dashboard as the first tab
+    pub fn show_dashboard(&mut self, dashboard: Dashboard) {
+        self.dashboard = Some(dashboard);
+        self.tabs.tabs.insert(0, Tab::new(TabKind::Dashboard, "Dashboard"));
+        self.tabs.index = 0;
+    }
+
+    /// Panels of the current tab that are actually shown
+    pub fn visible_panels(&self) -> Vec<Panel> {
+        if let (TabKind::Dashboard, Some(d)) = (&self.tabs.current().kind, &self.dashboard) {
+            return d.panels();
+        }
+        // panels of missing features are shown with the reason
+        let has_hikari = matches!(&self.slick, Some(s) if s.has_hikari || s.hikari_missing.is_some());
+        let has_statements = matches!(&self.slick, Some(s) if s.has_statements || s.statements_missing.is_some());
+        let has_pool_errors = matches!(&self.slick, Some(s) if s.has_pool_errors);
+        let has_sharding = matches!(&self.actor_tree, Some(t) if t.has_sharding || t.sharding_missing.is_some());
+        let has_restarts = matches!(&self.actor_tree, Some(t) if t.restarts.is_some());
+        self.tabs.current().kind.panels().into_iter()
+            .filter(|p| *p != Panel::Hikari || has_hikari)
+            .filter(|p| *p != Panel::Statements || has_statements)
+            .filter(|p| *p != Panel::PoolErrors || has_pool_errors)
+            .filter(|p| *p != Panel::Sharding || has_sharding)
+            .filter(|p| *p != Panel::ActorRestarts || has_restarts)
+            .collect()
+    }
+
+    pub fn focused_panel(&self) -> Panel {
+        let panels = self.visible_panels();
+        panels[self.tabs.current().focus % panels.len()]
+    }
+
+    ///
+    /// Selects the tab of the startup view and focuses its panel. The panels of features that are probed,
+    /// e.g. HikariCP, are only shown once found, so they're focused then unless the user moved on.
+    pub fn open_view(&mut self, view: &StartupView) {
+        self.run_step(Step::Tab(view.tab.to_owned()));
+        if self.tabs.current().title.eq_ignore_ascii_case(&view.tab) {
+            self.startup_panel = view.panel.map(|p| (self.tabs.index, p));
+            self.focus_startup_panel();
+        }
+    }
+
+    fn focus_startup_panel(&mut self) {
+        match self.startup_panel {
+            Some((tab, panel)) if tab == self.tabs.index => {
+                if let Some(i) = self.visible_panels().iter().position(|p| *p == panel) {
+                    self.tabs.current_mut().focus = i;
+                    self.startup_panel = None;
+                }
+            }
+            Some(_) => self.startup_panel = None,
+            None => {}
+        }
+    }
+
+    pub fn on_tab(&mut self) {
+        self.startup_panel = None;
+        let n = self.visible_panels().len();
+        let tab = self.tabs.current_mut();
+        tab.focus = (tab.focus + 1) % n;
+    }
+
+    /// Views of the charts with the time axis ending at the given time, usually when drawing
+    pub fn chart_views(&self, now: Instant) -> ChartViews {
+        ChartViews { shared: self.series_view.as_of(now), panels: self.panel_views.clone() }
+    }
+
+    /// Focused panel if it's a chart, or `None` with a notice telling to focus one
+    fn focused_chart(&mut self) -> Option<Panel> {
+        let panel = self.focused_panel();
+        if !panel.is_time_chart() {
+            self.notice = Some("Focus a chart with <Tab> first".to_owned());
+            return None;
+        }
+        Some(panel)
+    }
+
+    /// Cycles the time range of the focused chart through the last 5 and 15 minutes, the whole history and the shared range
+    pub fn cycle_time_range(&mut self) {
+        if let Some(panel) = self.focused_chart() {
+            let view = self.panel_views.entry(panel).or_default();
+            view.range = view.range.next();
+        }
+    }
+
+    /// Freezes the focused chart as it is now, or lets it follow the time again, while the other charts keep updating
+    pub fn toggle_freeze(&mut self) {
+        if let Some(panel) = self.focused_chart() {
+            let view = self.panel_views.entry(panel).or_default();
+            view.frozen_at = match view.frozen_at {
+                Some(_) => None,
+                None => Some(Instant::now()),
+            };
+        }
+    }
+
+    pub fn toggle_zoom(&mut self) {
+        let tab = self.tabs.current_mut();
+        tab.zoomed = !tab.zoomed;
+    }
+
+    /// Records the skew of a source's clock, logging it when it goes over the threshold
+    pub fn record_clock_skew(&mut self, source: &'static str, skew_ms: i64) {
+        if self.clock.record(source, skew_ms) {
+            let threshold = self.clock.threshold.to_string();
+            self.logger.log(LogLevel::Warn, "clock", &[("source", source), ("skew_ms", &skew_ms.to_string()), ("threshold_ms", &threshold)]);
+        }
+    }
+
+    /// Turns off polling of missing features, their panels show why they're missing instead
+    pub fn record_capabilities(&mut self, probed: Vec<Capability>) {
+        let features: Vec<(Feature, bool)> = probed.iter().map(|c| (c.feature, c.missing.is_none())).collect();
+        self.capabilities.record(probed);
+        for (feature, available) in features {
+            let placeholder = self.capabilities.placeholder(feature);
+            match (feature, self.slick.as_mut(), self.actor_tree.as_mut()) {
+                (Feature::HikariPool, Some(s), _) => {
+                    s.has_hikari = available;
+                    s.hikari_missing = placeholder;
+                }
+                // timers are usually registered on the first execution of their statement, so they're still polled
+                (Feature::StatementTimers, Some(s), _) => s.statements_missing = placeholder,
+                (Feature::AkkaHealthChecks, _, Some(t)) => {
+                    t.has_health_checks = available;
+                    t.health_missing = placeholder;
+                }
+                (Feature::ClusterSharding, _, Some(t)) => {
+                    t.has_sharding = available;
+                    t.sharding_missing = placeholder;
+                }
+                _ => {}
+            }
+        }
+        self.focus_startup_panel();
+    }
+
+    /// Sorts the focused table by its next column, or by none after the last one
+    pub fn sort_by_next_column(&mut self) {
+        let columns = self.focused_panel().table_columns().len();
+        if columns > 0 {
+            let tab = self.tabs.current_mut();
+            tab.sort = TableSort::next(tab.sort, columns);
+        }
+    }
+
+    /// Reverses the order of the focused table, if it's sorted
+    pub fn reverse_sort(&mut self) {
+        if !self.focused_panel().table_columns().is_empty() {
+            let tab = self.tabs.current_mut();
+            tab.sort = tab.sort.map(TableSort::reversed);
+        }
+    }
+
+    /// Runs a built-in action of a quick action, telling why in a notice when it doesn't apply
+    pub fn run_step(&mut self, step: Step) {
+        let failure = match step {
+            Step::Tab(title) => match self.tabs.tabs.iter().position(|t| t.title.eq_ignore_ascii_case(&title)) {
+                Some(i) => {
+                    self.tabs.select(i);
+                    None
+                }
+                None => Some(format!("No {} tab", title)),
+            },
+            Step::StatusFilter(status) => match self.zmx.as_mut() {
+                Some(zmx) => {
+                    zmx.filter_by_status(status);
+                    None
+                }
+                None => Some("No fiber list to filter".to_owned()),
+            },
+            Step::Sort(column, descending) => {
+                match self.focused_panel().table_columns().iter().position(|c| c.eq_ignore_ascii_case(&column)) {
+                    Some(column) => {
+                        self.tabs.current_mut().sort = Some(TableSort { column, descending });
+                        None
+                    }
+                    None => Some(format!("No {} column in the focused table", column)),
+                }
+            }
+            Step::Key(_) => None,
+        };
+        if failure.is_some() {
+            self.notice = failure;
+        }
+    }
+
+    /// Whether left/right keys should scroll the fiber dump instead of switching tabs
+    fn scrolls_fiber_dump(&self) -> bool {
+        self.focused_panel() == Panel::FiberDump && matches!(&self.zmx, Some(z) if !z.wrap)
+    }
+
+    pub fn on_right(&mut self) {
+        if self.scrolls_fiber_dump() {
+            self.zmx.as_mut().unwrap().scroll_right()
+        } else {
+            self.tabs.next();
+        }
+    }
+
+    pub fn on_left(&mut self) {
+        if self.scrolls_fiber_dump() {
+            self.zmx.as_mut().unwrap().scroll_left()
+        } else {
+            self.tabs.previous();
+        }
+    }
+
+    pub fn on_key(&mut self, c: char) {
+        match c {
+            'q' => self.quit(None),
+            'a' => self.series_view.toggle(SeriesKind::MovingAverage),
+            'r' => self.series_view.toggle(SeriesKind::RateOfChange),
+            'h' => self.series_view.toggle_long_range(),
+            'H' if matches!(self.tabs.current().kind, TabKind::JVM) => self.ask(JmxAction::HeapDump),
+            'T' if matches!(self.tabs.current().kind, TabKind::JVM) => self.ask(JmxAction::ThreadDump),
+            '[' => self.series_view.cursor_back(),
+            ']' => self.series_view.cursor_forward(),
+            'z' => self.toggle_zoom(),
+            't' => self.cycle_time_range(),
+            'P' => self.toggle_freeze(),
+            's' => self.sort_by_next_column(),
+            'S' => self.reverse_sort(),
+            'e' => self.export_tree(),
+            'E' => self.export_chart(),
+            'x' => self.notice = Some(self.scrub.toggle()),
+            'd' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.export_fiber_dumps(),
+            'd' if matches!(self.tabs.current().kind, TabKind::AkkaActorTree) => self.export_actor_tree(),
+            'w' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_wrap(),
+            'f' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_dump_section(),
+            'F' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_dump_sections(),
+            'g' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().cycle_status_filter(),
+            'o' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().cycle_fiber_sort(),
+            'p' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().select_parent_fiber(),
+            'c' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().select_first_child_fiber(),
+            'n' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().select_next_sibling_fiber(),
+            '1' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_status(FiberStatus::Running),
+            '2' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_status(FiberStatus::Done),
+            '3' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_status(FiberStatus::Finishing),
+            '4' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().toggle_status(FiberStatus::Suspended),
+            '/' if matches!(self.tabs.current().kind, TabKind::Prometheus) => self.prometheus.as_mut().unwrap().open_search(),
+            ':' if matches!(self.tabs.current().kind, TabKind::Zookeeper) => self.zookeeper.as_mut().unwrap().console.open_prompt(),
+            ':' if matches!(self.tabs.current().kind, TabKind::ZMX) => self.zmx.as_mut().unwrap().open_fiber_prompt(),
+            ':' => self.command_line = Some(String::new()),
+            _ => {}
+        }
+    }
+
+    /// Whether typed keys go to the metric explorer of the Prometheus tab
+    pub fn is_searching(&self) -> bool {
+        self.tabs.current().kind == TabKind::Prometheus && matches!(&self.prometheus, Some(p) if p.search.is_some())
+    }
+
+    /// Whether typed keys go to the prompt of the Zookeeper console
+    pub fn is_typing_command(&self) -> bool {
+        self.tabs.current().kind == TabKind::Zookeeper && matches!(&self.zookeeper, Some(z) if z.console.input.is_some())
+    }
+
+    /// Whether typed keys go to the command line
+    pub fn is_typing_command_line(&self) -> bool {
+        self.command_line.is_some()
+    }
+
+    pub fn on_command_line_input(&mut self, c: char) {
+        if let Some(input) = self.command_line.as_mut() {
+            input.push(c);
+        }
+    }
+
+    pub fn on_command_line_backspace(&mut self) {
+        if let Some(input) = self.command_line.as_mut() {
+            input.pop();
+        }
+    }
+
+    pub fn close_command_line(&mut self) {
+        self.command_line = None;
+    }
+
+    ///
+    /// Closes the command line, returning the source to connect to, if the command is valid
+    /// and there's no tab of that kind of source yet, telling why otherwise.
+    pub fn submit_command_line(&mut self) -> Option<Connection> {
+        let input = self.command_line.take()?;
+        let connection = match Connection::parse(&input) {
+            Ok(c) => c,
+            Err(e) => {
+                self.notice = Some(e);
+                return None;
+            }
+        };
+        let connected = match connection {
+            Connection::Zmx(_) => self.zmx.is_some(),
+            Connection::Redis(_) => self.redis.is_some(),
+            Connection::Consul(_) => self.consul.is_some(),
+        };
+        if connected {
+            self.notice = Some(format!("A {} source is already connected", connection.source()));
+            return None;
+        }
+        Some(connection)
+    }
+
+    ///
+    /// Adds the tab of a source connected in the command line, before the Latency and Log tabs, and shows it.
+    /// The tab is set up as it would have been at startup.
+    pub fn connect(&mut self, connection: &Connection, list_columns: &ListColumns, fiber_label: Option<FiberLabel>, retention: Retention, availability_window: Duration) {
+        let sample_interval = self.series_view.sample_interval;
+        let tab = match connection {
+            Connection::Zmx(_) => {
+                self.zmx = Some(ZMXTab::new(list_columns.fibers.clone(), fiber_label, retention, availability_window, sample_interval));
+                Tab::new(TabKind::ZMX, "ZIO")
+            }
+            Connection::Redis(_) => {
+                self.redis = Some(RedisTab::new(retention, availability_window, sample_interval));
+                Tab::new(TabKind::Redis, "Redis")
+            }
+            Connection::Consul(_) => {
+                self.consul = Some(ConsulTab::new(retention, availability_window, sample_interval));
+                self.clock.sources.push("consul");
+                Tab::new(TabKind::Consul, "Consul")
+            }
+        };
+        let i = self.tabs.tabs.iter().position(|t| t.kind == TabKind::Latency).unwrap_or(self.tabs.tabs.len());
+        self.tabs.tabs.insert(i, tab);
+        self.tabs.select(i);
+        self.startup_panel = None;
+        self.notice = Some(format!("Connected {} at {}", connection.source(), connection.address()));
+    }
+
+    /// Whether typed keys go to the prompt of the fiber to jump to on the ZIO tab
+    pub fn is_typing_fiber_id(&self) -> bool {
+        self.tabs.current().kind == TabKind::ZMX && matches!(&self.zmx, Some(z) if z.fiber_prompt.is_some())
+    }
+
+    /// Jumps to the fiber typed in the prompt of the ZIO tab, telling why if it isn't in the snapshot
+    pub fn jump_to_typed_fiber(&mut self) {
+        if let Err(e) = self.zmx.as_mut().unwrap().submit_fiber_prompt() {
+            self.notice = Some(e);
+        }
+    }
+
+    /// Command typed in the Zookeeper console along with the node to send it to, if it's a four letter word
+    pub fn submit_command(&mut self) -> Option<(usize, ZkCommand)> {
+        let zookeeper = self.zookeeper.as_mut()?;
+        zookeeper.console.submit(&zookeeper.nodes)
+    }
+
+    /// Pins or unpins the metric selected in the metric explorer
+    pub fn toggle_selected_pin(&mut self) {
+        match self.prometheus.as_mut().and_then(|p| p.toggle_selected_pin()) {
+            Some(Ok(notice)) => self.notice = Some(notice),
+            Some(Err(e)) => {
+                self.logger.log(LogLevel::Error, "pin", &[("error", &e)]);
+                self.notice = Some(e);
+            }
+            None => {}
+        }
+    }
+
+    /// Writes the fiber or actor tree of the current tab to a Graphviz DOT file in the working directory
+    pub fn export_tree(&mut self) {
+        let (name, dot) = match self.tabs.current().kind {
+            TabKind::ZMX => ("fibers", tree::tree_to_dot("fibers", self.zmx.as_ref().unwrap().snapshot().0, true)),
+            TabKind::AkkaActorTree => ("actors", tree::tree_to_dot("actors", &self.actor_tree.as_ref().unwrap().actor_nodes, false)),
+            _ => return,
+        };
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        self.write_export(&format!("{} tree", name), format!("{}-{}.dot", name, secs), dot);
+    }
+
+    /// Renders the chart of the current tab, as currently shown, to a PNG file in the working directory
+    pub fn export_chart(&mut self) {
+        let (name, title, series) = match self.chart_series() {
+            Some(c) => c,
+            None => {
+                self.notice = Some("No chart to export on this tab".to_owned());
+                return;
+            }
+        };
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let path = format!("{}-{}.png", name, secs);
+        let written = plot::write_png(&path, &title, &series, secs);
+        self.on_export(&format!("{} chart", name), path, written);
+    }
+
+    ///
+    /// Name, title and series of the main chart of the current tab, with the moving average or rate of change
+    /// and the time range of the chart applied, or `None` if the tab has no chart or nothing was charted yet.
+    fn chart_series(&self) -> Option<(String, String, ChartSeries)> {
+        let views = self.chart_views(Instant::now());
+        let view = &match self.tabs.current().kind {
+            TabKind::ZMX => views.of(Panel::FiberCounts),
+            TabKind::Slick => views.of(Panel::Hikari),
+            _ => views.shared,
+        };
+        let (name, title, series): (String, String, ChartSeries) = match self.tabs.current().kind {
+            TabKind::ZMX => {
+                let zmx = self.zmx.as_ref()?;
+                let series = ZMXTab::FILTERED_STATUSES.iter()
+                    .filter(|s| !zmx.hidden_statuses.contains(*s))
+                    .map(|s| (s.to_string(), view.apply(view.history_timeline(&zmx.fiber_counts, |c| c.count(s) as f64))))
+                    .collect();
+                ("fibers".to_owned(), "Fiber counts".to_owned(), series)
+            }
+            TabKind::Slick => {
+                let hikari = &self.slick.as_ref()?.hikari_metrics;
+                let fields: [(&str, fn(&HikariMetrics) -> i32); 4] = [
+                    ("total", |m| m.total),
+                    ("active", |m| m.active),
+                    ("idle", |m| m.idle),
+                    ("waiting", |m| m.waiting),
+                ];
+                let series = fields.iter()
+                    .map(|(field, f)| (field.to_string(), view.apply(view.history_timeline(hikari, |m| f(m) as f64))))
+                    .collect();
+                ("hikari".to_owned(), "HikariCP connections".to_owned(), series)
+            }
+            TabKind::AkkaActorTree => {
+                let counts = view.history_timeline(&self.actor_tree.as_ref()?.actor_counts, |c| *c as f64);
+                ("actors".to_owned(), "Running actors".to_owned(), vec![("actors".to_owned(), view.apply(counts))])
+            }
+            TabKind::Prometheus => {
+                let prometheus = self.prometheus.as_ref()?;
+                let series = prometheus.series.iter().zip(prometheus.values.iter())
+                    .map(|(name, values)| (name.to_owned(), view.apply(view.history_timeline(values, |v| *v))))
+                    .collect();
+                ("prometheus".to_owned(), "Prometheus metrics".to_owned(), series)
+            }
+            TabKind::Source(i) => {
+                let source = self.sources.get(i)?;
+                let series = source.series.iter().zip(source.values.iter())
+                    .map(|(name, values)| (name.to_owned(), view.apply(view.history_timeline(values, |v| *v))))
+                    .collect();
+                (source.name.to_lowercase().replace(' ', "-"), source.name.to_owned(), series)
+            }
+            TabKind::Derived => {
+                let derived = self.derived.as_ref()?;
+                let series = derived.metrics.iter().zip(derived.values.iter())
+                    .map(|(m, values)| (m.name.to_owned(), view.apply(view.history_timeline(values, |v| *v))))
+                    .collect();
+                ("derived".to_owned(), "Derived metrics".to_owned(), series)
+            }
+            _ => return None,
+        };
+        let title = format!("{}{}", title, view.title_suffix());
+        Some((name, title, series)).filter(|(_, _, s)| s.iter().any(|(_, data)| !data.is_empty()))
+    }
+
+    /// Writes every fiber of the last fiber dump, with its stack trace, to a JSON file in the working directory
+    pub fn export_fiber_dumps(&mut self) {
+        let (nodes, dumps) = self.zmx.as_ref().unwrap().snapshot();
+        if nodes.is_empty() {
+            self.notice = Some("No fiber dump to export, press <Enter> to dump fibers first".to_owned());
+            return;
+        }
+        let fibers: Vec<Fiber> = nodes.iter()
+            .zip(dumps.iter())
+            .map(|(fb, dump)| Fiber { dump: self.scrub.scrub(dump), ..fb.to_owned() })
+            .collect();
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let json = FiberDumpArchive { exported_at: secs, fibers: &fibers }.to_json();
+        let path = format!("{}.json", self.scrub.file_name(&format!("fiber-dumps-{}", secs)));
+        self.write_export("fiber dumps", path, json);
+    }
+
+    /// Writes every actor of the last loaded actor tree, with its parent and counts, to a JSON file in the working directory
+    pub fn export_actor_tree(&mut self) {
+        let actors = &self.actor_tree.as_ref().unwrap().actor_nodes;
+        if actors.is_empty() {
+            self.notice = Some("No actor tree to export, press <Enter> to load it first".to_owned());
+            return;
+        }
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let json = ActorTreeSnapshot::of(actors, secs).to_json();
+        self.write_export("actor tree", format!("actor-tree-{}.json", secs), json);
+    }
+
+    /// Name and content to upload to the paste service, if there's one, see `paste_content`
+    pub fn start_paste(&mut self, paste: Option<&PasteSettings>) -> Option<(String, String)> {
+        let paste = match paste {
+            Some(p) => p,
+            None => {
+                self.notice = Some("No paste service to upload to, set `paste` in the config file".to_owned());
+                return None;
+            }
+        };
+        let (name, contents) = self.paste_content();
+        self.notice = Some(format!("Uploading {} to {}...", name, paste.url_for(&name)));
+        Some((name, contents))
+    }
+
+    /// The dump of the selected fiber on the ZIO tab, or else a summary of the session
+    fn paste_content(&self) -> (String, String) {
+        if let Some(zmx) = self.zmx.as_ref().filter(|_| self.tabs.current().kind == TabKind::ZMX) {
+            if let Some(n) = zmx.selected_fiber() {
+                return (format!("fiber-{}", zmx.fiber_nodes[n].id), self.scrub.scrub(&zmx.fiber_dump_all[n]));
+            }
+        }
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        (format!("session-{}", secs), self.session_summary())
+    }
+
+    /// Availability, firing alerts and latest metric values of every source, as plain text
+    pub fn session_summary(&self) -> String {
+        let mut lines = vec![self.title.to_owned(), "Availability:".to_owned()];
+        lines.extend(self.availability_labels().iter().map(|l| format!("  {}", l)));
+        let alerts = self.active_alerts();
+        lines.push(format!("Firing alerts: {}", if alerts.is_empty() { "none".to_owned() } else { alerts.join(", ") }));
+        lines.push("Metrics:".to_owned());
+        let metrics: BTreeMap<String, f64> = self.sample_vars().into_iter().collect();
+        lines.extend(metrics.iter().map(|(name, value)| format!("  {} {}", name, value)));
+        lines.join("\n")
+    }
+
+    ///
+    /// Incident summary in markdown, as posted to a chat channel: fiber counts, pool utilization, Zookeeper health,
+    /// the fibers suspended the longest and the alerts that fired recently. Scrubbed while scrubbing is on.
+    pub fn incident_summary(&self) -> String {
+        let mut lines = vec![format!("*Incident summary: {}* ({})", self.title, logging::utc_time())];
+        if let Some(zmx) = &self.zmx {
+            lines.push(match zmx.fiber_counts.back() {
+                Some(c) => format!(
+                    "- Fibers: {} running, {} suspended, {} finishing, {} done ({} in total)",
+                    c.running, c.suspended, c.finishing, c.done, c.total()
+                ),
+                None => "- Fibers: not polled yet".to_owned(),
+            });
+        }
+        if let Some(t) = &self.slick {
+            let hikari = t.hikari_metrics.back()
+                .map(|m| format!("{}/{} connections active, {} threads waiting", m.active, m.total, m.waiting));
+            let threads = t.thread_utilization.back().map(|u| format!("{:.0}% of Slick threads busy", u));
+            let pool: Vec<String> = hikari.into_iter().chain(threads).collect();
+            lines.push(format!("- Pool: {}", if pool.is_empty() { "not polled yet".to_owned() } else { pool.join(", ") }));
+        }
+        if let Some(z) = &self.zookeeper {
+            lines.push(format!(
+                "- Zookeeper: leader {}, {} ok, {} read-only, {} not responding, {}/{} voters serving",
+                z.leader().unwrap_or("none"), z.count(ZkNodeState::Ok), z.count(ZkNodeState::ReadOnly), z.count(ZkNodeState::NotResponding),
+                z.voters().0, z.voters().1
+            ));
+        }
+        let firing = self.active_alerts();
+        lines.push(format!("- Firing alerts: {}", if firing.is_empty() { "none".to_owned() } else { firing.join(", ") }));
+
+        let stuck = self.zmx.as_ref().map_or(vec![], |t| t.stuck_fibers(App::INCIDENT_STUCK_FIBERS));
+        if !stuck.is_empty() {
+            lines.push("*Top stuck fibers*".to_owned());
+            lines.extend(stuck.iter().map(|(id, lifetime, frame)| format!("- `#{}` alive for {}s: `{}`", id, lifetime / 1000, frame)));
+        }
+        let alerts: Vec<&String> = self.hooks.as_ref()
+            .map_or(vec![], |h| h.log.iter().filter(|l| l.contains(" UTC ALERT ")).collect());
+        if !alerts.is_empty() {
+            lines.push("*Recent alerts*".to_owned());
+            lines.extend(alerts.iter().skip(alerts.len().saturating_sub(App::INCIDENT_RECENT_ALERTS)).map(|a| format!("- {}", a)));
+        }
+        self.scrub.scrub(&lines.join("\n"))
+    }
+
+    ///
+    /// Summary to post to the incident webhook, if there's one. Without one, the summary is written
+    /// to a markdown file in the working directory instead and `None` is returned.
+    pub fn start_incident_summary(&mut self, incident: Option<&IncidentSettings>) -> Option<String> {
+        let summary = self.incident_summary();
+        match incident {
+            Some(_) => {
+                self.notice = Some("Posting the incident summary...".to_owned());
+                Some(summary)
+            }
+            None => {
+                let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+                let path = format!("{}.md", self.scrub.file_name(&format!("incident-{}", secs)));
+                self.write_export("incident summary", path, summary);
+                None
+            }
+        }
+    }
 
-        if let Some(_) = jmx {
-            tabs.push(Tab { kind: TabKind::Slick, title: "Slick" })
+    pub fn on_incident_posted(&mut self, posted: Result<(), String>) {
+        self.notice = Some(match posted {
+            Ok(_) => {
+                self.logger.log(LogLevel::Info, "incident", &[]);
+                "Posted the incident summary".to_owned()
+            }
+            Err(e) => {
+                self.logger.log(LogLevel::Error, "incident", &[("error", &e)]);
+                e
+            }
+        });
+    }
+
+    pub fn on_paste(&mut self, name: &str, link: Result<String, String>) {
+        self.notice = Some(match link {
+            Ok(link) => {
+                self.logger.log(LogLevel::Info, "paste", &[("name", name), ("link", &link)]);
+                format!("Uploaded {} to {}", name, link)
+            }
+            Err(e) => {
+                self.logger.log(LogLevel::Error, "paste", &[("error", &e)]);
+                e
+            }
+        });
+    }
+
+    ///
+    /// Asks the user to confirm the action in the status bar
+    pub fn ask(&mut self, action: JmxAction) {
+        self.notice = Some(match action {
+            JmxAction::HeapDump => "Dump the heap to a file on the JVM host? It pauses the JVM for a full GC (y/n)",
+            JmxAction::ThreadDump => "Save a thread dump to the working directory? (y/n)",
+        }.to_owned());
+        self.pending_action = Some(action);
+    }
+
+    /// The action the user has just confirmed, if any
+    pub fn confirm_action(&mut self) -> Option<JmxAction> {
+        let action = self.pending_action.take();
+        self.notice = action.map(|a| match a {
+            JmxAction::HeapDump => "Dumping heap...".to_owned(),
+            JmxAction::ThreadDump => "Dumping threads...".to_owned(),
+        });
+        action
+    }
+
+    pub fn cancel_action(&mut self) {
+        self.pending_action = None;
+        self.notice = None;
+    }
+
+    pub fn on_heap_dump(&mut self, result: Result<String, String>) {
+        self.notice = Some(match result {
+            Ok(path) => {
+                self.logger.log(LogLevel::Info, "heap_dump", &[("path", &path)]);
+                format!("Heap dumped to {} in the working directory of the JVM", path)
+            }
+            Err(e) => e,
+        });
+    }
+
+    pub fn on_thread_dump(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(dump) => {
+                let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+                let path = format!("{}.txt", self.scrub.file_name(&format!("thread-dump-{}", secs)));
+                self.write_export("thread dump", path, self.scrub.scrub(&dump));
+            }
+            Err(e) => self.notice = Some(e),
         }
+    }
 
-        if let Some(_) = akka {
-            tabs.push(Tab { kind: TabKind::AkkaActorTree, title: "Akka" })
+    /// Starts saving fiber dumps polled back to back for the given time to a directory in the working directory.
+    /// Returns false if a burst is already in progress or the directory can't be created.
+    pub fn start_burst(&mut self, duration: Duration) -> bool {
+        let zmx = self.zmx.as_mut().unwrap();
+        if zmx.burst.is_some() {
+            self.notice = Some("Burst capture already in progress".to_owned());
+            return false;
+        }
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        match BurstCapture::start(format!("fiber-burst-{}", secs), duration) {
+            Ok(burst) => {
+                self.logger.log(LogLevel::Info, "burst", &[("dir", &burst.dir), ("seconds", &duration.as_secs().to_string())]);
+                self.notice = Some(format!("Capturing fiber dumps for {}s to {}", duration.as_secs(), burst.dir));
+                zmx.burst = Some(burst);
+                true
+            }
+            Err(e) => {
+                self.logger.log(LogLevel::Error, "burst", &[("error", &e)]);
+                self.notice = Some(e);
+                false
+            }
         }
+    }
 
-        App {
-            title,
-            should_quit: false,
-            exit_reason: None,
-            tabs: TabsState::new(tabs),
-            zmx: zio_zmx_addr.map(|_| ZMXTab::new()),
-            slick: jmx.map(|_| SlickTab::new()),
-            actor_tree: akka.map(|_| AkkaActorTreeTab::new()),
+    /// Saves a dump of the running burst capture, returns true if the next dump should be polled
+    pub fn on_burst_dump(&mut self, dump: Result<Vec<Fiber>, String>) -> bool {
+        let burst = match self.zmx.as_mut().and_then(|t| t.burst.as_mut()) {
+            Some(b) => b,
+            None => return false,
+        };
+        let scrub = &self.scrub;
+        let saved = dump
+            .map(|fibers| fibers.into_iter().map(|f| Fiber { dump: scrub.scrub(&f.dump), ..f }).collect::<Vec<Fiber>>())
+            .and_then(|fibers| burst.save(&fibers));
+        if let Err(e) = saved {
+            burst.errors += 1;
+            self.logger.log(LogLevel::Warn, "burst", &[("error", &e)]);
+        }
+        if burst.is_running(Instant::now()) {
+            self.notice = Some(format!("Capturing fiber dumps to {}: {} saved, {} failed", burst.dir, burst.dumps, burst.errors));
+            true
+        } else {
+            self.notice = Some(format!("Captured {} fiber dumps to {} ({} failed)", burst.dumps, burst.dir, burst.errors));
+            self.logger.log(LogLevel::Info, "burst", &[("dir", &burst.dir), ("dumps", &burst.dumps.to_string())]);
+            self.zmx.as_mut().unwrap().burst = None;
+            false
         }
     }
 
-    pub fn on_up(&mut self) {
-        match self.tabs.current().kind {
-            TabKind::ZMX => self.zmx.as_mut().unwrap().select_prev_fiber(),
-            TabKind::Slick => {}
-            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().select_prev_actor(),
+    fn write_export(&mut self, what: &str, path: String, contents: String) {
+        let written = fs::write(&path, contents).map_err(|e| e.to_string());
+        self.on_export(what, path, written);
+    }
+
+    fn on_export(&mut self, what: &str, path: String, written: Result<(), String>) {
+        self.notice = Some(match written {
+            Ok(_) => {
+                self.logger.log(LogLevel::Info, "export", &[("path", &path)]);
+                format!("Exported {} to {}", what, path)
+            }
+            Err(e) => {
+                let e = format!("Couldn't write {}: {}", path, e);
+                self.logger.log(LogLevel::Error, "export", &[("error", &e)]);
+                e
+            }
+        });
+    }
+
+    /// Labels like `ZMX 98.5% last 15m, every 2s` for every configured data source
+    pub fn availability_labels(&self) -> Vec<String> {
+        let mut labels = vec![];
+        if let Some(t) = &self.zmx {
+            labels.push(t.availability.label("ZMX", &t.backoff));
+        }
+        if let Some(t) = &self.slick {
+            labels.push(t.availability.label("JMX", &t.backoff));
+        }
+        if let Some(t) = &self.actor_tree {
+            labels.push(t.availability.label("Akka", &t.backoff));
+        }
+        if let Some(t) = &self.redis {
+            labels.push(t.availability.label("Redis", &t.backoff));
+        }
+        if let Some(t) = &self.consul {
+            labels.push(t.availability.label("Consul", &t.backoff));
         }
+        if let Some(t) = &self.gc_log {
+            labels.push(t.availability.label("GC log", &t.backoff));
+        }
+        if let Some(t) = &self.prometheus {
+            labels.push(t.availability.label("Prometheus", &t.backoff));
+        }
+        for t in self.sources.iter() {
+            labels.push(t.availability.label(&t.name, &t.backoff));
+        }
+        if let Some(z) = &self.zookeeper {
+            labels.push(z.label());
+        }
+        labels
     }
 
-    pub fn on_down(&mut self) {
-        match self.tabs.current().kind {
-            TabKind::ZMX => self.zmx.as_mut().unwrap().select_next_fiber(),
-            TabKind::Slick => {}
-            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().select_next_actor(),
+    /// Latest metric values including derived metrics, as seen by hooks
+    pub fn sample_vars(&self) -> HashMap<String, f64> {
+        let mut vars = self.source_vars();
+        if let Some(d) = &self.derived {
+            vars.extend(d.vars().into_iter().map(|(name, v)| (name.to_owned(), v)));
         }
+        vars
     }
 
-    pub fn on_right(&mut self) {
-        self.tabs.next();
+    /// Latest values of the metrics read from the sources
+    fn source_vars(&self) -> HashMap<String, f64> {
+        let mut vars = HashMap::new();
+        if let Some(c) = self.zmx.as_ref().and_then(|t| t.fiber_counts.back()) {
+            vars.insert("fibers_running".to_owned(), c.running as f64);
+            vars.insert("fibers_suspended".to_owned(), c.suspended as f64);
+            vars.insert("fibers_done".to_owned(), c.done as f64);
+            vars.insert("fibers_finishing".to_owned(), c.finishing as f64);
+            vars.insert("fibers_total".to_owned(), c.total() as f64);
+        }
+        if let Some(l) = self.zmx.as_ref().and_then(|t| t.fiber_lifecycle.back()) {
+            vars.insert("fibers_created".to_owned(), l.created as f64);
+            vars.insert("fibers_completed".to_owned(), l.completed as f64);
+        }
+        if let Some(s) = self.zmx.as_ref().and_then(|t| t.tree_shape.as_ref()) {
+            vars.insert("fibers_tree_depth".to_owned(), s.max_depth as f64);
+            vars.insert("fibers_max_children".to_owned(), s.max_children as f64);
+            vars.insert("fibers_orphans".to_owned(), s.orphans as f64);
+        }
+        if let Some(t) = &self.slick {
+            if let Some(m) = t.slick_metrics.back() {
+                vars.insert("slick_active_threads".to_owned(), m.active_threads as f64);
+                vars.insert("slick_queue_size".to_owned(), m.queue_size as f64);
+                vars.insert("slick_max_threads".to_owned(), t.slick_config.max_threads as f64);
+                vars.insert("slick_max_queue_size".to_owned(), t.slick_config.max_queue_size as f64);
+            }
+            if let Some(u) = t.thread_utilization.back() {
+                vars.insert("slick_thread_utilization".to_owned(), *u);
+            }
+            if let Some(m) = t.hikari_metrics.back() {
+                vars.insert("hikari_total".to_owned(), m.total as f64);
+                vars.insert("hikari_active".to_owned(), m.active as f64);
+                vars.insert("hikari_idle".to_owned(), m.idle as f64);
+                vars.insert("hikari_waiting".to_owned(), m.waiting as f64);
+            }
+            if let Some(r) = t.pool_error_rates.back() {
+                let rates = [
+                    ("pool_connection_timeouts_per_sec", r.connection_timeouts),
+                    ("pool_creation_failures_per_sec", r.creation_failures),
+                    ("slick_rejections_per_sec", r.rejections),
+                ];
+                for (name, rate) in rates.iter() {
+                    if let Some(x) = rate {
+                        vars.insert(name.to_string(), *x);
+                    }
+                }
+            }
+        }
+        if let Some(m) = self.jvm.as_ref().and_then(|t| t.runtime.back()) {
+            vars.insert("jvm_loaded_classes".to_owned(), m.loaded_classes as f64);
+            vars.insert("jvm_live_threads".to_owned(), m.live_threads as f64);
+            vars.insert("jvm_daemon_threads".to_owned(), m.daemon_threads as f64);
+            vars.insert("jvm_peak_threads".to_owned(), m.peak_threads as f64);
+        }
+        if let Some(t) = &self.actor_tree {
+            if let Some(c) = t.actor_counts.back() {
+                vars.insert("actor_count".to_owned(), *c as f64);
+            }
+            for (watch, counts) in t.watches.iter().zip(t.watch_counts.iter()) {
+                if let Some(c) = counts.back() {
+                    vars.insert(format!("actors_{}", watch.name), *c as f64);
+                }
+            }
+            if let Some(h) = t.health.back() {
+                vars.insert("akka_alive".to_owned(), if h.alive.ok { 1.0 } else { 0.0 });
+                vars.insert("akka_ready".to_owned(), if h.ready.ok { 1.0 } else { 0.0 });
+            }
+            if let Some(r) = &t.restarts {
+                vars.insert("actor_restarts_last_minute".to_owned(), r.last_minute(None, Instant::now()) as f64);
+            }
+            if !t.sharding.shards.is_empty() {
+                vars.insert("sharding_entities".to_owned(), t.sharding.shards.iter().map(|s| s.entities).sum::<u64>() as f64);
+                vars.insert("sharding_shards".to_owned(), t.sharding.shards.len() as f64);
+            }
+        }
+        if let Some(t) = &self.redis {
+            if let Some(i) = t.info.back() {
+                vars.insert("redis_used_memory".to_owned(), i.used_memory as f64);
+                vars.insert("redis_connected_clients".to_owned(), i.connected_clients as f64);
+                vars.insert("redis_ops_per_sec".to_owned(), i.ops_per_sec as f64);
+            }
+            if let Some(r) = t.hit_rates.back() {
+                vars.insert("redis_hit_rate".to_owned(), *r);
+            }
+        }
+        if let Some(t) = self.consul.as_ref().filter(|t| !t.history.is_empty()) {
+            vars.insert("consul_passing".to_owned(), t.count(HealthStatus::Passing) as f64);
+            vars.insert("consul_warning".to_owned(), t.count(HealthStatus::Warning) as f64);
+            vars.insert("consul_critical".to_owned(), t.count(HealthStatus::Critical) as f64);
+        }
+        if let Some(t) = &self.gc_log {
+            if let Some(p) = t.pauses.back() {
+                vars.insert("gc_pause_ms".to_owned(), p.duration_ms);
+                vars.insert("gc_heap_after_mb".to_owned(), p.heap_after);
+            }
+            if let Some(r) = t.allocation_rates.back() {
+                vars.insert("gc_allocation_rate".to_owned(), *r);
+            }
+        }
+        if let Some(z) = &self.zookeeper {
+            vars.insert("zk_ok".to_owned(), z.count(ZkNodeState::Ok) as f64);
+            vars.insert("zk_read_only".to_owned(), z.count(ZkNodeState::ReadOnly) as f64);
+            vars.insert("zk_not_responding".to_owned(), z.count(ZkNodeState::NotResponding) as f64);
+            vars.insert("zk_has_leader".to_owned(), if z.leader().is_some() { 1.0 } else { 0.0 });
+            vars.insert("zk_has_quorum".to_owned(), if z.has_quorum() { 1.0 } else { 0.0 });
+            vars.insert("zk_observers".to_owned(), z.observers.len() as f64);
+            if let Some(Ok(brokers)) = &z.brokers {
+                vars.insert("kafka_brokers".to_owned(), brokers.len() as f64);
+                vars.insert("kafka_unreachable_brokers".to_owned(), brokers.iter().filter(|b| !b.is_reachable()).count() as f64);
+                vars.insert("kafka_failing_brokers".to_owned(), brokers.iter().filter(|b| b.is_failing()).count() as f64);
+                vars.insert(
+                    "kafka_under_replicated_partitions".to_owned(),
+                    brokers.iter().map(|b| b.under_replicated_partitions()).sum::<u64>() as f64,
+                );
+            }
+        }
+        for (source, values) in self.latency.sources.iter().zip(self.latency.values.iter()) {
+            if let Some(ms) = values.back() {
+                vars.insert(format!("latency_{}_ms", source), *ms as f64);
+            }
+        }
+        for (source, skew) in self.clock.skews.iter() {
+            vars.insert(format!("clock_skew_{}_ms", source), *skew as f64);
+        }
+        vars
     }
 
-    pub fn on_left(&mut self) {
-        self.tabs.previous();
+    /// Snapshot of the current state served by the HTTP API, sources that aren't configured are null
+    pub fn state_json(&self) -> String {
+        let latency: HashMap<&str, &History<u64>> = self.latency.sources.iter()
+            .copied()
+            .zip(self.latency.values.iter())
+            .collect();
+        let state = json!({
+            "metrics": self.sample_vars(),
+            "labels": self.labels,
+            "alerts": self.active_alerts(),
+            "availability": self.availability_labels(),
+            "zmx": self.zmx.as_ref().map(|t| json!({
+                "fibers": t.snapshot().0,
+                "fiber_counts": t.fiber_counts,
+                "fiber_lifecycle": t.fiber_lifecycle,
+                "tree_shape": t.tree_shape,
+            })),
+            "slick": self.slick.as_ref().map(|t| json!({
+                "slick_metrics": t.slick_metrics,
+                "hikari_metrics": t.hikari_metrics,
+                "statements": t.statements,
+                "pool_error_rates": t.pool_error_rates,
+            })),
+            "jvm": self.jvm.as_ref().map(|t| json!({
+                "runtime": t.runtime,
+            })),
+            "custom": self.custom.as_ref().map(|t| json!({
+                "labels": t.labels,
+                "values": t.values,
+            })),
+            "akka": self.actor_tree.as_ref().map(|t| json!({
+                "actors": t.actor_nodes,
+                "actor_events": t.actor_events,
+                "actor_counts": t.actor_counts,
+                "actor_watches": t.watches.iter().zip(t.watch_counts.iter())
+                    .map(|(w, counts)| (w.name.as_str(), counts))
+                    .collect::<BTreeMap<&str, &History<u64>>>(),
+                "sharding": t.sharding,
+                "rebalances": t.rebalances,
+            })),
+            "redis": self.redis.as_ref().map(|t| json!({
+                "info": t.info,
+                "hit_rates": t.hit_rates,
+            })),
+            "consul": self.consul.as_ref().map(|t| json!({
+                "services": t.services,
+                "history": t.history,
+            })),
+            "gc_log": self.gc_log.as_ref().map(|t| json!({
+                "pauses": t.pauses,
+                "allocation_rates": t.allocation_rates,
+            })),
+            "prometheus": self.prometheus.as_ref().map(|t| json!({
+                "series": t.series,
+                "values": t.values,
+            })),
+            "sources": self.sources.iter().map(|t| json!({
+                "name": t.name,
+                "series": t.series,
+                "values": t.values,
+            })).collect::<Vec<serde_json::Value>>(),
+            "zookeeper": self.zookeeper.as_ref().map(|z| &z.nodes),
+            "zookeeper_leader_changes": self.zookeeper.as_ref().map(|z| &z.leader_changes),
+            "derived": self.derived.as_ref().map(|d| json!({
+                "names": d.metrics.iter().map(|m| &m.name).collect::<Vec<&String>>(),
+                "values": d.values,
+            })),
+            "latency": latency,
+            "capabilities": self.capabilities,
+        });
+        state.to_string()
     }
 
-    pub fn on_key(&mut self, c: char) {
-        match c {
-            'q' => self.quit(None),
-            _ => {}
+    /// Current metric values and suspended fibers, as recorded with `--record`
+    pub fn session_sample(&self) -> SessionSample {
+        let suspended_fibers = self.zmx.as_ref()
+            .and_then(|t| t.last_regular_dump.as_ref())
+            .map_or(vec![], |dump| dump.iter().filter(|f| f.status == FiberStatus::Suspended).map(|f| f.id).collect());
+        SessionSample {
+            t: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64),
+            metrics: self.sample_vars(),
+            suspended_fibers,
+        }
+    }
+
+    /// Computes derived metrics and evaluates hooks against the latest sample, captured at the given time,
+    /// returns the names of alerts that started firing
+    pub fn run_hooks(&mut self, at: Instant) -> Vec<String> {
+        let mut vars = self.source_vars();
+        if let Some(d) = self.derived.as_mut() {
+            d.on_sample(&mut vars, at);
+        }
+        let fired = self.hooks.as_mut().map_or(vec![], |h| h.on_sample(&vars));
+        self.flag_alerts(&fired);
+        fired
+    }
+
+    /// Marks the tabs charting the metrics the fired alerts refer to, except the current one
+    fn flag_alerts(&mut self, fired: &[String]) {
+        let kinds: Vec<TabKind> = match &self.hooks {
+            Some(h) => h.hooks.iter()
+                .filter(|h| h.kind == HookKind::Alert && fired.contains(&h.name))
+                .flat_map(|h| h.vars())
+                .filter_map(|v| self.metric_tab(v))
+                .collect(),
+            None => vec![],
+        };
+        let current = self.tabs.index;
+        for (i, tab) in self.tabs.tabs.iter_mut().enumerate() {
+            if i != current && kinds.contains(&tab.kind) {
+                tab.alert = true;
+            }
+        }
+    }
+
+    /// Tab charting the metric available to hooks with the given name
+    fn metric_tab(&self, name: &str) -> Option<TabKind> {
+        let prefixes = [
+            ("fibers_", TabKind::ZMX),
+            ("slick_", TabKind::Slick),
+            ("hikari_", TabKind::Slick),
+            ("jvm_", TabKind::JVM),
+            ("actor_count", TabKind::AkkaActorTree),
+            ("actors_", TabKind::AkkaActorTree),
+            ("akka_", TabKind::AkkaActorTree),
+            ("sharding_", TabKind::AkkaActorTree),
+            ("redis_", TabKind::Redis),
+            ("consul_", TabKind::Consul),
+            ("gc_", TabKind::GcLog),
+            ("latency_", TabKind::Latency),
+        ];
+        let is_derived = self.derived.as_ref().map_or(false, |d| d.metrics.iter().any(|m| m.name == name));
+        if is_derived {
+            return Some(TabKind::Derived);
+        }
+        prefixes.iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, kind)| kind.clone())
+    }
+
+    ///
+    /// Tab titles with a badge summarizing the tab and a dot while it has an alert that wasn't seen yet,
+    /// e.g. `ZIO [1250]`, `Slick [8/10]` or `Akka [341] ●`
+    pub fn tab_titles(&self) -> Vec<String> {
+        self.tabs.tabs.iter()
+            .map(|t| {
+                let badge = match t.kind {
+                    TabKind::ZMX => self.zmx.as_ref()
+                        .and_then(|z| z.fiber_counts.back())
+                        .map(|c| c.total().to_string()),
+                    TabKind::Slick => self.slick.as_ref()
+                        .and_then(|s| s.hikari_metrics.back())
+                        .map(|m| format!("{}/{}", m.active, m.total)),
+                    TabKind::AkkaActorTree => self.actor_tree.as_ref()
+                        .and_then(|a| a.actor_counts.back())
+                        .map(|c| c.to_string()),
+                    _ => None,
+                };
+                let mut title = t.title.to_owned();
+                let label = t.kind.source().and_then(|s| self.labels.get(s)).map(SourceLabel::summary);
+                if let Some(l) = label.filter(|l| !l.is_empty()) {
+                    title.push_str(&format!(" {}", l));
+                }
+                if let Some(b) = badge {
+                    title.push_str(&format!(" [{}]", b));
+                }
+                if t.alert {
+                    title.push_str(" ●");
+                }
+                title
+            })
+            .collect()
+    }
+
+    pub fn active_alerts(&self) -> Vec<String> {
+        self.hooks.as_ref()
+            .map_or(vec![], |h| h.active_alerts().iter().map(|a| a.to_string()).collect())
+    }
+
+    /// Time since the data source of the current tab last delivered a sample, if it's stale
+    pub fn current_stale_for(&self) -> Option<Duration> {
+        let health = match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::Slick | TabKind::JVM | TabKind::Custom => self.slick.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::AkkaActorTree => self.actor_tree.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::Redis => self.redis.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::Consul => self.consul.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::GcLog => self.gc_log.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::Prometheus => self.prometheus.as_ref().map(|t| (&t.availability, &t.backoff)),
+            TabKind::Source(i) => self.sources.get(i).map(|t| (&t.availability, &t.backoff)),
+            TabKind::Compare | TabKind::Derived | TabKind::Hooks | TabKind::Zookeeper | TabKind::Latency | TabKind::Log | TabKind::Dashboard => None,
+        };
+        health.and_then(|(availability, backoff)| availability.stale_for(backoff))
+    }
+
+    /// Availability and polling schedule of the data source shown on the given tab
+    fn source_health(&mut self, kind: TabKind) -> Option<(&mut Availability, &mut Backoff)> {
+        match kind {
+            TabKind::ZMX => self.zmx.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Slick | TabKind::JVM | TabKind::Custom => self.slick.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Redis => self.redis.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Consul => self.consul.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::GcLog => self.gc_log.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Prometheus => self.prometheus.as_mut().map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Source(i) => self.sources.get_mut(i).map(|t| (&mut t.availability, &mut t.backoff)),
+            TabKind::Compare | TabKind::Derived | TabKind::Hooks | TabKind::Zookeeper | TabKind::Latency | TabKind::Log | TabKind::Dashboard => None,
+        }
+    }
+
+    pub fn on_poll_success(&mut self, kind: TabKind) {
+        if let Some(source) = self.source_name(&kind) {
+            self.diagnostics.on_reached(&source);
+        }
+        if let Some((availability, backoff)) = self.source_health(kind) {
+            availability.record(true);
+            backoff.on_response(true);
+        }
+    }
+
+    /// Records a failed regular poll.
+    /// A source that never responded is likely misconfigured, so it's shown on the diagnostics screen.
+    pub fn on_poll_failure(&mut self, kind: TabKind, error: String) {
+        let source = self.source_name(&kind);
+        let reached = match self.source_health(kind) {
+            Some((availability, backoff)) => {
+                availability.record(false);
+                backoff.on_response(false);
+                availability.succeeded_once()
+            }
+            None => true,
+        };
+        if let Some(source) = source.filter(|_| !reached) {
+            self.diagnostics.record(&source, error, false);
+        }
+    }
+
+    /// Name of the data source shown on the tab, a command source being named after its tab
+    fn source_name(&self, kind: &TabKind) -> Option<String> {
+        match kind {
+            TabKind::Source(i) => self.sources.get(*i).map(|s| s.name.to_owned()),
+            _ => kind.source().map(str::to_owned),
+        }
+    }
+
+    /// Tries the sources of the diagnostics screen again right away, returning whether their fetchers have to connect again
+    pub fn retry_sources(&mut self) -> bool {
+        let kinds: Vec<TabKind> = self.tabs.tabs.iter().map(|t| t.kind.clone()).collect();
+        for kind in kinds {
+            if let Some((_, backoff)) = self.source_health(kind) {
+                backoff.reset();
+            }
         }
+        self.diagnostics.retry()
     }
 
     pub fn quit(&mut self, error: Option<String>) {
@@ -351,7 +3129,21 @@ impl<'a> App<'a> {
         match self.tabs.current().kind {
             TabKind::ZMX => self.zmx.as_mut().unwrap().scroll_up(),
             TabKind::Slick => {}
+            TabKind::JVM => {}
+            TabKind::Custom => {}
             TabKind::AkkaActorTree => {}
+            TabKind::Redis => {}
+            TabKind::Consul => {}
+            TabKind::GcLog => {}
+            TabKind::Prometheus => {}
+            TabKind::Source(_) => {}
+            TabKind::Compare => {}
+            TabKind::Derived => {}
+            TabKind::Hooks => {}
+            TabKind::Zookeeper => self.zookeeper.as_mut().unwrap().console.scroll_up(),
+            TabKind::Latency => {}
+            TabKind::Log => {}
+            TabKind::Dashboard => {}
         }
     }
 
@@ -359,18 +3151,39 @@ impl<'a> App<'a> {
         match self.tabs.current().kind {
             TabKind::ZMX => self.zmx.as_mut().unwrap().scroll_down(),
             TabKind::Slick => {}
+            TabKind::JVM => {}
+            TabKind::Custom => {}
             TabKind::AkkaActorTree => {}
+            TabKind::Redis => {}
+            TabKind::Consul => {}
+            TabKind::GcLog => {}
+            TabKind::Prometheus => {}
+            TabKind::Source(_) => {}
+            TabKind::Compare => {}
+            TabKind::Derived => {}
+            TabKind::Hooks => {}
+            TabKind::Zookeeper => self.zookeeper.as_mut().unwrap().console.scroll_down(),
+            TabKind::Latency => {}
+            TabKind::Log => {}
+            TabKind::Dashboard => {}
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::VecDeque;
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
 
-    use crate::app::{StatefulList, ZMXTab};
-    use crate::zio::model::{Fiber, FiberStatus};
+    use crate::app::{Availability, Backoff, CustomTab, PollLatency, SlickTab, StatefulList, Tab, TabKind, TabsState, ZMXTab, ZookeeperStatus};
+    use crate::history::{History, Retention};
+    use crate::jmx::model::{PoolErrorCounts, SlickConfig, SlickMetrics, WatchedAttribute};
+    use crate::widgets::tree::Column;
+    use crate::zio::diff::LineChange;
+    use crate::zio::model::{Fiber, FiberCount, FiberSort, FiberStatus};
+    use crate::zio::outline::DumpOutline;
     use crate::zio::zmx::StubZMXClient;
+    use crate::zookeeper::model::{ZkNodeState, ZkNodeStatus};
 
     #[test]
     fn zmx_tab_dumps_fibers() {
@@ -398,9 +3211,33 @@ mod tests {
         let mut tab = ZMXTab {
             fibers: StatefulList::with_items(vec!["Fiber #1".to_owned()]),
             selected_fiber_dump: ("".to_string(), 0),
+            dump_outline: DumpOutline::default(),
+            dump_line_sections: vec![],
+            dump_changes: vec![],
+            previous_dumps: HashMap::new(),
             fiber_dump_all: vec![],
+            fiber_nodes: vec![],
+            fiber_labels: vec![],
+            shown: vec![],
+            fiber_limit: ZMXTab::DEFAULT_FIBER_LIMIT,
+            group_sizes: None,
+            ungrouped: None,
+            status_filter: None,
+            fiber_sort: FiberSort::Tree,
+            fiber_prompt: None,
+            columns: vec![Column::Label],
+            fiber_label: None,
             scroll: 0,
-            fiber_counts: VecDeque::new(),
+            wrap: true,
+            x_offset: 0,
+            fiber_counts: History::new(Retention::default()),
+            hidden_statuses: vec![],
+            fiber_lifecycle: History::new(Retention::default()),
+            tree_shape: None,
+            last_regular_dump: None,
+            burst: None,
+            availability: Availability::new(Duration::from_secs(60)),
+            backoff: Backoff::new(Duration::from_secs(2)),
         };
 
         tab.replace_fiber_dump(fibers);
@@ -412,5 +3249,505 @@ mod tests {
             "└─#4   Done"
         ]);
         assert_eq!(tab.fibers.state.selected(), Some(0));
+
+        assert_eq!(tab.status_counts(), vec![
+            (FiberStatus::Running, 1),
+            (FiberStatus::Suspended, 1),
+            (FiberStatus::Finishing, 0),
+            (FiberStatus::Done, 1),
+        ]);
+        tab.cycle_status_filter();
+        tab.cycle_status_filter();
+        assert_eq!(tab.status_filter, Some(FiberStatus::Suspended));
+        assert_eq!(tab.fibers.items, vec!["│ └─#2 Suspended"]);
+        assert_eq!(tab.selected_fiber(), Some(1));
+        assert_eq!(tab.selected_fiber_dump.0, "2");
+        tab.cycle_status_filter();
+        assert_eq!(tab.selected_fiber(), None);
+        tab.cycle_status_filter();
+        tab.cycle_status_filter();
+        assert_eq!(tab.status_filter, None);
+        assert_eq!(tab.fibers.items.len(), 3);
+
+        // too many fibers to list one by one
+        let fiber = |id: usize, status: FiberStatus, line: &str| Fiber { id, parent_id: None, status, dump: format!("#{} (1s)\nStatus: {}", id, line) };
+        tab.fiber_limit = 2;
+        tab.replace_fiber_dump(vec![
+            fiber(5, FiberStatus::Suspended, "Suspended(interruptible, 1 asyncs, zio.ZIO.sleep)"),
+            fiber(6, FiberStatus::Suspended, "Suspended(interruptible, 2 asyncs, zio.ZIO.sleep)"),
+            fiber(7, FiberStatus::Running, "Running()"),
+        ]);
+        assert_eq!(tab.grouped_fibers(), Some(3));
+        assert_eq!(tab.fibers.items, vec![
+            "      2 × Suspended(interruptible, zio.ZIO.sleep) (e.g. #5)",
+            "      1 × Running() (e.g. #7)",
+        ]);
+        assert_eq!(tab.status_counts()[1], (FiberStatus::Suspended, 2));
+        // exports and analysis still see every fiber
+        let (nodes, dumps) = tab.snapshot();
+        assert_eq!(nodes.iter().map(|f| f.id).collect::<Vec<usize>>(), vec![5, 6, 7]);
+        assert!(nodes.iter().all(|f| f.dump.is_empty()));
+        assert_eq!(dumps[1], "#6 (1s)\nStatus: Suspended(interruptible, 2 asyncs, zio.ZIO.sleep)");
+        assert_eq!(tab.stuck_fibers(5).len(), 2);
+        assert!(tab.jump_to_fiber(6).is_err());
+        assert_eq!(tab.jump_to_fiber(7), Ok(()));
+    }
+
+    #[test]
+    fn zmx_tab_lists_fibers_suspended_longest() {
+        let fiber = |id: usize, status: FiberStatus, dump: &str| Fiber { id, parent_id: None, status, dump: dump.to_owned() };
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        tab.replace_fiber_dump(vec![
+            fiber(5, FiberStatus::Suspended, "#5 (1s1500ms)\n  a future continuation at orders.Repo.find(Repo.scala:42)"),
+            fiber(9, FiberStatus::Running, "#9 (5m300s300000ms)\n  at orders.Api.serve(Api.scala:10)"),
+            fiber(2, FiberStatus::Suspended, "#2 (1m96s96402ms)\n  a future continuation at orders.Pool.acquire(Pool.scala:7)"),
+            fiber(7, FiberStatus::Suspended, "#7 (0s10ms)"),
+        ]);
+
+        assert_eq!(tab.stuck_fibers(2), vec![
+            (2, 96402, "a future continuation at orders.Pool.acquire(Pool.scala:7)"),
+            (5, 1500, "a future continuation at orders.Repo.find(Repo.scala:42)"),
+        ]);
+        assert_eq!(tab.stuck_fibers(5).last(), Some(&(7, 10, "")));
+    }
+
+    #[test]
+    fn custom_tab_follows_beans_matching_patterns() {
+        let watched = |label: &str, object_name: &str| WatchedAttribute {
+            label: label.to_owned(),
+            object_name: object_name.to_owned(),
+            attribute: "Value".to_owned(),
+            key: None,
+        };
+        let mut tab = CustomTab::new(&[watched("Threads", "java.lang:type=Threading"), watched("In", "kafka.server:topic=*")], Retention::default());
+        assert_eq!(tab.labels, vec!["Threads"]);
+
+        let value = |label: &str, v: Option<f64>| (label.to_owned(), v);
+        let start = Instant::now();
+        tab.append_values(vec![value("Threads", Some(12.0)), value("In topic=a", Some(1.0)), value("In topic=b", Some(2.0))], start);
+        tab.append_values(vec![value("Threads", None), value("In topic=b", Some(3.0)), value("In topic=c", Some(4.0))], start + Duration::from_secs(2));
+
+        assert_eq!(tab.labels, vec!["Threads", "In topic=b", "In topic=c"]);
+        let values: Vec<Vec<f64>> = tab.values.iter().map(|h| h.iter().copied().collect()).collect();
+        assert_eq!(values, vec![vec![12.0], vec![2.0, 3.0], vec![4.0]]);
+        assert_eq!(tab.latest, vec![None, Some(3.0), Some(4.0)]);
+    }
+
+    #[test]
+    fn zmx_tab_sorts_fibers() {
+        let fiber = |id: usize, parent_id: Option<usize>, status: FiberStatus, dump: &str| Fiber { id, parent_id, status, dump: dump.to_owned() };
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        tab.replace_fiber_dump(vec![
+            fiber(5, None, FiberStatus::Suspended, "#5 (1s1500ms)"),
+            fiber(9, Some(5), FiberStatus::Running, "#9 (0s400ms)"),
+            fiber(2, None, FiberStatus::Suspended, "#2 (1m96s96402ms)"),
+            fiber(3, Some(2), FiberStatus::Done, "#3 (1m60s60000ms)"),
+        ]);
+        let ids = |tab: &ZMXTab| tab.fibers.items.iter()
+            .map(|l| l.split('#').nth(1).unwrap().split(' ').next().unwrap().to_owned())
+            .collect::<Vec<String>>();
+        assert_eq!(ids(&tab), vec!["2", "3", "5", "9"]);
+
+        tab.cycle_fiber_sort();
+        assert_eq!(tab.fiber_sort, FiberSort::Id);
+        assert_eq!(ids(&tab), vec!["2", "3", "5", "9"]);
+
+        tab.cycle_fiber_sort();
+        assert_eq!(ids(&tab), vec!["9", "2", "5", "3"]);
+
+        tab.cycle_fiber_sort();
+        assert_eq!(tab.fiber_sort, FiberSort::SuspendedAge);
+        assert_eq!(ids(&tab), vec!["2", "5", "3", "9"]);
+        assert_eq!(tab.selected_fiber_dump.0, "#2 (1m96s96402ms)");
+
+        tab.filter_by_status(Some(FiberStatus::Running));
+        assert_eq!(ids(&tab), vec!["9"]);
+        tab.filter_by_status(None);
+        tab.cycle_fiber_sort();
+        assert_eq!(tab.fiber_sort, FiberSort::Tree);
+    }
+
+    #[test]
+    fn zmx_tab_counts_and_filters_fibers_by_status() {
+        let fiber = |id: usize, parent_id: Option<usize>, status: FiberStatus| Fiber { id, parent_id, status, dump: format!("#{} (1s)", id) };
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        tab.replace_fiber_dump(vec![
+            fiber(1, None, FiberStatus::Running),
+            fiber(2, Some(1), FiberStatus::Suspended),
+            fiber(3, Some(1), FiberStatus::Suspended),
+            fiber(4, Some(3), FiberStatus::Finishing),
+            fiber(5, None, FiberStatus::Done),
+            fiber(6, Some(5), FiberStatus::Suspended),
+        ]);
+        let ids = |tab: &ZMXTab| tab.fibers.items.iter()
+            .map(|l| l.split('#').nth(1).unwrap().split(' ').next().unwrap().parse::<usize>().unwrap())
+            .collect::<Vec<usize>>();
+
+        assert_eq!(tab.status_counts(), vec![
+            (FiberStatus::Running, 1),
+            (FiberStatus::Suspended, 3),
+            (FiberStatus::Finishing, 1),
+            (FiberStatus::Done, 1),
+        ]);
+        assert_eq!(ids(&tab), vec![1, 2, 3, 4, 5, 6]);
+
+        let mut shown = vec![];
+        for _ in 0..5 {
+            tab.cycle_status_filter();
+            shown.push((tab.status_filter.clone(), ids(&tab)));
+        }
+        assert_eq!(shown, vec![
+            (Some(FiberStatus::Running), vec![1]),
+            (Some(FiberStatus::Suspended), vec![2, 3, 6]),
+            (Some(FiberStatus::Finishing), vec![4]),
+            (Some(FiberStatus::Done), vec![5]),
+            (None, vec![1, 2, 3, 4, 5, 6]),
+        ]);
+
+        // counts cover the whole snapshot whatever the filter, and a new snapshot is filtered the same way
+        tab.filter_by_status(Some(FiberStatus::Suspended));
+        assert_eq!(tab.status_counts()[0], (FiberStatus::Running, 1));
+        tab.replace_fiber_dump(vec![fiber(7, None, FiberStatus::Suspended), fiber(8, None, FiberStatus::Running)]);
+        assert_eq!(ids(&tab), vec![7]);
+        assert_eq!(tab.selected_fiber_dump.0, "#7 (1s)");
+        assert_eq!(tab.status_counts()[1], (FiberStatus::Suspended, 1));
+    }
+
+    #[test]
+    fn zmx_tab_jumps_to_fiber_by_id() {
+        let fiber = |id: usize, status: FiberStatus| Fiber { id, parent_id: None, status, dump: format!("#{} (1s)", id) };
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        tab.replace_fiber_dump(vec![fiber(2, FiberStatus::Suspended), fiber(4812, FiberStatus::Done), fiber(7, FiberStatus::Running)]);
+        tab.filter_by_status(Some(FiberStatus::Suspended));
+
+        tab.open_fiber_prompt();
+        "#4812x".chars().for_each(|c| tab.on_fiber_prompt_input(c));
+        assert_eq!(tab.fiber_prompt, Some("4812".to_owned()));
+        assert_eq!(tab.submit_fiber_prompt(), Ok(()));
+        assert_eq!(tab.fiber_prompt, None);
+        assert_eq!(tab.status_filter, None);
+        assert_eq!(tab.selected_fiber().map(|n| tab.fiber_nodes[n].id), Some(4812));
+        assert_eq!(tab.selected_fiber_dump.0, "#4812 (1s)");
+
+        assert!(tab.jump_to_fiber(13).is_err());
+        assert_eq!(tab.selected_fiber().map(|n| tab.fiber_nodes[n].id), Some(4812));
+    }
+
+    #[test]
+    fn zmx_tab_marks_dump_changes_since_previous_snapshot() {
+        let fiber = |dump: &str| Fiber { id: 7, parent_id: None, status: FiberStatus::Suspended, dump: dump.to_owned() };
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+
+        tab.replace_fiber_dump(vec![fiber("#7 (1s)\nStatus: Suspended()")]);
+        assert!(tab.dump_changes.iter().all(|c| *c == LineChange::Unchanged));
+
+        tab.replace_fiber_dump(vec![fiber("#7 (3s)\nStatus: Suspended()")]);
+        assert_eq!(tab.selected_fiber_dump, ("#7 (1s)\n#7 (3s)\nStatus: Suspended()".to_owned(), 3));
+        assert_eq!(tab.dump_changes, vec![LineChange::Removed, LineChange::Added, LineChange::Unchanged]);
+
+        tab.replace_fiber_dump(vec![fiber("#7 (3s)\nStatus: Suspended()")]);
+        assert_eq!(tab.dump_changes, vec![LineChange::Unchanged, LineChange::Unchanged]);
+    }
+
+    #[test]
+    fn zmx_tab_scrolls_unwrapped_dump_horizontally() {
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        tab.selected_fiber_dump = ("abcd\nxy".to_owned(), 2);
+
+        tab.scroll_right();
+        assert_eq!(tab.x_offset, 0);
+
+        tab.toggle_wrap();
+        tab.scroll_right();
+        tab.scroll_right();
+        tab.scroll_right();
+        assert_eq!(tab.visible_dump(), "d\n");
+
+        tab.scroll_right();
+        tab.scroll_right();
+        assert_eq!(tab.x_offset, 4);
+
+        tab.toggle_wrap();
+        assert_eq!(tab.x_offset, 0);
+        assert_eq!(tab.visible_dump(), "abcd\nxy");
+    }
+
+    #[test]
+    fn zmx_tab_scales_chart_to_shown_statuses() {
+        let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        let start = Instant::now();
+        tab.fiber_counts.push_back_at(FiberCount { done: 1000, finishing: 0, running: 10, suspended: 40 }, start);
+        tab.fiber_counts.push_back_at(FiberCount { done: 900, finishing: 2, running: 20, suspended: 50 }, start + Duration::from_secs(2));
+        assert_eq!(tab.shown_fibers_max(), 1050);
+
+        tab.toggle_status(FiberStatus::Done);
+        assert_eq!(tab.shown_fibers_max(), 72);
+
+        tab.toggle_status(FiberStatus::Done);
+        assert!(tab.hidden_statuses.is_empty());
+    }
+
+    #[test]
+    fn slick_tab_records_thread_utilization_once_max_threads_is_known() {
+        let mut tab = SlickTab::new(Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        let start = Instant::now();
+        tab.append_slick_metrics(SlickMetrics { active_threads: 3, queue_size: 0 }, start);
+        assert!(tab.thread_utilization.is_empty());
+
+        tab.replace_slick_config(SlickConfig { max_threads: 20, max_queue_size: 1000 });
+        tab.append_slick_metrics(SlickMetrics { active_threads: 5, queue_size: 0 }, start + Duration::from_secs(2));
+        tab.append_slick_metrics(SlickMetrics { active_threads: 20, queue_size: 12 }, start + Duration::from_secs(4));
+        assert_eq!(tab.thread_utilization.iter().cloned().collect::<Vec<f64>>(), vec![25.0, 100.0]);
+        // stamped when captured, a response applied late doesn't shift the chart
+        assert_eq!(tab.slick_metrics.points().last().map(|(at, _)| *at), Some(start + Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn slick_tab_charts_pool_errors_per_second() {
+        let mut tab = SlickTab::new(Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+        let counts = |timeouts: u64| PoolErrorCounts { connection_timeouts: Some(timeouts), creation_failures: None, rejections: None };
+        let start = Instant::now();
+
+        tab.pool_errors_error = Some("Couldn't read the pool failure counters".to_owned());
+        tab.append_pool_error_counts(counts(40), start);
+        assert!(tab.pool_error_rates.is_empty());
+        assert_eq!(tab.pool_errors_error, None);
+
+        tab.append_pool_error_counts(counts(40), start + Duration::from_secs(2));
+        tab.append_pool_error_counts(counts(50), start + Duration::from_secs(4));
+        let timeouts: Vec<Option<f64>> = tab.pool_error_rates.iter().map(|r| r.connection_timeouts).collect();
+        assert_eq!(timeouts, vec![Some(0.0), Some(5.0)]);
+        assert_eq!(tab.pool_error_rates.back().unwrap().creation_failures, None);
+    }
+
+    #[test]
+    fn zookeeper_records_leader_changes() {
+        let node = |address: &str, mode: &str| ZkNodeStatus {
+            address: address.to_owned(),
+            state: ZkNodeState::Ok,
+            mode: Some(mode.to_owned()),
+            server_id: None,
+            zxid: None,
+            error: None,
+            monitor: None,
+            not_whitelisted: vec![],
+            admin_fallback: false,
+            config: None,
+        };
+        let mut zk = ZookeeperStatus::new(&["zk1:2181".to_owned(), "zk2:2181".to_owned()], Retention::default(), Duration::from_secs(2));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert!(!zk.replace_nodes(vec![node("zk1:2181", "leader"), node("zk2:2181", "follower")], at(0)));
+        assert!(!zk.replace_nodes(vec![node("zk1:2181", "leader"), node("zk2:2181", "follower")], at(2)));
+        assert!(zk.replace_nodes(vec![node("zk1:2181", "follower"), node("zk2:2181", "leader")], at(4)));
+        assert!(zk.replace_nodes(vec![node("zk1:2181", "follower"), node("zk2:2181", "follower")], at(6)));
+
+        let leaders: Vec<Option<String>> = zk.leader_changes.iter().map(|(_, l)| l.clone()).collect();
+        assert_eq!(leaders, vec![Some("zk2:2181".to_owned()), None]);
+        assert_eq!(zk.leader(), None);
+        // marked on the probes that noticed them
+        let changes: Vec<Option<Option<String>>> = zk.samples.iter().map(|s| s.leader_change.clone()).collect();
+        assert_eq!(changes, vec![None, None, Some(Some("zk2:2181".to_owned())), Some(None)]);
+        assert_eq!(zk.samples.back().map(|s| s.serving), Some(2));
+    }
+
+    #[test]
+    fn zookeeper_quorum_leaves_out_observers() {
+        let node = |address: &str, state: ZkNodeState, mode: Option<&str>| ZkNodeStatus {
+            address: address.to_owned(),
+            state,
+            mode: mode.map(|m| m.to_owned()),
+            server_id: None,
+            zxid: None,
+            error: None,
+            monitor: None,
+            not_whitelisted: vec![],
+            admin_fallback: false,
+            config: None,
+        };
+        let addresses: Vec<String> = (1..=5).map(|i| format!("zk{}:2181", i)).collect();
+        let mut zk = ZookeeperStatus::new(&addresses, Retention::default(), Duration::from_secs(2));
+        let start = Instant::now();
+        assert_eq!(zk.voters(), (0, 5));
+        assert!(!zk.has_quorum());
+
+        zk.replace_nodes(vec![
+            node("zk1:2181", ZkNodeState::Ok, Some("leader")),
+            node("zk2:2181", ZkNodeState::Ok, Some("follower")),
+            node("zk3:2181", ZkNodeState::Ok, Some("follower")),
+            node("zk4:2181", ZkNodeState::Ok, Some("observer")),
+            node("zk5:2181", ZkNodeState::Ok, Some("observer")),
+        ], start);
+        assert_eq!(zk.voters(), (3, 3));
+
+        // observers are still known as such while they're down, unlike a voter
+        zk.replace_nodes(vec![
+            node("zk1:2181", ZkNodeState::Ok, Some("leader")),
+            node("zk2:2181", ZkNodeState::Ok, Some("follower")),
+            node("zk3:2181", ZkNodeState::NotResponding, None),
+            node("zk4:2181", ZkNodeState::NotResponding, None),
+            node("zk5:2181", ZkNodeState::NotResponding, None),
+        ], start + Duration::from_secs(2));
+        assert_eq!(zk.voters(), (2, 3));
+        assert!(zk.has_quorum());
+
+        zk.replace_nodes(vec![
+            node("zk1:2181", ZkNodeState::Ok, Some("leader")),
+            node("zk2:2181", ZkNodeState::NotResponding, None),
+            node("zk3:2181", ZkNodeState::NotResponding, None),
+            node("zk4:2181", ZkNodeState::Ok, Some("observer")),
+            node("zk5:2181", ZkNodeState::Ok, Some("observer")),
+        ], start + Duration::from_secs(4));
+        assert_eq!(zk.voters(), (1, 3));
+        assert!(!zk.has_quorum());
+        assert_eq!(zk.observers.len(), 2);
+    }
+
+    #[test]
+    fn zookeeper_console_submits_four_letter_words() {
+        let mut zk = ZookeeperStatus::new(&["zk1:2181".to_owned(), "zk2:2181".to_owned()], Retention::default(), Duration::from_secs(2));
+        zk.console.select_next_node(zk.nodes.len());
+        zk.console.select_next_node(zk.nodes.len());
+        zk.console.open_prompt();
+        for c in "stats".chars() {
+            zk.console.on_input(c);
+        }
+        assert_eq!(zk.console.input, Some("stat".to_owned()));
+
+        let (node, command) = zk.console.submit(&zk.nodes).unwrap();
+        assert_eq!((node, command.as_str()), (1, "stat"));
+        assert_eq!(zk.console.input, None);
+
+        zk.console.open_prompt();
+        zk.console.on_input('x');
+        assert_eq!(zk.console.submit(&zk.nodes), None);
+        assert!(matches!(&zk.console.output, Some((address, command, Err(_))) if address == "zk2:2181" && command == "x"));
+    }
+
+    #[test]
+    fn visiting_tab_acknowledges_its_alert() {
+        let mut tabs = TabsState::new(vec![Tab::new(TabKind::ZMX, "ZIO"), Tab::new(TabKind::Slick, "Slick")]);
+        tabs.tabs[1].alert = true;
+
+        tabs.previous();
+        assert!(!tabs.tabs[1].alert);
+        tabs.tabs[0].alert = true;
+        tabs.next();
+        assert!(!tabs.tabs[0].alert);
+    }
+
+    #[test]
+    fn list_shows_window_around_selected_item() {
+        let mut list = StatefulList::with_items((0..10).collect::<Vec<i32>>());
+
+        list.state.select(Some(5));
+        assert_eq!(list.visible_items(3), (&[3, 4, 5][..], Some(2)));
+        list.state.select(Some(4));
+        assert_eq!(list.visible_items(3), (&[3, 4, 5][..], Some(1)));
+        list.state.select(Some(1));
+        assert_eq!(list.visible_items(3), (&[1, 2, 3][..], Some(0)));
+
+        list.items.truncate(2);
+        list.state.select(None);
+        assert_eq!(list.visible_items(3), (&[0, 1][..], None));
+    }
+
+    #[test]
+    fn availability_counts_polls_within_window() {
+        let mut a = Availability::new(Duration::from_secs(60));
+        let backoff = Backoff::new(Duration::from_secs(2));
+        assert_eq!(a.percentage(), None);
+        assert_eq!(a.label("ZMX", &backoff), "ZMX n/a last 1m, every 2s");
+
+        let start = Instant::now();
+        a.record_at(start, false);
+        assert!(!a.succeeded_once());
+        a.record_at(start + Duration::from_secs(10), true);
+        a.record_at(start + Duration::from_secs(20), true);
+        a.record_at(start + Duration::from_secs(30), true);
+        assert!(a.succeeded_once());
+        assert_eq!(a.percentage(), Some(75.0));
+
+        // the failed poll falls out of the window
+        a.record_at(start + Duration::from_secs(65), true);
+        assert_eq!(a.percentage(), Some(100.0));
+        assert_eq!(a.label("ZMX", &backoff), "ZMX 100.0% last 1m, every 2s");
+    }
+
+    #[test]
+    fn availability_is_stale_without_samples_for_three_intervals() {
+        let start = Instant::now();
+        let backoff = Backoff::new(Duration::from_secs(2));
+        let mut a = Availability::new(Duration::from_secs(60));
+        assert_eq!(a.stale_at(start, &backoff), None);
+
+        a.record_at(start, true);
+        a.record_at(start + Duration::from_secs(2), false);
+        assert_eq!(a.stale_at(start + Duration::from_secs(6), &backoff), None);
+        assert_eq!(a.stale_at(start + Duration::from_secs(7), &backoff), Some(Duration::from_secs(7)));
+
+        a.record_at(start + Duration::from_secs(8), true);
+        assert_eq!(a.stale_at(start + Duration::from_secs(9), &backoff), None);
+    }
+
+    #[test]
+    fn availability_of_backed_off_source_is_stale_after_three_effective_intervals() {
+        let start = Instant::now();
+        let mut backoff = Backoff::new(Duration::from_secs(2));
+        let mut a = Availability::new(Duration::from_secs(60));
+        a.record_at(start, true);
+        for _ in 0..2 {
+            backoff.on_response(false);
+        }
+        assert_eq!(backoff.interval(), Duration::from_secs(8));
+
+        assert_eq!(a.stale_at(start + Duration::from_secs(7), &backoff), None);
+        assert_eq!(a.stale_at(start + Duration::from_secs(24), &backoff), None);
+        assert_eq!(a.stale_at(start + Duration::from_secs(25), &backoff), Some(Duration::from_secs(25)));
+    }
+
+    #[test]
+    fn backoff_slows_down_polling_on_failures() {
+        let mut b = Backoff::new(Duration::from_secs(2));
+        assert!(b.due());
+        // previous poll hasn't been answered yet
+        assert!(!b.due());
+
+        b.on_response(false);
+        b.on_response(false);
+        assert_eq!(b.interval(), Duration::from_secs(8));
+        let polls: Vec<bool> = (0..8).map(|_| {
+            let due = b.due();
+            if due {
+                b.on_response(false);
+            }
+            due
+        }).collect();
+        assert_eq!(polls, vec![true, false, false, false, true, false, false, false]);
+        assert_eq!(b.interval(), Duration::from_secs(32));
+
+        b.on_response(true);
+        assert_eq!(b.interval(), Duration::from_secs(16));
+        for _ in 0..10 {
+            b.on_response(true);
+        }
+        assert_eq!(b.interval(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn poll_latency_keeps_recent_measures_per_source() {
+        let start = Instant::now();
+        let mut latency = PollLatency::new(Retention { full_resolution: 100, downsample_every: 30, downsampled: 0 });
+        latency.record("zmx", Duration::from_millis(120), start);
+        latency.record("jmx", Duration::from_millis(15), start);
+        for ms in 0..100 {
+            latency.record("zmx", Duration::from_millis(ms), start + Duration::from_millis(ms + 1));
+        }
+
+        assert_eq!(latency.sources, vec!["zmx", "jmx"]);
+        assert_eq!(latency.values[0].len(), 100);
+        assert_eq!(latency.values[0].get(0), Some(&0));
+        assert_eq!(latency.values[1].iter().copied().collect::<Vec<u64>>(), vec![15]);
     }
 }
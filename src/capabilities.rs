@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Optional feature of a source, probed when the source is connected
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// HikariCP pool MBean named after `--db-pool-name`
+    HikariPool,
+    /// Timers matching `statements` in the config file
+    StatementTimers,
+    /// `/health/alive` and `/health/ready` routes of Akka Management
+    AkkaHealthChecks,
+    /// `/cluster/shards/<entity type>` route of Akka Management
+    ClusterSharding,
+}
+
+impl Feature {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Feature::HikariPool => "HikariCP pool MBean",
+            Feature::StatementTimers => "Statement timers",
+            Feature::AkkaHealthChecks => "Akka Management health checks",
+            Feature::ClusterSharding => "Akka Management cluster sharding",
+        }
+    }
+}
+
+/// Outcome of probing a feature
+#[derive(Clone, Debug, PartialEq)]
+pub struct Capability {
+    pub feature: Feature,
+    /// Why the feature isn't available, e.g. the error of the probe
+    pub missing: Option<String>,
+}
+
+impl Capability {
+    pub fn probe<T>(feature: Feature, result: Result<T, String>) -> Capability {
+        Capability { feature, missing: result.err() }
+    }
+}
+
+/// Features probed so far, along with the reason of the missing ones
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct Capabilities {
+    probed: BTreeMap<Feature, Option<String>>,
+}
+
+impl Capabilities {
+    pub fn record(&mut self, capabilities: Vec<Capability>) {
+        self.probed.extend(capabilities.into_iter().map(|c| (c.feature, c.missing)));
+    }
+
+    /// Whether the feature was probed and found missing
+    pub fn is_missing(&self, feature: Feature) -> bool {
+        matches!(self.probed.get(&feature), Some(Some(_)))
+    }
+
+    /// Text shown instead of the panels relying on a missing feature
+    pub fn placeholder(&self, feature: Feature) -> Option<String> {
+        match self.probed.get(&feature) {
+            Some(Some(reason)) => Some(format!("{} not available: {}", feature.description(), reason)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capabilities::{Capabilities, Capability, Feature};
+
+    #[test]
+    fn explains_missing_features() {
+        let mut capabilities = Capabilities::default();
+        assert!(!capabilities.is_missing(Feature::HikariPool));
+
+        capabilities.record(vec![
+            Capability::probe(Feature::HikariPool, Err::<(), String>("InstanceNotFoundException".to_owned())),
+            Capability::probe(Feature::StatementTimers, Ok(())),
+        ]);
+        assert!(capabilities.is_missing(Feature::HikariPool));
+        assert!(!capabilities.is_missing(Feature::StatementTimers));
+        assert_eq!(
+            capabilities.placeholder(Feature::HikariPool),
+            Some("HikariCP pool MBean not available: InstanceNotFoundException".to_owned())
+        );
+        assert_eq!(capabilities.placeholder(Feature::StatementTimers), None);
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use serde::Deserialize;
+
+///
+/// Threshold of a metric evaluated by the check command, the metric is named as in hooks.
+///
+/// eg. `{ "metric": "hikari_waiting", "warning": 1, "critical": 5 }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Threshold {
+    pub metric: String,
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+    /// Whether values below the thresholds are bad, e.g. for a hit rate
+    #[serde(default)]
+    pub below: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl CheckStatus {
+    /// Exit code as expected by Nagios-compatible checks
+    pub fn exit_code(self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warning => 1,
+            CheckStatus::Critical => 2,
+        }
+    }
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Warning => "WARN",
+            CheckStatus::Critical => "CRIT",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl Threshold {
+    pub fn check(&self, vars: &HashMap<String, f64>) -> CheckResult {
+        let value = match vars.get(&self.metric) {
+            Some(v) => *v,
+            None => return CheckResult {
+                status: CheckStatus::Warning,
+                message: format!("{} not available", self.metric),
+            },
+        };
+        let exceeds = |limit: Option<f64>| match limit {
+            Some(l) if self.below => value < l,
+            Some(l) => value > l,
+            None => false,
+        };
+        let (status, limit) = if exceeds(self.critical) {
+            (CheckStatus::Critical, self.critical)
+        } else if exceeds(self.warning) {
+            (CheckStatus::Warning, self.warning)
+        } else {
+            (CheckStatus::Ok, None)
+        };
+        let message = match limit {
+            Some(l) => format!("{} = {} ({} {})", self.metric, value, if self.below { "below" } else { "above" }, l),
+            None => format!("{} = {}", self.metric, value),
+        };
+        CheckResult { status, message }
+    }
+}
+
+/// Overall status, the worst of the results, and a summary with a line per result
+pub fn summarize(results: &[CheckResult]) -> (CheckStatus, String) {
+    let status = results.iter().map(|r| r.status).max().unwrap_or(CheckStatus::Ok);
+    let count = |s: CheckStatus| results.iter().filter(|r| r.status == s).count();
+    let mut lines = vec![format!(
+        "panopticon {}: {} critical, {} warning, {} ok",
+        status, count(CheckStatus::Critical), count(CheckStatus::Warning), count(CheckStatus::Ok)
+    )];
+    lines.extend(results.iter().map(|r| format!("{} {}", r.status, r.message)));
+    (status, lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::check::{CheckResult, CheckStatus, summarize, Threshold};
+
+    #[test]
+    fn checks_thresholds() {
+        let vars: HashMap<String, f64> = vec![("hikari_waiting".to_owned(), 3.0), ("redis_hit_rate".to_owned(), 40.0)]
+            .into_iter().collect();
+        let threshold = |metric: &str, warning, critical, below| Threshold { metric: metric.to_owned(), warning, critical, below };
+
+        assert_eq!(threshold("hikari_waiting", Some(1.0), Some(5.0), false).check(&vars).status, CheckStatus::Warning);
+        assert_eq!(threshold("hikari_waiting", Some(1.0), Some(2.0), false).check(&vars), CheckResult {
+            status: CheckStatus::Critical,
+            message: "hikari_waiting = 3 (above 2)".to_owned(),
+        });
+        assert_eq!(threshold("redis_hit_rate", Some(80.0), Some(50.0), true).check(&vars).status, CheckStatus::Critical);
+        assert_eq!(threshold("redis_hit_rate", None, Some(20.0), true).check(&vars).status, CheckStatus::Ok);
+        assert_eq!(threshold("actor_count", None, Some(1.0), false).check(&vars).status, CheckStatus::Warning);
+    }
+
+    #[test]
+    fn summarizes_worst_status() {
+        let result = |status, message: &str| CheckResult { status, message: message.to_owned() };
+        let (status, summary) = summarize(&[
+            result(CheckStatus::Ok, "fibers_total = 10"),
+            result(CheckStatus::Warning, "hikari_waiting = 3 (above 1)"),
+        ]);
+
+        assert_eq!(status.exit_code(), 1);
+        assert_eq!(summary, "panopticon WARN: 0 critical, 1 warning, 1 ok\nOK fibers_total = 10\nWARN hikari_waiting = 3 (above 1)");
+        assert_eq!(summarize(&[]).0, CheckStatus::Ok);
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::Proxy;
+use crate::http;
+
+/// Time of a remote clock, along with when it was asked for and answered, in ms since the Unix epoch
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockReading {
+    pub remote: u64,
+    pub sent: u64,
+    pub received: u64,
+}
+
+impl ClockReading {
+    /// Offset of the remote clock from the local one, positive when it's ahead, taken as read halfway through the round trip
+    pub fn skew_ms(&self) -> i64 {
+        self.remote as i64 - ((self.sent + self.received) / 2) as i64
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+///
+/// Clock of an HTTP server, from the `Date` header of its response to a GET of the URL.
+/// The header is truncated to the second, so the middle of that second is taken.
+#[tokio::main]
+pub async fn http_clock(url: &str, timeout: u64, proxy: Option<&Proxy>) -> Result<ClockReading, String> {
+    let sent = now_ms();
+    let response = http::client(proxy)?
+        .get(url)
+        .timeout(Duration::from_millis(timeout))
+        .send().await
+        .map_err(|e| e.to_string())?;
+    let received = now_ms();
+    let date = response.headers().get("date")
+        .and_then(|d| d.to_str().ok())
+        .ok_or(format!("{} sent no Date header", url))?;
+    let remote = parse_http_date(date).ok_or(format!("Invalid Date header {}", date))?;
+    Ok(ClockReading { remote: remote + 500, sent, received })
+}
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+///
+/// Time of an HTTP date in ms since the Unix epoch, e.g. 784111777000 for `Sun, 06 Nov 1994 08:49:37 GMT`.
+/// Dates before the epoch or past the four digit years are rejected.
+pub fn parse_http_date(date: &str) -> Option<u64> {
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    let (day, month, year, time) = match parts.as_slice() {
+        [_, day, month, year, time, "GMT"] => (day.parse::<u64>().ok()?, *month, year.parse::<i64>().ok()?, *time),
+        _ => return None,
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? as u64 + 1;
+    let hms: Vec<u64> = time.split(':').map(|p| p.parse().ok()).collect::<Option<Vec<u64>>>()?;
+    match hms.as_slice() {
+        [h, m, s] if (1970..=9999).contains(&year) && (1..=31).contains(&day) && *h < 24 && *m < 60 && *s < 61 => {
+            let days = days_from_civil(year, month, day);
+            Some(((days as u64) * 86_400 + h * 3600 + m * 60 + s) * 1000)
+        }
+        _ => None,
+    }
+}
+
+/// Days since the Unix epoch of a date of the Gregorian calendar
+fn days_from_civil(year: i64, month: u64, day: u64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let m = month as i64;
+    let day_of_year = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+///
+/// Skew of the clocks of the sources from the local one, which makes timestamps of different systems
+/// disagree while debugging across them. Sources whose skew is over the threshold either way are warned about.
+pub struct ClockSkew {
+    /// Skew (in ms) over which a clock is warned about
+    pub threshold: u64,
+    /// Sources whose clock can be read
+    pub sources: Vec<&'static str>,
+    /// Last skew (in ms) of each source that could be read
+    pub skews: BTreeMap<&'static str, i64>,
+    last_check: Option<Instant>,
+}
+
+impl ClockSkew {
+    pub const DEFAULT_THRESHOLD: u64 = 2000;
+    /// Clocks drift slowly, they're read once a minute
+    pub const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(sources: Vec<&'static str>, threshold: u64) -> ClockSkew {
+        ClockSkew { threshold, sources, skews: BTreeMap::new(), last_check: None }
+    }
+
+    /// Whether the clocks are due to be read again, starting the interval over if so
+    pub fn due(&mut self, now: Instant) -> bool {
+        let due = !self.sources.is_empty() && self.last_check.map_or(true, |t| now.duration_since(t) >= ClockSkew::CHECK_INTERVAL);
+        if due {
+            self.last_check = Some(now);
+        }
+        due
+    }
+
+    fn is_skewed(&self, skew: i64) -> bool {
+        skew.abs() as u64 > self.threshold
+    }
+
+    /// Records the skew of a source, returning whether it just went over the threshold
+    pub fn record(&mut self, source: &'static str, skew: i64) -> bool {
+        let was_skewed = self.skews.get(source).map_or(false, |s| self.is_skewed(*s));
+        self.skews.insert(source, skew);
+        !was_skewed && self.is_skewed(skew)
+    }
+
+    /// Sources whose clock is over the threshold, e.g. `CLOCK SKEW jmx +3.2s`
+    pub fn warnings(&self) -> Vec<String> {
+        self.skews.iter()
+            .filter(|(_, skew)| self.is_skewed(**skew))
+            .map(|(source, skew)| format!("CLOCK SKEW {} {:+.1}s", source, *skew as f64 / 1000.0))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::clock::{ClockReading, ClockSkew, parse_http_date};
+
+    #[test]
+    fn parses_http_dates() {
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777_000));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("Tue, 29 Feb 2028 23:59:59 GMT"), Some(1_835_481_599_000));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 CET"), None);
+        assert_eq!(parse_http_date("Wed, 31 Dec 1969 23:59:59 GMT"), None);
+        assert_eq!(parse_http_date("Mon, 01 Jan 9223372036854775807 00:00:00 GMT"), None);
+    }
+
+    #[test]
+    fn warns_about_skewed_clocks() {
+        let reading = ClockReading { remote: 10_500, sent: 7_000, received: 7_200 };
+        assert_eq!(reading.skew_ms(), 3_400);
+
+        let mut clock = ClockSkew::new(vec!["jmx", "consul"], 2000);
+        let now = Instant::now();
+        assert!(clock.due(now));
+        assert!(!clock.due(now + Duration::from_secs(30)));
+        assert!(clock.due(now + Duration::from_secs(60)));
+
+        assert!(clock.record("jmx", reading.skew_ms()));
+        assert!(!clock.record("jmx", 3_000));
+        assert!(!clock.record("consul", -1_500));
+        assert_eq!(clock.warnings(), vec!["CLOCK SKEW jmx +3.0s"]);
+        assert!(!clock.record("jmx", 100));
+        assert!(clock.warnings().is_empty());
+    }
+}
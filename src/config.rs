@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::akka::model::{ActorWatch, ShardingSettings};
+use crate::akka::restarts::ActorRestartLog;
+use crate::check::Threshold;
+use crate::app::Panel;
+use crate::dashboard::{self, Dashboard};
+use crate::history::Retention;
+use crate::incident::IncidentSettings;
+use crate::jmx::model::{PoolErrorCounters, StatementMetrics, WatchedAttribute};
+use crate::paste::PasteSettings;
+use crate::port_forward::PortForward;
+use crate::prometheus::model::PrometheusTarget;
+use crate::quick_actions::QuickAction;
+use crate::script::derived::DerivedMetric;
+use crate::scrub::ScrubRule;
+use crate::source::command::CommandSettings;
+use crate::source::pulsar::PulsarSettings;
+use crate::source::local_jvm::LocalJvmSettings;
+use crate::source::tcp::TcpProbeSettings;
+use crate::widgets::tree::Column;
+use crate::zio::label::FiberLabel;
+use crate::zookeeper::model::{ZkAdminSettings, ZkKafkaSettings, ZkSshSettings, ZkTlsSettings};
+
+///
+/// Optional settings read from a JSON file passed with `--config`.
+///
+/// eg.
+///   ```text
+///   {
+///     "watchlist": [
+///       { "label": "Threads", "object_name": "java.lang:type=Threading", "attribute": "ThreadCount" },
+///       { "label": "Heap used", "object_name": "java.lang:type=Memory", "attribute": "HeapMemoryUsage", "key": "used" }
+///     ],
+///     "statements": { "object_name": "metrics:name=db.statements.*", "top": 10 },
+///     "pool_errors": { "connection_timeouts": "metrics:name=myDb.pool.ConnectionTimeoutRate" },
+///     "sharding": { "entity_types": ["Cart"], "nodes": ["http://node1:8558", "http://node2:8558"] },
+///     "actor_watches": [{ "name": "payments", "pattern": "/user/payment/**" }],
+///     "actor_restarts": { "path": "/var/log/orders/app.log" },
+///     "prometheus": [
+///       { "url": "http://localhost:8080/metrics", "metrics": ["jvm_threads_live", "http_requests_total"] }
+///     ],
+///     "fiber_label": { "pattern": "a future continuation at ([\\w.$]+)", "template": "$1" },
+///     "columns": { "fibers": ["label", "id", "children", "age"], "actors": ["label", "children"] },
+///     "fiber_limit": 50000,
+///     "commands": [{ "name": "Queues", "command": "./queue-depths.sh", "panel": "table" }],
+///     "pulsar": { "url": "http://localhost:8080", "cluster": "standalone", "topics": ["persistent://public/default/orders"] },
+///     "tcp_probe": { "targets": ["db.internal:5432", "kafka-1.internal:9092"] },
+///     "local_jvm": { "main_class": "com.example.OrdersApp" },
+///     "derived": [{ "name": "hikari_wait_ratio", "expr": "hikari_waiting / hikari_total" }],
+///     "thresholds": [{ "metric": "hikari_waiting", "warning": 1, "critical": 5 }],
+///     "max_clock_skew_ms": 5000,
+///     "retention": { "full_resolution": 100, "downsample_every": 30, "downsampled": 240 },
+///     "proxies": { "akka": "socks5://localhost:1080", "consul": "none" },
+///     "zookeeper_tls": { "ca": "zk-ca.pem", "keystore": "panopticon.p12" },
+///     "zookeeper_admin": { "port": 8080 },
+///     "zookeeper_ssh": { "nodes": { "zk3:2181": "ops@zk3.internal" } },
+///     "zookeeper_kafka": { "chroot": "/kafka", "jmx": true },
+///     "paste": { "url": "https://paste.internal/api/create?title={name}" },
+///     "incident": { "webhook": "https://hooks.slack.com/services/T0000/B0000/XXXX" },
+///     "port_forwards": [{ "selector": "app=orders-api", "namespace": "prod", "remote_port": 6789, "local_port": 16789, "source": "zio_zmx" }],
+///     "scrub": [{ "pattern": "customer-\\d+", "replacement": "customer-<id>" }],
+///     "labels": { "zmx": { "name": "orders-api", "tags": { "team": "payments", "env": "prod" } } },
+///     "quick_actions": [{ "key": "F5", "actions": ["tab:ZIO", "<Enter>", "filter:suspended"] }],
+///     "dashboard": { "rows": [["fiber_counts", "hikari"], ["zookeeper_nodes"]] },
+///     "startup": { "tab": "ZIO" },
+///     "profiles": {
+///       "prod": { "zio_zmx": "prod-app:6789", "jmx": "prod-app:9010", "db_pool_name": "myDb", "startup": { "tab": "Slick", "panel": "hikari" } },
+///       "staging": { "zio_zmx": "staging-app:6789", "jmx": "staging-app:9010", "db_pool_name": "myDb" }
+///     }
+///   }
+///   ```
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Config {
+    /// JMX attributes charted on the Custom tab
+    pub watchlist: Vec<WatchedAttribute>,
+    /// Statement timers listed on the Slick tab
+    pub statements: Option<StatementMetrics>,
+    /// Failure counters of the connection pool and the Slick executor, charted as rates on the Slick tab
+    pub pool_errors: Option<PoolErrorCounters>,
+    /// Cluster Sharding shown in the Akka tab
+    pub sharding: Option<ShardingSettings>,
+    /// Subtrees of the actor tree whose actor counts are charted on the Akka tab
+    pub actor_watches: Vec<ActorWatch>,
+    /// Application log tailed for restarted actors, counted per supervisor on the Akka tab
+    pub actor_restarts: Option<ActorRestartLog>,
+    /// Prometheus endpoints scraped for the Prometheus tab
+    pub prometheus: Vec<PrometheusTarget>,
+    /// Name extracted from each fiber's dump, shown in the fiber list
+    pub fiber_label: Option<FiberLabel>,
+    /// Columns shown after the tree in the fiber and actor lists
+    pub columns: ListColumns,
+    /// Fibers listed one by one at most, larger snapshots being listed as groups of fibers, 100000 unless set
+    pub fiber_limit: Option<usize>,
+    /// Shell commands polled for `<series> <value>` lines, each charted on its own tab
+    pub commands: Vec<CommandSettings>,
+    /// Pulsar cluster whose brokers and topics are charted on the Pulsar tab
+    pub pulsar: Option<PulsarSettings>,
+    /// Targets whose TCP connect latency and reachability are charted on their own tab
+    pub tcp_probe: Option<TcpProbeSettings>,
+    /// JVM on this host whose heap, GC and threads are charted from its perf data, without JMX
+    pub local_jvm: Option<LocalJvmSettings>,
+    /// Settings of the data sources of companion crates, under the name of their kind, see `source::kinds`
+    pub sources: BTreeMap<String, serde_json::Value>,
+    /// Series computed from other metrics, charted on the Derived tab
+    pub derived: Vec<DerivedMetric>,
+    /// Metric thresholds evaluated by the check command
+    pub thresholds: Vec<Threshold>,
+    /// Skew (in ms) of a source's clock from the local one over which it's warned about, 2000 unless set
+    pub max_clock_skew_ms: Option<u64>,
+    /// Samples kept in each charted history
+    pub retention: Retention,
+    /// Proxies of HTTP-based sources, overriding `HTTPS_PROXY` and `HTTP_PROXY`
+    pub proxies: ProxySettings,
+    /// TLS settings of the Zookeeper nodes, which are probed over plain TCP without them
+    pub zookeeper_tls: Option<ZkTlsSettings>,
+    /// AdminServer of the Zookeeper nodes, answering the four letter words that aren't whitelisted
+    pub zookeeper_admin: Option<ZkAdminSettings>,
+    /// SSH destinations of the Zookeeper nodes whose client port is only reachable from their host
+    pub zookeeper_ssh: Option<ZkSshSettings>,
+    /// Kafka cluster registered in the Zookeeper ensemble, whose brokers are listed on the Zookeeper tab
+    pub zookeeper_kafka: Option<ZkKafkaSettings>,
+    /// Paste service the selected fiber dump or a session summary is uploaded to with `u`
+    pub paste: Option<PasteSettings>,
+    /// Webhook the incident summary is posted to with `I`, written to a file without one
+    pub incident: Option<IncidentSettings>,
+    /// `kubectl port-forward` processes run while panopticon runs, their local ports used as addresses of sources
+    pub port_forwards: Vec<PortForward>,
+    /// Patterns scrubbed from exported and uploaded dumps once turned on with `x`
+    pub scrub: Vec<ScrubRule>,
+    /// Display names and tags of the sources, shown in tab titles and served by the HTTP API
+    pub labels: SourceLabels,
+    /// Sequences of built-in actions run with a function key
+    pub quick_actions: Vec<QuickAction>,
+    /// Panels of different tabs shown together on the Dashboard tab
+    pub dashboard: Option<Dashboard>,
+    /// Tab and panel shown on startup, unless the profile passed with `--profile` has its own
+    pub startup: Option<StartupView>,
+    /// Environments that can be compared side by side with `--compare`
+    pub profiles: HashMap<String, Profile>,
+}
+
+///
+/// Sources of an environment charted on the Compare tab.
+/// JMX credentials are read from the OS keyring for the profile's name, see `panopticon-tui login`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    pub zio_zmx: Option<String>,
+    pub jmx: Option<String>,
+    pub db_pool_name: Option<String>,
+    pub actor_count: Option<String>,
+    /// Tab and panel shown on startup when panopticon is run with `--profile` set to this profile
+    pub startup: Option<StartupView>,
+}
+
+///
+/// Tab shown on startup instead of the first one, matched by its title regardless of case,
+/// and the panel focused on it, named as on the dashboard.
+///
+/// eg. `{ "tab": "Slick", "panel": "hikari" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "StartupViewDef")]
+pub struct StartupView {
+    pub tab: String,
+    pub panel: Option<Panel>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StartupViewDef {
+    tab: String,
+    panel: Option<String>,
+}
+
+impl TryFrom<StartupViewDef> for StartupView {
+    type Error = String;
+
+    fn try_from(def: StartupViewDef) -> Result<StartupView, String> {
+        let panel = match def.panel {
+            Some(name) => Some(dashboard::panel_named(&name).ok_or(format!("unknown startup panel {}", name))?),
+            None => None,
+        };
+        Ok(StartupView { tab: def.tab, panel })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ListColumns {
+    pub fibers: Vec<Column>,
+    pub actors: Vec<Column>,
+}
+
+impl Default for ListColumns {
+    fn default() -> ListColumns {
+        ListColumns { fibers: vec![Column::Label], actors: vec![Column::Label] }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProxySettings {
+    pub akka: Option<Proxy>,
+    pub consul: Option<Proxy>,
+    pub prometheus: Option<Proxy>,
+    pub pulsar: Option<Proxy>,
+    pub paste: Option<Proxy>,
+    pub incident: Option<Proxy>,
+}
+
+/// Labels of the sources by their name, as used in logs
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SourceLabels {
+    pub zmx: Option<SourceLabel>,
+    pub jmx: Option<SourceLabel>,
+    pub akka: Option<SourceLabel>,
+    pub redis: Option<SourceLabel>,
+    pub consul: Option<SourceLabel>,
+    pub gclog: Option<SourceLabel>,
+    pub prometheus: Option<SourceLabel>,
+}
+
+impl SourceLabels {
+    pub fn get(&self, source: &str) -> Option<&SourceLabel> {
+        match source {
+            "zmx" => self.zmx.as_ref(),
+            "jmx" => self.jmx.as_ref(),
+            "akka" => self.akka.as_ref(),
+            "redis" => self.redis.as_ref(),
+            "consul" => self.consul.as_ref(),
+            "gclog" => self.gclog.as_ref(),
+            "prometheus" => self.prometheus.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Display name of a source, e.g. the service it monitors, and tags such as its team or environment
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SourceLabel {
+    pub name: Option<String>,
+    pub tags: BTreeMap<String, String>,
+}
+
+impl SourceLabel {
+    /// Name followed by the tag values, e.g. `orders-api (prod, payments)`
+    pub fn summary(&self) -> String {
+        let tags: Vec<&str> = self.tags.values().map(String::as_str).collect();
+        match (&self.name, tags.is_empty()) {
+            (Some(name), true) => name.to_owned(),
+            (Some(name), false) => format!("{} ({})", name, tags.join(", ")),
+            (None, true) => String::new(),
+            (None, false) => format!("({})", tags.join(", ")),
+        }
+    }
+}
+
+/// URL of an HTTP or SOCKS5 proxy, or `none` to connect directly
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub struct Proxy(pub String);
+
+impl TryFrom<String> for Proxy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Proxy, String> {
+        const SCHEMES: [&str; 4] = ["http://", "https://", "socks5://", "socks5h://"];
+        if s == "none" || SCHEMES.iter().any(|p| s.starts_with(p)) {
+            Ok(Proxy(s))
+        } else {
+            Err(format!("proxy {}: expected none or an http://, https://, socks5:// or socks5h:// url", s))
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, String> {
+        let s = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read config {}: {}", path, e))?;
+        Config::parse(&s).map_err(|e| format!("Invalid config {}: {}", path, e))
+    }
+
+    pub fn parse(s: &str) -> Result<Config, String> {
+        serde_json::from_str(s).map_err(|e| e.to_string())
+    }
+
+    /// Startup view of the profile, or else the one shared by all profiles
+    pub fn startup_view(&self, profile: Option<&str>) -> Option<&StartupView> {
+        profile.and_then(|p| self.profiles.get(p))
+            .and_then(|p| p.startup.as_ref())
+            .or_else(|| self.startup.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Panel;
+    use crate::config::{Config, Proxy, SourceLabel};
+    use crate::widgets::tree::Column;
+
+    #[test]
+    fn parses_watchlist() {
+        let config = Config::parse(r#"{
+            "watchlist": [
+                { "label": "Threads", "object_name": "java.lang:type=Threading", "attribute": "ThreadCount" },
+                { "label": "Heap", "object_name": "java.lang:type=Memory", "attribute": "HeapMemoryUsage", "key": "used" }
+            ]
+        }"#).unwrap();
+
+        assert_eq!(config.watchlist.len(), 2);
+        assert_eq!(config.watchlist[0].key, None);
+        assert_eq!(config.watchlist[1].key, Some("used".to_owned()));
+        assert!(config.prometheus.is_empty());
+        assert_eq!(config.statements, None);
+        assert_eq!(Config::parse("{}"), Ok(Config::default()));
+        assert!(Config::parse(r#"{ "watchlist": [{ "label": "x" }] }"#).is_err());
+    }
+
+    #[test]
+    fn parses_list_columns() {
+        let config = Config::parse(r#"{ "columns": { "fibers": ["label", "children", "age"] } }"#).unwrap();
+
+        assert_eq!(config.columns.fibers, vec![Column::Label, Column::Children, Column::Age]);
+        assert_eq!(config.columns.actors, vec![Column::Label]);
+        assert!(Config::parse(r#"{ "columns": { "actors": ["size"] } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_profiles() {
+        let config = Config::parse(r#"{
+            "profiles": {
+                "prod": { "zio_zmx": "prod:6789", "actor_count": "http://prod:8080/actors/count" },
+                "staging": { "jmx": "staging:9010", "db_pool_name": "myDb" }
+            }
+        }"#).unwrap();
+
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles["prod"].zio_zmx, Some("prod:6789".to_owned()));
+        assert_eq!(config.profiles["prod"].jmx, None);
+        assert_eq!(config.profiles["staging"].db_pool_name, Some("myDb".to_owned()));
+    }
+
+    #[test]
+    fn parses_derived_metrics() {
+        let config = Config::parse(r#"{ "derived": [{ "name": "ratio", "expr": "hikari_waiting / hikari_total" }] }"#).unwrap();
+
+        assert_eq!(config.derived[0].name, "ratio");
+        assert_eq!(config.derived[0].source, "hikari_waiting / hikari_total");
+        assert!(Config::parse(r#"{ "derived": [{ "name": "ratio", "expr": "hikari_waiting /" }] }"#).is_err());
+    }
+
+    #[test]
+    fn parses_actor_watches() {
+        let config = Config::parse(r#"{ "actor_watches": [{ "name": "payments", "pattern": "/user/payment/**" }] }"#).unwrap();
+
+        assert_eq!(config.actor_watches[0].name, "payments");
+        assert_eq!(config.actor_watches[0].pattern, "/user/payment/**");
+        assert!(Config::parse(r#"{ "actor_watches": [{ "name": "payments", "path": "/user/payment/**" }] }"#).is_err());
+    }
+
+    #[test]
+    fn parses_proxies() {
+        let config = Config::parse(r#"{ "proxies": { "akka": "socks5h://localhost:1080", "consul": "none" } }"#).unwrap();
+
+        assert_eq!(config.proxies.akka, Some(Proxy("socks5h://localhost:1080".to_owned())));
+        assert_eq!(config.proxies.consul, Some(Proxy("none".to_owned())));
+        assert_eq!(config.proxies.prometheus, None);
+        assert!(Config::parse(r#"{ "proxies": { "akka": "localhost:1080" } }"#).is_err());
+        assert!(Config::parse(r#"{ "proxies": { "redis": "none" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_source_labels() {
+        let config = Config::parse(r#"{
+            "labels": {
+                "zmx": { "name": "orders-api", "tags": { "team": "payments", "env": "prod" } },
+                "jmx": { "tags": { "env": "prod" } }
+            }
+        }"#).unwrap();
+
+        assert_eq!(config.labels.get("zmx").map(SourceLabel::summary), Some("orders-api (prod, payments)".to_owned()));
+        assert_eq!(config.labels.get("jmx").map(SourceLabel::summary), Some("(prod)".to_owned()));
+        assert_eq!(config.labels.get("akka"), None);
+        assert!(Config::parse(r#"{ "labels": { "slick": { "name": "db" } } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_retention() {
+        let config = Config::parse(r#"{ "retention": { "downsample_every": 10 } }"#).unwrap();
+
+        assert_eq!(config.retention.full_resolution, 100);
+        assert_eq!(config.retention.downsample_every, 10);
+        assert!(Config::parse(r#"{ "retention": { "hours": 4 } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_zookeeper_tls() {
+        let config = Config::parse(r#"{ "zookeeper_tls": { "ca": "zk-ca.pem" } }"#).unwrap();
+        let tls = config.zookeeper_tls.unwrap();
+
+        assert_eq!(tls.ca, Some("zk-ca.pem".to_owned()));
+        assert_eq!(tls.keystore, None);
+        assert_eq!(Config::parse(r#"{ "zookeeper_tls": {} }"#).unwrap().zookeeper_tls, Some(Default::default()));
+        assert!(Config::parse(r#"{ "zookeeper_tls": { "truststore": "zk.jks" } }"#).is_err());
+        // secrets belong in the keyring
+        assert!(Config::parse(r#"{ "zookeeper_tls": { "keystore_password": "secret" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_zookeeper_admin() {
+        let config = Config::parse(r#"{ "zookeeper_admin": {} }"#).unwrap();
+        let admin = config.zookeeper_admin.unwrap();
+
+        assert_eq!(admin.port, 8080);
+        assert_eq!(admin.command_url("zk1:2181", "srvr"), "http://zk1:8080/commands/srvr");
+        assert!(!admin.is_transport_of("zk1:2181"));
+
+        let config = Config::parse(r#"{ "zookeeper_admin": { "port": 9090, "nodes": ["zk3:2181"] } }"#).unwrap();
+        assert!(config.zookeeper_admin.unwrap().is_transport_of("zk3:2181"));
+        assert!(Config::parse(r#"{ "zookeeper_admin": { "url": "http://zk1:8080" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_zookeeper_kafka() {
+        assert_eq!(Config::parse(r#"{ "zookeeper_kafka": {} }"#).unwrap().zookeeper_kafka.unwrap().brokers_path(), "/brokers/ids");
+        let config = Config::parse(r#"{ "zookeeper_kafka": { "chroot": "/kafka/" } }"#).unwrap();
+        assert_eq!(config.zookeeper_kafka.unwrap().brokers_path(), "/kafka/brokers/ids");
+        assert!(Config::parse(r#"{ "zookeeper_kafka": { "path": "/kafka" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_zookeeper_ssh() {
+        let config = Config::parse(r#"{ "zookeeper_ssh": { "nodes": { "zk3:2181": "ops@zk3.internal" }, "options": ["-p", "2222"] } }"#).unwrap();
+        let ssh = config.zookeeper_ssh.unwrap();
+
+        assert!(ssh.is_transport_of("zk3:2181"));
+        assert!(!ssh.is_transport_of("zk1:2181"));
+        assert_eq!(ssh.args("zk3:2181", "ruok", 2).unwrap(), vec![
+            "-o", "BatchMode=yes", "-o", "ConnectTimeout=2", "-p", "2222", "ops@zk3.internal",
+            "bash -c 'exec 3<>/dev/tcp/zk3/2181 && printf ruok >&3 && cat <&3'",
+        ]);
+        assert_eq!(ssh.args("zk3:2181", "ruok; rm", 2), Err("ruok; rm isn't a four letter word command".to_owned()));
+        assert!(ssh.args("zk1:2181", "ruok", 2).is_err());
+        assert!(Config::parse(r#"{ "zookeeper_ssh": { "user": "ops" } }"#).is_err());
+    }
+
+    #[test]
+    fn picks_startup_view_of_profile() {
+        let config = Config::parse(r#"{
+            "startup": { "tab": "ZIO" },
+            "profiles": {
+                "payments": { "jmx": "payments:9010", "startup": { "tab": "Slick", "panel": "hikari" } },
+                "orders": { "jmx": "orders:9010" }
+            }
+        }"#).unwrap();
+
+        let payments = config.startup_view(Some("payments")).unwrap();
+        assert_eq!((payments.tab.as_str(), payments.panel), ("Slick", Some(Panel::Hikari)));
+        assert_eq!(config.startup_view(Some("orders")).map(|v| v.tab.as_str()), Some("ZIO"));
+        assert_eq!(config.startup_view(None).and_then(|v| v.panel), None);
+        assert!(Config::parse(r#"{ "startup": { "tab": "Slick", "panel": "heap" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_paste_settings() {
+        let config = Config::parse(r#"{ "paste": { "url": "https://paste.internal/api/create?title={name}" } }"#).unwrap();
+
+        assert_eq!(config.paste.unwrap().url, "https://paste.internal/api/create?title={name}");
+        assert!(Config::parse(r#"{ "paste": { "url": "https://paste.internal", "token": "secret" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_pulsar_settings() {
+        let config = Config::parse(r#"{ "pulsar": { "url": "http://localhost:8080", "cluster": "standalone", "topics": ["persistent://public/default/orders"] } }"#).unwrap();
+
+        let pulsar = config.pulsar.unwrap();
+        assert_eq!(pulsar.cluster, "standalone");
+        assert_eq!(pulsar.topics, vec!["persistent://public/default/orders"]);
+        assert_eq!(pulsar.token, None);
+        assert!(Config::parse(r#"{ "pulsar": { "url": "http://localhost:8080" } }"#).is_err());
+    }
+
+    #[test]
+    fn parses_quick_actions() {
+        let config = Config::parse(r#"{ "quick_actions": [{ "key": "F5", "actions": ["tab:ZIO", "filter:suspended"] }] }"#).unwrap();
+
+        assert_eq!(config.quick_actions[0].key, "F5");
+        assert_eq!(config.quick_actions[0].actions, vec!["tab:ZIO", "filter:suspended"]);
+        assert!(Config::parse(r#"{ "quick_actions": [{ "key": "F5", "steps": [] }] }"#).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+
+use crate::diagnostics::host_port;
+
+///
+/// Source connected while the TUI runs, typed in the command line opened with `:`,
+/// e.g. `connect zmx localhost:6789` when another instance turns up mid-session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Connection {
+    /// Address of a zio-zmx server, or its Unix domain socket
+    Zmx(String),
+    Redis(String),
+    /// URL of a consul agent's HTTP API, `http://` being added to addresses without a scheme
+    Consul(String),
+}
+
+impl Connection {
+    const USAGE: &'static str = "Usage: connect <zmx|redis|consul> <host:port>";
+
+    pub fn parse(command: &str) -> Result<Connection, String> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words.as_slice() {
+            ["connect", kind, address] => {
+                let address = address.to_string();
+                match *kind {
+                    "zmx" if address.starts_with("unix://") || host_port(&address).is_some() => Ok(Connection::Zmx(address)),
+                    "redis" if host_port(&address).is_some() => Ok(Connection::Redis(address)),
+                    "consul" => {
+                        let url = if address.contains("://") { address } else { format!("http://{}", address) };
+                        match host_port(&url) {
+                            Some(_) => Ok(Connection::Consul(url)),
+                            None => Err(format!("{} isn't a URL", url)),
+                        }
+                    }
+                    "zmx" | "redis" => Err(format!("{} isn't a host:port", address)),
+                    _ => Err(format!("Can't connect {}. {}", kind, Connection::USAGE)),
+                }
+            }
+            ["connect", ..] => Err(Connection::USAGE.to_owned()),
+            [] => Err("No command typed".to_owned()),
+            _ => Err(format!("Unknown command {}, only connect is", words[0])),
+        }
+    }
+
+    /// Source of the connection, as assigned to the fetch workers
+    pub fn source(&self) -> &'static str {
+        match self {
+            Connection::Zmx(_) => "zmx",
+            Connection::Redis(_) => "redis",
+            Connection::Consul(_) => "consul",
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        match self {
+            Connection::Zmx(a) | Connection::Redis(a) | Connection::Consul(a) => a,
+        }
+    }
+}
+
+/// Connections made in the command line, read by the fetch workers as they're built again to connect to them
+#[derive(Clone, Default)]
+pub struct Connections(Arc<Mutex<Vec<Connection>>>);
+
+impl Connections {
+    pub fn add(&self, connection: Connection) {
+        self.0.lock().unwrap().push(connection);
+    }
+
+    /// Address of the source connected in the command line, e.g. of `zmx`
+    pub fn address(&self, source: &str) -> Option<String> {
+        self.0.lock().unwrap().iter()
+            .find(|c| c.source() == source)
+            .map(|c| c.address().to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::connect::{Connection, Connections};
+
+    #[test]
+    fn parses_connect_command() {
+        assert_eq!(Connection::parse("connect zmx localhost:6789"), Ok(Connection::Zmx("localhost:6789".to_owned())));
+        assert_eq!(Connection::parse(" connect  zmx unix:///var/run/zmx.sock"), Ok(Connection::Zmx("unix:///var/run/zmx.sock".to_owned())));
+        assert_eq!(Connection::parse("connect redis cache:6379"), Ok(Connection::Redis("cache:6379".to_owned())));
+        assert_eq!(Connection::parse("connect consul consul:8500"), Ok(Connection::Consul("http://consul:8500".to_owned())));
+        assert_eq!(Connection::parse("connect consul https://consul"), Ok(Connection::Consul("https://consul".to_owned())));
+
+        assert_eq!(Connection::parse("connect zmx localhost"), Err("localhost isn't a host:port".to_owned()));
+        assert_eq!(Connection::parse("connect jmx localhost:9010"), Err("Can't connect jmx. Usage: connect <zmx|redis|consul> <host:port>".to_owned()));
+        assert_eq!(Connection::parse("connect zmx"), Err("Usage: connect <zmx|redis|consul> <host:port>".to_owned()));
+        assert_eq!(Connection::parse("disconnect zmx"), Err("Unknown command disconnect, only connect is".to_owned()));
+
+        let connections = Connections::default();
+        connections.add(Connection::Redis("cache:6379".to_owned()));
+        assert_eq!(connections.clone().address("redis"), Some("cache:6379".to_owned()));
+        assert_eq!(connections.address("zmx"), None);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::config::Proxy;
+use crate::consul::model::{ConsulCheck, ServiceHealth};
+use crate::http;
+
+/// Health of all services registered in the Consul agent's datacenter
+pub fn get_services(address: &str, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<ServiceHealth>, String> {
+    get_services_async(address, timeout, proxy)
+}
+
+#[tokio::main]
+async fn get_services_async(address: &str, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<ServiceHealth>, String> {
+    let url = format!("{}/v1/health/state/any", address.trim_end_matches('/'));
+    let response = http::client(proxy)?
+        .get(&url)
+        .timeout(std::time::Duration::from_millis(timeout))
+        .send().await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Request to get health checks failed with status {}", response.status()));
+    }
+    let checks: Vec<ConsulCheck> = response.json().await.map_err(|e| e.to_string())?;
+    Ok(ServiceHealth::summarize(&checks))
+}
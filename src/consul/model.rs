@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::Downsample;
+
+/// Status of a Consul health check, ordered from the best to the worst
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Passing,
+    Warning,
+    Critical,
+}
+
+/// The worst status of the bucket
+impl Downsample for HealthStatus {
+    fn merge(&mut self, later: HealthStatus) {
+        *self = (*self).max(later);
+    }
+}
+
+/// A health check as returned by the `/v1/health/state/any` endpoint
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConsulCheck {
+    pub node: String,
+    pub name: String,
+    pub status: HealthStatus,
+    /// Empty for node-level checks
+    #[serde(default)]
+    pub service_name: String,
+}
+
+/// Checks of a service summarized, its status is the worst status of its checks
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ServiceHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    pub passing: usize,
+    pub warning: usize,
+    pub critical: usize,
+}
+
+impl ServiceHealth {
+    ///
+    /// Groups checks by service, sorted by service name.
+    /// Node-level checks are grouped under `node <node name>`.
+    pub fn summarize(checks: &[ConsulCheck]) -> Vec<ServiceHealth> {
+        let mut services: BTreeMap<String, ServiceHealth> = BTreeMap::new();
+        for c in checks {
+            let name = if c.service_name.is_empty() { format!("node {}", c.node) } else { c.service_name.to_owned() };
+            let s = services.entry(name.clone()).or_insert(ServiceHealth {
+                name,
+                status: HealthStatus::Passing,
+                passing: 0,
+                warning: 0,
+                critical: 0,
+            });
+            s.status = s.status.max(c.status);
+            match c.status {
+                HealthStatus::Passing => s.passing += 1,
+                HealthStatus::Warning => s.warning += 1,
+                HealthStatus::Critical => s.critical += 1,
+            }
+        }
+        services.into_values().collect()
+    }
+}
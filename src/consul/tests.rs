@@ -0,0 +1,17 @@
+#[test]
+fn summarizes_checks_by_service() {
+    use crate::consul::model::{ConsulCheck, HealthStatus, ServiceHealth};
+
+    let checks: Vec<ConsulCheck> = serde_json::from_str(r#"[
+        { "Node": "node-1", "CheckID": "serfHealth", "Name": "Serf Health Status", "Status": "passing", "ServiceName": "" },
+        { "Node": "node-1", "CheckID": "service:web-1", "Name": "web", "Status": "passing", "ServiceName": "web" },
+        { "Node": "node-2", "CheckID": "service:web-2", "Name": "web", "Status": "critical", "ServiceName": "web" },
+        { "Node": "node-2", "CheckID": "service:kafka", "Name": "kafka", "Status": "warning", "ServiceName": "kafka" }
+    ]"#).unwrap();
+
+    assert_eq!(ServiceHealth::summarize(&checks), vec![
+        ServiceHealth { name: "kafka".to_owned(), status: HealthStatus::Warning, passing: 0, warning: 1, critical: 0 },
+        ServiceHealth { name: "node node-1".to_owned(), status: HealthStatus::Passing, passing: 1, warning: 0, critical: 0 },
+        ServiceHealth { name: "web".to_owned(), status: HealthStatus::Critical, passing: 1, warning: 0, critical: 1 },
+    ]);
+}
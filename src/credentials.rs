@@ -0,0 +1,173 @@
+use std::env;
+use std::io::{stdout, Write};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    terminal::{disable_raw_mode, enable_raw_mode},
+};
+use serde::{Deserialize, Serialize};
+
+/// Service name the secrets are stored under in the OS keyring
+pub const KEYRING_SERVICE: &str = "panopticon-tui";
+pub const USERNAME_VAR: &str = "PANOPTICON_JMX_USERNAME";
+pub const PASSWORD_VAR: &str = "PANOPTICON_JMX_PASSWORD";
+pub const ZOOKEEPER_KEYSTORE_PASSWORD_VAR: &str = "PANOPTICON_ZOOKEEPER_KEYSTORE_PASSWORD";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    /// Credentials from `PANOPTICON_JMX_USERNAME` and `PANOPTICON_JMX_PASSWORD`, e.g. for CI
+    pub fn from_env() -> Option<Credentials> {
+        Credentials::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> Option<Credentials> {
+        match (var(USERNAME_VAR), var(PASSWORD_VAR)) {
+            (Some(username), Some(password)) => Some(Credentials { username, password }),
+            _ => None,
+        }
+    }
+
+    /// Credentials stored for the profile with `panopticon-tui login <profile>`
+    pub fn load(profile: &str) -> Result<Option<Credentials>, String> {
+        match read_secret(profile, &format!("credentials for profile {}", profile))? {
+            Some(secret) => serde_json::from_str(&secret)
+                .map(Some)
+                .map_err(|e| format!("Invalid credentials stored for profile {}: {}", profile, e)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn store(&self, profile: &str) -> Result<(), String> {
+        let secret = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        store_secret(profile, &format!("credentials for profile {}", profile), &secret)
+    }
+
+    ///
+    /// Looks up credentials for the profile in the OS keyring,
+    /// falling back to environment variables when there's no profile, no keyring or no entry for it.
+    pub fn resolve(profile: Option<&str>) -> Result<Option<Credentials>, String> {
+        let stored = match profile {
+            Some(p) => Credentials::load(p).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                None
+            }),
+            None => None,
+        };
+        match stored.or_else(Credentials::from_env) {
+            Some(c) => Ok(Some(c)),
+            None => match profile {
+                Some(p) => Err(format!(
+                    "No credentials found for profile {}. Run `panopticon-tui login {}` or set {} and {}",
+                    p, p, USERNAME_VAR, PASSWORD_VAR
+                )),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// Keyring entry of the Zookeeper keystore password of a profile, next to its jmx credentials
+fn zookeeper_keystore_entry(profile: &str) -> String {
+    format!("{}/zookeeper-keystore", profile)
+}
+
+///
+/// Password of the Zookeeper client keystore, stored for the profile with `panopticon-tui login <profile> --zookeeper`,
+/// falling back to `PANOPTICON_ZOOKEEPER_KEYSTORE_PASSWORD` when there's no profile, no keyring or no entry for it.
+pub fn zookeeper_keystore_password(profile: Option<&str>) -> Option<String> {
+    let stored = profile.and_then(|p| {
+        read_secret(&zookeeper_keystore_entry(p), &format!("Zookeeper keystore password for profile {}", p)).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            None
+        })
+    });
+    stored.or_else(|| env::var(ZOOKEEPER_KEYSTORE_PASSWORD_VAR).ok())
+}
+
+/// Secret stored under the given account, `what` naming it in errors, e.g. `credentials for profile prod`
+fn read_secret(account: &str, what: &str) -> Result<Option<String>, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account)
+        .map_err(|e| format!("Couldn't access the OS keyring: {}", e))?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Couldn't read {} from the OS keyring: {}", what, e)),
+    }
+}
+
+fn store_secret(account: &str, what: &str, secret: &str) -> Result<(), String> {
+    keyring::Entry::new(KEYRING_SERVICE, account)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| format!("Couldn't store {} in the OS keyring: {}", what, e))
+}
+
+/// Asks for jmx credentials, or the Zookeeper keystore password, on the terminal and stores them in the OS keyring
+pub fn login(profile: &str, zookeeper: bool) -> Result<(), String> {
+    if zookeeper {
+        let password = prompt("Zookeeper keystore password: ", true)?;
+        store_secret(&zookeeper_keystore_entry(profile), &format!("Zookeeper keystore password for profile {}", profile), &password)?;
+        println!("Zookeeper keystore password for profile {} stored in the OS keyring", profile);
+        return Ok(());
+    }
+    let username = prompt("JMX username: ", false)?;
+    let password = prompt("JMX password: ", true)?;
+    Credentials { username, password }.store(profile)?;
+    println!("Credentials for profile {} stored in the OS keyring", profile);
+    Ok(())
+}
+
+fn prompt(label: &str, hidden: bool) -> Result<String, String> {
+    print!("{}", label);
+    stdout().flush().map_err(|e| e.to_string())?;
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    let mut line = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(KeyEvent { code: KeyCode::Enter, .. })) => break Ok(line),
+            Ok(Event::Key(KeyEvent { code: KeyCode::Esc, .. })) |
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL })) =>
+                break Err("Login cancelled".to_owned()),
+            Ok(Event::Key(KeyEvent { code: KeyCode::Backspace, .. })) => {
+                if line.pop().is_some() && !hidden {
+                    print!("\u{8} \u{8}");
+                }
+            }
+            Ok(Event::Key(KeyEvent { code: KeyCode::Char(c), .. })) => {
+                line.push(c);
+                if !hidden {
+                    print!("{}", c);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => break Err(e.to_string()),
+        }
+        let _ = stdout().flush();
+    };
+    disable_raw_mode().map_err(|e| e.to_string())?;
+    print!("\r\n");
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::credentials::{Credentials, PASSWORD_VAR, USERNAME_VAR};
+
+    #[test]
+    fn reads_credentials_from_vars() {
+        let vars = |name: &str| match name {
+            USERNAME_VAR => Some("monitor".to_owned()),
+            PASSWORD_VAR => Some("secret".to_owned()),
+            _ => None,
+        };
+        assert_eq!(
+            Credentials::from_vars(vars),
+            Some(Credentials { username: "monitor".to_owned(), password: "secret".to_owned() })
+        );
+        assert_eq!(Credentials::from_vars(|name| if name == USERNAME_VAR { Some("monitor".to_owned()) } else { None }), None);
+    }
+}
@@ -0,0 +1,128 @@
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+
+use crate::app::Panel;
+
+///
+/// Grid of panels from different tabs shown together on the Dashboard tab, e.g. to follow an incident
+/// without flipping between tabs. Rows share the height of the tab, and panels the width of their row.
+///
+/// eg. `{ "rows": [["fiber_counts", "hikari"], ["zookeeper_nodes"]] }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "DashboardDef")]
+pub struct Dashboard {
+    pub rows: Vec<Vec<Panel>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DashboardDef {
+    rows: Vec<Vec<String>>,
+}
+
+/// Panels that can be put on the dashboard by their name in the config file
+const PANELS: [(&str, Panel); 40] = [
+    ("fibers", Panel::Fibers),
+    ("fiber_counts", Panel::FiberCounts),
+    ("fiber_dump", Panel::FiberDump),
+    ("fiber_tree", Panel::FiberTree),
+    ("slick_threads", Panel::SlickThreads),
+    ("slick_utilization", Panel::SlickUtilization),
+    ("slick_queue", Panel::SlickQueue),
+    ("hikari", Panel::Hikari),
+    ("pool_errors", Panel::PoolErrors),
+    ("statements", Panel::Statements),
+    ("jvm_attributes", Panel::JVMAttributes),
+    ("jvm_classes", Panel::JVMClasses),
+    ("jvm_threads", Panel::JVMThreads),
+    ("jvm_daemon_threads", Panel::JVMDaemonThreads),
+    ("watchlist", Panel::Watchlist),
+    ("actors", Panel::Actors),
+    ("actor_count", Panel::ActorCount),
+    ("actor_events", Panel::ActorEvents),
+    ("actor_restarts", Panel::ActorRestarts),
+    ("sharding", Panel::Sharding),
+    ("redis_memory", Panel::RedisMemory),
+    ("redis_clients", Panel::RedisClients),
+    ("redis_ops", Panel::RedisOps),
+    ("redis_hit_rate", Panel::RedisHitRate),
+    ("redis_slowlog", Panel::RedisSlowlog),
+    ("consul_services", Panel::ConsulServices),
+    ("gc_pauses", Panel::GcPauses),
+    ("gc_allocation_rate", Panel::GcAllocationRate),
+    ("prometheus_series", Panel::PrometheusSeries),
+    ("compare_fibers", Panel::CompareFibers),
+    ("compare_hikari", Panel::CompareHikari),
+    ("compare_actors", Panel::CompareActors),
+    ("derived", Panel::Derived),
+    ("hooks", Panel::Hooks),
+    ("hook_log", Panel::HookLog),
+    ("zookeeper_nodes", Panel::ZookeeperNodes),
+    ("zookeeper_console", Panel::ZookeeperConsole),
+    ("zookeeper_chart", Panel::ZookeeperChart),
+    ("latency", Panel::Latency),
+    ("log", Panel::Log),
+];
+
+impl TryFrom<DashboardDef> for Dashboard {
+    type Error = String;
+
+    fn try_from(def: DashboardDef) -> Result<Dashboard, String> {
+        if def.rows.is_empty() || def.rows.iter().any(|r| r.is_empty()) {
+            return Err("dashboard rows need at least one panel each".to_owned());
+        }
+        let mut seen = vec![];
+        let mut rows = vec![];
+        for row in def.rows {
+            let mut panels = vec![];
+            for name in row {
+                let panel = panel_named(&name).ok_or(format!("unknown dashboard panel {}", name))?;
+                if seen.contains(&panel) {
+                    return Err(format!("dashboard panel {} is shown more than once", name));
+                }
+                seen.push(panel);
+                panels.push(panel);
+            }
+            rows.push(panels);
+        }
+        Ok(Dashboard { rows })
+    }
+}
+
+impl Dashboard {
+    /// Panels row by row, in focus order
+    pub fn panels(&self) -> Vec<Panel> {
+        self.rows.iter().flatten().copied().collect()
+    }
+}
+
+/// Name of a panel in the config file, e.g. `fiber_counts`
+pub fn panel_name(panel: Panel) -> &'static str {
+    PANELS.iter().find(|(_, p)| *p == panel).map_or("", |(n, _)| *n)
+}
+
+/// Panel with the given name in the config file
+pub fn panel_named(name: &str) -> Option<Panel> {
+    PANELS.iter().find(|(n, _)| *n == name).map(|(_, p)| *p)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::Panel;
+    use crate::dashboard::{panel_name, Dashboard};
+
+    #[test]
+    fn lays_out_named_panels() {
+        let dashboard: Dashboard = serde_json::from_str(r#"{ "rows": [["fiber_counts", "hikari"], ["zookeeper_nodes"]] }"#).unwrap();
+
+        assert_eq!(dashboard.rows, vec![vec![Panel::FiberCounts, Panel::Hikari], vec![Panel::ZookeeperNodes]]);
+        assert_eq!(dashboard.panels(), vec![Panel::FiberCounts, Panel::Hikari, Panel::ZookeeperNodes]);
+        assert_eq!(panel_name(Panel::ZookeeperNodes), "zookeeper_nodes");
+
+        assert!(serde_json::from_str::<Dashboard>(r#"{ "rows": [["fiber_counts", "heap"]] }"#).is_err());
+        assert!(serde_json::from_str::<Dashboard>(r#"{ "rows": [["fiber_counts"], ["fiber_counts"]] }"#).is_err());
+        assert!(serde_json::from_str::<Dashboard>(r#"{ "rows": [["fiber_counts"], []] }"#).is_err());
+        assert!(serde_json::from_str::<Dashboard>(r#"{ "rows": [] }"#).is_err());
+    }
+}
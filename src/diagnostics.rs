@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::net::ToSocketAddrs;
+
+/// Source that couldn't be reached since the start, or since the last retry
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnosis {
+    /// Name of the source, e.g. `zmx` or the title of a command source's tab
+    pub source: String,
+    /// Addresses of the source as configured
+    pub addresses: Vec<String>,
+    pub error: String,
+    /// Whether the fetcher of the source couldn't connect at all, so retrying has to connect it again
+    pub fatal: bool,
+}
+
+///
+/// Diagnostics screen shown instead of the tabs when configured sources can't be reached at startup,
+/// with the addresses attempted, the errors and what their hosts resolve to, so misconfigured
+/// addresses can be told apart from unreachable ones.
+#[derive(Default)]
+pub struct Diagnostics {
+    addresses: HashMap<String, Vec<String>>,
+    pub failures: Vec<Diagnosis>,
+    /// Addresses of the hosts of the failures resolved so far, `None` while they're being resolved
+    pub dns: HashMap<String, Option<Result<Vec<String>, String>>>,
+    unresolved: Vec<String>,
+    /// Set with `<Esc>` to go on to the tabs, until the next retry
+    pub dismissed: bool,
+}
+
+impl Diagnostics {
+    pub fn new(addresses: Vec<(&str, Vec<String>)>) -> Diagnostics {
+        Diagnostics {
+            addresses: addresses.into_iter().map(|(s, a)| (s.to_owned(), a)).collect(),
+            ..Diagnostics::default()
+        }
+    }
+
+    pub fn is_shown(&self) -> bool {
+        !self.dismissed && !self.failures.is_empty()
+    }
+
+    /// Records a failure of a source that was never reached, replacing the previous one of the source
+    pub fn record(&mut self, source: &str, error: String, fatal: bool) {
+        let addresses = self.addresses.get(source).cloned().unwrap_or_default();
+        for host in addresses.iter().filter_map(|a| host_port(a)) {
+            if !self.dns.contains_key(&host) {
+                self.dns.insert(host.clone(), None);
+                self.unresolved.push(host);
+            }
+        }
+        let diagnosis = Diagnosis { source: source.to_owned(), addresses, error, fatal };
+        match self.failures.iter_mut().find(|d| d.source == source) {
+            Some(d) => *d = diagnosis,
+            None => self.failures.push(diagnosis),
+        }
+    }
+
+    /// Hosts to resolve since the last call
+    pub fn take_unresolved(&mut self) -> Vec<String> {
+        self.unresolved.drain(..).collect()
+    }
+
+    pub fn on_resolved(&mut self, host: String, addresses: Result<Vec<String>, String>) {
+        self.dns.insert(host, Some(addresses));
+    }
+
+    /// Forgets the failure of a source once it responded
+    pub fn on_reached(&mut self, source: &str) {
+        self.failures.retain(|d| d.source != source);
+    }
+
+    /// Clears the failures to try the sources again, returning whether their fetchers have to connect again
+    pub fn retry(&mut self) -> bool {
+        let reconnect = self.failures.iter().any(|d| d.fatal);
+        self.failures.clear();
+        self.dns.clear();
+        self.unresolved.clear();
+        self.dismissed = false;
+        reconnect
+    }
+}
+
+///
+/// Host and port to resolve for an address of a source, `None` for Unix sockets and file paths.
+/// URLs without a port get the default one of their scheme.
+///
+/// eg. `http://user@prometheus:9090/metrics` gives `prometheus:9090`
+pub fn host_port(address: &str) -> Option<String> {
+    let (scheme, rest) = match address.find("://") {
+        Some(i) => (&address[..i], &address[i + 3..]),
+        None => ("", address),
+    };
+    let authority = rest.split('/').next().unwrap_or("");
+    let authority = authority.rsplit('@').next().unwrap_or("");
+    if scheme == "unix" || authority.is_empty() {
+        return None;
+    }
+    let has_port = authority.rsplit_once(':').and_then(|(_, p)| p.parse::<u16>().ok()).is_some();
+    match scheme {
+        _ if has_port => Some(authority.to_owned()),
+        "https" => Some(format!("{}:443", authority)),
+        "http" => Some(format!("{}:80", authority)),
+        _ => None,
+    }
+}
+
+/// IP addresses a `host:port` resolves to, blocking until the resolver answers
+pub fn resolve(host_port: &str) -> Result<Vec<String>, String> {
+    let mut addresses: Vec<String> = host_port.to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .map(|a| a.ip().to_string())
+        .collect();
+    addresses.dedup();
+    if addresses.is_empty() {
+        Err("no addresses".to_owned())
+    } else {
+        Ok(addresses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::{Diagnostics, host_port};
+
+    #[test]
+    fn finds_hosts_of_addresses() {
+        assert_eq!(host_port("localhost:6789"), Some("localhost:6789".to_owned()));
+        assert_eq!(host_port("http://user@prometheus:9090/metrics"), Some("prometheus:9090".to_owned()));
+        assert_eq!(host_port("https://consul.internal/v1"), Some("consul.internal:443".to_owned()));
+        assert_eq!(host_port("unix:///var/run/zmx.sock"), None);
+        assert_eq!(host_port("/var/log/gc.log"), None);
+        assert_eq!(host_port("[::1]:8080"), Some("[::1]:8080".to_owned()));
+    }
+
+    #[test]
+    fn records_failures_until_sources_are_reached() {
+        let mut diagnostics = Diagnostics::new(vec![
+            ("zmx", vec!["zmx.internal:6789".to_owned()]),
+            ("redis", vec!["zmx.internal:6789".to_owned(), "unix:///var/run/redis.sock".to_owned()]),
+        ]);
+        diagnostics.record("zmx", "Connection refused".to_owned(), false);
+        diagnostics.record("zmx", "Connection timed out".to_owned(), false);
+        diagnostics.record("redis", "Couldn't connect".to_owned(), true);
+
+        assert!(diagnostics.is_shown());
+        assert_eq!(diagnostics.failures.len(), 2);
+        assert_eq!(diagnostics.failures[0].error, "Connection timed out");
+        assert_eq!(diagnostics.take_unresolved(), vec!["zmx.internal:6789"]);
+        assert!(diagnostics.take_unresolved().is_empty());
+
+        diagnostics.on_reached("zmx");
+        assert_eq!(diagnostics.failures.len(), 1);
+        assert!(diagnostics.retry());
+        assert!(!diagnostics.is_shown());
+    }
+}
@@ -1,68 +1,392 @@
-use jmx::MBeanClient;
+use std::cell::{Ref, RefCell};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use native_tls::TlsConnector;
 
 use crate::akka;
-use crate::akka::model::{ActorTreeNode, AkkaSettings};
+use crate::akka::model::{ActorTreeNode, AkkaHealth, AkkaSettings, ShardingSettings, ShardingStats};
+use crate::akka::restarts::ActorRestartLog;
+use crate::capabilities::{Capability, Feature};
+use crate::clock;
+use crate::clock::ClockReading;
+use crate::config::ProxySettings;
+use crate::consul;
+use crate::consul::model::ServiceHealth;
+use crate::gclog::model::GcPause;
+use crate::gclog::tail::GcLogTail;
+use crate::jmx;
 use crate::jmx::client::JMXClient;
-use crate::jmx::model::{HikariMetrics, JMXConnectionSettings, SlickConfig, SlickMetrics};
+use crate::jmx::connection::{MBeanConnection, RmiMBeanConnection};
+use crate::jmx::model::{format_thread_dump, HikariMetrics, JMXConnectionSettings, JMXValue, JVMRuntimeMetrics, KafkaBrokerMetrics, PoolErrorCounters, PoolErrorCounts, SlickConfig, SlickMetrics, StatementMetrics, StatementStats, WatchedAttribute};
+use crate::prometheus;
+use crate::prometheus::model::{PrometheusSample, PrometheusTarget};
+use crate::redis::client::{NetworkRedisClient, RedisClient};
+use crate::redis::model::RedisStats;
+use crate::source::{DataSource, Sample};
+use crate::tail::LogTail;
 use crate::zio::model::Fiber;
 use crate::zio::zmx::{NetworkZMXClient, ZMXClient};
+use crate::zookeeper;
+use crate::zookeeper::model::{KafkaBroker, ZkAdminSettings, ZkCommand, ZkKafkaSettings, ZkNodeStatus, ZkSshSettings, ZkTlsSettings};
 
+#[derive(Clone, Copy)]
 pub enum FetcherRequest {
     FiberDump,
     RegularFiberDump,
+    BurstFiberDump,
     HikariMetrics,
     SlickMetrics,
     SlickConfig,
+    JVMAttributes,
+    JVMRuntime,
+    WatchedAttributes,
+    StatementStats,
+    PoolErrors,
+    HeapDump,
+    ThreadDump,
     ActorTree,
     ActorCount,
+    AkkaHealth,
+    ShardingStats,
+    ActorRestarts,
+    RedisStats,
+    ConsulServices,
+    GcPauses,
+    PrometheusSamples,
+    ZookeeperStatus,
+    /// Command typed in the Zookeeper console, sent to the node with the given index
+    ZookeeperCommand(usize, ZkCommand),
+    /// Brokers of the Kafka cluster registered in the Zookeeper ensemble
+    KafkaBrokers,
+    /// Optional features of the source with the given name, probed once it's connected
+    Capabilities(&'static str),
+    /// Samples of the data source with the given index, see `source::registered`
+    SourceSamples(usize),
+    /// Clock of the source with the given name, to tell its skew from the local one
+    Clock(&'static str),
+}
+
+impl FetcherRequest {
+    /// Name of the data source the request is sent to, requests of a source share its connection
+    pub fn source(&self) -> &'static str {
+        match self {
+            FetcherRequest::FiberDump | FetcherRequest::RegularFiberDump | FetcherRequest::BurstFiberDump => "zmx",
+            FetcherRequest::HikariMetrics |
+            FetcherRequest::SlickMetrics |
+            FetcherRequest::SlickConfig |
+            FetcherRequest::JVMAttributes |
+            FetcherRequest::JVMRuntime |
+            FetcherRequest::WatchedAttributes |
+            FetcherRequest::StatementStats |
+            FetcherRequest::PoolErrors |
+            FetcherRequest::HeapDump |
+            FetcherRequest::ThreadDump => "jmx",
+            FetcherRequest::ActorTree |
+            FetcherRequest::ActorCount |
+            FetcherRequest::AkkaHealth |
+            FetcherRequest::ShardingStats |
+            FetcherRequest::ActorRestarts => "akka",
+            FetcherRequest::RedisStats => "redis",
+            FetcherRequest::ConsulServices => "consul",
+            FetcherRequest::GcPauses => "gclog",
+            FetcherRequest::PrometheusSamples => "prometheus",
+            FetcherRequest::ZookeeperStatus | FetcherRequest::ZookeeperCommand(_, _) | FetcherRequest::KafkaBrokers => "zookeeper",
+            FetcherRequest::SourceSamples(_) => "sources",
+            FetcherRequest::Capabilities(source) | FetcherRequest::Clock(source) => source,
+        }
+    }
+
+    /// Key of the worker the request runs on, each data source having its own so that a slow one doesn't hold up the others
+    pub fn worker(&self) -> String {
+        match self {
+            FetcherRequest::SourceSamples(i) => format!("source{}", i),
+            _ => self.source().to_owned(),
+        }
+    }
 }
 
 pub enum FetcherResponse {
     FiberDump(Result<Vec<Fiber>, String>),
     RegularFiberDump(Result<Vec<Fiber>, String>),
+    BurstFiberDump(Result<Vec<Fiber>, String>),
     HikariMetrics(Result<HikariMetrics, String>),
     SlickMetrics(Result<SlickMetrics, String>),
     SlickConfig(Result<SlickConfig, String>),
+    JVMAttributes(Result<Vec<(String, JMXValue)>, String>),
+    JVMRuntime(Result<JVMRuntimeMetrics, String>),
+    WatchedAttributes(Result<Vec<(String, Option<f64>)>, String>),
+    StatementStats(Result<Vec<StatementStats>, String>),
+    PoolErrors(Result<PoolErrorCounts, String>),
+    /// Path of the heap dump on the JVM host
+    HeapDump(Result<String, String>),
+    /// Thread dump formatted like jstack does
+    ThreadDump(Result<String, String>),
     ActorTree(Result<Vec<ActorTreeNode>, String>),
     ActorCount(Result<u64, String>),
-    FatalFailure(String),
+    AkkaHealth(AkkaHealth),
+    ShardingStats(Result<ShardingStats, String>),
+    /// Paths of the actors restarted since the previous read of the log
+    ActorRestarts(Result<Vec<String>, String>),
+    RedisStats(Result<RedisStats, String>),
+    ConsulServices(Result<Vec<ServiceHealth>, String>),
+    GcPauses(Result<Vec<GcPause>, String>),
+    PrometheusSamples(Result<Vec<PrometheusSample>, String>),
+    ZookeeperStatus(Vec<ZkNodeStatus>),
+    /// Raw response of a node to a console command
+    ZookeeperCommand(usize, ZkCommand, Result<String, String>),
+    KafkaBrokers(Result<Vec<KafkaBroker>, String>),
+    /// Probed features of the source with the given name, missing ones aren't errors
+    Capabilities(&'static str, Vec<Capability>),
+    SourceSamples(usize, Result<Vec<Sample>, String>),
+    /// Skew (in ms) of the clock of the source with the given name, positive when it's ahead of the local one
+    Clock(&'static str, Result<i64, String>),
+    /// The fetcher of the source with the given name couldn't connect
+    FatalFailure(&'static str, String),
+}
+
+impl FetcherResponse {
+    /// Name of the data source the response came from, as used in logs
+    pub fn source(&self) -> &'static str {
+        match self {
+            FetcherResponse::FiberDump(_) | FetcherResponse::RegularFiberDump(_) | FetcherResponse::BurstFiberDump(_) => "zmx",
+            FetcherResponse::HikariMetrics(_) => "hikari",
+            FetcherResponse::SlickMetrics(_) | FetcherResponse::SlickConfig(_) => "slick",
+            FetcherResponse::JVMAttributes(_) | FetcherResponse::JVMRuntime(_) | FetcherResponse::WatchedAttributes(_) => "jvm",
+            FetcherResponse::HeapDump(_) | FetcherResponse::ThreadDump(_) => "jvm",
+            FetcherResponse::StatementStats(_) | FetcherResponse::PoolErrors(_) => "slick",
+            FetcherResponse::ActorTree(_) |
+            FetcherResponse::ActorCount(_) |
+            FetcherResponse::AkkaHealth(_) |
+            FetcherResponse::ShardingStats(_) |
+            FetcherResponse::ActorRestarts(_) => "akka",
+            FetcherResponse::RedisStats(_) => "redis",
+            FetcherResponse::ConsulServices(_) => "consul",
+            FetcherResponse::GcPauses(_) => "gclog",
+            FetcherResponse::PrometheusSamples(_) => "prometheus",
+            FetcherResponse::ZookeeperStatus(_) | FetcherResponse::ZookeeperCommand(_, _, _) | FetcherResponse::KafkaBrokers(_) => "zookeeper",
+            FetcherResponse::SourceSamples(_, _) => "sources",
+            FetcherResponse::Capabilities(source, _) | FetcherResponse::Clock(source, _) => source,
+            FetcherResponse::FatalFailure(source, _) => source,
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            FetcherResponse::FiberDump(Err(e)) |
+            FetcherResponse::RegularFiberDump(Err(e)) |
+            FetcherResponse::BurstFiberDump(Err(e)) |
+            FetcherResponse::HikariMetrics(Err(e)) |
+            FetcherResponse::SlickMetrics(Err(e)) |
+            FetcherResponse::SlickConfig(Err(e)) |
+            FetcherResponse::JVMAttributes(Err(e)) |
+            FetcherResponse::JVMRuntime(Err(e)) |
+            FetcherResponse::WatchedAttributes(Err(e)) |
+            FetcherResponse::StatementStats(Err(e)) |
+            FetcherResponse::PoolErrors(Err(e)) |
+            FetcherResponse::HeapDump(Err(e)) |
+            FetcherResponse::ThreadDump(Err(e)) |
+            FetcherResponse::ActorTree(Err(e)) |
+            FetcherResponse::ActorCount(Err(e)) |
+            FetcherResponse::ShardingStats(Err(e)) |
+            FetcherResponse::ActorRestarts(Err(e)) |
+            FetcherResponse::RedisStats(Err(e)) |
+            FetcherResponse::ConsulServices(Err(e)) |
+            FetcherResponse::GcPauses(Err(e)) |
+            FetcherResponse::PrometheusSamples(Err(e)) |
+            FetcherResponse::ZookeeperCommand(_, _, Err(e)) |
+            FetcherResponse::KafkaBrokers(Err(e)) |
+            FetcherResponse::SourceSamples(_, Err(e)) |
+            FetcherResponse::Clock(_, Err(e)) |
+            FetcherResponse::FatalFailure(_, e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 pub struct Fetcher {
     pub zmx_client: Option<Box<dyn ZMXClient>>,
-    pub jmx: Option<JMXClient>,
+    pub jmx: Option<JMXConnectionSettings>,
+    /// Client of the JVM, connected on the first request and again on the next one after failing to
+    pub jmx_client: RefCell<Option<JMXClient>>,
+    pub watchlist: Vec<WatchedAttribute>,
+    pub statements: Option<StatementMetrics>,
+    pub pool_errors: Option<PoolErrorCounters>,
     pub akka_settings: Option<AkkaSettings>,
+    pub sharding: Option<ShardingSettings>,
+    /// Application log tailed for restarted actors
+    pub actor_restarts: Option<(ActorRestartLog, LogTail)>,
+    pub redis_client: Option<Box<dyn RedisClient>>,
+    pub consul_address: Option<String>,
+    pub gc_log: Option<GcLogTail>,
+    pub prometheus_targets: Vec<PrometheusTarget>,
+    /// Nodes of the ensemble, replaced by the members it lists on each poll while discovering them
+    pub zookeeper_nodes: RefCell<Vec<String>>,
+    pub zookeeper_discover: bool,
+    /// Secure client port of the nodes, which are probed over plain TCP without it
+    pub zookeeper_tls: Option<ZkTlsSettings>,
+    /// Connector of the secure client port, built from the keystores on the first request and again after failing to
+    pub zookeeper_tls_connector: RefCell<Option<TlsConnector>>,
+    /// AdminServer of the nodes, answering the commands that aren't whitelisted
+    pub zookeeper_admin: Option<ZkAdminSettings>,
+    /// SSH destinations of the nodes whose client port is only reachable from their host
+    pub zookeeper_ssh: Option<ZkSshSettings>,
+    /// Kafka cluster registered in the ensemble, whose brokers are read from it
+    pub zookeeper_kafka: Option<ZkKafkaSettings>,
+    /// JMX connections of the Kafka brokers by their address, kept from one poll to the next while they work
+    pub kafka_jmx: RefCell<HashMap<String, Box<dyn MBeanConnection>>>,
+    /// Data sources by their index in `source::registered`, the ones polled by other workers left out
+    pub sources: Vec<Option<Box<dyn DataSource>>>,
+    pub proxies: ProxySettings,
+    /// Timeout (in ms) of requests to Consul, Prometheus and Zookeeper endpoints and of data source polls
+    pub http_timeout: u64,
 }
 
-impl Fetcher {
-    pub fn new(
-        zio_zmx_addr: Option<String>,
-        jmx: Option<JMXConnectionSettings>,
-        akka: Option<AkkaSettings>) -> Result<Fetcher, String> {
-        let jmx_client: Option<JMXClient> = match jmx {
-            None => Ok(None),
-            Some(conn) => {
-                let url_str = format!(
-                    "service:jmx:rmi://{}/jndi/rmi://{}/jmxrmi",
-                    &conn.address, &conn.address
-                );
-                let url = jmx::MBeanAddress::service_url(url_str.clone());
-                MBeanClient::connect(url)
-                    .map(|x| Some(JMXClient::new(x, conn.db_pool_name.clone())))
-                    .map_err(|e| format!(
-                        "Couldn't connect to jmx at {}. Error: {}", url_str, e
-                    ))
-            }
-        }?;
+/// What a fetcher connects to, the sources left unset aren't fetched from
+#[derive(Default)]
+pub struct FetcherSettings {
+    pub zio_zmx_addr: Option<String>,
+    pub jmx: Option<JMXConnectionSettings>,
+    pub watchlist: Vec<WatchedAttribute>,
+    pub statements: Option<StatementMetrics>,
+    pub pool_errors: Option<PoolErrorCounters>,
+    pub akka: Option<AkkaSettings>,
+    pub sharding: Option<ShardingSettings>,
+    pub actor_restarts: Option<ActorRestartLog>,
+    pub redis_addr: Option<String>,
+    pub consul_addr: Option<String>,
+    pub gc_log_path: Option<String>,
+    pub prometheus_targets: Vec<PrometheusTarget>,
+    pub zookeeper_nodes: Vec<String>,
+    pub zookeeper_tls: Option<ZkTlsSettings>,
+    pub zookeeper_admin: Option<ZkAdminSettings>,
+    pub zookeeper_ssh: Option<ZkSshSettings>,
+    pub zookeeper_kafka: Option<ZkKafkaSettings>,
+    pub zookeeper_discover: bool,
+    pub sources: Vec<Option<Box<dyn DataSource>>>,
+    pub proxies: ProxySettings,
+    pub http_timeout: u64,
+}
 
-        Ok(Fetcher {
+impl Fetcher {
+    ///
+    /// Fetcher of the given sources. Connections are made on the first request of their source, so a source
+    /// that can't be reached fails its own requests rather than the other sources of the worker.
+    pub fn new(settings: FetcherSettings) -> Fetcher {
+        let FetcherSettings {
+            zio_zmx_addr, jmx, watchlist, statements, pool_errors, akka, sharding, actor_restarts, redis_addr, consul_addr,
+            gc_log_path, prometheus_targets, zookeeper_nodes, zookeeper_tls, zookeeper_admin, zookeeper_ssh, zookeeper_kafka,
+            zookeeper_discover, sources, proxies, http_timeout,
+        } = settings;
+        Fetcher {
             zmx_client: zio_zmx_addr.map(|x| {
                 let a: Box<dyn ZMXClient> = Box::new(NetworkZMXClient::new(x));
                 a
             }),
-            jmx: jmx_client,
+            jmx,
+            jmx_client: RefCell::new(None),
+            watchlist,
+            statements,
+            pool_errors,
             akka_settings: akka,
-        })
+            sharding,
+            actor_restarts: actor_restarts.map(|l| {
+                let tail = LogTail::new(l.path.to_owned());
+                (l, tail)
+            }),
+            redis_client: redis_addr.map(|x| {
+                let a: Box<dyn RedisClient> = Box::new(NetworkRedisClient::new(x));
+                a
+            }),
+            consul_address: consul_addr,
+            gc_log: gc_log_path.map(GcLogTail::new),
+            prometheus_targets,
+            zookeeper_nodes: RefCell::new(zookeeper_nodes),
+            zookeeper_tls,
+            zookeeper_tls_connector: RefCell::new(None),
+            zookeeper_admin,
+            zookeeper_ssh,
+            zookeeper_kafka,
+            kafka_jmx: RefCell::new(HashMap::new()),
+            zookeeper_discover,
+            sources,
+            proxies,
+            http_timeout,
+        }
+    }
+
+    pub fn fetch(&self, request: FetcherRequest) -> FetcherResponse {
+        match request {
+            FetcherRequest::FiberDump => FetcherResponse::FiberDump(self.dump_fibers()),
+            FetcherRequest::RegularFiberDump => FetcherResponse::RegularFiberDump(self.dump_fibers()),
+            FetcherRequest::BurstFiberDump => FetcherResponse::BurstFiberDump(self.dump_fibers()),
+            FetcherRequest::HikariMetrics => FetcherResponse::HikariMetrics(self.get_hikari_metrics()),
+            FetcherRequest::SlickMetrics => FetcherResponse::SlickMetrics(self.get_slick_metrics()),
+            FetcherRequest::SlickConfig => FetcherResponse::SlickConfig(self.get_slick_config()),
+            FetcherRequest::JVMAttributes => FetcherResponse::JVMAttributes(self.get_jvm_attributes()),
+            FetcherRequest::JVMRuntime => FetcherResponse::JVMRuntime(self.get_jvm_runtime_metrics()),
+            FetcherRequest::WatchedAttributes => FetcherResponse::WatchedAttributes(self.get_watched_attributes()),
+            FetcherRequest::StatementStats => FetcherResponse::StatementStats(self.get_statement_stats()),
+            FetcherRequest::PoolErrors => FetcherResponse::PoolErrors(self.get_pool_error_counts()),
+            FetcherRequest::HeapDump => FetcherResponse::HeapDump(self.dump_heap()),
+            FetcherRequest::ThreadDump => FetcherResponse::ThreadDump(self.dump_threads()),
+            FetcherRequest::ActorTree => FetcherResponse::ActorTree(self.get_actor_tree()),
+            FetcherRequest::ActorCount => FetcherResponse::ActorCount(self.get_actor_count()),
+            FetcherRequest::AkkaHealth => FetcherResponse::AkkaHealth(self.get_akka_health()),
+            FetcherRequest::ShardingStats => FetcherResponse::ShardingStats(self.get_sharding_stats()),
+            FetcherRequest::ActorRestarts => FetcherResponse::ActorRestarts(self.get_actor_restarts()),
+            FetcherRequest::RedisStats => FetcherResponse::RedisStats(self.get_redis_stats()),
+            FetcherRequest::ConsulServices => FetcherResponse::ConsulServices(self.get_consul_services()),
+            FetcherRequest::GcPauses => FetcherResponse::GcPauses(self.get_gc_pauses()),
+            FetcherRequest::PrometheusSamples => FetcherResponse::PrometheusSamples(self.get_prometheus_samples()),
+            FetcherRequest::ZookeeperStatus => FetcherResponse::ZookeeperStatus(self.get_zookeeper_status()),
+            FetcherRequest::ZookeeperCommand(node, command) =>
+                FetcherResponse::ZookeeperCommand(node, command, self.run_zookeeper_command(node, command)),
+            FetcherRequest::KafkaBrokers => FetcherResponse::KafkaBrokers(self.get_kafka_brokers()),
+            FetcherRequest::SourceSamples(i) => FetcherResponse::SourceSamples(i, self.get_source_samples(i)),
+            FetcherRequest::Capabilities(source) => match self.probe_capabilities(source) {
+                Ok(probed) => FetcherResponse::Capabilities(source, probed),
+                // nothing can be probed until the source is reached, which the diagnostics screen retries
+                Err(e) => FetcherResponse::FatalFailure(source, e),
+            },
+            FetcherRequest::Clock(source) => FetcherResponse::Clock(source, self.read_clock(source)),
+        }
+    }
+
+    /// Client of the JVM, connecting to it if it isn't yet
+    fn jmx(&self) -> Result<Ref<'_, JMXClient>, String> {
+        if self.jmx_client.borrow().is_none() {
+            let conn = self.jmx.as_ref().ok_or("No JMX connection configured")?;
+            let url_str = format!(
+                "service:jmx:rmi://{}/jndi/rmi://{}/jmxrmi",
+                &conn.address, &conn.address
+            );
+            // the credentials go into the environment of the connector, as `jmx.remote.credentials`
+            let credentials = match (&conn.username, &conn.password) {
+                (Some(username), Some(password)) => Some((username.as_str(), password.as_str())),
+                _ => None,
+            };
+            let connection = RmiMBeanConnection::connect(&url_str, credentials)
+                .map_err(|e| format!(
+                    "Couldn't connect to jmx at {}. Error: {}", url_str, e
+                ))?;
+            *self.jmx_client.borrow_mut() = Some(JMXClient::new(Box::new(connection), conn.db_pool_name.clone()));
+        }
+        Ok(Ref::map(self.jmx_client.borrow(), |c| c.as_ref().unwrap()))
+    }
+
+    /// Connector of the secure client port if there's one, reading its keystores if they aren't yet
+    fn zookeeper_tls(&self) -> Result<Option<TlsConnector>, String> {
+        let settings = match &self.zookeeper_tls {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        let mut connector = self.zookeeper_tls_connector.borrow_mut();
+        if connector.is_none() {
+            *connector = Some(zookeeper::client::tls_connector(settings)?);
+        }
+        Ok(connector.clone())
     }
 
     pub fn dump_fibers(&self) -> Result<Vec<Fiber>, String> {
@@ -77,30 +401,278 @@ impl Fetcher {
     }
 
     pub fn get_hikari_metrics(&self) -> Result<HikariMetrics, String> {
-        self.jmx.as_ref().unwrap().get_hikari_metrics().map_err(|e| Fetcher::format_slick_error(e))
+        self.jmx()?.get_hikari_metrics().map_err(|e| Fetcher::format_slick_error(e))
     }
 
     pub fn get_slick_metrics(&self) -> Result<SlickMetrics, String> {
-        self.jmx.as_ref().unwrap().get_slick_metrics().map_err(|e| Fetcher::format_slick_error(e))
+        self.jmx()?.get_slick_metrics().map_err(|e| Fetcher::format_slick_error(e))
     }
 
     pub fn get_slick_config(&self) -> Result<SlickConfig, String> {
-        self.jmx.as_ref().unwrap().get_slick_config().map_err(|e| Fetcher::format_slick_error(e))
+        self.jmx()?.get_slick_config().map_err(|e| Fetcher::format_slick_error(e))
+    }
+
+    pub fn get_jvm_attributes(&self) -> Result<Vec<(String, JMXValue)>, String> {
+        self.jmx()?.get_jvm_attributes()
+            .map_err(|e| format!("Couldn't read JVM attributes: {}", e))
+    }
+
+    pub fn get_jvm_runtime_metrics(&self) -> Result<JVMRuntimeMetrics, String> {
+        self.jmx()?.get_jvm_runtime_metrics()
+            .map_err(|e| format!("Couldn't read JVM class loading and threading metrics: {}", e))
+    }
+
+    /// Heap dump written on the JVM host, relative to the working directory of the JVM
+    pub fn dump_heap(&self) -> Result<String, String> {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let path = format!("heap-{}.hprof", secs);
+        self.jmx()?.dump_heap(&path)
+            .map(|_| path)
+            .map_err(|e| format!("Couldn't dump heap: {}", e))
+    }
+
+    pub fn dump_threads(&self) -> Result<String, String> {
+        self.jmx()?.dump_threads()
+            .map(|threads| format_thread_dump(&threads))
+            .map_err(|e| format!("Couldn't dump threads: {}", e))
+    }
+
+    /// Labelled values of the watched attributes in the watchlist order, None for unavailable ones.
+    /// A pattern that can't be queried has no series until it can.
+    pub fn get_watched_attributes(&self) -> Result<Vec<(String, Option<f64>)>, String> {
+        let jmx = self.jmx()?;
+        let results: Vec<Result<Vec<(String, Option<f64>)>, String>> = self.watchlist.iter()
+            .map(|w| jmx.get_watched_values(w))
+            .collect();
+        if results.iter().all(|r| r.is_err()) {
+            if let Some(Err(e)) = results.first() {
+                return Err(format!("Couldn't read any of the watched attributes: {}", e));
+            }
+        }
+        Ok(self.watchlist.iter().zip(results)
+            .flat_map(|(w, r)| r.unwrap_or_else(|_| if w.is_pattern() { vec![] } else { vec![(w.label.to_owned(), None)] }))
+            .collect())
+    }
+
+    pub fn get_statement_stats(&self) -> Result<Vec<StatementStats>, String> {
+        let statements = self.statements.as_ref().unwrap();
+        self.jmx()?.get_statement_stats(statements)
+            .map_err(|e| format!("Couldn't read statement timers matching {}: {}", statements.object_name, e))
+    }
+
+    pub fn get_pool_error_counts(&self) -> Result<PoolErrorCounts, String> {
+        self.jmx()?.get_pool_error_counts(self.pool_errors.as_ref().unwrap())
+            .map_err(|e| format!("Couldn't read the pool failure counters: {}", e))
     }
 
     pub fn get_actor_tree(&self) -> Result<Vec<ActorTreeNode>, String> {
         let s = self.akka_settings.as_ref().unwrap();
-        akka::client::get_actors(&s.tree_address, s.tree_timeout)
+        akka::client::get_actors(&s.tree_address, s.tree_timeout, self.proxies.akka.as_ref())
             .map_err(|e| format!("Error loading akka actor tree tree: {}", e))
     }
 
     pub fn get_actor_count(&self) -> Result<u64, String> {
         let s = self.akka_settings.as_ref().unwrap();
-        akka::client::get_actor_count(&s.count_address, s.count_timeout)
+        akka::client::get_actor_count(&s.count_address, s.count_timeout, self.proxies.akka.as_ref())
             .map_err(|e| format!("Error loading akka actor count: {}", e))
     }
 
-    fn format_slick_error(e: jmx::Error) -> String {
+    /// Actors restarted since the previous call, according to the log
+    pub fn get_actor_restarts(&self) -> Result<Vec<String>, String> {
+        let (log, tail) = self.actor_restarts.as_ref().unwrap();
+        tail.read_lines()
+            .map(|lines| log.restarted_actors(&lines))
+            .map_err(|e| format!("Couldn't read actor restarts from {}. Underlying error: {}", log.path, e))
+    }
+
+    /// Failed health checks are reported in the result rather than as an error
+    pub fn get_akka_health(&self) -> AkkaHealth {
+        let s = self.akka_settings.as_ref().unwrap();
+        akka::client::get_health(s.management_address.as_ref().unwrap(), s.count_timeout, self.proxies.akka.as_ref())
+    }
+
+    pub fn get_sharding_stats(&self) -> Result<ShardingStats, String> {
+        akka::client::get_sharding(self.sharding.as_ref().unwrap(), self.http_timeout, self.proxies.akka.as_ref())
+            .map_err(|e| format!("Couldn't get cluster sharding from Akka Management. Underlying error: {}", e))
+    }
+
+    pub fn get_redis_stats(&self) -> Result<RedisStats, String> {
+        let client = self.redis_client.as_ref().unwrap();
+        client.get_stats()
+            .map_err(|e| format!("Couldn't get stats from redis at {}. Underlying error: {}", client.address(), e))
+    }
+
+    pub fn get_consul_services(&self) -> Result<Vec<ServiceHealth>, String> {
+        let address = self.consul_address.as_ref().unwrap();
+        consul::client::get_services(address, self.http_timeout, self.proxies.consul.as_ref())
+            .map_err(|e| format!("Couldn't get health checks from consul at {}. Underlying error: {}", address, e))
+    }
+
+    /// Pauses logged since the previous call
+    pub fn get_gc_pauses(&self) -> Result<Vec<GcPause>, String> {
+        let tail = self.gc_log.as_ref().unwrap();
+        tail.read_pauses()
+            .map_err(|e| format!("Couldn't read GC log {}. Underlying error: {}", tail.path(), e))
+    }
+
+    /// Samples of all targets, prefixed with the target url if there are several of them
+    pub fn get_prometheus_samples(&self) -> Result<Vec<PrometheusSample>, String> {
+        let mut all = vec![];
+        for target in self.prometheus_targets.iter() {
+            let samples = prometheus::client::get_samples(target, self.http_timeout, self.proxies.prometheus.as_ref())
+                .map_err(|e| format!("Couldn't scrape metrics from {}. Underlying error: {}", target.url, e))?;
+            if self.prometheus_targets.len() > 1 {
+                all.extend(samples.into_iter().map(|s| PrometheusSample { series: format!("{} {}", target.url, s.series), ..s }));
+            } else {
+                all.extend(samples);
+            }
+        }
+        Ok(all)
+    }
+
+    ///
+    /// Nodes that don't respond are reported in the result rather than as an error.
+    /// While discovering the members, the known nodes are kept if none lists them, each telling why.
+    pub fn get_zookeeper_status(&self) -> Vec<ZkNodeStatus> {
+        let nodes = self.zookeeper_nodes.borrow().clone();
+        let tls = match self.zookeeper_tls() {
+            Ok(tls) => tls,
+            // no node can be probed over the secure port, each tells why
+            Err(e) => return nodes.iter().map(|n| ZkNodeStatus::from_responses(n, Err(e.clone()), Err(e.clone()))).collect(),
+        };
+        let statuses = zookeeper::client::probe_ensemble(
+            &nodes,
+            self.zookeeper_discover,
+            self.http_timeout,
+            tls.as_ref(),
+            self.zookeeper_admin.as_ref(),
+            self.zookeeper_ssh.as_ref(),
+        );
+        *self.zookeeper_nodes.borrow_mut() = statuses.iter().map(|s| s.address.to_owned()).collect();
+        statuses
+    }
+
+    ///
+    /// Optional features of a source, e.g. the HikariCP pool MBean over JMX.
+    /// Only the features of configured panels are probed, none of them while the source can't be reached.
+    pub fn probe_capabilities(&self, source: &str) -> Result<Vec<Capability>, String> {
+        let mut probed = vec![];
+        match source {
+            "jmx" => {
+                let jmx = self.jmx()?;
+                probed.push(Capability::probe(Feature::HikariPool, jmx.get_hikari_metrics()));
+                if self.statements.is_some() {
+                    let object_name = &self.statements.as_ref().unwrap().object_name;
+                    let timers = self.get_statement_stats().and_then(|stats| {
+                        if stats.is_empty() { Err(format!("no MBean matches {}", object_name)) } else { Ok(()) }
+                    });
+                    probed.push(Capability::probe(Feature::StatementTimers, timers));
+                }
+            }
+            "akka" => {
+                let s = self.akka_settings.as_ref().unwrap();
+                if let Some(address) = &s.management_address {
+                    let health = akka::client::has_health_checks(address, s.count_timeout, self.proxies.akka.as_ref());
+                    probed.push(Capability::probe(Feature::AkkaHealthChecks, health));
+                }
+                if self.sharding.is_some() {
+                    probed.push(Capability::probe(Feature::ClusterSharding, self.get_sharding_stats()));
+                }
+            }
+            _ => {}
+        }
+        Ok(probed)
+    }
+
+    ///
+    /// Skew of the clock of a source, read from the Runtime MBean over JMX and from the `Date` header of HTTP endpoints.
+    /// Four letter words carry no time, so Zookeeper nodes are only read through their AdminServer.
+    pub fn read_clock(&self, source: &str) -> Result<i64, String> {
+        let reading = match source {
+            "jmx" => {
+                let sent = clock::now_ms();
+                let remote = self.jmx()?.get_remote_time()
+                    .map_err(|e| format!("Couldn't read the JVM clock: {}", e))?;
+                ClockReading { remote: remote.max(0) as u64, sent, received: clock::now_ms() }
+            }
+            "akka" => {
+                let s = self.akka_settings.as_ref().unwrap();
+                let url = s.management_address.as_ref().unwrap_or(&s.tree_address);
+                clock::http_clock(url, s.count_timeout, self.proxies.akka.as_ref())?
+            }
+            "consul" => {
+                let url = format!("{}/v1/status/leader", self.consul_address.as_ref().unwrap());
+                clock::http_clock(&url, self.http_timeout, self.proxies.consul.as_ref())?
+            }
+            "prometheus" => {
+                let target = self.prometheus_targets.first().ok_or("No Prometheus target")?;
+                clock::http_clock(&target.url, self.http_timeout, self.proxies.prometheus.as_ref())?
+            }
+            "zookeeper" => {
+                let admin = self.zookeeper_admin.as_ref().ok_or("Zookeeper's clock is only read through the AdminServer")?;
+                let node = self.zookeeper_nodes.borrow().first().cloned().ok_or("No Zookeeper node")?;
+                clock::http_clock(&admin.command_url(&node, "ruok"), self.http_timeout, None)?
+            }
+            _ => return Err(format!("Can't read the clock of {}", source)),
+        };
+        Ok(reading.skew_ms())
+    }
+
+    pub fn run_zookeeper_command(&self, node: usize, command: ZkCommand) -> Result<String, String> {
+        let address = self.zookeeper_nodes.borrow().get(node).cloned().ok_or(format!("No Zookeeper node {}", node))?;
+        let tls = self.zookeeper_tls()?;
+        zookeeper::client::run(&address, command.as_str(), self.http_timeout, tls.as_ref(), self.zookeeper_admin.as_ref(), self.zookeeper_ssh.as_ref())?
+            .output(command.as_str())
+    }
+
+    pub fn get_kafka_brokers(&self) -> Result<Vec<KafkaBroker>, String> {
+        let kafka = self.zookeeper_kafka.as_ref().ok_or("No Kafka cluster registered in Zookeeper, see zookeeper_kafka")?;
+        let mut brokers = zookeeper::client::get_brokers(
+            &self.zookeeper_nodes.borrow(),
+            kafka,
+            self.http_timeout,
+            self.zookeeper_tls()?.as_ref(),
+            self.zookeeper_admin.as_ref(),
+            self.zookeeper_ssh.as_ref(),
+        )?;
+        if kafka.jmx {
+            for broker in brokers.iter_mut() {
+                broker.jmx = Some(self.get_kafka_broker_metrics(broker));
+            }
+            // brokers that left the cluster
+            let addresses: Vec<String> = brokers.iter().filter_map(KafkaBroker::jmx_address).collect();
+            self.kafka_jmx.borrow_mut().retain(|address, _| addresses.contains(address));
+        }
+        Ok(brokers)
+    }
+
+    /// Health of a broker over JMX, connecting to it on the first poll and again after a failed one
+    fn get_kafka_broker_metrics(&self, broker: &KafkaBroker) -> Result<KafkaBrokerMetrics, String> {
+        let address = broker.jmx_address().ok_or("JMX isn't enabled on the broker, it registered no jmx_port")?;
+        let mut connections = self.kafka_jmx.borrow_mut();
+        let connection = match connections.entry(address.to_owned()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let url = format!("service:jmx:rmi://{}/jndi/rmi://{}/jmxrmi", address, address);
+                let connection = RmiMBeanConnection::connect(&url, None)
+                    .map_err(|e| format!("Couldn't connect to jmx at {}. Error: {}", url, e))?;
+                e.insert(Box::new(connection))
+            }
+        };
+        let metrics = jmx::client::get_kafka_broker_metrics(&**connection);
+        if metrics.is_err() {
+            connections.remove(&address);
+        }
+        metrics
+    }
+
+    pub fn get_source_samples(&self, i: usize) -> Result<Vec<Sample>, String> {
+        let source = self.sources.get(i).and_then(Option::as_ref).ok_or(format!("No data source {} on this worker", i))?;
+        source.poll(self.http_timeout)
+            .map_err(|e| format!("Couldn't poll {}. Underlying error: {}", source.name(), e))
+    }
+
+    fn format_slick_error(e: String) -> String {
         format!(
             "No Slick JMX metrics found. Are you sure you have registerMbeans=true in your Slick config?\r\nUnderlying error: {}", e
         )
@@ -0,0 +1,4 @@
+pub mod model;
+pub mod tail;
+mod parser;
+mod tests;
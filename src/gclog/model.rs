@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+use crate::history::Downsample;
+
+/// A stop-the-world pause from a unified JVM GC log, sizes in megabytes
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GcPause {
+    /// JVM uptime in seconds at the end of the pause, if the log is decorated with it
+    pub uptime: Option<f64>,
+    /// e.g. `Pause Young (Normal) (G1 Evacuation Pause)`
+    pub name: String,
+    pub heap_before: f64,
+    pub heap_after: f64,
+    pub heap_total: f64,
+    pub duration_ms: f64,
+}
+
+impl GcPause {
+    /// Megabytes allocated per second between the previous pause and this one
+    pub fn allocation_rate_since(&self, prev: &GcPause) -> Option<f64> {
+        match (prev.uptime, self.uptime) {
+            (Some(t0), Some(t1)) if t1 > t0 => Some((self.heap_before - prev.heap_after).max(0.0) / (t1 - t0)),
+            _ => None,
+        }
+    }
+}
+
+/// The longest pause of the bucket
+impl Downsample for GcPause {
+    fn merge(&mut self, later: GcPause) {
+        if later.duration_ms > self.duration_ms {
+            *self = later;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::gclog::model::GcPause;
+
+///
+/// Parses a pause event of the unified GC log format (`-Xlog:gc`), other lines give None.
+///
+/// eg.
+///   ```text
+///   [2020-05-25T10:00:00.123+0000][12.345s][info][gc] GC(3) Pause Young (Normal) (G1 Evacuation Pause) 24M->4M(256M) 3.456ms
+///   ```
+pub fn parse_pause(line: &str) -> Option<GcPause> {
+    let (decorations, message) = split_decorations(line);
+    let message = message.trim();
+    let start = message.find("Pause ")?;
+    let duration = message.rsplit(' ').next()?.strip_suffix("ms")?.parse::<f64>().ok()?;
+    let heap = message.split(' ').find(|w| w.contains("->"))?;
+    let (before, rest) = split_once(heap, "->")?;
+    let (after, total) = split_once(rest.strip_suffix(')')?, "(")?;
+    let end = message.find(heap)?;
+
+    Some(GcPause {
+        uptime: decorations.iter()
+            .filter_map(|d| d.strip_suffix('s').and_then(|s| s.parse::<f64>().ok()))
+            .next(),
+        name: message[start..end].trim().to_owned(),
+        heap_before: megabytes(before)?,
+        heap_after: megabytes(after)?,
+        heap_total: megabytes(total)?,
+        duration_ms: duration,
+    })
+}
+
+/// Splits leading `[...]` decorations, e.g. time, uptime, level and tags, from the message
+fn split_decorations(line: &str) -> (Vec<&str>, &str) {
+    let mut decorations = vec![];
+    let mut rest = line.trim_start();
+    while rest.starts_with('[') {
+        match rest.find(']') {
+            Some(end) => {
+                decorations.push(&rest[1..end]);
+                rest = &rest[end + 1..];
+            }
+            None => break,
+        }
+    }
+    (decorations, rest)
+}
+
+fn split_once<'a>(s: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    s.find(separator).map(|i| (&s[..i], &s[i + separator.len()..]))
+}
+
+/// Size like `24M`, `512K` or `2G` in megabytes
+fn megabytes(size: &str) -> Option<f64> {
+    const UNITS: [(char, f64); 4] = [('K', 1.0 / 1024.0), ('M', 1.0), ('G', 1024.0), ('B', 1.0 / 1024.0 / 1024.0)];
+    let (number, factor) = UNITS.iter().find_map(|(unit, factor)| size.strip_suffix(*unit).map(|n| (n, factor)))?;
+    number.parse::<f64>().ok().map(|n| n * factor)
+}
@@ -0,0 +1,22 @@
+use crate::gclog::model::GcPause;
+use crate::gclog::parser;
+use crate::tail::LogTail;
+
+/// Reads pauses appended to a GC log since the previous read, starting from the beginning of the file
+pub struct GcLogTail {
+    lines: LogTail,
+}
+
+impl GcLogTail {
+    pub fn new(path: String) -> GcLogTail {
+        GcLogTail { lines: LogTail::new(path) }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.lines.path
+    }
+
+    pub fn read_pauses(&self) -> Result<Vec<GcPause>, String> {
+        Ok(self.lines.read_lines()?.iter().filter_map(|l| parser::parse_pause(l)).collect())
+    }
+}
@@ -0,0 +1,91 @@
+#[test]
+fn parser_reads_unified_pause_events() {
+    use crate::gclog::model::GcPause;
+    use crate::gclog::parser::parse_pause;
+
+    assert_eq!(
+        parse_pause("[2020-05-25T10:00:00.123+0000][12.345s][info][gc] GC(3) Pause Young (Normal) (G1 Evacuation Pause) 24M->4M(256M) 3.456ms"),
+        Some(GcPause {
+            uptime: Some(12.345),
+            name: "Pause Young (Normal) (G1 Evacuation Pause)".to_owned(),
+            heap_before: 24.0,
+            heap_after: 4.0,
+            heap_total: 256.0,
+            duration_ms: 3.456,
+        })
+    );
+    assert_eq!(
+        parse_pause("[info][gc] GC(7) Pause Full (System.gc()) 2G->512K(4G) 120.5ms").map(|p| (p.uptime, p.heap_before, p.heap_after)),
+        Some((None, 2048.0, 0.5))
+    );
+    assert_eq!(parse_pause("[0.010s][info][gc] Using G1"), None);
+    assert_eq!(parse_pause("[5.0s][info][gc] GC(4) Concurrent Cycle 12.1ms"), None);
+}
+
+#[test]
+fn allocation_rate_between_pauses() {
+    use crate::gclog::parser::parse_pause;
+
+    let prev = parse_pause("[10.0s][info][gc] GC(1) Pause Young (Normal) (G1 Evacuation Pause) 30M->10M(256M) 2.0ms").unwrap();
+    let next = parse_pause("[12.0s][info][gc] GC(2) Pause Young (Normal) (G1 Evacuation Pause) 50M->12M(256M) 2.5ms").unwrap();
+
+    assert_eq!(next.allocation_rate_since(&prev), Some(20.0));
+    assert_eq!(prev.allocation_rate_since(&next), None);
+}
+
+#[test]
+fn tail_reads_appended_lines_only() {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use crate::gclog::tail::GcLogTail;
+
+    let path = std::env::temp_dir().join(format!("panopticon-gc-{}.log", std::process::id()));
+    let path_str = path.to_str().unwrap().to_owned();
+    let _ = std::fs::remove_file(&path);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).unwrap();
+    let tail = GcLogTail::new(path_str);
+
+    write!(file, "[1.0s][info][gc] GC(0) Pause Young (Normal) (G1 Evacuation Pause) 20M->5M(256M) 1.0ms\n[2.0s][info][gc] GC(1) Pause Young").unwrap();
+    assert_eq!(tail.read_pauses().unwrap().len(), 1);
+
+    write!(file, " (Normal) (G1 Evacuation Pause) 25M->6M(256M) 1.5ms\n").unwrap();
+    let pauses = tail.read_pauses().unwrap();
+    assert_eq!(pauses.len(), 1);
+    assert_eq!(pauses[0].duration_ms, 1.5);
+    assert!(tail.read_pauses().unwrap().is_empty());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn parser_rejects_multibyte_units() {
+    use crate::gclog::parser::parse_pause;
+
+    assert_eq!(parse_pause("[1.0s][info][gc] GC(0) Pause Young (Normal) 24M->4€(256M) 1.0ms"), None);
+}
+
+#[test]
+fn tail_keeps_characters_split_across_reads() {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    use crate::gclog::tail::GcLogTail;
+
+    let path = std::env::temp_dir().join(format!("panopticon-gc-utf8-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).unwrap();
+    let tail = GcLogTail::new(path.to_str().unwrap().to_owned());
+
+    let line = "[1.0s][info][gc] GC(0) Pause Young (Évacuation) 20M->5M(256M) 1.0ms\n".as_bytes();
+    let split = line.iter().position(|b| *b == 0xC3).unwrap() + 1;
+    file.write_all(&line[..split]).unwrap();
+    assert!(tail.read_pauses().unwrap().is_empty());
+
+    file.write_all(&line[split..]).unwrap();
+    let pauses = tail.read_pauses().unwrap();
+    assert_eq!(pauses.len(), 1);
+    assert_eq!(pauses[0].name, "Pause Young (Évacuation)");
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,126 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use crate::config::{Proxy, ProxySettings};
+use crate::fetcher::Fetcher;
+
+mod tests;
+
+///
+/// In-process fake of a server panopticon polls, listening on a free local port, so that the flow from a fetch
+/// to the state of a tab can be tested end to end. Each connection is answered once and then closed,
+/// as zio-zmx and Zookeeper do, and the server keeps accepting connections until the tests exit.
+///
+/// JMX isn't faked: it talks RMI through an embedded JVM rather than a protocol that can be served here.
+pub struct FakeServer {
+    pub address: String,
+}
+
+impl FakeServer {
+    /// Reads each request until `complete` tells it's all there, and writes back the response of the handler
+    fn start<C, H>(complete: C, handler: H) -> FakeServer
+        where C: Fn(&[u8]) -> bool + Send + 'static, H: Fn(&[u8]) -> Vec<u8> + Send + 'static {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Couldn't bind a fake server");
+        let address = listener.local_addr().expect("Fake server has no address").to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = FakeServer::answer(stream, &complete, &handler);
+            }
+        });
+        FakeServer { address }
+    }
+
+    fn answer<C, H>(mut stream: TcpStream, complete: &C, handler: &H) -> std::io::Result<()>
+        where C: Fn(&[u8]) -> bool, H: Fn(&[u8]) -> Vec<u8> {
+        let mut request = vec![];
+        let mut buffer = [0; 1024];
+        // clients wait for the response without closing their side of the connection
+        while !complete(&request) {
+            let n = stream.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..n]);
+        }
+        stream.write_all(&handler(&request))
+    }
+
+    /// zio-zmx server answering `dump` with the given fiber dumps, e.g. `#1 (1s)\nStatus: Running()`
+    pub fn zmx(dumps: &[&str]) -> FakeServer {
+        let mut response = format!("*{}\r\n", dumps.len());
+        for dump in dumps {
+            response.push_str(&format!("${}\r\n{}\r\n", dump.len(), dump));
+        }
+        FakeServer::start(|r| r.ends_with(b"dump\r\n"), move |_| response.clone().into_bytes())
+    }
+
+    /// Zookeeper node answering the given four letter words, and the other ones as not whitelisted
+    pub fn zookeeper(replies: &[(&str, &str)]) -> FakeServer {
+        let replies: HashMap<String, String> = replies.iter().map(|(c, r)| (c.to_string(), r.to_string())).collect();
+        FakeServer::start(|r| r.len() >= 4, move |request| {
+            let command = String::from_utf8_lossy(request).into_owned();
+            replies.get(&command).cloned()
+                .unwrap_or_else(|| format!("{} is not executed because it is not in the whitelist.\n", command))
+                .into_bytes()
+        })
+    }
+
+    /// HTTP server, e.g. Akka Management or akka-periscope, answering GET requests of the given paths with a JSON body
+    /// and the other ones with a 404. The query string is ignored.
+    pub fn http(routes: &[(&str, &str)]) -> FakeServer {
+        let routes: HashMap<String, String> = routes.iter().map(|(p, b)| (p.to_string(), b.to_string())).collect();
+        FakeServer::start(|r| r.windows(4).any(|w| w == b"\r\n\r\n"), move |request| {
+            let request = String::from_utf8_lossy(request);
+            let target = request.split(' ').nth(1).unwrap_or("");
+            let path = target.split('?').next().unwrap_or("");
+            let (status, body) = match routes.get(path) {
+                Some(body) => ("200 OK", body.as_str()),
+                None => ("404 Not Found", ""),
+            };
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            ).into_bytes()
+        })
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.address, path)
+    }
+}
+
+/// Fetcher of nothing, to set the clients of the fake servers on
+pub fn fetcher() -> Fetcher {
+    Fetcher {
+        zmx_client: None,
+        jmx: None,
+        jmx_client: RefCell::new(None),
+        watchlist: vec![],
+        statements: None,
+        pool_errors: None,
+        akka_settings: None,
+        sharding: None,
+        actor_restarts: None,
+        redis_client: None,
+        consul_address: None,
+        gc_log: None,
+        prometheus_targets: vec![],
+        zookeeper_nodes: RefCell::new(vec![]),
+        zookeeper_discover: false,
+        zookeeper_tls: None,
+        zookeeper_tls_connector: RefCell::new(None),
+        zookeeper_admin: None,
+        zookeeper_ssh: None,
+        zookeeper_kafka: None,
+        kafka_jmx: RefCell::new(HashMap::new()),
+        sources: vec![],
+        // the fake servers are local, whatever proxy the environment sets
+        proxies: ProxySettings { akka: Some(Proxy("none".to_owned())), ..ProxySettings::default() },
+        http_timeout: 1000,
+    }
+}
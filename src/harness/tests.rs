@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+use crate::akka::model::{ActorTreeNode, AkkaSettings};
+use crate::app::{AkkaActorTreeTab, ZMXTab, ZookeeperStatus};
+use crate::fetcher::{Fetcher, FetcherRequest, FetcherResponse};
+use crate::harness::{fetcher, FakeServer};
+use crate::history::Retention;
+use crate::widgets::tree::Column;
+use crate::zio::model::FiberCount;
+use crate::zio::zmx::NetworkZMXClient;
+use crate::zookeeper::model::{ZkNodeState, ZkTlsSettings};
+
+#[cfg(test)]
+fn actor(name: &str, parent: Option<usize>, id: usize) -> ActorTreeNode {
+    ActorTreeNode { name: name.to_owned(), parent, id }
+}
+
+#[test]
+fn zmx_dump_is_counted_by_status() {
+    let server = FakeServer::zmx(&[
+        "#1 (10s)\nStatus: Running()",
+        "#2 (5s)\nStatus: Suspended(interruptible, 3 asyncs, zio.ZIO.sleep)\nFiber:Id(1588237280480,2) was spawned by:\nFiber:Id(1588237280394,1) was supposed to continue to:",
+        "#3 (1s)\nStatus: Done",
+    ]);
+    let fetcher = Fetcher { zmx_client: Some(Box::new(NetworkZMXClient::new(server.address.clone()))), ..fetcher() };
+    let mut tab = ZMXTab::new(vec![Column::Label], None, Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+
+    match fetcher.fetch(FetcherRequest::RegularFiberDump) {
+        FetcherResponse::RegularFiberDump(Ok(dump)) => tab.append_fiber_dump_for_counts(dump, Instant::now()),
+        _ => panic!("Expected a fiber dump"),
+    }
+    assert_eq!(tab.fiber_counts.back(), Some(&FiberCount { done: 1, suspended: 1, running: 1, finishing: 0 }));
+
+    match fetcher.fetch(FetcherRequest::FiberDump) {
+        FetcherResponse::FiberDump(Ok(dump)) => tab.replace_fiber_dump(dump),
+        _ => panic!("Expected a fiber dump"),
+    }
+    assert_eq!(tab.fiber_dump_all.len(), 3);
+    assert_eq!(tab.fibers.items.len(), 3);
+}
+
+#[test]
+fn akka_tree_and_count_fill_actor_tab() {
+    let server = FakeServer::http(&[
+        ("/actors/tree", r#"{ "user": { "orders": { "order-1": {} }, "payments": {} } }"#),
+        ("/actors/count", r#"{ "result": 4 }"#),
+    ]);
+    let settings = AkkaSettings {
+        tree_address: server.url("/actors/tree"),
+        count_address: server.url("/actors/count"),
+        tree_timeout: 1000,
+        count_timeout: 1000,
+        management_address: Some(server.url("")),
+    };
+    let fetcher = Fetcher { akka_settings: Some(settings), ..fetcher() };
+    let mut tab = AkkaActorTreeTab::new(vec![Column::Label], false, false, vec![], Retention::default(), Duration::from_secs(60), Duration::from_secs(2));
+
+    match fetcher.fetch(FetcherRequest::ActorTree) {
+        FetcherResponse::ActorTree(Ok(actors)) => tab.update_actor_tree(actors, Instant::now()),
+        _ => panic!("Expected an actor tree"),
+    }
+    assert_eq!(tab.actor_nodes, vec![
+        actor("user", None, 1),
+        actor("orders", Some(1), 2),
+        actor("order-1", Some(2), 3),
+        actor("payments", Some(1), 4),
+    ]);
+    assert_eq!(tab.actors.items.len(), 4);
+
+    match fetcher.fetch(FetcherRequest::ActorCount) {
+        FetcherResponse::ActorCount(Ok(count)) => tab.append_actor_count(count, Instant::now()),
+        _ => panic!("Expected an actor count"),
+    }
+    assert_eq!(tab.actor_counts.back(), Some(&4));
+
+    // no health check routes
+    match fetcher.fetch(FetcherRequest::AkkaHealth) {
+        FetcherResponse::AkkaHealth(health) => assert!(!health.alive.ok && !health.ready.ok),
+        _ => panic!("Expected the health of the node"),
+    }
+}
+
+#[test]
+fn zookeeper_ensemble_has_a_leader() {
+    let leader = FakeServer::zookeeper(&[("ruok", "imok"), ("isro", "rw"), ("srvr", "Zookeeper version: 3.6.1\nMode: leader\n")]);
+    let follower = FakeServer::zookeeper(&[("ruok", "imok"), ("isro", "ro"), ("srvr", "Zookeeper version: 3.6.1\nMode: follower\n")]);
+    let nodes = vec![leader.address.clone(), follower.address.clone()];
+    let fetcher = fetcher();
+    fetcher.zookeeper_nodes.replace(nodes.clone());
+    let mut status = ZookeeperStatus::new(&nodes, Retention::default(), Duration::from_secs(2));
+
+    match fetcher.fetch(FetcherRequest::ZookeeperStatus) {
+        FetcherResponse::ZookeeperStatus(x) => status.replace_nodes(x, Instant::now()),
+        _ => panic!("Expected the status of the ensemble"),
+    };
+    assert_eq!(status.leader(), Some(leader.address.as_str()));
+    assert_eq!(status.nodes.iter().map(|n| n.state).collect::<Vec<ZkNodeState>>(), vec![ZkNodeState::Ok, ZkNodeState::ReadOnly]);
+    // mntr isn't whitelisted and there's no AdminServer to ask instead
+    assert!(status.nodes.iter().all(|n| n.monitor.is_none()));
+}
+
+#[test]
+fn unreadable_zookeeper_ca_only_fails_zookeeper() {
+    let zmx = FakeServer::zmx(&["#1 (10s)\nStatus: Running()"]);
+    let node = FakeServer::zookeeper(&[("ruok", "imok"), ("isro", "rw")]);
+    let fetcher = Fetcher {
+        zmx_client: Some(Box::new(NetworkZMXClient::new(zmx.address.clone()))),
+        zookeeper_tls: Some(ZkTlsSettings { ca: Some("/nonexistent/ca.pem".to_owned()), keystore: None, keystore_password: None }),
+        ..fetcher()
+    };
+    fetcher.zookeeper_nodes.replace(vec![node.address.clone()]);
+
+    match fetcher.fetch(FetcherRequest::ZookeeperStatus) {
+        FetcherResponse::ZookeeperStatus(nodes) => {
+            assert_eq!(nodes.iter().map(|n| n.state).collect::<Vec<ZkNodeState>>(), vec![ZkNodeState::NotResponding]);
+            assert!(nodes[0].error.as_ref().map_or(false, |e| e.starts_with("Couldn't read Zookeeper CA /nonexistent/ca.pem")));
+        }
+        _ => panic!("Expected the status of the ensemble"),
+    }
+    // the other sources of the worker are still fetched
+    assert!(matches!(fetcher.fetch(FetcherRequest::RegularFiberDump), FetcherResponse::RegularFiberDump(Ok(_))));
+}
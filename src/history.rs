@@ -0,0 +1,201 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+///
+/// How many samples of each charted history are kept, set with `retention` in the config file.
+///
+/// eg. with a 2s tick rate, the default keeps the last 200s at full resolution
+/// and the peak of each minute for the 4 hours before.
+///   ```text
+///   { "full_resolution": 100, "downsample_every": 30, "downsampled": 240 }
+///   ```
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Retention {
+    /// Most recent samples, all of them kept
+    pub full_resolution: usize,
+    /// Older samples are combined into one for each bucket of this many, see `Downsample`
+    pub downsample_every: usize,
+    /// Older samples kept after downsampling
+    pub downsampled: usize,
+}
+
+impl Default for Retention {
+    fn default() -> Retention {
+        Retention { full_resolution: 100, downsample_every: 30, downsampled: 240 }
+    }
+}
+
+impl Retention {
+    /// Number of ticks covered by a full history
+    pub fn span(&self) -> usize {
+        self.full_resolution + self.downsample_every.max(1) * self.downsampled
+    }
+}
+
+///
+/// Samples combined into one when downsampled, keeping what stands out of the bucket, e.g. the highest value,
+/// so that a spike between two buckets doesn't vanish from the older part of the chart.
+pub trait Downsample {
+    /// Combines a later sample of the same bucket into this one
+    fn merge(&mut self, later: Self);
+}
+
+impl Downsample for f64 {
+    fn merge(&mut self, later: f64) {
+        *self = self.max(later);
+    }
+}
+
+impl Downsample for u64 {
+    fn merge(&mut self, later: u64) {
+        *self = (*self).max(later);
+    }
+}
+
+///
+/// Samples taken on each tick along with the time they were captured, the most recent ones at full resolution
+/// and the older ones downsampled, so that hours of samples can be charted while memory stays bounded.
+/// Charts place samples at their capture time, so that late or missed polls show as gaps.
+///
+/// `len`, `get`, `back` and `iter` only see the samples kept at full resolution.
+pub struct History<T> {
+    retention: Retention,
+    recent: VecDeque<(Instant, T)>,
+    /// Buckets of the samples older than the recent ones, the oldest first, each at the time of its first sample
+    older: VecDeque<(Instant, T)>,
+    /// Samples moved out of the recent ones into the last bucket of `older`, modulo `downsample_every`
+    bucketed: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(retention: Retention) -> History<T> {
+        History { retention, recent: VecDeque::new(), older: VecDeque::new(), bucketed: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.recent.get(i).map(|(_, x)| x)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.recent.back().map(|(_, x)| x)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.recent.iter().map(|(_, x)| x)
+    }
+
+    /// The `n` most recent samples, the oldest first, e.g. the ones that fit as bars of a chart
+    pub fn latest(&self, n: usize) -> impl Iterator<Item = &T> {
+        self.iter().skip(self.len().saturating_sub(n))
+    }
+
+    /// Every kept sample along with the time it was captured, the oldest first
+    pub fn points(&self) -> Vec<(Instant, &T)> {
+        self.older.iter().chain(self.recent.iter()).map(|(at, x)| (*at, x)).collect()
+    }
+}
+
+impl<T: Downsample> History<T> {
+    ///
+    /// Adds a sample captured at the given time. A sample captured at the same time as the latest one
+    /// replaces it, e.g. a response handled twice, and one captured before the latest one is dropped.
+    pub fn push_back_at(&mut self, x: T, at: Instant) {
+        match self.recent.back() {
+            Some((latest, _)) if at < *latest => return,
+            Some((latest, _)) if at == *latest => {
+                self.recent.pop_back();
+            }
+            _ => {}
+        }
+        self.recent.push_back((at, x));
+        if self.recent.len() <= self.retention.full_resolution {
+            return;
+        }
+        let evicted = self.recent.pop_front();
+        if let Some((at, x)) = evicted.filter(|_| self.retention.downsampled > 0) {
+            match self.older.back_mut() {
+                Some((_, bucket)) if self.bucketed > 0 => bucket.merge(x),
+                _ => {
+                    if self.older.len() >= self.retention.downsampled {
+                        self.older.pop_front();
+                    }
+                    self.older.push_back((at, x));
+                }
+            }
+        }
+        self.bucketed = (self.bucketed + 1) % self.retention.downsample_every.max(1);
+    }
+}
+
+/// Serialized as the samples kept at full resolution
+impl<T: Serialize> Serialize for History<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::history::{History, Retention};
+
+    #[test]
+    fn downsamples_older_samples() {
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut history = History::new(Retention { full_resolution: 3, downsample_every: 2, downsampled: 2 });
+        for x in 0..10 {
+            history.push_back_at(x, at(x));
+        }
+
+        assert_eq!(history.iter().copied().collect::<Vec<u64>>(), vec![7, 8, 9]);
+        assert_eq!(history.back(), Some(&9));
+        assert_eq!(history.latest(2).copied().collect::<Vec<u64>>(), vec![8, 9]);
+        let points = |h: &History<u64>| h.points().into_iter().map(|(t, x)| (t.duration_since(start).as_secs(), *x)).collect::<Vec<_>>();
+        // each bucket is at the time of its first sample, with the highest of its samples
+        assert_eq!(points(&history), vec![(4, 5), (6, 6), (7, 7), (8, 8), (9, 9)]);
+
+        history.push_back_at(10, at(10));
+        assert_eq!(points(&history), vec![(4, 5), (6, 7), (8, 8), (9, 9), (10, 10)]);
+        assert_eq!(Retention::default().span(), 7300);
+    }
+
+    #[test]
+    fn spike_survives_downsampling() {
+        let start = Instant::now();
+        let mut history = History::new(Retention { full_resolution: 2, downsample_every: 5, downsampled: 10 });
+        for x in 0..20 {
+            history.push_back_at(if x == 7 { 100.0 } else { 1.0 }, start + Duration::from_secs(x));
+        }
+
+        // the spike isn't the first sample of its bucket, which is all decimating would have kept
+        let points = history.points();
+        let older: Vec<f64> = points[..points.len() - history.len()].iter().map(|(_, x)| **x).collect();
+        assert_eq!(older, vec![1.0, 100.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn tolerates_duplicate_samples() {
+        let start = Instant::now();
+        let mut history = History::new(Retention::default());
+        history.push_back_at(1u64, start);
+        history.push_back_at(2, start + Duration::from_secs(2));
+        history.push_back_at(3, start + Duration::from_secs(2));
+        history.push_back_at(4, start + Duration::from_secs(1));
+
+        assert_eq!(history.iter().copied().collect::<Vec<u64>>(), vec![1, 3]);
+        assert_eq!(serde_json::to_string(&history).unwrap(), "[1,3]");
+    }
+}
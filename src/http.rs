@@ -0,0 +1,16 @@
+use reqwest::{Client, Proxy as ReqwestProxy};
+
+use crate::config::Proxy;
+
+///
+/// HTTP client going through the given proxy.
+/// Without one, proxies are taken from `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY`.
+pub fn client(proxy: Option<&Proxy>) -> Result<Client, String> {
+    let builder = match proxy {
+        None => Client::builder(),
+        Some(Proxy(url)) if url == "none" => Client::builder().no_proxy(),
+        Some(Proxy(url)) => Client::builder()
+            .proxy(ReqwestProxy::all(url.as_str()).map_err(|e| format!("Invalid proxy {}: {}", url, e))?),
+    };
+    builder.build().map_err(|e| e.to_string())
+}
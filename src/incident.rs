@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config::Proxy;
+use crate::http;
+
+///
+/// Incoming webhook incident summaries are posted to with `I`, e.g. of a Slack or Teams channel,
+/// instead of writing them to a file in the working directory.
+///
+/// eg. `{ "webhook": "https://hooks.slack.com/services/T0000/B0000/XXXX" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct IncidentSettings {
+    pub webhook: String,
+}
+
+/// Message posted to the webhook, the `text` payload both Slack and Teams incoming webhooks accept
+pub fn payload(summary: &str) -> String {
+    json!({ "text": summary }).to_string()
+}
+
+/// Posts the summary to the webhook
+pub fn post(settings: &IncidentSettings, summary: &str, proxy: Option<&Proxy>) -> Result<(), String> {
+    post_async(&settings.webhook, payload(summary), proxy)
+}
+
+#[tokio::main]
+async fn post_async(url: &str, payload: String, proxy: Option<&Proxy>) -> Result<(), String> {
+    let response = http::client(proxy)?
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(payload)
+        .timeout(Duration::from_secs(10))
+        .send().await
+        .map_err(|e| format!("Couldn't post the incident summary to {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Posting the incident summary to {} failed with status {}", url, response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::incident::payload;
+
+    #[test]
+    fn wraps_summary_in_text_payload() {
+        assert_eq!(payload("*Incident summary*\n- Fibers: \"12\" running"), r#"{"text":"*Incident summary*\n- Fibers: \"12\" running"}"#);
+    }
+}
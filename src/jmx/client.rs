@@ -1,18 +1,167 @@
+use crate::jmx::connection::{MBeanConnection, OperationParam};
 use crate::jmx::model::*;
-use jmx::{MBeanClient, MBeanClientTrait};
 use serde::de::DeserializeOwned;
 
+fn get_attribute<T: DeserializeOwned>(connection: &dyn MBeanConnection, object_name: &str, attr: &str) -> Result<T, String> {
+    let v = connection.get_attribute(object_name, attr)?;
+    serde_json::from_value(v).map_err(|e| format!("Unexpected value of {} of {}: {}", attr, object_name, e))
+}
+
+/// Health of a Kafka broker, read from its JMX connector rather than the JVM of the monitored app
+pub fn get_kafka_broker_metrics(connection: &dyn MBeanConnection) -> Result<KafkaBrokerMetrics, String> {
+    Ok(KafkaBrokerMetrics {
+        under_replicated_partitions: get_attribute(connection, "kafka.server:type=ReplicaManager,name=UnderReplicatedPartitions", "Value")?,
+        request_handler_idle: get_attribute(connection, "kafka.server:type=KafkaRequestHandlerPool,name=RequestHandlerAvgIdlePercent", "OneMinuteRate")?,
+        isr_shrinks_per_sec: get_attribute(connection, "kafka.server:type=ReplicaManager,name=IsrShrinksPerSec", "OneMinuteRate")?,
+    })
+}
+
 pub struct JMXClient {
-    connection: MBeanClient,
+    connection: Box<dyn MBeanConnection>,
     db_pool_name: String,
+    gc_names: Vec<String>,
 }
 
 impl JMXClient {
-    pub fn new(connection: MBeanClient, db_pool_name: String) -> JMXClient {
-        JMXClient { connection, db_pool_name }
+    /// Garbage collectors of the common JVM implementations, the ones registered in the remote JVM are monitored
+    const KNOWN_GC_NAMES: [&'static str; 10] = [
+        "G1 Young Generation",
+        "G1 Old Generation",
+        "PS Scavenge",
+        "PS MarkSweep",
+        "ParNew",
+        "ConcurrentMarkSweep",
+        "Copy",
+        "MarkSweepCompact",
+        "Shenandoah Cycles",
+        "ZGC",
+    ];
+
+    pub fn new(connection: Box<dyn MBeanConnection>, db_pool_name: String) -> JMXClient {
+        let gc_names = JMXClient::KNOWN_GC_NAMES.iter()
+            .filter(|name| connection.get_attribute(&JMXClient::gc_object_name(name), "Name").is_ok())
+            .map(|name| name.to_string())
+            .collect();
+        JMXClient { connection, db_pool_name, gc_names }
+    }
+
+    fn get_attribute<T: DeserializeOwned>(&self, object_name: &str, attr: &str) -> Result<T, String> {
+        get_attribute(self.connection.as_ref(), object_name, attr)
+    }
+
+    pub fn get_attribute_value(&self, object_name: &str, attr: &str) -> Result<JMXValue, String> {
+        Ok(JMXValue::from(self.connection.get_attribute(object_name, attr)?))
+    }
+
+    /// Heap and non-heap memory usage, along with info about the last run of each garbage collector
+    pub fn get_jvm_attributes(&self) -> Result<Vec<(String, JMXValue)>, String> {
+        let mut attributes = vec![
+            ("Heap memory".to_owned(), self.get_attribute_value("java.lang:type=Memory", "HeapMemoryUsage")?),
+            ("Non-heap memory".to_owned(), self.get_attribute_value("java.lang:type=Memory", "NonHeapMemoryUsage")?),
+        ];
+        for name in self.gc_names.iter() {
+            let info = self.get_attribute_value(&JMXClient::gc_object_name(name), "LastGcInfo")?;
+            attributes.push((format!("Last GC: {}", name), info));
+        }
+        Ok(attributes)
+    }
+
+    /// Makes the remote JVM write a heap dump of live objects to a path on its own filesystem, which must end with `.hprof`
+    pub fn dump_heap(&self, path: &str) -> Result<(), String> {
+        let params = [OperationParam::Text(path.to_owned()), OperationParam::Bool(true)];
+        self.connection.invoke("com.sun.management:type=HotSpotDiagnostic", "dumpHeap", &params)?;
+        Ok(())
+    }
+
+    /// Stack traces of all live threads, along with the monitors and synchronizers they hold
+    pub fn dump_threads(&self) -> Result<JMXValue, String> {
+        let params = [OperationParam::Bool(true), OperationParam::Bool(true)];
+        Ok(JMXValue::from(self.connection.invoke("java.lang:type=Threading", "dumpAllThreads", &params)?))
+    }
+
+    pub fn get_jvm_runtime_metrics(&self) -> Result<JVMRuntimeMetrics, String> {
+        let loaded_classes: i32 = self.get_attribute("java.lang:type=ClassLoading", "LoadedClassCount")?;
+        let live_threads: i32 = self.get_attribute("java.lang:type=Threading", "ThreadCount")?;
+        let daemon_threads: i32 = self.get_attribute("java.lang:type=Threading", "DaemonThreadCount")?;
+        let peak_threads: i32 = self.get_attribute("java.lang:type=Threading", "PeakThreadCount")?;
+
+        Result::Ok(JVMRuntimeMetrics {
+            loaded_classes,
+            live_threads,
+            daemon_threads,
+            peak_threads,
+        })
+    }
+
+    /// Time (in ms since the Unix epoch) of the remote JVM's clock, as the start time of the JVM plus its uptime
+    pub fn get_remote_time(&self) -> Result<i64, String> {
+        let start_time: i64 = self.get_attribute("java.lang:type=Runtime", "StartTime")?;
+        let uptime: i64 = self.get_attribute("java.lang:type=Runtime", "Uptime")?;
+        Ok(start_time + uptime)
+    }
+
+    /// Current value of a watched attribute, None if it's not numeric
+    pub fn get_watched_value(&self, watched: &WatchedAttribute) -> Result<Option<f64>, String> {
+        let v = self.get_attribute_value(&watched.object_name, &watched.attribute)?;
+        let v = match &watched.key {
+            Some(key) => v.get(key).and_then(JMXValue::as_f64),
+            None => v.as_f64(),
+        };
+        Ok(v)
+    }
+
+    /// Labelled values of a watched attribute, of each bean matching its object name if it's a pattern
+    pub fn get_watched_values(&self, watched: &WatchedAttribute) -> Result<Vec<(String, Option<f64>)>, String> {
+        if !watched.is_pattern() {
+            return Ok(vec![(watched.label.to_owned(), self.get_watched_value(watched)?)]);
+        }
+        let mut names: Vec<String> = self.connection.query_names(&watched.object_name)?;
+        names.sort();
+        Ok(names.into_iter()
+            .map(|name| {
+                // a bean unregistered since the query is unavailable until it's gone from the next one
+                let bean = WatchedAttribute { object_name: name.to_owned(), ..watched.clone() };
+                (watched.label_of(&name), self.get_watched_value(&bean).unwrap_or(None))
+            })
+            .collect())
+    }
+
+    /// Count and mean latency of every statement timer matching the pattern
+    pub fn get_statement_stats(&self, statements: &StatementMetrics) -> Result<Vec<StatementStats>, String> {
+        let names: Vec<String> = self.connection.query_names(&statements.object_name)?;
+        let mut stats = vec![];
+        for name in names {
+            let count: i64 = self.get_attribute(&name, "Count")?;
+            let mean_ms: f64 = self.get_attribute(&name, "Mean")?;
+            stats.push(StatementStats { name: StatementStats::name_of(&name), count: count.max(0) as u64, mean_ms });
+        }
+        Ok(StatementStats::slowest(stats, statements.top))
+    }
+
+    /// `Count` of each configured failure counter
+    pub fn get_pool_error_counts(&self, counters: &PoolErrorCounters) -> Result<PoolErrorCounts, String> {
+        Ok(PoolErrorCounts {
+            connection_timeouts: self.get_count(&counters.connection_timeouts)?,
+            creation_failures: self.get_count(&counters.creation_failures)?,
+            rejections: self.get_count(&counters.rejections)?,
+        })
+    }
+
+    fn get_count(&self, object_name: &Option<String>) -> Result<Option<u64>, String> {
+        match object_name {
+            Some(name) => {
+                let count: i64 = self.get_attribute(name, "Count")?;
+                Ok(Some(count.max(0) as u64))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn gc_object_name(name: &str) -> String {
+        format!("java.lang:type=GarbageCollector,name={}", name)
     }
 
-    pub fn get_hikari_metrics(&self) -> Result<HikariMetrics, jmx::Error> {
+    pub fn get_hikari_metrics(&self) -> Result<HikariMetrics, String> {
         let total: i32 = self.get_hikari_attribute("TotalConnections")?;
         let active: i32 = self.get_hikari_attribute("ActiveConnections")?;
         let waiting: i32 = self.get_hikari_attribute("ThreadsAwaitingConnection")?;
@@ -26,7 +175,7 @@ impl JMXClient {
         })
     }
 
-    pub fn get_slick_metrics(&self) -> Result<SlickMetrics, jmx::Error> {
+    pub fn get_slick_metrics(&self) -> Result<SlickMetrics, String> {
         let active_threads: i32 = self.get_slick_attribute("ActiveThreads")?;
         let queue_size: i32 = self.get_slick_attribute("QueueSize")?;
 
@@ -36,7 +185,7 @@ impl JMXClient {
         })
     }
 
-    pub fn get_slick_config(&self) -> Result<SlickConfig, jmx::Error> {
+    pub fn get_slick_config(&self) -> Result<SlickConfig, String> {
         let max_threads: i32 = self.get_slick_attribute("MaxThreads")?;
         let max_queue_size: i32 = self.get_slick_attribute("MaxQueueSize")?;
 
@@ -46,12 +195,12 @@ impl JMXClient {
         })
     }
 
-    fn get_slick_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, jmx::Error> {
-        self.connection.get_attribute(format!("slick:type=AsyncExecutor,name={}", self.db_pool_name), attr)
+    fn get_slick_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, String> {
+        self.get_attribute(&format!("slick:type=AsyncExecutor,name={}", self.db_pool_name), attr)
     }
 
-    fn get_hikari_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, jmx::Error> {
-        self.connection.get_attribute(format!("com.zaxxer.hikari:type=Pool ({})", self.db_pool_name), attr)
+    fn get_hikari_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, String> {
+        self.get_attribute(&format!("com.zaxxer.hikari:type=Pool ({})", self.db_pool_name), attr)
     }
 }
 
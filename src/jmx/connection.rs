@@ -0,0 +1,276 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use j4rs::{Instance, InvocationArg, Jvm, JvmBuilder};
+use serde_json::{Map, Value};
+
+const COMPOSITE_DATA: &str = "javax.management.openmbean.CompositeData";
+const TABULAR_DATA: &str = "javax.management.openmbean.TabularData";
+const OBJECT_ARRAY: &str = "[Ljava.lang.Object;";
+const OBJECT_NAME: &str = "javax.management.ObjectName";
+
+/// Parameter of an MBean operation, typed as in the signature of the operation
+#[derive(Clone, Debug, PartialEq)]
+pub enum OperationParam {
+    Bool(bool),
+    Text(String),
+}
+
+impl OperationParam {
+    fn java_type(&self) -> &'static str {
+        match self {
+            OperationParam::Bool(_) => "boolean",
+            OperationParam::Text(_) => "java.lang.String",
+        }
+    }
+}
+
+///
+/// Connection to the MBean server of a JVM. Values are returned as JSON,
+/// open data (CompositeData and TabularData) along with their type, as `JMXValue` reads them.
+pub trait MBeanConnection {
+    fn get_attribute(&self, object_name: &str, attribute: &str) -> Result<Value, String>;
+    /// Names of the MBeans matching an object name pattern, e.g. `kafka.server:type=BrokerTopicMetrics,*`
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, String>;
+    fn invoke(&self, object_name: &str, operation: &str, params: &[OperationParam]) -> Result<Value, String>;
+}
+
+///
+/// Connection over RMI, through a JVM embedded with j4rs.
+/// Credentials are passed to the connector as `jmx.remote.credentials`, as `jconsole` does.
+pub struct RmiMBeanConnection {
+    jvm: Jvm,
+    connection: Instance,
+    // the connection is closed along with its connector
+    _connector: Instance,
+}
+
+impl RmiMBeanConnection {
+    pub fn connect(service_url: &str, credentials: Option<(&str, &str)>) -> Result<RmiMBeanConnection, String> {
+        let jvm = JvmBuilder::new().build().map_err(|e| format!("Couldn't start the JVM: {}", e))?;
+        let url = jvm.create_instance("javax.management.remote.JMXServiceURL", &[InvocationArg::from(service_url)])
+            .map_err(|e| e.to_string())?;
+        let environment = jvm.create_instance("java.util.HashMap", &[]).map_err(|e| e.to_string())?;
+        if let Some((username, password)) = credentials {
+            let key = object(&jvm, string(&jvm, "jmx.remote.credentials")?)?;
+            let value = object(&jvm, array(&jvm, "java.lang.String", vec![string(&jvm, username)?, string(&jvm, password)?])?)?;
+            jvm.invoke(&environment, "put", &[InvocationArg::from(key), InvocationArg::from(value)])
+                .map_err(|e| e.to_string())?;
+        }
+        let environment = jvm.cast(&environment, "java.util.Map").map_err(|e| e.to_string())?;
+        let connector = jvm.invoke_static(
+            "javax.management.remote.JMXConnectorFactory",
+            "connect",
+            &[InvocationArg::from(url), InvocationArg::from(environment)],
+        ).map_err(|e| e.to_string())?;
+        let connection = jvm.invoke(&connector, "getMBeanServerConnection", &[]).map_err(|e| e.to_string())?;
+        Ok(RmiMBeanConnection { jvm, connection, _connector: connector })
+    }
+
+    fn object_name(&self, name: &str) -> Result<Instance, String> {
+        self.jvm.create_instance(OBJECT_NAME, &[InvocationArg::from(name)])
+            .map_err(|_| format!("Invalid object name {}", name))
+    }
+
+    /// Null values can't be cast, results are declared as `Object` though so they can be passed on
+    fn is_null(&self, value: &Instance) -> Result<bool, String> {
+        let value = self.jvm.clone_instance(value).map_err(|e| e.to_string())?;
+        let is = self.jvm.invoke_static("java.util.Objects", "isNull", &[InvocationArg::from(value)]).map_err(|e| e.to_string())?;
+        self.jvm.to_rust(is).map_err(|e| e.to_string())
+    }
+
+    fn is_instance(&self, value: &Instance, class: &str) -> Result<bool, String> {
+        let class = self.jvm.invoke_static("java.lang.Class", "forName", &[InvocationArg::from(class)]).map_err(|e| e.to_string())?;
+        let value = object(&self.jvm, self.jvm.clone_instance(value).map_err(|e| e.to_string())?)?;
+        let is = self.jvm.invoke(&class, "isInstance", &[InvocationArg::from(value)]).map_err(|e| e.to_string())?;
+        self.jvm.to_rust(is).map_err(|e| e.to_string())
+    }
+
+    fn elements(&self, array: Instance) -> Result<Vec<Instance>, String> {
+        let array = object(&self.jvm, array)?;
+        let length = self.jvm.invoke_static(
+            "java.lang.reflect.Array",
+            "getLength",
+            &[InvocationArg::from(self.jvm.clone_instance(&array).map_err(|e| e.to_string())?)],
+        ).map_err(|e| e.to_string())?;
+        let length: i32 = self.jvm.to_rust(length).map_err(|e| e.to_string())?;
+        (0..length)
+            .map(|i| {
+                let array = self.jvm.clone_instance(&array).map_err(|e| e.to_string())?;
+                self.jvm.invoke_static("java.lang.reflect.Array", "get", &[InvocationArg::from(array), InvocationArg::from(int(&self.jvm, i)?)])
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    /// Methods are looked up on the declared class, `getTypeName` is declared by `OpenType`
+    fn type_name(&self, open_type: &Instance) -> Result<String, String> {
+        let open_type = self.jvm.cast(open_type, "javax.management.openmbean.OpenType").map_err(|e| e.to_string())?;
+        let name = self.jvm.invoke(&open_type, "getTypeName", &[]).map_err(|e| e.to_string())?;
+        self.jvm.to_rust(name).map_err(|e| e.to_string())
+    }
+
+    /// JSON of a value, walking open data since it has no getters for its contents
+    fn to_json(&self, value: Instance) -> Result<Value, String> {
+        if self.is_null(&value)? {
+            Ok(Value::Null)
+        } else if self.is_instance(&value, COMPOSITE_DATA)? {
+            let composite = self.jvm.cast(&value, COMPOSITE_DATA).map_err(|e| e.to_string())?;
+            let composite_type = self.jvm.invoke(&composite, "getCompositeType", &[]).map_err(|e| e.to_string())?;
+            let type_name = self.type_name(&composite_type)?;
+            let keys = self.jvm.invoke(&composite_type, "keySet", &[]).map_err(|e| e.to_string())?;
+            let keys: Vec<String> = self.jvm.to_rust(keys).map_err(|e| e.to_string())?;
+            let mut contents = Map::new();
+            for key in keys {
+                let v = self.jvm.invoke(&composite, "get", &[InvocationArg::from(key.as_str())]).map_err(|e| e.to_string())?;
+                contents.insert(key, self.to_json(v)?);
+            }
+            let mut json = Map::new();
+            json.insert("compositeType".to_owned(), Value::String(type_name));
+            json.insert("contents".to_owned(), Value::Object(contents));
+            Ok(Value::Object(json))
+        } else if self.is_instance(&value, TABULAR_DATA)? {
+            let tabular = self.jvm.cast(&value, TABULAR_DATA).map_err(|e| e.to_string())?;
+            let rows = self.jvm.invoke(&tabular, "values", &[]).map_err(|e| e.to_string())?;
+            let rows = self.jvm.invoke(&rows, "toArray", &[]).map_err(|e| e.to_string())?;
+            let rows = self.elements(rows)?.into_iter().map(|r| self.to_json(r)).collect::<Result<Vec<Value>, String>>()?;
+            let tabular_type = self.jvm.invoke(&tabular, "getTabularType", &[]).map_err(|e| e.to_string())?;
+            let type_name = self.type_name(&tabular_type)?;
+            let mut json = Map::new();
+            json.insert("tabularType".to_owned(), Value::String(type_name));
+            json.insert("dataMap".to_owned(), Value::Array(rows));
+            Ok(Value::Object(json))
+        } else if self.is_instance(&value, OBJECT_ARRAY)? {
+            let items = self.elements(value)?.into_iter().map(|x| self.to_json(x)).collect::<Result<Vec<Value>, String>>()?;
+            Ok(Value::Array(items))
+        } else {
+            self.jvm.to_rust(value).map_err(|e| e.to_string())
+        }
+    }
+}
+
+impl MBeanConnection for RmiMBeanConnection {
+    fn get_attribute(&self, object_name: &str, attribute: &str) -> Result<Value, String> {
+        let name = self.object_name(object_name)?;
+        let value = self.jvm.invoke(&self.connection, "getAttribute", &[InvocationArg::from(name), InvocationArg::from(attribute)])
+            .map_err(|_| format!("Couldn't read {} of {}", attribute, object_name))?;
+        self.to_json(value)
+    }
+
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let name = self.object_name(pattern)?;
+        // an ObjectName is a query too, the wildcard one matching every MBean
+        let query = self.jvm.cast(&self.object_name("*:*")?, "javax.management.QueryExp").map_err(|e| e.to_string())?;
+        let names = self.jvm.invoke(&self.connection, "queryNames", &[InvocationArg::from(name), InvocationArg::from(query)])
+            .map_err(|_| format!("Couldn't query MBeans matching {}", pattern))?;
+        let names = self.jvm.invoke(&names, "toArray", &[]).map_err(|e| e.to_string())?;
+        self.elements(names)?
+            .into_iter()
+            .map(|n| {
+                let n = self.jvm.invoke(&n, "toString", &[]).map_err(|e| e.to_string())?;
+                self.jvm.to_rust(n).map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    fn invoke(&self, object_name: &str, operation: &str, params: &[OperationParam]) -> Result<Value, String> {
+        let values = params.iter()
+            .map(|p| match p {
+                OperationParam::Bool(b) => self.jvm.create_instance("java.lang.Boolean", &[InvocationArg::from(b.to_string())]),
+                OperationParam::Text(s) => self.jvm.create_instance("java.lang.String", &[InvocationArg::from(s.as_str())]),
+            })
+            .collect::<Result<Vec<Instance>, _>>()
+            .map_err(|e| e.to_string())?;
+        let signature = params.iter().map(|p| string(&self.jvm, p.java_type())).collect::<Result<Vec<Instance>, String>>()?;
+        let result = self.jvm.invoke(&self.connection, "invoke", &[
+            InvocationArg::from(self.object_name(object_name)?),
+            InvocationArg::from(operation),
+            InvocationArg::from(array(&self.jvm, "java.lang.Object", values)?),
+            InvocationArg::from(array(&self.jvm, "java.lang.String", signature)?),
+        ]).map_err(|_| format!("Couldn't invoke {} of {}", operation, object_name))?;
+        self.to_json(result)
+    }
+}
+
+fn string(jvm: &Jvm, s: &str) -> Result<Instance, String> {
+    jvm.create_instance("java.lang.String", &[InvocationArg::from(s)]).map_err(|e| e.to_string())
+}
+
+/// Primitive int, for the parameters of reflection calls
+fn int(jvm: &Jvm, i: i32) -> Result<Instance, String> {
+    let boxed = jvm.create_instance("java.lang.Integer", &[InvocationArg::from(i.to_string())]).map_err(|e| e.to_string())?;
+    jvm.invoke(&boxed, "intValue", &[]).map_err(|e| e.to_string())
+}
+
+/// The instance passed as an Object, methods being looked up by the declared types of their parameters
+fn object(jvm: &Jvm, instance: Instance) -> Result<Instance, String> {
+    jvm.cast(&instance, "java.lang.Object").map_err(|e| e.to_string())
+}
+
+/// Java array of the given class, e.g. the `String[]` of credentials
+fn array(jvm: &Jvm, class: &str, items: Vec<Instance>) -> Result<Instance, String> {
+    let element_class = jvm.invoke_static("java.lang.Class", "forName", &[InvocationArg::from(class)]).map_err(|e| e.to_string())?;
+    let array = jvm.invoke_static(
+        "java.lang.reflect.Array",
+        "newInstance",
+        &[InvocationArg::from(element_class), InvocationArg::from(int(jvm, items.len() as i32)?)],
+    ).map_err(|e| e.to_string())?;
+    for (i, item) in items.into_iter().enumerate() {
+        let target = object(jvm, jvm.clone_instance(&array).map_err(|e| e.to_string())?)?;
+        jvm.invoke_static(
+            "java.lang.reflect.Array",
+            "set",
+            &[InvocationArg::from(target), InvocationArg::from(int(jvm, i as i32)?), InvocationArg::from(object(jvm, item)?)],
+        ).map_err(|e| e.to_string())?;
+    }
+    jvm.cast(&array, &format!("[L{};", class)).map_err(|e| e.to_string())
+}
+
+/// Object name, operation and params of an invoked operation
+pub type Invocation = (String, String, Vec<OperationParam>);
+
+///
+/// Connection answering with canned attribute values and operation results,
+/// recording the operations invoked.
+pub struct StubMBeanConnection {
+    pub attributes: Vec<(String, String, Value)>,
+    pub results: Vec<(String, Value)>,
+    pub invoked: Rc<RefCell<Vec<Invocation>>>,
+}
+
+impl StubMBeanConnection {
+    pub fn new(attributes: Vec<(&str, &str, Value)>, results: Vec<(&str, Value)>) -> StubMBeanConnection {
+        StubMBeanConnection {
+            attributes: attributes.into_iter().map(|(n, a, v)| (n.to_owned(), a.to_owned(), v)).collect(),
+            results: results.into_iter().map(|(o, v)| (o.to_owned(), v)).collect(),
+            invoked: Rc::new(RefCell::new(vec![])),
+        }
+    }
+}
+
+impl MBeanConnection for StubMBeanConnection {
+    fn get_attribute(&self, object_name: &str, attribute: &str) -> Result<Value, String> {
+        self.attributes.iter()
+            .find(|(n, a, _)| n == object_name && a == attribute)
+            .map(|(_, _, v)| v.clone())
+            .ok_or(format!("Couldn't read {} of {}", attribute, object_name))
+    }
+
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let domain = pattern.split(':').next().unwrap_or("");
+        let mut names: Vec<String> = self.attributes.iter()
+            .filter(|(n, _, _)| n.starts_with(&format!("{}:", domain)))
+            .map(|(n, _, _)| n.to_owned())
+            .collect();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn invoke(&self, object_name: &str, operation: &str, params: &[OperationParam]) -> Result<Value, String> {
+        self.invoked.borrow_mut().push((object_name.to_owned(), operation.to_owned(), params.to_vec()));
+        self.results.iter()
+            .find(|(o, _)| o == operation)
+            .map(|(_, v)| v.clone())
+            .ok_or(format!("Couldn't invoke {} of {}", operation, object_name))
+    }
+}
@@ -1,2 +1,4 @@
 pub mod model;
 pub mod client;
+pub mod connection;
+mod tests;
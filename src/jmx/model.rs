@@ -1,3 +1,7 @@
+use serde::{Deserialize, Serialize};
+
+use crate::history::Downsample;
+
 #[derive(Clone)]
 pub struct JMXConnectionSettings {
     pub address: String,
@@ -6,21 +10,511 @@ pub struct JMXConnectionSettings {
     pub db_pool_name: String,
 }
 
-#[derive(Clone)]
+/// Loaded classes and threads of the JVM, from the ClassLoading and Threading MBeans
+#[derive(Clone, Serialize)]
+pub struct JVMRuntimeMetrics {
+    pub loaded_classes: i32,
+    pub live_threads: i32,
+    pub daemon_threads: i32,
+    pub peak_threads: i32,
+}
+
+impl Downsample for JVMRuntimeMetrics {
+    fn merge(&mut self, later: JVMRuntimeMetrics) {
+        self.loaded_classes = self.loaded_classes.max(later.loaded_classes);
+        self.live_threads = self.live_threads.max(later.live_threads);
+        self.daemon_threads = self.daemon_threads.max(later.daemon_threads);
+        self.peak_threads = self.peak_threads.max(later.peak_threads);
+    }
+}
+
+#[derive(Clone, Serialize)]
 pub struct SlickMetrics {
     pub active_threads: i32,
     pub queue_size: i32,
 }
 
+impl Downsample for SlickMetrics {
+    fn merge(&mut self, later: SlickMetrics) {
+        self.active_threads = self.active_threads.max(later.active_threads);
+        self.queue_size = self.queue_size.max(later.queue_size);
+    }
+}
+
 pub struct SlickConfig {
     pub max_threads: i32,
     pub max_queue_size: i32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct HikariMetrics {
     pub total: i32,
     pub active: i32,
     pub idle: i32,
     pub waiting: i32,
 }
+
+impl Downsample for HikariMetrics {
+    fn merge(&mut self, later: HikariMetrics) {
+        self.total = self.total.max(later.total);
+        self.active = self.active.max(later.active);
+        self.idle = self.idle.max(later.idle);
+        self.waiting = self.waiting.max(later.waiting);
+    }
+}
+
+/// Health of a Kafka broker, from the ReplicaManager and KafkaRequestHandlerPool MBeans
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct KafkaBrokerMetrics {
+    /// Partitions led by the broker whose followers lag behind
+    pub under_replicated_partitions: u64,
+    /// Share of the time the request handler threads were idle over the last minute, from 0 to 1
+    pub request_handler_idle: f64,
+    /// Shrinks of the in-sync replicas per second over the last minute
+    pub isr_shrinks_per_sec: f64,
+}
+
+/// Numeric JMX attribute charted on the Custom tab, `key` selects an item of CompositeData
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct WatchedAttribute {
+    pub label: String,
+    /// Object name, or a pattern charting the attribute of each matching bean, e.g. `kafka.server:type=BrokerTopicMetrics,topic=*`
+    pub object_name: String,
+    pub attribute: String,
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+impl WatchedAttribute {
+    /// Whether the object name is a pattern, matching any number of beans
+    pub fn is_pattern(&self) -> bool {
+        self.object_name.contains(|c| c == '*' || c == '?')
+    }
+
+    ///
+    /// Label of the series of a bean matching the pattern, followed by the parts of its object name
+    /// the pattern leaves open.
+    ///
+    /// eg. `Messages in topic=orders` for `kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec,topic=orders`
+    /// matching `kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec,topic=*`
+    pub fn label_of(&self, object_name: &str) -> String {
+        let is_open = |part: &str| part.contains(|c| c == '*' || c == '?');
+        let (pattern_domain, pattern_properties) = split_object_name(&self.object_name);
+        let (domain, properties) = split_object_name(object_name);
+        let mut open: Vec<&str> = properties.into_iter()
+            .filter(|p| !pattern_properties.iter().any(|x| x == p && !is_open(x)))
+            .collect();
+        if is_open(pattern_domain) {
+            open.insert(0, domain);
+        }
+        if open.is_empty() {
+            self.label.to_owned()
+        } else {
+            format!("{} {}", self.label, open.join(","))
+        }
+    }
+}
+
+/// Domain and key properties of an object name, e.g. `java.lang` and `["type=GarbageCollector", "name=G1 Young Generation"]`
+fn split_object_name(object_name: &str) -> (&str, Vec<&str>) {
+    let mut parts = object_name.splitn(2, ':');
+    let domain = parts.next().unwrap_or("");
+    (domain, parts.next().map_or(vec![], |p| p.split(',').collect()))
+}
+
+/// Statement timers registered over JMX, e.g. by a Dropwizard instrumented data source
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct StatementMetrics {
+    /// Object name pattern matching one timer per statement, e.g. `metrics:name=db.statements.*`
+    pub object_name: String,
+    /// Number of the slowest statements shown
+    #[serde(default = "StatementMetrics::default_top")]
+    pub top: usize,
+}
+
+impl StatementMetrics {
+    fn default_top() -> usize {
+        10
+    }
+}
+
+///
+/// Failure counters of the connection pool and of the Slick executor, each the object name of a bean with a `Count`
+/// attribute, e.g. a meter registered by HikariCP's Dropwizard metrics tracker. Neither HikariCP's pool bean
+/// nor Slick's executor bean count failures, so only counters registered by the application can be charted.
+///
+/// eg. `{ "connection_timeouts": "metrics:name=myDb.pool.ConnectionTimeoutRate" }`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolErrorCounters {
+    /// Requests for a connection that timed out
+    pub connection_timeouts: Option<String>,
+    /// Connections the pool couldn't open
+    pub creation_failures: Option<String>,
+    /// Database actions rejected by the Slick executor as its queue was full
+    pub rejections: Option<String>,
+}
+
+/// Counts of the failure counters, None for the ones that aren't configured
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PoolErrorCounts {
+    pub connection_timeouts: Option<u64>,
+    pub creation_failures: Option<u64>,
+    pub rejections: Option<u64>,
+}
+
+/// Failures per second between two polls of the counters
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PoolErrorRates {
+    pub connection_timeouts: Option<f64>,
+    pub creation_failures: Option<f64>,
+    pub rejections: Option<f64>,
+}
+
+impl PoolErrorRates {
+    /// Rates between counts taken `secs` apart, None for a counter that isn't configured or was reset meanwhile
+    pub fn between(prev: &PoolErrorCounts, current: &PoolErrorCounts, secs: f64) -> PoolErrorRates {
+        let rate = |a: Option<u64>, b: Option<u64>| match (a, b) {
+            (Some(a), Some(b)) if b >= a && secs > 0.0 => Some((b - a) as f64 / secs),
+            _ => None,
+        };
+        PoolErrorRates {
+            connection_timeouts: rate(prev.connection_timeouts, current.connection_timeouts),
+            creation_failures: rate(prev.creation_failures, current.creation_failures),
+            rejections: rate(prev.rejections, current.rejections),
+        }
+    }
+}
+
+impl Downsample for PoolErrorRates {
+    fn merge(&mut self, later: PoolErrorRates) {
+        let max = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.connection_timeouts = max(self.connection_timeouts, later.connection_timeouts);
+        self.creation_failures = max(self.creation_failures, later.creation_failures);
+        self.rejections = max(self.rejections, later.rejections);
+    }
+}
+
+/// Executions of a statement and their mean latency, as reported by its timer
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StatementStats {
+    pub name: String,
+    pub count: u64,
+    pub mean_ms: f64,
+}
+
+impl StatementStats {
+    /// Statement name from the `name` key of its timer's object name, the whole object name if there's none
+    pub fn name_of(object_name: &str) -> String {
+        object_name.splitn(2, ':').nth(1).unwrap_or("")
+            .split(',')
+            .find_map(|p| p.strip_prefix("name="))
+            .unwrap_or(object_name)
+            .trim_matches('"')
+            .to_owned()
+    }
+
+    /// The `top` statements with the highest mean latency, the slowest first
+    pub fn slowest(mut stats: Vec<StatementStats>, top: usize) -> Vec<StatementStats> {
+        stats.sort_by(|a, b| b.mean_ms.partial_cmp(&a.mean_ms).unwrap_or(std::cmp::Ordering::Equal));
+        stats.truncate(top);
+        stats
+    }
+}
+
+///
+/// Typed representation of a JMX attribute value.
+///
+/// Open types are mapped as follows:
+/// CompositeData becomes `Composite` (key/value pairs in key order),
+/// TabularData becomes `Tabular` (one `Composite` per row),
+/// arrays become `Array` and simple types become scalars.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JMXValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Array(Vec<JMXValue>),
+    Composite(Vec<(String, JMXValue)>),
+    Tabular(Vec<JMXValue>),
+}
+
+impl From<serde_json::Value> for JMXValue {
+    fn from(v: serde_json::Value) -> JMXValue {
+        use serde_json::Value;
+        match v {
+            Value::Null => JMXValue::Null,
+            Value::Bool(b) => JMXValue::Bool(b),
+            Value::Number(n) => n.as_f64().map_or(JMXValue::Text(n.to_string()), JMXValue::Number),
+            Value::String(s) => JMXValue::Text(s),
+            Value::Array(a) => JMXValue::Array(a.into_iter().map(JMXValue::from).collect()),
+            Value::Object(mut o) => {
+                // serialized CompositeDataSupport/TabularDataSupport carry their type next to the data
+                if o.contains_key("compositeType") && o.contains_key("contents") {
+                    JMXValue::from(o.remove("contents").unwrap())
+                } else if o.contains_key("tabularType") && o.contains_key("dataMap") {
+                    match o.remove("dataMap").unwrap() {
+                        Value::Object(rows) => JMXValue::Tabular(rows.into_iter().map(|(_, r)| JMXValue::from(r)).collect()),
+                        Value::Array(rows) => JMXValue::Tabular(rows.into_iter().map(JMXValue::from).collect()),
+                        other => JMXValue::from(other),
+                    }
+                } else {
+                    let mut entries: Vec<(String, JMXValue)> = o.into_iter().map(|(k, v)| (k, JMXValue::from(v))).collect();
+                    entries.sort_by(|a, b| a.0.cmp(&b.0));
+                    JMXValue::Composite(entries)
+                }
+            }
+        }
+    }
+}
+
+impl JMXValue {
+    /// Single-line representation, nested values are summarized
+    pub fn summary(&self) -> String {
+        match self {
+            JMXValue::Null => "null".to_owned(),
+            JMXValue::Bool(b) => b.to_string(),
+            JMXValue::Number(n) => n.to_string(),
+            JMXValue::Text(s) => s.to_owned(),
+            JMXValue::Array(a) if a.iter().all(|x| !x.is_nested()) =>
+                format!("[{}]", a.iter().map(|x| x.summary()).collect::<Vec<String>>().join(", ")),
+            JMXValue::Array(a) => format!("[{} items]", a.len()),
+            JMXValue::Composite(_) => "".to_owned(),
+            JMXValue::Tabular(rows) => format!("{} rows", rows.len()),
+        }
+    }
+
+    /// Numeric value, if the value is a number or a boolean
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JMXValue::Number(n) => Some(*n),
+            JMXValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// Item of CompositeData by its key
+    pub fn get(&self, key: &str) -> Option<&JMXValue> {
+        match self {
+            JMXValue::Composite(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn is_nested(&self) -> bool {
+        matches!(self, JMXValue::Array(_) | JMXValue::Composite(_) | JMXValue::Tabular(_))
+    }
+
+    ///
+    /// Flattens the value into (key, value) rows of a table, nested keys are indented.
+    ///
+    /// eg. HeapMemoryUsage gives:
+    ///   committed  264241152
+    ///   init       264241152
+    ///   max        4164943872
+    ///   used       38346856
+    pub fn table_rows(&self) -> Vec<(String, String)> {
+        let mut rows = vec![];
+        self.append_rows("", &mut rows);
+        rows
+    }
+
+    fn append_rows(&self, indent: &str, rows: &mut Vec<(String, String)>) {
+        let nested_indent = format!("{}  ", indent);
+        match self {
+            JMXValue::Composite(entries) =>
+                for (k, v) in entries {
+                    rows.push((format!("{}{}", indent, k), v.summary()));
+                    if v.is_nested() {
+                        v.append_rows(&nested_indent, rows);
+                    }
+                },
+            JMXValue::Tabular(items) | JMXValue::Array(items) =>
+                for (i, v) in items.iter().enumerate() {
+                    if v.is_nested() {
+                        rows.push((format!("{}[{}]", indent, i), v.summary()));
+                        v.append_rows(&nested_indent, rows);
+                    }
+                },
+            _ => rows.push((indent.to_owned(), self.summary())),
+        }
+    }
+}
+
+///
+/// Formats the ThreadInfo array returned by `dumpAllThreads` of `java.lang:type=Threading` like jstack does.
+///
+/// eg.
+///   ```text
+///   "main" #1 WAITING on java.lang.Object@6d06d69c
+///   	at java.lang.Object.wait(Native Method)
+///   	at com.example.Main.main(Main.java:12)
+///   ```
+pub fn format_thread_dump(threads: &JMXValue) -> String {
+    let text = |v: &JMXValue, key: &str| v.get(key).filter(|x| **x != JMXValue::Null).map(JMXValue::summary);
+    let threads = match threads {
+        JMXValue::Array(items) => items.as_slice(),
+        _ => &[],
+    };
+    let mut out = String::new();
+    for t in threads {
+        out.push_str(&format!(
+            "\"{}\" #{} {}",
+            text(t, "threadName").unwrap_or_default(),
+            text(t, "threadId").unwrap_or_default(),
+            text(t, "threadState").unwrap_or_default(),
+        ));
+        if let Some(lock) = text(t, "lockName") {
+            out.push_str(&format!(" on {}", lock));
+        }
+        if let Some(owner) = text(t, "lockOwnerName") {
+            out.push_str(&format!(" owned by \"{}\"", owner));
+        }
+        out.push('\n');
+        if let Some(JMXValue::Array(frames)) = t.get("stackTrace") {
+            for f in frames {
+                let location = match (text(f, "fileName"), f.get("lineNumber").and_then(JMXValue::as_f64)) {
+                    _ if f.get("nativeMethod") == Some(&JMXValue::Bool(true)) => "Native Method".to_owned(),
+                    (Some(file), Some(line)) if line >= 0.0 => format!("{}:{}", file, line),
+                    (Some(file), _) => file,
+                    (None, _) => "Unknown Source".to_owned(),
+                };
+                out.push_str(&format!(
+                    "\tat {}.{}({})\n",
+                    text(f, "className").unwrap_or_default(),
+                    text(f, "methodName").unwrap_or_default(),
+                    location,
+                ));
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::jmx::model::{format_thread_dump, JMXValue, PoolErrorCounts, PoolErrorRates, StatementStats, WatchedAttribute};
+
+    #[test]
+    fn composite_data_as_key_value_rows() {
+        let v = JMXValue::from(json!({
+            "compositeType": { "typeName": "java.lang.management.MemoryUsage" },
+            "contents": { "used": 38, "max": 4096, "init": 256, "committed": 264 }
+        }));
+        assert_eq!(v.table_rows(), vec![
+            ("committed".to_owned(), "264".to_owned()),
+            ("init".to_owned(), "256".to_owned()),
+            ("max".to_owned(), "4096".to_owned()),
+            ("used".to_owned(), "38".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn nested_tabular_data_is_indented() {
+        let v = JMXValue::from(json!({
+            "duration": 12,
+            "memoryUsageAfterGc": {
+                "tabularType": {},
+                "dataMap": [{ "key": "Eden", "value": { "used": 0 } }]
+            }
+        }));
+        assert_eq!(v.table_rows(), vec![
+            ("duration".to_owned(), "12".to_owned()),
+            ("memoryUsageAfterGc".to_owned(), "1 rows".to_owned()),
+            ("  [0]".to_owned(), "".to_owned()),
+            ("    key".to_owned(), "Eden".to_owned()),
+            ("    value".to_owned(), "".to_owned()),
+            ("      used".to_owned(), "0".to_owned()),
+        ]);
+        assert_eq!(JMXValue::from(json!([1, "a"])).summary(), "[1, a]");
+    }
+
+    #[test]
+    fn numeric_items_of_composite_data() {
+        let v = JMXValue::from(json!({ "used": 38, "name": "heap", "valid": true }));
+        assert_eq!(v.get("used").and_then(JMXValue::as_f64), Some(38.0));
+        assert_eq!(v.get("valid").and_then(JMXValue::as_f64), Some(1.0));
+        assert_eq!(v.get("name").and_then(JMXValue::as_f64), None);
+        assert_eq!(v.get("max"), None);
+    }
+
+    #[test]
+    fn formats_thread_dump_like_jstack() {
+        let threads = JMXValue::from(json!([
+            {
+                "threadName": "main", "threadId": 1, "threadState": "WAITING",
+                "lockName": "java.lang.Object@6d06d69c", "lockOwnerName": null,
+                "stackTrace": [
+                    { "className": "java.lang.Object", "methodName": "wait", "fileName": null, "lineNumber": -2, "nativeMethod": true },
+                    { "className": "com.example.Main", "methodName": "main", "fileName": "Main.java", "lineNumber": 12, "nativeMethod": false }
+                ]
+            },
+            { "threadName": "worker", "threadId": 7, "threadState": "RUNNABLE", "lockName": null, "stackTrace": [] }
+        ]));
+
+        assert_eq!(format_thread_dump(&threads), "\
+\"main\" #1 WAITING on java.lang.Object@6d06d69c
+\tat java.lang.Object.wait(Native Method)
+\tat com.example.Main.main(Main.java:12)
+
+\"worker\" #7 RUNNABLE
+
+");
+    }
+
+    #[test]
+    fn labels_beans_matching_watched_pattern() {
+        let watched = |object_name: &str| WatchedAttribute {
+            label: "Messages in".to_owned(),
+            object_name: object_name.to_owned(),
+            attribute: "OneMinuteRate".to_owned(),
+            key: None,
+        };
+        let topics = watched("kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec,topic=*");
+        assert!(topics.is_pattern());
+        assert!(!watched("java.lang:type=Threading").is_pattern());
+
+        assert_eq!(topics.label_of("kafka.server:type=BrokerTopicMetrics,name=MessagesInPerSec,topic=orders"), "Messages in topic=orders");
+        assert_eq!(
+            watched("kafka.server:type=BrokerTopicMetrics,*").label_of("kafka.server:type=BrokerTopicMetrics,name=BytesInPerSec,topic=orders"),
+            "Messages in name=BytesInPerSec,topic=orders"
+        );
+        assert_eq!(watched("kafka.*:type=KafkaServer").label_of("kafka.server:type=KafkaServer"), "Messages in kafka.server");
+    }
+
+    #[test]
+    fn pool_error_rates_between_polls() {
+        let counts = |timeouts: u64, rejections: Option<u64>| PoolErrorCounts {
+            connection_timeouts: Some(timeouts),
+            creation_failures: None,
+            rejections,
+        };
+
+        assert_eq!(PoolErrorRates::between(&counts(10, Some(4)), &counts(16, Some(4)), 2.0), PoolErrorRates {
+            connection_timeouts: Some(3.0),
+            creation_failures: None,
+            rejections: Some(0.0),
+        });
+        // a counter going down was reset, e.g. by a restart of the application
+        assert_eq!(PoolErrorRates::between(&counts(16, Some(4)), &counts(2, Some(5)), 1.0).connection_timeouts, None);
+        assert_eq!(PoolErrorRates::between(&counts(16, Some(4)), &counts(2, Some(5)), 1.0).rejections, Some(1.0));
+    }
+
+    #[test]
+    fn picks_slowest_statements() {
+        assert_eq!(StatementStats::name_of("metrics:name=db.statements.select_users"), "db.statements.select_users");
+        assert_eq!(StatementStats::name_of("metrics:type=timers,name=\"select 1\""), "select 1");
+        assert_eq!(StatementStats::name_of("metrics:type=timers"), "metrics:type=timers");
+
+        let stats = |name: &str, mean_ms: f64| StatementStats { name: name.to_owned(), count: 1, mean_ms };
+        let slowest = StatementStats::slowest(vec![stats("a", 1.0), stats("b", 30.0), stats("c", 2.5)], 2);
+        assert_eq!(slowest, vec![stats("b", 30.0), stats("c", 2.5)]);
+    }
+}
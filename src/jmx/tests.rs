@@ -0,0 +1,93 @@
+#[cfg(test)]
+use serde_json::json;
+
+#[cfg(test)]
+use crate::jmx::client::JMXClient;
+#[cfg(test)]
+use crate::jmx::connection::{OperationParam, StubMBeanConnection};
+#[cfg(test)]
+use crate::jmx::model::format_thread_dump;
+
+#[test]
+fn reads_kafka_broker_metrics() {
+    use crate::jmx::client::get_kafka_broker_metrics;
+    use crate::jmx::model::KafkaBrokerMetrics;
+
+    let connection = StubMBeanConnection::new(vec![
+        ("kafka.server:type=ReplicaManager,name=UnderReplicatedPartitions", "Value", json!(3)),
+        ("kafka.server:type=KafkaRequestHandlerPool,name=RequestHandlerAvgIdlePercent", "OneMinuteRate", json!(0.85)),
+        ("kafka.server:type=ReplicaManager,name=IsrShrinksPerSec", "OneMinuteRate", json!(0.5)),
+    ], vec![]);
+    assert_eq!(get_kafka_broker_metrics(&connection), Ok(KafkaBrokerMetrics {
+        under_replicated_partitions: 3,
+        request_handler_idle: 0.85,
+        isr_shrinks_per_sec: 0.5,
+    }));
+    // not a broker
+    assert!(get_kafka_broker_metrics(&StubMBeanConnection::new(vec![], vec![])).is_err());
+}
+
+#[test]
+fn dumps_heap_of_live_objects() {
+    let connection = StubMBeanConnection::new(vec![], vec![("dumpHeap", json!(null))]);
+    let invoked = connection.invoked.clone();
+    let client = JMXClient::new(Box::new(connection), "db".to_owned());
+
+    assert_eq!(client.dump_heap("heap-1600000000.hprof"), Ok(()));
+    assert_eq!(*invoked.borrow(), vec![(
+        "com.sun.management:type=HotSpotDiagnostic".to_owned(),
+        "dumpHeap".to_owned(),
+        vec![OperationParam::Text("heap-1600000000.hprof".to_owned()), OperationParam::Bool(true)],
+    )]);
+
+    let unsupported = JMXClient::new(Box::new(StubMBeanConnection::new(vec![], vec![])), "db".to_owned());
+    assert_eq!(
+        unsupported.dump_heap("heap-1600000000.hprof"),
+        Err("Couldn't invoke dumpHeap of com.sun.management:type=HotSpotDiagnostic".to_owned())
+    );
+}
+
+#[test]
+fn dumps_threads_from_thread_infos() {
+    let frame = |class: &str, method: &str, file: Option<&str>, line: i32| json!({
+        "compositeType": "java.lang.StackTraceElement",
+        "contents": { "className": class, "methodName": method, "fileName": file, "lineNumber": line, "nativeMethod": line == -2 },
+    });
+    let thread_info = json!({
+        "compositeType": "java.lang.management.ThreadInfo",
+        "contents": {
+            "threadName": "main",
+            "threadId": 1,
+            "threadState": "WAITING",
+            "lockName": "java.lang.Object@6d06d69c",
+            "lockOwnerName": null,
+            "stackTrace": [frame("java.lang.Object", "wait", None, -2), frame("com.example.Main", "main", Some("Main.java"), 12)],
+        },
+    });
+    let connection = StubMBeanConnection::new(vec![], vec![("dumpAllThreads", json!([thread_info]))]);
+    let invoked = connection.invoked.clone();
+    let client = JMXClient::new(Box::new(connection), "db".to_owned());
+
+    let threads = client.dump_threads().unwrap();
+    assert_eq!(
+        format_thread_dump(&threads),
+        "\"main\" #1 WAITING on java.lang.Object@6d06d69c\n\tat java.lang.Object.wait(Native Method)\n\tat com.example.Main.main(Main.java:12)\n\n"
+    );
+    // locked monitors and synchronizers
+    assert_eq!(invoked.borrow()[0].2, vec![OperationParam::Bool(true), OperationParam::Bool(true)]);
+}
+
+#[test]
+fn reads_runtime_metrics_of_the_stub() {
+    let connection = StubMBeanConnection::new(vec![
+        ("java.lang:type=ClassLoading", "LoadedClassCount", json!(5120)),
+        ("java.lang:type=Threading", "ThreadCount", json!(42)),
+        ("java.lang:type=Threading", "DaemonThreadCount", json!(30)),
+        ("java.lang:type=Threading", "PeakThreadCount", json!(50)),
+    ], vec![]);
+    let client = JMXClient::new(Box::new(connection), "db".to_owned());
+
+    let metrics = client.get_jvm_runtime_metrics().unwrap();
+    assert_eq!((metrics.loaded_classes, metrics.live_threads, metrics.daemon_threads, metrics.peak_threads), (5120, 42, 30, 50));
+    assert_eq!(client.get_slick_metrics().err(), Some("Couldn't read ActiveThreads of slick:type=AsyncExecutor,name=db".to_owned()));
+}
@@ -0,0 +1,108 @@
+use crate::app::TabKind;
+
+/// Key, or group of keys, along with what it does on the tabs it applies to
+pub struct KeyHint {
+    pub keys: &'static str,
+    pub action: &'static str,
+    /// Tabs the keys work on, every tab if empty, but the ones where the same keys do something of their own
+    pub tabs: &'static [TabKind],
+}
+
+impl KeyHint {
+    pub fn applies_to(&self, kind: &TabKind) -> bool {
+        if self.tabs.is_empty() {
+            !KEYMAP.iter().any(|h| h.keys == self.keys && h.tabs.contains(kind))
+        } else {
+            self.tabs.contains(kind)
+        }
+    }
+}
+
+const PANELS: &[TabKind] = &[
+    TabKind::ZMX, TabKind::Slick, TabKind::JVM, TabKind::AkkaActorTree, TabKind::Redis, TabKind::GcLog,
+    TabKind::Compare, TabKind::Hooks, TabKind::Zookeeper, TabKind::Dashboard,
+];
+const TABLES: &[TabKind] = &[TabKind::Slick, TabKind::JVM, TabKind::AkkaActorTree, TabKind::Redis, TabKind::Dashboard];
+const CHARTS: &[TabKind] = &[TabKind::ZMX, TabKind::Slick, TabKind::Compare, TabKind::Dashboard];
+
+///
+/// Keys of the app, the ones of specific tabs first and then the ones of every tab, most useful first.
+/// Keys handled in `App::on_key` and the event loop are listed here so that the footer shows them.
+pub const KEYMAP: &[KeyHint] = &[
+    KeyHint { keys: "↑/↓", action: "select", tabs: &[TabKind::ZMX, TabKind::AkkaActorTree, TabKind::Prometheus, TabKind::Zookeeper] },
+    KeyHint { keys: "PgUp/PgDn", action: "scroll", tabs: &[TabKind::ZMX, TabKind::Zookeeper] },
+    KeyHint { keys: "Enter", action: "dump fibers", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: ":", action: "jump to fiber", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "Enter", action: "reload tree", tabs: &[TabKind::AkkaActorTree] },
+    KeyHint { keys: "Enter", action: "pin", tabs: &[TabKind::Prometheus] },
+    KeyHint { keys: "/", action: "explore metrics", tabs: &[TabKind::Prometheus] },
+    KeyHint { keys: ":", action: "console", tabs: &[TabKind::Zookeeper] },
+    KeyHint { keys: "g", action: "filter", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "o", action: "sort", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "1-4", action: "toggle status", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "p/c/n", action: "parent/child/sibling", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "f/F", action: "fold dump", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "w", action: "wrap", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "b", action: "burst", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "d", action: "save dumps", tabs: &[TabKind::ZMX] },
+    KeyHint { keys: "d", action: "save tree", tabs: &[TabKind::AkkaActorTree] },
+    KeyHint { keys: "H", action: "heap dump", tabs: &[TabKind::JVM] },
+    KeyHint { keys: "T", action: "thread dump", tabs: &[TabKind::JVM] },
+    KeyHint { keys: "s/S", action: "sort", tabs: TABLES },
+    KeyHint { keys: "e", action: "export tree", tabs: &[TabKind::ZMX, TabKind::AkkaActorTree] },
+    KeyHint { keys: "Tab", action: "focus", tabs: PANELS },
+    KeyHint { keys: "z", action: "zoom", tabs: PANELS },
+    KeyHint { keys: "t", action: "time range", tabs: CHARTS },
+    KeyHint { keys: "P", action: "freeze chart", tabs: CHARTS },
+    KeyHint { keys: "←/→", action: "tabs", tabs: &[] },
+    KeyHint { keys: "[/]", action: "cursor", tabs: &[] },
+    KeyHint { keys: "a", action: "average", tabs: &[] },
+    KeyHint { keys: "r", action: "rate", tabs: &[] },
+    KeyHint { keys: "h", action: "history", tabs: &[] },
+    KeyHint { keys: "E", action: "export chart", tabs: &[] },
+    KeyHint { keys: "u", action: "upload", tabs: &[] },
+    KeyHint { keys: "I", action: "incident summary", tabs: &[] },
+    KeyHint { keys: "x", action: "scrub", tabs: &[] },
+    KeyHint { keys: ":", action: "connect", tabs: &[] },
+    KeyHint { keys: "q", action: "quit", tabs: &[] },
+];
+
+/// Hints of the keys working on a tab, as many as fit in the width, e.g. `↑/↓ select · PgUp/PgDn scroll`
+pub fn footer(kind: &TabKind, width: usize) -> String {
+    let mut footer = String::new();
+    for hint in KEYMAP.iter().filter(|h| h.applies_to(kind)) {
+        let next = if footer.is_empty() {
+            format!("{} {}", hint.keys, hint.action)
+        } else {
+            format!("{} · {} {}", footer, hint.keys, hint.action)
+        };
+        if next.chars().count() > width {
+            break;
+        }
+        footer = next;
+    }
+    footer
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::TabKind;
+    use crate::keymap::{footer, KEYMAP};
+
+    #[test]
+    fn shows_keys_of_the_tab_first() {
+        assert_eq!(footer(&TabKind::Prometheus, 60), "↑/↓ select · Enter pin · / explore metrics · ←/→ tabs");
+        assert_eq!(footer(&TabKind::Log, 30), "←/→ tabs · [/] cursor");
+        assert!(footer(&TabKind::ZMX, 200).starts_with("↑/↓ select · PgUp/PgDn scroll · Enter dump fibers · : jump to fiber · g filter"));
+        assert_eq!(footer(&TabKind::ZMX, 5), "");
+        assert!(footer(&TabKind::Log, 200).ends_with(": connect · q quit"));
+        assert!(!footer(&TabKind::Zookeeper, 200).contains(": connect"));
+
+        // a key does one thing on a tab
+        let tabs = [TabKind::ZMX, TabKind::Slick, TabKind::JVM, TabKind::AkkaActorTree, TabKind::Prometheus, TabKind::Zookeeper];
+        for tab in tabs.iter() {
+            let keys: Vec<&str> = KEYMAP.iter().filter(|h| h.applies_to(tab)).map(|h| h.keys).collect();
+            assert!(keys.iter().enumerate().all(|(i, k)| !keys[i + 1..].contains(k)), "{:?}", keys);
+        }
+    }
+}
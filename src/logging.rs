@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let s = match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LogLevel, String> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(LogLevel::Error),
+            "warn" => Ok(LogLevel::Warn),
+            "info" => Ok(LogLevel::Info),
+            "debug" => Ok(LogLevel::Debug),
+            _ => Err(format!("Unknown log level '{}', expected error, warn, info or debug", s)),
+        }
+    }
+}
+
+/// Current time of day formatted as `HH:MM:SS UTC`
+pub fn utc_time() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) % 86400;
+    format!("{:02}:{:02}:{:02} UTC", secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+///
+/// Writes structured `key=value` log lines to an optional file,
+/// keeping the most recent ones for the in-app log viewer.
+///
+/// eg. `12:30:01 UTC level=warn event=fetch source=zmx error="Connection refused"`
+pub struct Logger {
+    level: LogLevel,
+    file: Option<File>,
+    pub recent: VecDeque<(LogLevel, String)>,
+}
+
+impl Logger {
+    pub const MAX_RECENT_ENTRIES: usize = 500;
+
+    pub fn new(level: LogLevel, path: Option<&str>) -> Result<Logger, String> {
+        let file = match path {
+            Some(p) => Some(
+                OpenOptions::new().create(true).append(true).open(p)
+                    .map_err(|e| format!("Couldn't open log file {}: {}", p, e))?
+            ),
+            None => None,
+        };
+        Ok(Logger { level, file, recent: VecDeque::new() })
+    }
+
+    pub fn log(&mut self, level: LogLevel, event: &str, fields: &[(&str, &str)]) {
+        if level > self.level {
+            return;
+        }
+        let line = format_entry(&utc_time(), level, event, fields);
+        if let Some(f) = self.file.as_mut() {
+            // logging must never break monitoring, so write errors are ignored
+            let _ = writeln!(f, "{}", line);
+        }
+        if self.recent.len() >= Logger::MAX_RECENT_ENTRIES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((level, line));
+    }
+}
+
+fn format_entry(time: &str, level: LogLevel, event: &str, fields: &[(&str, &str)]) -> String {
+    let mut line = format!("{} level={} event={}", time, level, event);
+    for (k, v) in fields {
+        if v.is_empty() || v.contains(char::is_whitespace) || v.contains('"') || v.contains('=') {
+            line.push_str(&format!(" {}={:?}", k, v));
+        } else {
+            line.push_str(&format!(" {}={}", k, v));
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logging::{format_entry, Logger, LogLevel};
+
+    #[test]
+    fn formats_structured_entries() {
+        assert_eq!(
+            format_entry("10:00:00 UTC", LogLevel::Warn, "fetch", &[("source", "zmx"), ("error", "Connection \"refused\"")]),
+            "10:00:00 UTC level=warn event=fetch source=zmx error=\"Connection \\\"refused\\\"\""
+        );
+    }
+
+    #[test]
+    fn filters_by_level() {
+        let mut logger = Logger::new(LogLevel::Info, None).unwrap();
+        logger.log(LogLevel::Debug, "fetch", &[]);
+        logger.log(LogLevel::Error, "fetch", &[]);
+        assert_eq!(logger.recent.len(), 1);
+        assert_eq!(logger.recent[0].0, LogLevel::Error);
+        assert_eq!("DEBUG".parse::<LogLevel>(), Ok(LogLevel::Debug));
+        assert!("verbose".parse::<LogLevel>().is_err());
+    }
+}
@@ -2,8 +2,43 @@ mod ui;
 mod zio;
 mod jmx;
 mod akka;
+mod api;
+mod capabilities;
+mod check;
+mod clock;
+mod redis;
+mod consul;
+mod gclog;
+mod history;
+mod prometheus;
+mod zookeeper;
 mod app;
+mod config;
+mod connect;
+mod credentials;
+mod dashboard;
+mod diagnostics;
 mod fetcher;
+#[cfg(all(test, feature = "test-harness"))]
+mod harness;
+mod http;
+mod incident;
+mod keymap;
+mod logging;
+mod notify;
+mod paste;
+mod plot;
+mod port_forward;
+mod process;
+mod quick_actions;
+mod redraw;
+mod scheduler;
+mod script;
+mod scrub;
+mod series;
+mod session;
+mod source;
+mod tail;
 mod widgets;
 
 use std::{
@@ -15,7 +50,7 @@ use std::{
 };
 
 use crossterm::{
-    event::{self, Event as CEvent, KeyCode},
+    event::{self, Event as CEvent, KeyCode, KeyEvent, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -25,16 +60,49 @@ use tui::{
     Terminal,
 };
 
-use crate::app::{App, TabKind};
-use crate::fetcher::{Fetcher, FetcherRequest, FetcherResponse};
+use crate::api::ApiServer;
+use crate::app::{App, JmxAction, TabKind, ZMXTab};
+use crate::check::{CheckResult, CheckStatus};
+use crate::clock::ClockSkew;
+use crate::fetcher::{Fetcher, FetcherRequest, FetcherResponse, FetcherSettings};
+use crate::redraw::Redraw;
+use crate::scheduler::Scheduler;
+use crate::session::Recorder;
+use crate::source::DataSource;
 
 use crate::akka::model::AkkaSettings;
+use crate::akka::restarts::RestartCounts;
+use crate::config::{Config, Profile, ProxySettings};
+use crate::connect::Connections;
+use crate::credentials::Credentials;
 use crate::jmx::model::JMXConnectionSettings;
+use crate::logging::{Logger, LogLevel};
+use crate::prometheus::explorer::PinnedMetrics;
+use crate::prometheus::model::PrometheusTarget;
+use crate::notify::Notifier;
+use crate::port_forward::{ForwardedSource, PortForward, PortForwards};
+use crate::script::hooks::Hooks;
+use crate::zookeeper::model::ZkNodeState;
+use crate::zookeeper::report::ZkOutput;
 
 enum Event<I> {
     Input(I),
+    Resize,
     Tick,
-    FetcherResponse(FetcherResponse),
+    /// Response along with when it was captured and the time it took to fetch
+    FetcherResponse(FetcherResponse, Instant, Duration),
+    /// Response for the compared environment with the given index, along with when it was captured
+    CompareResponse(usize, FetcherResponse, Instant),
+    /// Link to the content with the given name uploaded to the paste service
+    Pasted(String, Result<String, String>),
+    /// Incident summary posted to the webhook
+    IncidentPosted(Result<(), String>),
+    /// IP addresses a host of an unreachable source resolved to
+    Resolved(String, Result<Vec<String>, String>),
+    /// A port-forward started or dropped
+    PortForward(String),
+    /// Built-in action of a quick action, other than a key press
+    QuickAction(quick_actions::Step),
 }
 
 /// At least one of the following option sets has to be specified for panopticon-tui to launch:
@@ -44,13 +112,37 @@ enum Event<I> {
 /// - jmx + db-pool-name
 ///
 /// - actor-tree + actor-count
+///
+/// - redis
+///
+/// - consul
+///
+/// - gc-log
+///
+/// - zookeeper
+///
+/// - prometheus targets in the config file
+///
+/// - commands in the config file
+///
+/// - pulsar in the config file
+///
+/// - tcp_probe in the config file
+///
+/// - local_jvm in the config file
+///
+/// - compare + profiles in the config file
 #[derive(Debug, StructOpt)]
 struct Cli {
     /// Frequency (in ms) to use for fetching metrics.
     /// Don't set this too low, because currently zmx tab does a full fiber dump every tick
     #[structopt(long = "tick-rate", default_value = "2000")]
     tick_rate: u64,
-    /// Address of zio-zmx server, e.g. localhost:6789
+    /// Longest time (in ms) between two redraws of the terminal when nothing has changed,
+    /// keeping the stale indicators and other durations up to date
+    #[structopt(long = "redraw-interval", default_value = "5000")]
+    redraw_interval: u64,
+    /// Address of zio-zmx server, e.g. localhost:6789, or its Unix domain socket, e.g. unix:///var/run/zmx.sock
     #[structopt(long = "zio-zmx")]
     zio_zmx: Option<String>,
     /// Address of remote jmx source, e.g. localhost:9010
@@ -62,6 +154,11 @@ struct Cli {
     /// Optional password for authorized jmx access
     #[structopt(long = "jmx-password")]
     jmx_password: Option<String>,
+    /// Profile to read jmx credentials and the Zookeeper keystore password for from the OS keyring,
+    /// stored with `panopticon-tui login <profile>` (`--zookeeper` for the keystore password).
+    /// Without it, they're read from PANOPTICON_JMX_USERNAME, PANOPTICON_JMX_PASSWORD and PANOPTICON_ZOOKEEPER_KEYSTORE_PASSWORD
+    #[structopt(long = "profile")]
+    profile: Option<String>,
     /// Connection pool name, used to qualify JMX beans for Slick and/or HikariCP
     #[structopt(long = "db-pool-name")]
     db_pool_name: Option<String>,
@@ -71,9 +168,101 @@ struct Cli {
     /// Address of http endpoint to get current actor count
     #[structopt(long = "actor-count")]
     actor_count: Option<String>,
+    /// Optional address of Akka Management, e.g. http://localhost:8558, to show health checks in the Akka tab
+    #[structopt(long = "akka-management")]
+    akka_management: Option<String>,
+    /// Address of redis server, e.g. localhost:6379
+    #[structopt(long = "redis")]
+    redis: Option<String>,
+    /// Address of consul agent's HTTP API, e.g. http://localhost:8500
+    #[structopt(long = "consul")]
+    consul: Option<String>,
+    /// Addresses of Zookeeper ensemble nodes, e.g. zk1:2181,zk2:2181,zk3:2181, probed with `ruok` and `isro`
+    #[structopt(long = "zookeeper", use_delimiter = true)]
+    zookeeper: Vec<String>,
+    /// Monitor every member of the ensemble listed in the `conf` of the --zookeeper nodes (Zookeeper 3.5+),
+    /// following members as they're added or removed, so that a single node can be given
+    #[structopt(long = "zookeeper-discover")]
+    zookeeper_discover: bool,
+    /// Path to a JVM GC log in the unified format (`-Xlog:gc:file=gc.log`), tailed to chart GC pauses
+    #[structopt(long = "gc-log")]
+    gc_log: Option<String>,
+    /// Time (in seconds) fiber dumps are polled back to back for after pressing `b` on the ZIO tab
+    #[structopt(long = "burst-duration", default_value = "10")]
+    burst_duration: u64,
     /// Time period (in ms) to assemble akka actor tree
     #[structopt(long = "actor-tree-timeout", default_value = "1000")]
     actor_tree_timeout: u64,
+    /// Number of threads polling the sources, each source is always polled by the same thread
+    #[structopt(long = "fetch-workers", default_value = "4")]
+    fetch_workers: usize,
+    /// Time window (in minutes) over which the percentage of successful polls is shown for each source
+    #[structopt(long = "availability-window", default_value = "15")]
+    availability_window: u64,
+    /// Path to a file with hooks (Rhai scripts) evaluated on each new sample, one per line, e.g.
+    /// `alert too many suspended: fibers_suspended / fibers_running > 3`
+    #[structopt(long = "script")]
+    script: Option<String>,
+    /// Ring the terminal bell when an alert of the script starts firing
+    #[structopt(long = "bell")]
+    bell: bool,
+    /// Show a desktop notification when an alert of the script starts firing
+    #[structopt(long = "notify")]
+    notify: bool,
+    /// Path to a JSON file the metrics pinned in the metric explorer of the Prometheus tab are saved to,
+    /// per `--profile`
+    #[structopt(long = "pins", default_value = "panopticon-pins.json")]
+    pins: String,
+    /// Path to a JSON config file, e.g. with a watchlist of JMX attributes to chart on the Custom tab
+    #[structopt(long = "config")]
+    config: Option<String>,
+    /// Two profiles of the config file to chart side by side on the Compare tab, e.g. `--compare prod staging`
+    #[structopt(long = "compare", number_of_values = 2)]
+    compare: Vec<String>,
+    /// Port to serve the current state as JSON over HTTP on, e.g. `curl localhost:8090/state`
+    #[structopt(long = "api-port")]
+    api_port: Option<u16>,
+    /// Address the API server listens on, use 0.0.0.0 to make it reachable from other hosts
+    #[structopt(long = "api-host", default_value = "127.0.0.1")]
+    api_host: String,
+    /// Path to a session file to append a sample of all metrics to on each tick, see the analyze command
+    #[structopt(long = "record")]
+    record: Option<String>,
+    /// Path to a file to append structured logs to, e.g. fetch failures
+    #[structopt(long = "log-file")]
+    log_file: Option<String>,
+    /// Minimal level of logged events: error, warn, info or debug
+    #[structopt(long = "log-level", default_value = "info")]
+    log_level: LogLevel,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Stores jmx credentials for the profile in the OS keyring
+    Login {
+        profile: String,
+        /// Stores the password of the Zookeeper client keystore (`zookeeper_tls`) instead
+        #[structopt(long = "zookeeper")]
+        zookeeper: bool,
+    },
+    /// Prints summary statistics of a session recorded with --record, without launching the UI
+    Analyze {
+        session: String,
+    },
+    /// Polls the configured sources once, prints the status of the thresholds in the config file and exits
+    /// with 0 (OK), 1 (WARN) or 2 (CRIT), e.g. to be used as a Nagios check
+    Check,
+    /// Probes the --zookeeper nodes once, prints their status and exits with 1 if one of them doesn't respond
+    ZkStatus {
+        /// Format of the status: table, json or prometheus
+        #[structopt(long = "output", default_value = "table")]
+        output: ZkOutput,
+        /// Prints the table without colors, as when NO_COLOR is set
+        #[structopt(long = "no-color")]
+        no_color: bool,
+    },
 }
 
 impl Cli {
@@ -89,6 +278,16 @@ impl Cli {
         }
     }
 
+    /// Metrics charted on the Prometheus tab: the ones of the config file and the ones pinned for the profile
+    fn prometheus_metrics(&self, targets: &[PrometheusTarget]) -> Result<Option<(Vec<String>, PinnedMetrics)>, String> {
+        if targets.is_empty() {
+            return Ok(None);
+        }
+        let metrics = targets.iter().flat_map(|t| t.metrics.iter().cloned()).collect();
+        let pinned = PinnedMetrics::load(&self.pins, self.profile.as_deref().unwrap_or("default"))?;
+        Ok(Some((metrics, pinned)))
+    }
+
     fn akka_settings(&self) -> Option<AkkaSettings> {
         match (&self.actor_tree, &self.actor_count) {
             (Some(tree_addr), Some(count_addr)) => Some(AkkaSettings {
@@ -96,27 +295,247 @@ impl Cli {
                 tree_timeout: self.actor_tree_timeout,
                 count_address: count_addr.to_owned(),
                 count_timeout: (self.tick_rate as f64 * 0.8) as u64,
+                management_address: self.akka_management.clone(),
             }),
             _ => None
         }
     }
+
+    /// Sets the addresses of the sources connected through port-forwards, unless they're given on the command line
+    fn forward_sources(&mut self, forwards: &[PortForward]) {
+        let has_zookeeper = !self.zookeeper.is_empty();
+        for f in forwards {
+            let address = f.local_address();
+            match f.source {
+                Some(ForwardedSource::ZioZmx) if self.zio_zmx.is_none() => self.zio_zmx = Some(address),
+                Some(ForwardedSource::Jmx) if self.jmx.is_none() => self.jmx = Some(address),
+                Some(ForwardedSource::Redis) if self.redis.is_none() => self.redis = Some(address),
+                Some(ForwardedSource::Consul) if self.consul.is_none() => self.consul = Some(format!("http://{}", address)),
+                Some(ForwardedSource::Zookeeper) if !has_zookeeper => self.zookeeper.push(address),
+                _ => {}
+            }
+        }
+    }
+
+    /// Configured addresses of each network source, shown on the diagnostics screen when they can't be reached
+    fn source_addresses(&self, config: &Config) -> Vec<(&'static str, Vec<String>)> {
+        let akka = self.akka_settings().map_or(vec![], |s| {
+            let mut addresses = vec![s.tree_address, s.count_address];
+            addresses.extend(s.management_address);
+            addresses.dedup();
+            addresses
+        });
+        vec![
+            ("zmx", self.zio_zmx.iter().cloned().collect()),
+            ("jmx", self.jmx.iter().cloned().collect()),
+            ("akka", akka),
+            ("redis", self.redis.iter().cloned().collect()),
+            ("consul", self.consul.iter().cloned().collect()),
+            ("prometheus", config.prometheus.iter().map(|t| t.url.to_owned()).collect()),
+            ("Pulsar", config.pulsar.iter().map(|p| p.url.to_owned()).collect()),
+        ]
+    }
+
+    /// Sources whose clock can be read, Zookeeper nodes only telling the time through their AdminServer
+    fn clock_sources(&self, config: &Config) -> Vec<&'static str> {
+        let configured = [
+            ("jmx", self.jmx_settings().is_some()),
+            ("akka", self.akka_settings().is_some()),
+            ("consul", self.consul.is_some()),
+            ("prometheus", !config.prometheus.is_empty()),
+            ("zookeeper", !self.zookeeper.is_empty() && config.zookeeper_admin.is_some()),
+        ];
+        configured.iter().filter(|(_, on)| *on).map(|(source, _)| *source).collect()
+    }
 }
 
 fn main() -> Result<(), failure::Error> {
-    let cli = Cli::from_args();
+    let mut cli = Cli::from_args();
+
+    if let Some(Command::Login { profile, zookeeper }) = &cli.command {
+        if let Err(e) = credentials::login(profile, *zookeeper) {
+            println!("{}", e);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Analyze { session: path }) = &cli.command {
+        match session::analyze(path) {
+            Ok(report) => println!("{}", report),
+            Err(e) => println!("{}", e),
+        }
+        return Ok(());
+    }
 
-    // disable jmx crate logging
+    // disable j4rs logging of the embedded JVM used for JMX
     env::set_var("J4RS_CONSOLE_LOG_LEVEL", "disabled");
 
-    if cli.zio_zmx.is_none() && cli.jmx_settings().is_none() && cli.akka_settings().is_none() {
+    let mut config = match &cli.config {
+        Some(path) => match Config::load(path) {
+            Ok(c) => c,
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        },
+        None => Config::default(),
+    };
+    cli.forward_sources(&config.port_forwards);
+
+    if cli.jmx.is_some() && cli.jmx_username.is_none() {
+        match Credentials::resolve(cli.profile.as_deref()) {
+            Ok(Some(c)) => {
+                cli.jmx_username = Some(c.username);
+                cli.jmx_password = Some(c.password);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        }
+    }
+    if let Some(tls) = config.zookeeper_tls.as_mut().filter(|t| t.keystore.is_some()) {
+        tls.keystore_password = credentials::zookeeper_keystore_password(cli.profile.as_deref());
+    }
+
+    if let Some(s) = config.sharding.as_mut() {
+        if s.nodes.is_empty() {
+            match &cli.akka_management {
+                Some(address) => s.nodes.push(address.to_owned()),
+                None => {
+                    println!("Cluster sharding needs --akka-management or nodes in the config file");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    if let Some(p) = cli.compare.iter().find(|p| !config.profiles.contains_key(*p)) {
+        println!("Profile {} to compare isn't defined in the config file", p);
+        return Ok(());
+    }
+
+    if cli.zio_zmx.is_none() && cli.jmx_settings().is_none() && cli.akka_settings().is_none() && cli.redis.is_none() && cli.consul.is_none() &&
+        cli.zookeeper.is_empty() && cli.gc_log.is_none() && config.prometheus.is_empty() && config.commands.is_empty() && config.pulsar.is_none() && config.tcp_probe.is_none() &&
+        config.local_jvm.is_none() && config.sources.is_empty() && cli.compare.is_empty() {
         let mut clap = Cli::clap();
         println!("Nothing to monitor. Please check the following help message.\n");
         clap.print_long_help().expect("Failed printing help message");
         return Ok(());
     }
 
+    let hooks = match &cli.script {
+        Some(path) => match Hooks::load(path) {
+            Ok(h) => Some(h),
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let notifier = Notifier { bell: cli.bell, desktop: cli.notify };
+    if notifier.is_enabled() && hooks.is_none() {
+        println!("--bell and --notify need alerts defined in a --script");
+        return Ok(());
+    }
+
+    let mut logger = match Logger::new(cli.log_level, cli.log_file.as_deref()) {
+        Ok(l) => l,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    logger.log(LogLevel::Info, "start", &[("tick_rate", &cli.tick_rate.to_string())]);
+
+    // channel for main app event loop
+    let (tx, rx) = mpsc::channel();
+
+    // the sources connect through the port-forwards, which are killed once dropped
+    let port_forwards = {
+        let tx = tx.clone();
+        PortForwards::start(config.port_forwards.clone(), move |m| {
+            let _ = tx.send(Event::PortForward(m));
+        })
+    };
+    port_forward::wait_until_listening(&config.port_forwards, Duration::from_secs(10));
+
+    if let Some(Command::Check) = &cli.command {
+        let status = run_check(&cli, config, logger);
+        drop(port_forwards);
+        std::process::exit(status);
+    }
+
+    if let Some(Command::ZkStatus { output, no_color }) = &cli.command {
+        let color = zookeeper::report::use_color(*no_color, env::var("NO_COLOR").ok().as_deref());
+        let status = run_zk_status(&cli, &config, *output, color);
+        drop(port_forwards);
+        std::process::exit(status);
+    }
+
+    let mut recorder = match &cli.record {
+        Some(path) => match Recorder::create(path) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let api = match cli.api_port {
+        Some(port) => match ApiServer::start(&cli.api_host, port) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                println!("{}", e);
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    let prometheus = match cli.prometheus_metrics(&config.prometheus) {
+        Ok(p) => p,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+
+    let quick_actions = match quick_actions::bindings(&config.quick_actions) {
+        Ok(b) => b,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+
     let tick_rate = Duration::from_millis(cli.tick_rate);
+    let paste = config.paste.clone();
+    let incident = config.incident.clone();
+    // what a tab connected in the command line is set up with
+    let list_columns = config.columns.clone();
+    let fiber_label = config.fiber_label.clone();
+    let retention = config.retention;
+    let fiber_limit = config.fiber_limit.unwrap_or(ZMXTab::DEFAULT_FIBER_LIMIT);
+    let availability_window = Duration::from_secs(cli.availability_window * 60);
+    let connections = Connections::default();
     let has_jmx = cli.jmx_settings().is_some();
+    let has_statements = config.statements.is_some();
+    let has_pool_errors = config.pool_errors.is_some();
+    let has_kafka_brokers = config.zookeeper_kafka.is_some();
+    let sources = match source::registered(&config) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("{}", e);
+            return Ok(());
+        }
+    };
+    let source_tabs: Vec<Box<dyn DataSource>> = sources.iter().map(|build| build()).collect();
 
     enable_raw_mode()?;
 
@@ -132,72 +551,147 @@ fn main() -> Result<(), failure::Error> {
         "PANOPTICON-TUI",
         cli.zio_zmx.clone(),
         cli.jmx_settings(),
+        config.watchlist.clone(),
+        config.columns.clone(),
+        config.fiber_label.clone(),
         cli.akka_settings(),
+        config.sharding.is_some(),
+        config.actor_watches.clone(),
+        cli.redis.clone(),
+        cli.consul.clone(),
+        cli.zookeeper.clone(),
+        cli.gc_log.is_some(),
+        prometheus,
+        &source_tabs,
+        cli.compare.clone(),
+        config.derived.clone(),
+        config.retention,
+        config.labels.clone(),
+        hooks,
+        logger,
+        availability_window,
+        tick_rate,
     );
+    app.diagnostics = diagnostics::Diagnostics::new(cli.source_addresses(&config));
+    app.scrub = scrub::Scrubber::new(config.scrub.clone());
+    if let Some(d) = config.dashboard.clone() {
+        app.show_dashboard(d);
+    }
+    if let Some(v) = config.startup_view(cli.profile.as_deref()) {
+        app.open_view(v);
+    }
+    if let Some(s) = app.slick.as_mut() {
+        s.has_pool_errors = has_pool_errors;
+    }
+    if let Some(z) = app.zmx.as_mut() {
+        z.fiber_limit = fiber_limit;
+    }
+    app.clock = ClockSkew::new(cli.clock_sources(&config), config.max_clock_skew_ms.unwrap_or(ClockSkew::DEFAULT_THRESHOLD));
+    if let Some(t) = app.actor_tree.as_mut().filter(|_| config.actor_restarts.is_some()) {
+        t.restarts = Some(RestartCounts::default());
+    }
 
     terminal.clear()?;
 
-    // channel for main app event loop
-    let (tx, rx) = mpsc::channel();
-
     // Setup fetcher interaction
-    let (txf, rxf) = mpsc::channel();
-    {
+    let mut scheduler = {
         let tx = tx.clone();
-        thread::spawn(move || {
-            let respond = |r| tx.send(Event::FetcherResponse(r)).unwrap();
-
-            match Fetcher::new(cli.zio_zmx.clone(),
-                               cli.jmx_settings(),
-                               cli.akka_settings()) {
-                Err(e) => {
-                    eprintln!("Responding with failure {}", e);
-                    loop {
-                        rxf.recv().unwrap();
-                        respond(FetcherResponse::FatalFailure(e.to_owned()))
-                    }
-                }
-                Ok(fetcher) =>
-                    loop {
-                        match rxf.recv().unwrap() {
-                            FetcherRequest::FiberDump =>
-                                respond(FetcherResponse::FiberDump(fetcher.dump_fibers())),
-                            FetcherRequest::RegularFiberDump =>
-                                respond(FetcherResponse::RegularFiberDump(fetcher.dump_fibers())),
-                            FetcherRequest::HikariMetrics =>
-                                respond(FetcherResponse::HikariMetrics(fetcher.get_hikari_metrics())),
-                            FetcherRequest::SlickMetrics =>
-                                respond(FetcherResponse::SlickMetrics(fetcher.get_slick_metrics())),
-                            FetcherRequest::SlickConfig =>
-                                respond(FetcherResponse::SlickConfig(fetcher.get_slick_config())),
-                            FetcherRequest::ActorTree =>
-                                respond(FetcherResponse::ActorTree(fetcher.get_actor_tree())),
-                            FetcherRequest::ActorCount =>
-                                respond(FetcherResponse::ActorCount(fetcher.get_actor_count())),
-                        }
-                    }
-            }
-        });
-    }
+        let respond = move |r, at, latency| tx.send(Event::FetcherResponse(r, at, latency)).unwrap();
+
+        let zio_zmx = cli.zio_zmx.clone();
+        let jmx = cli.jmx_settings();
+        let akka = cli.akka_settings();
+        let redis = cli.redis.clone();
+        let consul = cli.consul.clone();
+        let gc_log = cli.gc_log.clone();
+        let zookeeper = cli.zookeeper.clone();
+        let zookeeper_tls = config.zookeeper_tls.clone();
+        let zookeeper_admin = config.zookeeper_admin.clone();
+        let zookeeper_ssh = config.zookeeper_ssh.clone();
+        let zookeeper_kafka = config.zookeeper_kafka.clone();
+        let zookeeper_discover = cli.zookeeper_discover;
+        let prometheus = config.prometheus;
+        let watchlist = config.watchlist;
+        let statements = config.statements;
+        let pool_errors = config.pool_errors;
+        let sharding = config.sharding;
+        let actor_restarts = config.actor_restarts.clone();
+        let proxies = config.proxies.clone();
+        let connections = connections.clone();
+        let timeout = (cli.tick_rate as f64 * 0.8) as u64;
+
+        let mut workers: Vec<String> = vec![
+            ("zmx", zio_zmx.is_some()),
+            ("jmx", jmx.is_some()),
+            ("akka", akka.is_some()),
+            ("redis", redis.is_some()),
+            ("consul", consul.is_some()),
+            ("gclog", gc_log.is_some()),
+            ("prometheus", !prometheus.is_empty()),
+            ("zookeeper", !zookeeper.is_empty()),
+        ].into_iter().filter(|(_, on)| *on).map(|(s, _)| s.to_owned()).collect();
+        // each data source has a worker key of its own, so a slow one only holds up the sources sharing its worker
+        workers.extend((0..sources.len()).map(|i| FetcherRequest::SourceSamples(i).worker()));
+
+        let connect = move |assigned: &[String]| {
+            let has = |s: &str| assigned.iter().any(|a| a == s);
+            Ok(Fetcher::new(FetcherSettings {
+                zio_zmx_addr: zio_zmx.clone().or_else(|| connections.address("zmx")).filter(|_| has("zmx")),
+                jmx: jmx.clone().filter(|_| has("jmx")),
+                watchlist: watchlist.clone(),
+                statements: statements.clone(),
+                pool_errors: pool_errors.clone(),
+                akka: akka.clone().filter(|_| has("akka")),
+                sharding: sharding.clone().filter(|_| has("akka")),
+                actor_restarts: actor_restarts.clone().filter(|_| has("akka")),
+                redis_addr: redis.clone().or_else(|| connections.address("redis")).filter(|_| has("redis")),
+                consul_addr: consul.clone().or_else(|| connections.address("consul")).filter(|_| has("consul")),
+                gc_log_path: gc_log.clone().filter(|_| has("gclog")),
+                prometheus_targets: if has("prometheus") { prometheus.clone() } else { vec![] },
+                zookeeper_nodes: if has("zookeeper") { zookeeper.clone() } else { vec![] },
+                zookeeper_tls: zookeeper_tls.clone().filter(|_| has("zookeeper")),
+                zookeeper_admin: zookeeper_admin.clone().filter(|_| has("zookeeper")),
+                zookeeper_ssh: zookeeper_ssh.clone().filter(|_| has("zookeeper")),
+                zookeeper_kafka: zookeeper_kafka.clone().filter(|_| has("zookeeper")),
+                zookeeper_discover,
+                sources: sources.iter()
+                    .enumerate()
+                    .map(|(i, build)| if has(&FetcherRequest::SourceSamples(i).worker()) { Some(build()) } else { None })
+                    .collect(),
+                proxies: proxies.clone(),
+                http_timeout: timeout,
+            }))
+        };
+        Scheduler::start(workers, cli.fetch_workers, tick_rate, connect, respond)
+    };
+
+    // each compared environment has its own workers, their responses are tagged with the environment's index
+    let profiles = &config.profiles;
+    let proxies = &config.proxies;
+    let compare_schedulers: Vec<(Scheduler, Vec<FetcherRequest>)> = cli.compare.iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let tx = tx.clone();
+            let respond = move |r, at, _| tx.send(Event::CompareResponse(i, r, at)).unwrap();
+            compare_scheduler(name, &profiles[name], proxies, &cli, respond)
+        })
+        .collect();
+
+    send_startup_requests(&scheduler, &app, has_jmx)?;
 
     // Setup input handling
     {
         let tx = tx.clone();
-        let txf = txf.clone();
         thread::spawn(move || {
             let mut last_tick = Instant::now();
 
-            if has_jmx {
-                txf.send(FetcherRequest::SlickConfig).unwrap();
-                txf.send(FetcherRequest::HikariMetrics).unwrap();
-                txf.send(FetcherRequest::SlickMetrics).unwrap();
-            }
-
             loop {
                 // poll for tick rate duration, if no events, sent tick event.
                 if event::poll(tick_rate - last_tick.elapsed()).unwrap() {
-                    if let CEvent::Key(key) = event::read().unwrap() {
-                        tx.send(Event::Input(key)).unwrap();
+                    match event::read().unwrap() {
+                        CEvent::Key(key) => tx.send(Event::Input(key)).unwrap(),
+                        CEvent::Resize(_, _) => tx.send(Event::Resize).unwrap(),
+                        _ => {}
                     }
                 }
                 if last_tick.elapsed() >= tick_rate {
@@ -208,16 +702,107 @@ fn main() -> Result<(), failure::Error> {
         });
     }
 
+    let mut redraw = Redraw::new(Duration::from_millis(cli.redraw_interval), tick_rate);
+    let mut pending = None;
     loop {
-        ui::draw(&mut terminal, &mut app)?;
-        match rx.recv()? {
+        if redraw.is_due(Instant::now(), pending.is_some()) {
+            ui::draw(&mut terminal, &mut app)?;
+            redraw.drawn(Instant::now());
+        }
+        let event = match pending.take() {
+            Some(e) => e,
+            None => rx.recv()?,
+        };
+        redraw.on_event(matches!(event, Event::Tick));
+        match event {
+            Event::Resize => {}
             Event::Input(event) => match event.code {
+                KeyCode::Char(c) if app.is_typing_command_line() => app.on_command_line_input(c),
+                KeyCode::Backspace if app.is_typing_command_line() => app.on_command_line_backspace(),
+                KeyCode::Esc if app.is_typing_command_line() => app.close_command_line(),
+                // the workers are built again, connecting to the new source along with the others
+                KeyCode::Enter if app.is_typing_command_line() => {
+                    if let Some(connection) = app.submit_command_line() {
+                        connections.add(connection.clone());
+                        scheduler.add_source(connection.source());
+                        send_startup_requests(&scheduler, &app, has_jmx)?;
+                        app.connect(&connection, &list_columns, fiber_label.clone(), retention, availability_window);
+                        if let Some(z) = app.zmx.as_mut() {
+                            z.fiber_limit = fiber_limit;
+                        }
+                    }
+                }
+                KeyCode::Char(c) if app.is_searching() => app.prometheus.as_mut().unwrap().on_search_input(c),
+                KeyCode::Backspace if app.is_searching() => app.prometheus.as_mut().unwrap().on_search_backspace(),
+                KeyCode::Esc if app.is_searching() => app.prometheus.as_mut().unwrap().close_search(),
+                KeyCode::Char(c) if app.is_typing_fiber_id() => app.zmx.as_mut().unwrap().on_fiber_prompt_input(c),
+                KeyCode::Backspace if app.is_typing_fiber_id() => app.zmx.as_mut().unwrap().on_fiber_prompt_backspace(),
+                KeyCode::Esc if app.is_typing_fiber_id() => app.zmx.as_mut().unwrap().close_fiber_prompt(),
+                KeyCode::Enter if app.is_typing_fiber_id() => app.jump_to_typed_fiber(),
+                KeyCode::Char(c) if app.is_typing_command() => app.zookeeper.as_mut().unwrap().console.on_input(c),
+                KeyCode::Backspace if app.is_typing_command() => app.zookeeper.as_mut().unwrap().console.on_backspace(),
+                KeyCode::Esc if app.is_typing_command() => app.zookeeper.as_mut().unwrap().console.close_prompt(),
+                KeyCode::Enter if app.is_typing_command() => {
+                    if let Some((node, command)) = app.submit_command() {
+                        scheduler.send(FetcherRequest::ZookeeperCommand(node, command))?;
+                    }
+                }
+                KeyCode::Char('y') if app.pending_action.is_some() => match app.confirm_action() {
+                    Some(JmxAction::HeapDump) => scheduler.send(FetcherRequest::HeapDump)?,
+                    Some(JmxAction::ThreadDump) => scheduler.send(FetcherRequest::ThreadDump)?,
+                    None => {}
+                },
+                _ if app.pending_action.is_some() => app.cancel_action(),
+                KeyCode::Char('r') if app.diagnostics.is_shown() => {
+                    if app.retry_sources() {
+                        scheduler.reconnect();
+                        send_startup_requests(&scheduler, &app, has_jmx)?;
+                    }
+                }
+                KeyCode::Esc if app.diagnostics.is_shown() => app.diagnostics.dismissed = true,
                 KeyCode::Char('q') => {
                     disable_raw_mode()?;
                     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
                     terminal.show_cursor()?;
                     break;
                 }
+                KeyCode::Char('b') if matches!(app.tabs.current().kind, TabKind::ZMX) => {
+                    if app.start_burst(Duration::from_secs(cli.burst_duration)) {
+                        scheduler.send(FetcherRequest::BurstFiberDump)?;
+                    }
+                }
+                // uploads run on their own thread, they can take longer than a tick
+                KeyCode::Char('u') => {
+                    if let Some((name, contents)) = app.start_paste(paste.as_ref()) {
+                        let tx = tx.clone();
+                        let settings = paste.clone().unwrap();
+                        let proxy = proxies.paste.clone();
+                        thread::spawn(move || {
+                            let link = paste::upload(&settings, &name, contents, proxy.as_ref());
+                            tx.send(Event::Pasted(name, link)).unwrap();
+                        });
+                    }
+                }
+                KeyCode::Char('I') => {
+                    if let Some(summary) = app.start_incident_summary(incident.as_ref()) {
+                        let tx = tx.clone();
+                        let settings = incident.clone().unwrap();
+                        let proxy = proxies.incident.clone();
+                        thread::spawn(move || {
+                            let posted = incident::post(&settings, &summary, proxy.as_ref());
+                            tx.send(Event::IncidentPosted(posted)).unwrap();
+                        });
+                    }
+                }
+                // steps are queued behind the events already received, keys of a step going through this match again
+                KeyCode::F(n) if quick_actions.contains_key(&n) => {
+                    for step in quick_actions[&n].iter().cloned() {
+                        match step {
+                            quick_actions::Step::Key(code) => tx.send(Event::Input(KeyEvent::new(code, KeyModifiers::NONE))).unwrap(),
+                            step => tx.send(Event::QuickAction(step)).unwrap(),
+                        }
+                    }
+                }
                 KeyCode::Char(c) => app.on_key(c),
                 KeyCode::Left => app.on_left(),
                 KeyCode::Up => app.on_up(),
@@ -225,84 +810,624 @@ fn main() -> Result<(), failure::Error> {
                 KeyCode::Down => app.on_down(),
                 KeyCode::PageUp => app.on_page_up(),
                 KeyCode::PageDown => app.on_page_down(),
+                KeyCode::Tab => app.on_tab(),
                 KeyCode::Enter => {
                     match app.tabs.current().kind {
-                        TabKind::ZMX => txf.send(FetcherRequest::FiberDump)?,
+                        TabKind::ZMX => scheduler.send(FetcherRequest::FiberDump)?,
                         TabKind::Slick => {}
-                        TabKind::AkkaActorTree => txf.send(FetcherRequest::ActorTree)?,
+                        TabKind::JVM => {}
+                        TabKind::Custom => {}
+                        TabKind::AkkaActorTree => scheduler.send(FetcherRequest::ActorTree)?,
+                        TabKind::Redis => {}
+                        TabKind::Consul => {}
+                        TabKind::GcLog => {}
+                        TabKind::Prometheus => app.toggle_selected_pin(),
+                        TabKind::Source(_) => {}
+                        TabKind::Compare => {}
+                        TabKind::Derived => {}
+                        TabKind::Hooks => {}
+                        TabKind::Zookeeper => {}
+                        TabKind::Latency => {}
+                        TabKind::Log => {}
+                        TabKind::Dashboard => {}
                     }
                 }
                 _ => {}
             },
-            Event::FetcherResponse(r) => match r {
-                FetcherResponse::FatalFailure(e) =>
-                    app.quit(Some(e)),
-
-                FetcherResponse::FiberDump(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.zmx.as_mut().unwrap().replace_fiber_dump(x),
-                    },
-                FetcherResponse::RegularFiberDump(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(x),
-                    },
-                FetcherResponse::HikariMetrics(d) =>
-                    match d {
-                        Err(_) => app.slick.as_mut().unwrap().has_hikari = false,
-                        Ok(x) => {
-                            app.slick.as_mut().unwrap().has_hikari = true;
-                            app.slick.as_mut().unwrap().append_hikari_metrics(x)
-                        }
-                    },
-                FetcherResponse::SlickMetrics(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.slick.as_mut().unwrap().append_slick_metrics(x)
-                    },
-                FetcherResponse::SlickConfig(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.slick.as_mut().unwrap().replace_slick_config(x)
-                    },
-                FetcherResponse::ActorTree(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.actor_tree.as_mut().unwrap().update_actor_tree(x)
-                    },
-                FetcherResponse::ActorCount(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.actor_tree.as_mut().unwrap().append_actor_count(x)
-                    },
+            Event::FetcherResponse(r, at, latency) => {
+                if !matches!(r, FetcherResponse::FatalFailure(_, _)) {
+                    app.latency.record(r.source(), latency, at);
+                }
+                // each dump of a burst capture is polled as soon as the previous one is saved
+                if let FetcherResponse::BurstFiberDump(d) = r {
+                    if app.on_burst_dump(d) {
+                        scheduler.send(FetcherRequest::BurstFiberDump)?;
+                    }
+                    continue;
+                }
+                apply_response(&mut app, r, at);
+                // DNS lookups can take seconds, they run on their own threads like uploads
+                for host in app.diagnostics.take_unresolved() {
+                    let tx = tx.clone();
+                    thread::spawn(move || {
+                        let addresses = diagnostics::resolve(&host);
+                        tx.send(Event::Resolved(host, addresses)).unwrap();
+                    });
+                }
+                let fired = app.run_hooks(at);
+                if let Err(e) = notifier.on_alerts(&app.title, &fired) {
+                    app.logger.log(LogLevel::Warn, "notify", &[("error", &e)]);
+                }
+            }
+
+            Event::CompareResponse(i, r, at) => {
+                let env = &mut app.compare.as_mut().unwrap().envs[i];
+                env.pending = env.pending.saturating_sub(1);
+                if let Some(e) = r.error() {
+                    app.logger.log(LogLevel::Warn, "fetch", &[("source", r.source()), ("profile", &env.name), ("error", e)]);
+                }
+                match r {
+                    FetcherResponse::RegularFiberDump(Ok(x)) => env.append_fibers_total(x.len(), at),
+                    FetcherResponse::HikariMetrics(Ok(x)) => env.append_hikari_active(x.active, at),
+                    FetcherResponse::ActorCount(Ok(x)) => env.append_actor_count(x, at),
+                    FetcherResponse::FatalFailure(_, e) => env.error = Some(e),
+                    _ => {}
+                }
+            }
+
+            Event::Pasted(name, link) => app.on_paste(&name, link),
+            Event::IncidentPosted(posted) => app.on_incident_posted(posted),
+            Event::Resolved(host, addresses) => app.diagnostics.on_resolved(host, addresses),
+            Event::PortForward(message) => {
+                app.logger.log(LogLevel::Info, "port-forward", &[("message", &message)]);
+                app.notice = Some(message);
             }
+            Event::QuickAction(step) => app.run_step(step),
 
             Event::Tick => {
-                if app.zmx.is_some() {
-                    txf.send(FetcherRequest::RegularFiberDump)?;
+                // the state is only serialized when a client asks for it
+                if let Some(api) = api.as_ref().filter(|a| a.is_requested()) {
+                    api.publish(app.state_json());
+                }
+                if let Some(r) = recorder.as_mut() {
+                    let sample = app.session_sample();
+                    // nothing to record until the first sample arrives
+                    if !sample.metrics.is_empty() {
+                        if let Err(e) = r.record(&sample) {
+                            app.logger.log(LogLevel::Warn, "record", &[("error", &e)]);
+                        }
+                    }
+                }
+
+                if let Some(zmx) = app.zmx.as_mut() {
+                    if zmx.backoff.due() {
+                        scheduler.poll(FetcherRequest::RegularFiberDump)?;
+                    }
+                }
+
+                // requests of a source are answered in order, so the Slick metrics request goes last
+                // to keep the JMX poll in flight until all of its requests are answered
+                let jmx_due = match app.slick.as_mut() {
+                    Some(s) => s.backoff.due(),
+                    None => false,
+                };
+                if jmx_due {
+                    if app.slick.as_ref().unwrap().has_hikari {
+                        scheduler.poll(FetcherRequest::HikariMetrics)?;
+                    }
+                    if app.jvm.is_some() {
+                        scheduler.poll(FetcherRequest::JVMAttributes)?;
+                        scheduler.poll(FetcherRequest::JVMRuntime)?;
+                    }
+                    if app.custom.is_some() {
+                        scheduler.poll(FetcherRequest::WatchedAttributes)?;
+                    }
+                    if has_statements {
+                        scheduler.poll(FetcherRequest::StatementStats)?;
+                    }
+                    if has_pool_errors {
+                        scheduler.poll(FetcherRequest::PoolErrors)?;
+                    }
+                    scheduler.poll(FetcherRequest::SlickMetrics)?;
                 }
 
-                match &app.slick {
-                    Some(s) => {
-                        txf.send(FetcherRequest::SlickMetrics)?;
-                        if s.has_hikari {
-                            txf.send(FetcherRequest::HikariMetrics)?;
+                if let Some(t) = app.actor_tree.as_mut() {
+                    if t.backoff.due() {
+                        if t.has_health_checks {
+                            scheduler.poll(FetcherRequest::AkkaHealth)?;
+                        }
+                        if t.has_sharding {
+                            scheduler.poll(FetcherRequest::ShardingStats)?;
+                        }
+                        if t.restarts.is_some() {
+                            scheduler.poll(FetcherRequest::ActorRestarts)?;
                         }
+                        // watched subtrees are counted on the tree, which is otherwise only assembled on demand
+                        if !t.watches.is_empty() {
+                            scheduler.poll(FetcherRequest::ActorTree)?;
+                        }
+                        scheduler.poll(FetcherRequest::ActorCount)?;
+                    }
+                }
+
+                if let Some(redis) = app.redis.as_mut() {
+                    if redis.backoff.due() {
+                        scheduler.poll(FetcherRequest::RedisStats)?;
+                    }
+                }
+
+                if let Some(consul) = app.consul.as_mut() {
+                    if consul.backoff.due() {
+                        scheduler.poll(FetcherRequest::ConsulServices)?;
+                    }
+                }
+
+                if let Some(gc_log) = app.gc_log.as_mut() {
+                    if gc_log.backoff.due() {
+                        scheduler.poll(FetcherRequest::GcPauses)?;
+                    }
+                }
+
+                if let Some(prometheus) = app.prometheus.as_mut() {
+                    if prometheus.backoff.due() {
+                        scheduler.poll(FetcherRequest::PrometheusSamples)?;
+                    }
+                }
+
+                for (i, source) in app.sources.iter_mut().enumerate() {
+                    if source.backoff.due() {
+                        scheduler.poll(FetcherRequest::SourceSamples(i))?;
+                    }
+                }
+
+                if let Some(zookeeper) = app.zookeeper.as_mut() {
+                    if zookeeper.backoff.due() {
+                        scheduler.poll(FetcherRequest::ZookeeperStatus)?;
+                        if has_kafka_brokers {
+                            scheduler.poll(FetcherRequest::KafkaBrokers)?;
+                        }
+                    }
+                }
+
+                if app.clock.due(Instant::now()) {
+                    for source in app.clock.sources.iter() {
+                        scheduler.poll(FetcherRequest::Clock(*source))?;
                     }
-                    None => {}
                 }
 
-                if app.actor_tree.is_some() {
-                    txf.send(FetcherRequest::ActorCount)?;
+                if let Some(compare) = app.compare.as_mut() {
+                    for (env, (scheduler, requests)) in compare.envs.iter_mut().zip(compare_schedulers.iter()) {
+                        if env.pending == 0 && env.error.is_none() {
+                            for request in requests.iter() {
+                                scheduler.poll(*request)?;
+                            }
+                            env.pending = requests.len();
+                        }
+                    }
                 }
             }
         }
         if app.should_quit {
             break;
         }
+        pending = rx.try_recv().ok();
     }
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
     app.exit_reason.map(|e| println!("{}", e));
     Ok(())
 }
+
+/// Requests sent once the fetchers are connected: panels relying on optional features are set up once the features are probed
+fn send_startup_requests(scheduler: &Scheduler, app: &App, has_jmx: bool) -> Result<(), failure::Error> {
+    if has_jmx {
+        scheduler.send(FetcherRequest::SlickConfig)?;
+        scheduler.send(FetcherRequest::Capabilities("jmx"))?;
+        scheduler.send(FetcherRequest::SlickMetrics)?;
+        scheduler.send(FetcherRequest::JVMAttributes)?;
+    }
+    if matches!(&app.actor_tree, Some(t) if t.has_health_checks || t.has_sharding) {
+        scheduler.send(FetcherRequest::Capabilities("akka"))?;
+    }
+    Ok(())
+}
+
+/// Logs a fetcher response and updates the app with it, its samples stamped with when it was captured
+fn apply_response(app: &mut App, r: FetcherResponse, at: Instant) {
+    match r.error() {
+        Some(e) => {
+            let level = match r {
+                FetcherResponse::FatalFailure(_, _) => LogLevel::Error,
+                _ => LogLevel::Warn,
+            };
+            app.logger.log(level, "fetch", &[("source", r.source()), ("error", e)])
+        }
+        None => app.logger.log(LogLevel::Debug, "fetch", &[("source", r.source()), ("result", "ok")]),
+    }
+    match r {
+        FetcherResponse::FatalFailure(source, e) =>
+            app.diagnostics.record(source, e, true),
+
+        FetcherResponse::FiberDump(d) =>
+            match d {
+                Err(e) => app.quit(Some(e)),
+                Ok(x) => app.zmx.as_mut().unwrap().replace_fiber_dump(x),
+            },
+        FetcherResponse::BurstFiberDump(d) => {
+            app.on_burst_dump(d);
+        }
+        FetcherResponse::RegularFiberDump(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::ZMX, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::ZMX);
+                    app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(x, at)
+                }
+            },
+        FetcherResponse::HikariMetrics(d) =>
+            match d {
+                Err(_) => app.slick.as_mut().unwrap().has_hikari = false,
+                Ok(x) => {
+                    app.slick.as_mut().unwrap().has_hikari = true;
+                    app.slick.as_mut().unwrap().append_hikari_metrics(x, at)
+                }
+            },
+        FetcherResponse::StatementStats(d) =>
+            match d {
+                Err(_) => app.slick.as_mut().unwrap().has_statements = false,
+                Ok(x) => {
+                    let slick = app.slick.as_mut().unwrap();
+                    if !x.is_empty() {
+                        slick.statements_missing = None;
+                    }
+                    slick.has_statements = true;
+                    slick.statements = x
+                }
+            },
+        FetcherResponse::PoolErrors(d) =>
+            match d {
+                Err(e) => app.slick.as_mut().unwrap().pool_errors_error = Some(e),
+                Ok(x) => app.slick.as_mut().unwrap().append_pool_error_counts(x, at),
+            },
+        FetcherResponse::SlickMetrics(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::Slick, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::Slick);
+                    app.slick.as_mut().unwrap().append_slick_metrics(x, at)
+                }
+            },
+        FetcherResponse::SlickConfig(d) =>
+            match d {
+                Err(e) => app.quit(Some(e)),
+                Ok(x) => app.slick.as_mut().unwrap().replace_slick_config(x)
+            },
+        FetcherResponse::JVMAttributes(d) =>
+            match d {
+                Err(e) => app.jvm.as_mut().unwrap().error = Some(e),
+                Ok(x) => app.jvm.as_mut().unwrap().replace_attributes(x)
+            },
+        FetcherResponse::JVMRuntime(d) =>
+            match d {
+                Err(e) => app.jvm.as_mut().unwrap().error = Some(e),
+                Ok(x) => app.jvm.as_mut().unwrap().append_runtime_metrics(x, at)
+            },
+        FetcherResponse::HeapDump(d) => app.on_heap_dump(d),
+        FetcherResponse::ThreadDump(d) => app.on_thread_dump(d),
+        FetcherResponse::WatchedAttributes(d) =>
+            match d {
+                Err(e) => app.custom.as_mut().unwrap().error = Some(e),
+                Ok(x) => app.custom.as_mut().unwrap().append_values(x, at)
+            },
+        FetcherResponse::ActorTree(d) =>
+            match d {
+                // the tree is polled for watches, a failure is logged and the actor count tracks availability
+                Err(_) if !app.actor_tree.as_ref().unwrap().watches.is_empty() => {}
+                Err(e) => app.quit(Some(e)),
+                Ok(x) => app.actor_tree.as_mut().unwrap().update_actor_tree(x, at)
+            },
+        FetcherResponse::ActorCount(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::AkkaActorTree, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::AkkaActorTree);
+                    app.actor_tree.as_mut().unwrap().append_actor_count(x, at)
+                }
+            },
+        FetcherResponse::ShardingStats(d) =>
+            match d {
+                Err(e) => app.actor_tree.as_mut().unwrap().sharding_error = Some(e),
+                Ok(x) => app.actor_tree.as_mut().unwrap().append_sharding(x),
+            },
+        FetcherResponse::AkkaHealth(h) =>
+            app.actor_tree.as_mut().unwrap().append_health(h, at),
+        FetcherResponse::ActorRestarts(d) =>
+            match d {
+                Err(e) => app.actor_tree.as_mut().unwrap().restarts_error = Some(e),
+                Ok(x) => app.actor_tree.as_mut().unwrap().record_restarts(&x, at),
+            },
+        FetcherResponse::RedisStats(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::Redis, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::Redis);
+                    app.redis.as_mut().unwrap().append_stats(x, at)
+                }
+            },
+        FetcherResponse::ConsulServices(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::Consul, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::Consul);
+                    app.consul.as_mut().unwrap().append_services(x, at)
+                }
+            },
+        FetcherResponse::GcPauses(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::GcLog, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::GcLog);
+                    app.gc_log.as_mut().unwrap().append_pauses(x, at)
+                }
+            },
+        FetcherResponse::PrometheusSamples(d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::Prometheus, e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::Prometheus);
+                    app.prometheus.as_mut().unwrap().append_samples(x, at)
+                }
+            },
+        FetcherResponse::SourceSamples(i, d) =>
+            match d {
+                Err(e) => app.on_poll_failure(TabKind::Source(i), e),
+                Ok(x) => {
+                    app.on_poll_success(TabKind::Source(i));
+                    app.sources[i].append_samples(x, at)
+                }
+            },
+        FetcherResponse::ZookeeperStatus(x) => {
+            let zookeeper = app.zookeeper.as_mut().unwrap();
+            if zookeeper.replace_nodes(x, at) {
+                let leader = zookeeper.leader().unwrap_or("none").to_owned();
+                app.logger.log(LogLevel::Warn, "zookeeper", &[("leader", &leader)]);
+            }
+        }
+        FetcherResponse::Capabilities(_, x) => app.record_capabilities(x),
+        FetcherResponse::Clock(source, Ok(ms)) => app.record_clock_skew(source, ms),
+        // a clock that can't be read is only logged
+        FetcherResponse::Clock(_, Err(_)) => {}
+        FetcherResponse::ZookeeperCommand(node, command, x) => {
+            let zookeeper = app.zookeeper.as_mut().unwrap();
+            let address = zookeeper.nodes.get(node).map_or("".to_owned(), |n| n.address.to_owned());
+            zookeeper.console.show_response(&address, command, x)
+        }
+        FetcherResponse::KafkaBrokers(x) => app.zookeeper.as_mut().unwrap().brokers = Some(x),
+    }
+}
+
+/// Polls every configured source once and evaluates the thresholds of the config, returning the exit code
+fn run_check(cli: &Cli, config: Config, logger: Logger) -> i32 {
+    let builders = match source::registered(&config) {
+        Ok(b) => b,
+        Err(e) => {
+            println!("{}", e);
+            return CheckStatus::Critical.exit_code();
+        }
+    };
+    let sources: Vec<Box<dyn DataSource>> = builders.iter().map(|build| build()).collect();
+    // Prometheus metrics aren't available to thresholds, so there's no need for pinned ones
+    let prometheus = if config.prometheus.is_empty() {
+        None
+    } else {
+        Some((vec![], PinnedMetrics { path: cli.pins.clone(), profile: String::new(), metrics: vec![] }))
+    };
+    let mut app = App::new(
+        "PANOPTICON-TUI",
+        cli.zio_zmx.clone(),
+        cli.jmx_settings(),
+        config.watchlist.clone(),
+        config.columns.clone(),
+        config.fiber_label.clone(),
+        cli.akka_settings(),
+        config.sharding.is_some(),
+        config.actor_watches.clone(),
+        cli.redis.clone(),
+        cli.consul.clone(),
+        cli.zookeeper.clone(),
+        cli.gc_log.is_some(),
+        prometheus,
+        &sources,
+        vec![],
+        config.derived.clone(),
+        config.retention,
+        config.labels.clone(),
+        None,
+        logger,
+        Duration::from_secs(cli.availability_window * 60),
+        Duration::from_millis(cli.tick_rate),
+    );
+    let fetcher = Fetcher::new(FetcherSettings {
+        zio_zmx_addr: cli.zio_zmx.clone(),
+        jmx: cli.jmx_settings(),
+        watchlist: config.watchlist.clone(),
+        statements: config.statements.clone(),
+        pool_errors: config.pool_errors.clone(),
+        akka: cli.akka_settings(),
+        sharding: config.sharding.clone(),
+        actor_restarts: config.actor_restarts.clone(),
+        redis_addr: cli.redis.clone(),
+        consul_addr: cli.consul.clone(),
+        gc_log_path: cli.gc_log.clone(),
+        prometheus_targets: config.prometheus.clone(),
+        zookeeper_nodes: cli.zookeeper.clone(),
+        zookeeper_tls: config.zookeeper_tls.clone(),
+        zookeeper_admin: config.zookeeper_admin.clone(),
+        zookeeper_ssh: config.zookeeper_ssh.clone(),
+        zookeeper_kafka: config.zookeeper_kafka.clone(),
+        zookeeper_discover: cli.zookeeper_discover,
+        sources: builders.iter().map(|build| Some(build())).collect(),
+        proxies: config.proxies.clone(),
+        http_timeout: (cli.tick_rate as f64 * 0.8) as u64,
+    });
+
+    let mut results = vec![];
+    let mut requests = vec![];
+    if app.zmx.is_some() {
+        requests.push(FetcherRequest::RegularFiberDump);
+    }
+    if app.slick.is_some() {
+        requests.push(FetcherRequest::SlickConfig);
+        requests.push(FetcherRequest::HikariMetrics);
+        requests.push(FetcherRequest::SlickMetrics);
+        requests.push(FetcherRequest::JVMRuntime);
+    }
+    if let Some(t) = &app.actor_tree {
+        if t.has_health_checks {
+            requests.push(FetcherRequest::AkkaHealth);
+        }
+        if !t.watches.is_empty() {
+            requests.push(FetcherRequest::ActorTree);
+        }
+        requests.push(FetcherRequest::ActorCount);
+    }
+    if app.redis.is_some() {
+        requests.push(FetcherRequest::RedisStats);
+    }
+    if app.consul.is_some() {
+        requests.push(FetcherRequest::ConsulServices);
+    }
+    if app.gc_log.is_some() {
+        requests.push(FetcherRequest::GcPauses);
+    }
+    if app.prometheus.is_some() {
+        requests.push(FetcherRequest::PrometheusSamples);
+    }
+    if app.zookeeper.is_some() {
+        requests.push(FetcherRequest::ZookeeperStatus);
+        if config.zookeeper_kafka.is_some() {
+            requests.push(FetcherRequest::KafkaBrokers);
+        }
+    }
+    requests.extend((0..app.sources.len()).map(FetcherRequest::SourceSamples));
+    for request in requests {
+        let response = fetcher.fetch(request);
+        let captured_at = Instant::now();
+        match (&response, response.error()) {
+            // HikariCP is optional, its metrics are only checked when the pool has them
+            (FetcherResponse::HikariMetrics(_), _) | (_, None) => {}
+            (_, Some(e)) => results.push(CheckResult {
+                status: CheckStatus::Critical,
+                message: format!("{}: {}", response.source(), e),
+            }),
+        }
+        apply_response(&mut app, response, captured_at);
+    }
+
+    // a read-only node is partitioned from the quorum, which isn't as bad as a node being down
+    for n in app.zookeeper.iter().flat_map(|z| z.nodes.iter()) {
+        let status = match n.state {
+            ZkNodeState::Ok => CheckStatus::Ok,
+            ZkNodeState::ReadOnly => CheckStatus::Warning,
+            ZkNodeState::NotResponding => CheckStatus::Critical,
+        };
+        let error = n.error.as_ref().map_or("".to_owned(), |e| format!(": {}", e));
+        results.push(CheckResult { status, message: format!("zookeeper {} {}{}", n.address, n.state, error) });
+    }
+    for b in app.zookeeper.iter().filter_map(|z| z.brokers.as_ref()).flat_map(|b| b.iter().flatten()) {
+        // a broker failing JMX is registered but can't tell whether it's healthy
+        let status = if b.is_failing() {
+            CheckStatus::Critical
+        } else if b.under_replicated_partitions() > 0 {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        };
+        results.push(CheckResult { status, message: format!("kafka {}", b.label()) });
+    }
+
+    app.run_hooks(Instant::now());
+    let vars = app.sample_vars();
+    results.extend(config.thresholds.iter().map(|t| t.check(&vars)));
+    let (status, summary) = check::summarize(&results);
+    println!("{}", summary);
+    status.exit_code()
+}
+
+/// Prints the status of the Zookeeper nodes, exiting with 1 if one of them doesn't respond and 2 if they can't be probed
+fn run_zk_status(cli: &Cli, config: &Config, output: ZkOutput, color: bool) -> i32 {
+    if cli.zookeeper.is_empty() {
+        println!("zk-status needs the nodes of the ensemble with --zookeeper");
+        return 2;
+    }
+    let tls = match config.zookeeper_tls.as_ref().map(zookeeper::client::tls_connector).transpose() {
+        Ok(t) => t,
+        Err(e) => {
+            println!("{}", e);
+            return 2;
+        }
+    };
+    let statuses = zookeeper::client::probe_ensemble(
+        &cli.zookeeper,
+        cli.zookeeper_discover,
+        (cli.tick_rate as f64 * 0.8) as u64,
+        tls.as_ref(),
+        config.zookeeper_admin.as_ref(),
+        config.zookeeper_ssh.as_ref(),
+    );
+    println!("{}", zookeeper::report::render(&statuses, output, color));
+    if statuses.iter().any(|s| s.state == ZkNodeState::NotResponding) { 1 } else { 0 }
+}
+
+/// Starts polling the sources of a compared environment, returning the scheduler and the requests of a poll
+fn compare_scheduler<R>(name: &str, profile: &Profile, proxies: &ProxySettings, cli: &Cli, respond: R) -> (Scheduler, Vec<FetcherRequest>)
+    where R: Fn(FetcherResponse, Instant, Duration) + Send + Clone + 'static {
+    let timeout = (cli.tick_rate as f64 * 0.8) as u64;
+    let zio_zmx = profile.zio_zmx.clone();
+    let jmx = match (&profile.jmx, &profile.db_pool_name) {
+        (Some(address), Some(db_pool_name)) => {
+            let credentials = Credentials::load(name).unwrap_or(None);
+            Some(JMXConnectionSettings {
+                address: address.to_owned(),
+                username: credentials.as_ref().map(|c| c.username.to_owned()),
+                password: credentials.map(|c| c.password),
+                db_pool_name: db_pool_name.to_owned(),
+            })
+        }
+        _ => None,
+    };
+    // only the actor count is compared, so there's no tree to assemble
+    let akka = profile.actor_count.clone().map(|count_address| AkkaSettings {
+        tree_address: String::new(),
+        tree_timeout: cli.actor_tree_timeout,
+        count_address,
+        count_timeout: timeout,
+        management_address: None,
+    });
+
+    let mut requests = vec![];
+    if zio_zmx.is_some() {
+        requests.push(FetcherRequest::RegularFiberDump);
+    }
+    if jmx.is_some() {
+        requests.push(FetcherRequest::HikariMetrics);
+    }
+    if akka.is_some() {
+        requests.push(FetcherRequest::ActorCount);
+    }
+    let workers: Vec<String> = requests.iter().map(|r| r.worker()).collect();
+    let proxies = proxies.clone();
+
+    let connect = move |assigned: &[String]| {
+        let has = |s: &str| assigned.iter().any(|a| a == s);
+        Ok(Fetcher::new(FetcherSettings {
+            zio_zmx_addr: zio_zmx.clone().filter(|_| has("zmx")),
+            jmx: jmx.clone().filter(|_| has("jmx")),
+            akka: akka.clone().filter(|_| has("akka")),
+            proxies: proxies.clone(),
+            http_timeout: timeout,
+            ..FetcherSettings::default()
+        }))
+    };
+    let scheduler = Scheduler::start(workers, cli.fetch_workers, Duration::from_millis(cli.tick_rate), connect, respond);
+    (scheduler, requests)
+}
@@ -0,0 +1,38 @@
+use std::io::{stdout, Write};
+
+use notify_rust::Notification;
+
+///
+/// Gets the attention of an operator who isn't looking at the TUI when an alert starts firing:
+/// rings the terminal bell (`--bell`) and/or shows a desktop notification (`--notify`).
+pub struct Notifier {
+    pub bell: bool,
+    pub desktop: bool,
+}
+
+impl Notifier {
+    pub fn is_enabled(&self) -> bool {
+        self.bell || self.desktop
+    }
+
+    pub fn on_alerts(&self, title: &str, alerts: &[String]) -> Result<(), String> {
+        if alerts.is_empty() {
+            return Ok(());
+        }
+        if self.bell {
+            let mut out = stdout();
+            write!(out, "\x07").and_then(|_| out.flush())
+                .map_err(|e| format!("Couldn't ring the bell: {}", e))?;
+        }
+        if self.desktop {
+            Notification::new()
+                .appname("panopticon-tui")
+                .summary(&format!("{}: ALERT", title))
+                .body(&alerts.join("\n"))
+                .show()
+                .map(|_| ())
+                .map_err(|e| format!("Couldn't show notification: {}", e))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::config::Proxy;
+use crate::http;
+
+///
+/// Paste service fiber dumps and session summaries are uploaded to with `u`,
+/// to share them over SSH where clipboards don't reach.
+///
+/// eg. `{ "url": "https://paste.internal/api/create?title={name}" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PasteSettings {
+    /// URL the content is POSTed to as plain text, with `{name}` replaced by its name, e.g. `fiber-42`
+    pub url: String,
+}
+
+impl PasteSettings {
+    pub fn url_for(&self, name: &str) -> String {
+        self.url.replace("{name}", name)
+    }
+}
+
+/// Uploads the content and returns the link to it
+pub fn upload(settings: &PasteSettings, name: &str, contents: String, proxy: Option<&Proxy>) -> Result<String, String> {
+    upload_async(&settings.url_for(name), contents, proxy)
+}
+
+#[tokio::main]
+async fn upload_async(url: &str, contents: String, proxy: Option<&Proxy>) -> Result<String, String> {
+    let response = http::client(proxy)?
+        .post(url)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(contents)
+        .timeout(Duration::from_secs(10))
+        .send().await
+        .map_err(|e| format!("Couldn't upload to {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("Upload to {} failed with status {}", url, response.status()));
+    }
+    let location = response.headers().get("Location")
+        .and_then(|l| l.to_str().ok())
+        .map(str::to_owned);
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    link(location.as_deref(), &body)
+}
+
+///
+/// Link to the uploaded content: the `Location` header of the response if there's one,
+/// or else the first line of its body, as returned by most pastebins.
+pub fn link(location: Option<&str>, body: &str) -> Result<String, String> {
+    location
+        .or_else(|| body.lines().map(str::trim).find(|l| !l.is_empty()))
+        .map(str::to_owned)
+        .ok_or_else(|| "Paste service returned no link".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::paste::{link, PasteSettings};
+
+    #[test]
+    fn builds_urls_and_reads_links() {
+        let settings = PasteSettings { url: "https://paste.internal/api/create?title={name}".to_owned() };
+        assert_eq!(settings.url_for("fiber-42"), "https://paste.internal/api/create?title=fiber-42");
+
+        assert_eq!(link(Some("https://paste.internal/p/abc"), "created"), Ok("https://paste.internal/p/abc".to_owned()));
+        assert_eq!(link(None, "\nhttps://paste.internal/p/abc\n"), Ok("https://paste.internal/p/abc".to_owned()));
+        assert!(link(None, "  \n").is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use plotters::prelude::*;
+
+use crate::series;
+
+/// Named series of a chart, as placed on the time axis: seconds before the export and values
+pub type ChartSeries = Vec<(String, Vec<(f64, f64)>)>;
+
+///
+/// Renders a chart to a PNG file, with the time axis labelled in UTC wall-clock time
+/// counted back from `exported_at` (seconds since the Unix epoch), so it can go into a post-mortem as is.
+pub fn write_png(path: &str, title: &str, series: &ChartSeries, exported_at: u64) -> Result<(), String> {
+    let root = BitMapBackend::new(path, (1280, 720)).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+
+    let (x_min, y_min, y_max) = bounds(series);
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("{} (exported at {} UTC)", title, clock(exported_at as f64)), ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(80)
+        .build_cartesian_2d(x_min..0.0, y_min..y_max)
+        .map_err(|e| e.to_string())?;
+    chart.configure_mesh()
+        .x_desc("Time (UTC)")
+        .x_label_formatter(&|x| clock(exported_at as f64 + x))
+        .y_label_formatter(&|y| series::axis_label(*y))
+        .draw()
+        .map_err(|e| e.to_string())?;
+
+    for (i, (name, data)) in series.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+        chart.draw_series(LineSeries::new(data.iter().cloned(), color.stroke_width(2)))
+            .map_err(|e| e.to_string())?
+            .label(name.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+    chart.configure_series_labels()
+        .background_style(&WHITE.mix(0.8))
+        .border_style(&BLACK)
+        .draw()
+        .map_err(|e| e.to_string())?;
+    root.present().map_err(|e| e.to_string())
+}
+
+/// Range of the axes: the time axis ends at the export, the value axis includes zero and leaves some room on top
+fn bounds(series: &ChartSeries) -> (f64, f64, f64) {
+    let points = || series.iter().flat_map(|(_, data)| data.iter());
+    let x_min = points().map(|(x, _)| *x).fold(0.0, f64::min);
+    let y_min = points().map(|(_, y)| *y).fold(0.0, f64::min);
+    let y_max = points().map(|(_, y)| *y).fold(0.0, f64::max);
+    let x_min = if x_min < 0.0 { x_min } else { -1.0 };
+    let y_max = if y_max > y_min { y_max + (y_max - y_min) * 0.1 } else { y_min + 1.0 };
+    (x_min, y_min, y_max)
+}
+
+/// Time of day in UTC of a Unix timestamp, e.g. `14:05:09`
+fn clock(unix_secs: f64) -> String {
+    let secs = unix_secs.max(0.0).round() as u64 % 86_400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plot::{bounds, clock};
+
+    #[test]
+    fn labels_time_axis_and_fits_values() {
+        assert_eq!(clock(1_600_000_000.0), "12:26:40");
+        assert_eq!(clock(1_600_000_000.0 - 90.0), "12:25:10");
+
+        let series = vec![
+            ("running".to_owned(), vec![(-4.0, 10.0), (-2.0, 30.0), (0.0, 20.0)]),
+            ("done".to_owned(), vec![(-2.0, 5.0), (0.0, 0.0)]),
+        ];
+        assert_eq!(bounds(&series), (-4.0, 0.0, 33.0));
+        assert_eq!(bounds(&vec![("idle".to_owned(), vec![(0.0, 0.0)])]), (-1.0, 0.0, 1.0));
+    }
+}
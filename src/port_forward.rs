@@ -0,0 +1,231 @@
+use std::io::Read;
+use std::net::{SocketAddr, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+///
+/// `kubectl port-forward` to a running pod matching a label selector, restarted whenever it drops.
+///
+/// eg. `{ "selector": "app=orders-api", "namespace": "prod", "remote_port": 6789, "local_port": 16789, "source": "zio_zmx" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PortForward {
+    pub selector: String,
+    pub namespace: Option<String>,
+    /// kubectl context, the current one if not set
+    pub context: Option<String>,
+    pub remote_port: u16,
+    pub local_port: u16,
+    /// Source connected to the local port, unless its address is given on the command line
+    pub source: Option<ForwardedSource>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwardedSource {
+    ZioZmx,
+    Jmx,
+    Redis,
+    Consul,
+    Zookeeper,
+}
+
+impl PortForward {
+    /// Address of the forwarded port, e.g. `localhost:16789`
+    pub fn local_address(&self) -> String {
+        format!("localhost:{}", self.local_port)
+    }
+
+    fn kubectl_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(c) = &self.context {
+            args.extend(vec!["--context".to_owned(), c.to_owned()]);
+        }
+        if let Some(n) = &self.namespace {
+            args.extend(vec!["--namespace".to_owned(), n.to_owned()]);
+        }
+        args
+    }
+
+    /// Arguments of the kubectl command printing the name of the first running pod matching the selector
+    pub fn pod_query(&self) -> Vec<String> {
+        let mut args = self.kubectl_args();
+        args.extend(vec![
+            "get".to_owned(), "pods".to_owned(),
+            "--selector".to_owned(), self.selector.to_owned(),
+            "--field-selector".to_owned(), "status.phase=Running".to_owned(),
+            "--output".to_owned(), "jsonpath={.items[0].metadata.name}".to_owned(),
+        ]);
+        args
+    }
+
+    /// Arguments of the kubectl command forwarding the local port to the pod
+    pub fn forward_command(&self, pod: &str) -> Vec<String> {
+        let mut args = self.kubectl_args();
+        args.extend(vec![
+            "port-forward".to_owned(),
+            format!("pod/{}", pod),
+            format!("{}:{}", self.local_port, self.remote_port),
+        ]);
+        args
+    }
+
+    fn find_pod(&self) -> Result<String, String> {
+        let output = Command::new("kubectl")
+            .args(self.pod_query())
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Couldn't run kubectl: {}", e))?;
+        let pod = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+        if !output.status.success() {
+            Err(format!("Couldn't list pods: {}", String::from_utf8_lossy(&output.stderr).trim()))
+        } else if pod.is_empty() {
+            Err(format!("No running pod matches {}", self.selector))
+        } else {
+            Ok(pod)
+        }
+    }
+
+    /// Spawns the port-forward, its output kept off the terminal
+    fn spawn(&self) -> Result<(String, Child), String> {
+        let pod = self.find_pod()?;
+        let child = Command::new("kubectl")
+            .args(self.forward_command(&pod))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Couldn't run kubectl: {}", e))?;
+        Ok((pod, child))
+    }
+}
+
+///
+/// Port-forward processes supervised on their own threads, each restarted with a growing delay
+/// after it drops or fails to start. The processes are killed once this is dropped.
+pub struct PortForwards {
+    children: Arc<Mutex<Vec<Option<Child>>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl PortForwards {
+    /// Starts the port-forwards, calling `report` with a message each time one starts or drops
+    pub fn start<F>(forwards: Vec<PortForward>, report: F) -> PortForwards
+        where F: Fn(String) + Clone + Send + 'static {
+        let children = Arc::new(Mutex::new(forwards.iter().map(|_| None).collect()));
+        let stopped = Arc::new(AtomicBool::new(false));
+        for (i, forward) in forwards.into_iter().enumerate() {
+            let children = children.clone();
+            let stopped = stopped.clone();
+            let report = report.clone();
+            thread::spawn(move || supervise(i, &forward, &children, &stopped, report));
+        }
+        PortForwards { children, stopped }
+    }
+}
+
+impl Drop for PortForwards {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        for child in self.children.lock().unwrap().iter_mut().flatten() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+fn supervise<F>(i: usize, forward: &PortForward, children: &Mutex<Vec<Option<Child>>>, stopped: &AtomicBool, report: F)
+    where F: Fn(String) {
+    let mut delay = Duration::from_secs(1);
+    while !stopped.load(Ordering::SeqCst) {
+        let started = Instant::now();
+        match forward.spawn() {
+            Ok((pod, mut child)) => {
+                {
+                    let mut children = children.lock().unwrap();
+                    // checked under the lock, as dropping sets it before killing the children it holds
+                    if stopped.load(Ordering::SeqCst) {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return;
+                    }
+                    children[i] = Some(child);
+                }
+                report(format!("Forwarding {} to {}:{}", forward.local_address(), pod, forward.remote_port));
+                let error = wait(i, children);
+                if stopped.load(Ordering::SeqCst) {
+                    return;
+                }
+                report(format!("Port-forward of {} to {} dropped: {}", forward.local_address(), pod, error));
+            }
+            Err(e) => report(format!("Couldn't forward {}: {}", forward.local_address(), e)),
+        }
+        // a forward that held for a while is restarted right away
+        delay = if started.elapsed() > MAX_RESTART_DELAY { Duration::from_secs(1) } else { (delay * 2).min(MAX_RESTART_DELAY) };
+        thread::sleep(delay);
+    }
+}
+
+/// Waits for the port-forward to exit, without holding the lock so that it can be killed meanwhile
+fn wait(i: usize, children: &Mutex<Vec<Option<Child>>>) -> String {
+    loop {
+        {
+            let mut children = children.lock().unwrap();
+            let child = match children[i].as_mut() {
+                Some(c) => c,
+                None => return "killed".to_owned(),
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    let mut stderr = String::new();
+                    if let Some(mut s) = child.stderr.take() {
+                        let _ = s.read_to_string(&mut stderr);
+                    }
+                    children[i] = None;
+                    return if stderr.trim().is_empty() { status.to_string() } else { stderr.trim().to_owned() };
+                }
+                Ok(None) => {}
+                Err(e) => return e.to_string(),
+            }
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Waits for the local ports of the port-forwards to accept connections, up to the timeout
+pub fn wait_until_listening(forwards: &[PortForward], timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    for forward in forwards {
+        let address = SocketAddr::from(([127, 0, 0, 1], forward.local_port));
+        while TcpStream::connect_timeout(&address, Duration::from_millis(200)).is_err() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::port_forward::{ForwardedSource, PortForward};
+
+    #[test]
+    fn forwards_first_running_pod_of_selector() {
+        let forward: PortForward = serde_json::from_str(
+            r#"{ "selector": "app=orders-api", "namespace": "prod", "remote_port": 6789, "local_port": 16789, "source": "zio_zmx" }"#
+        ).unwrap();
+
+        assert_eq!(forward.source, Some(ForwardedSource::ZioZmx));
+        assert_eq!(forward.local_address(), "localhost:16789");
+        assert_eq!(forward.pod_query().join(" "), "--namespace prod get pods --selector app=orders-api \
+            --field-selector status.phase=Running --output jsonpath={.items[0].metadata.name}");
+        assert_eq!(forward.forward_command("orders-api-7d9f").join(" "), "--namespace prod port-forward pod/orders-api-7d9f 16789:6789");
+
+        assert!(serde_json::from_str::<PortForward>(r#"{ "selector": "app=x", "remote_port": 1, "local_port": 1, "source": "akka" }"#).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+///
+/// Output of a command that's killed if it doesn't exit within the timeout.
+/// Its output is read on threads of their own, so that a chatty command doesn't block on a full pipe.
+pub fn output_within(command: &mut Command, timeout: Duration) -> Result<Output, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}ms", timeout.as_millis()));
+            }
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_all<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut p) = pipe {
+            let _ = p.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    use crate::process::output_within;
+
+    #[test]
+    fn kills_command_after_timeout() {
+        let output = output_within(Command::new("sh").arg("-c").arg("echo ok; echo oops >&2"), Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"ok\n");
+        assert_eq!(output.stderr, b"oops\n");
+
+        let started = Instant::now();
+        let hung = output_within(Command::new("sh").arg("-c").arg("exec sleep 10"), Duration::from_millis(100));
+        assert_eq!(hung, Err("timed out after 100ms".to_owned()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
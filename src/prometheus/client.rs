@@ -0,0 +1,23 @@
+use crate::config::Proxy;
+use crate::http;
+use crate::prometheus::model::{PrometheusSample, PrometheusTarget};
+use crate::prometheus::parser;
+
+/// Scrapes the target and returns samples of all of its metrics
+pub fn get_samples(target: &PrometheusTarget, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<PrometheusSample>, String> {
+    get_samples_async(target, timeout, proxy)
+}
+
+#[tokio::main]
+async fn get_samples_async(target: &PrometheusTarget, timeout: u64, proxy: Option<&Proxy>) -> Result<Vec<PrometheusSample>, String> {
+    let response = http::client(proxy)?
+        .get(&target.url)
+        .timeout(std::time::Duration::from_millis(timeout))
+        .send().await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Request to scrape metrics failed with status {}", response.status()));
+    }
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    parser::parse_samples(&text)
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+
+///
+/// How well a metric name matches a search query, lower is better.
+///
+/// Characters of the query have to appear in the name in the same order, case-insensitively,
+/// and the score is the number of other characters between the first and the last matched one.
+///
+/// eg. `jvmthr` matches `jvm_threads_live` with score 1
+pub fn fuzzy_score(query: &str, name: &str) -> Option<usize> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut first = None;
+    let mut last = 0;
+    let mut from = 0;
+    let mut matched = 0;
+    for q in query.to_lowercase().chars() {
+        let i = from + name[from..].iter().position(|c| *c == q)?;
+        first = first.or(Some(i));
+        last = i;
+        from = i + 1;
+        matched += 1;
+    }
+    Some(first.map_or(0, |f| last + 1 - f - matched))
+}
+
+/// Names matching the query, the best matches first
+pub fn search<'a, I>(query: &str, names: I) -> Vec<&'a str>
+    where I: Iterator<Item=&'a String> {
+    let mut matches: Vec<(usize, &str)> = names
+        .filter_map(|n| fuzzy_score(query, n).map(|s| (s, n.as_str())))
+        .collect();
+    matches.sort();
+    matches.into_iter().map(|(_, n)| n).collect()
+}
+
+///
+/// Metrics pinned with the metric explorer of the Prometheus tab, saved to a JSON file per profile.
+///
+/// eg.
+///   ```text
+///   { "default": ["jvm_threads_live"], "prod": ["http_server_requests_seconds_count"] }
+///   ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinnedMetrics {
+    pub path: String,
+    pub profile: String,
+    pub metrics: Vec<String>,
+}
+
+impl PinnedMetrics {
+    /// Metrics pinned for the profile, none if the file doesn't exist yet
+    pub fn load(path: &str, profile: &str) -> Result<PinnedMetrics, String> {
+        let metrics = PinnedMetrics::read_all(path)?.remove(profile).unwrap_or_default();
+        Ok(PinnedMetrics { path: path.to_owned(), profile: profile.to_owned(), metrics })
+    }
+
+    /// Pins or unpins the metric, returns whether it's pinned now
+    pub fn toggle(&mut self, metric: &str) -> bool {
+        match self.metrics.iter().position(|m| m == metric) {
+            Some(i) => {
+                self.metrics.remove(i);
+                false
+            }
+            None => {
+                self.metrics.push(metric.to_owned());
+                true
+            }
+        }
+    }
+
+    /// Writes the metrics of this profile, keeping the ones of other profiles
+    pub fn save(&self) -> Result<(), String> {
+        let mut all = PinnedMetrics::read_all(&self.path)?;
+        all.insert(self.profile.to_owned(), self.metrics.clone());
+        let json = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+        fs::write(&self.path, json).map_err(|e| format!("Couldn't write pinned metrics to {}: {}", self.path, e))
+    }
+
+    fn read_all(path: &str) -> Result<BTreeMap<String, Vec<String>>, String> {
+        match fs::read_to_string(path) {
+            Ok(s) => serde_json::from_str(&s).map_err(|e| format!("Invalid pinned metrics {}: {}", path, e)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(format!("Couldn't read pinned metrics {}: {}", path, e)),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+
+/// Prometheus-format endpoint to scrape, along with names of the metrics to chart.
+/// Other metrics of the endpoint can be found and pinned to the chart with the metric explorer
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PrometheusTarget {
+    pub url: String,
+    #[serde(default)]
+    pub metrics: Vec<String>,
+}
+
+/// Value of a single series, e.g. `http_requests_total{method="GET"}`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrometheusSample {
+    /// Name of the metric without labels, e.g. `http_requests_total`
+    pub metric: String,
+    pub series: String,
+    pub value: f64,
+}
@@ -0,0 +1,45 @@
+use crate::prometheus::model::PrometheusSample;
+
+///
+/// Reads samples of all series from a response in the Prometheus text exposition format.
+///
+/// eg.
+///   ```text
+///   # HELP jvm_threads_live The current number of live threads
+///   # TYPE jvm_threads_live gauge
+///   jvm_threads_live 42.0
+///   jvm_memory_used_bytes{area="heap",} 1.2E7
+///   ```
+/// gives samples `jvm_threads_live` = 42.0 and `jvm_memory_used_bytes{area="heap"}` = 1.2E7
+pub fn parse_samples(text: &str) -> Result<Vec<PrometheusSample>, String> {
+    let mut samples = vec![];
+    for line in text.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let name_end = line.find(|c: char| c == '{' || c.is_whitespace()).unwrap_or(line.len());
+        let name = &line[..name_end];
+        let (series, rest) = if line[name_end..].starts_with('{') {
+            let labels_end = line.rfind('}')
+                .ok_or(format!("Unterminated labels in line: {}", line))?;
+            (&line[..=labels_end], &line[labels_end + 1..])
+        } else {
+            (name, &line[name_end..])
+        };
+        let value = rest.split_whitespace().next()
+            .ok_or(format!("Missing value in line: {}", line))?;
+        let value = parse_value(value).ok_or(format!("Invalid value in line: {}", line))?;
+        // the trailing comma some clients emit after the last label is dropped
+        samples.push(PrometheusSample { metric: name.to_owned(), series: series.replace(",}", "}"), value });
+    }
+    Ok(samples)
+}
+
+fn parse_value(s: &str) -> Option<f64> {
+    match s {
+        "+Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => s.parse::<f64>().ok(),
+    }
+}
@@ -0,0 +1,64 @@
+#[test]
+fn parser_reads_all_series() {
+    use crate::prometheus::model::PrometheusSample;
+    use crate::prometheus::parser::parse_samples;
+
+    let text = "# HELP jvm_threads_live The current number of live threads
+# TYPE jvm_threads_live gauge
+jvm_threads_live 42.0
+http_requests_total{method=\"GET\",uri=\"/a b\",} 1.5E3 1589000000000
+http_requests_total{method=\"POST\"} +Inf
+";
+    let sample = |metric: &str, series: &str, value| PrometheusSample { metric: metric.to_owned(), series: series.to_owned(), value };
+
+    assert_eq!(parse_samples(text), Ok(vec![
+        sample("jvm_threads_live", "jvm_threads_live", 42.0),
+        sample("http_requests_total", "http_requests_total{method=\"GET\",uri=\"/a b\"}", 1500.0),
+        sample("http_requests_total", "http_requests_total{method=\"POST\"}", f64::INFINITY),
+    ]));
+}
+
+#[test]
+fn parser_invalid_value() {
+    use crate::prometheus::parser::parse_samples;
+
+    assert_eq!(
+        parse_samples("up abc"),
+        Err("Invalid value in line: up abc".to_owned())
+    );
+}
+
+#[test]
+fn explorer_ranks_fuzzy_matches() {
+    use crate::prometheus::explorer::{fuzzy_score, search};
+
+    assert_eq!(fuzzy_score("jvmthr", "jvm_threads_live"), Some(1));
+    assert_eq!(fuzzy_score("THREADS", "jvm_threads_live"), Some(0));
+    assert_eq!(fuzzy_score("heap", "jvm_threads_live"), None);
+
+    let names = vec!["jvm_threads_peak".to_owned(), "http_requests_total".to_owned(), "jvm_threads_live".to_owned()];
+    assert_eq!(search("jvmthr", names.iter()), vec!["jvm_threads_live", "jvm_threads_peak"]);
+    assert_eq!(search("", names.iter()).len(), 3);
+}
+
+#[test]
+fn explorer_pins_metrics_per_profile() {
+    use crate::prometheus::explorer::PinnedMetrics;
+
+    let path = std::env::temp_dir().join(format!("panopticon-pins-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut prod = PinnedMetrics::load(path, "prod").unwrap();
+    assert!(prod.metrics.is_empty());
+
+    assert!(prod.toggle("jvm_threads_live"));
+    assert!(prod.toggle("http_requests_total"));
+    assert!(!prod.toggle("jvm_threads_live"));
+    prod.save().unwrap();
+    let mut staging = PinnedMetrics::load(path, "staging").unwrap();
+    staging.toggle("up");
+    staging.save().unwrap();
+
+    assert_eq!(PinnedMetrics::load(path, "prod").unwrap().metrics, vec!["http_requests_total"]);
+    assert_eq!(PinnedMetrics::load(path, "staging").unwrap().metrics, vec!["up"]);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+
+use crate::zio::model::FiberStatus;
+
+///
+/// Function key running a sequence of built-in actions, e.g. to go through a recurring triage workflow with one keypress.
+///
+/// eg. `{ "key": "F5", "actions": ["tab:ZIO", "<Enter>", "filter:suspended"] }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct QuickAction {
+    pub key: String,
+    pub actions: Vec<String>,
+}
+
+/// Built-in action of a quick action
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Switches to the tab with the given title, e.g. `tab:ZIO`
+    Tab(String),
+    /// Narrows the fiber list to a status, e.g. `filter:suspended`, or shows every fiber again with `filter:all`
+    StatusFilter(Option<FiberStatus>),
+    /// Sorts the focused table by the column with the given title, e.g. `sort:Executions` or `sort:Executions desc`
+    Sort(String, bool),
+    /// Presses a key, e.g. `s`, `<Enter>` or `<Down>`
+    Key(KeyCode),
+}
+
+/// Steps of the quick actions by the number of their function key, failing on the first invalid one
+pub fn bindings(actions: &[QuickAction]) -> Result<HashMap<u8, Vec<Step>>, String> {
+    let mut bindings = HashMap::new();
+    for a in actions {
+        let n = a.key.strip_prefix('F')
+            .and_then(|n| n.parse::<u8>().ok())
+            .filter(|n| (1..=12).contains(n))
+            .ok_or(format!("Invalid quick action key {}, expected a function key from F1 to F12", a.key))?;
+        let steps = a.actions.iter()
+            .map(|s| step(s).map_err(|e| format!("Invalid action of quick action {}: {}", a.key, e)))
+            .collect::<Result<Vec<Step>, String>>()?;
+        if bindings.insert(n, steps).is_some() {
+            return Err(format!("Quick action {} is defined more than once", a.key));
+        }
+    }
+    Ok(bindings)
+}
+
+fn step(action: &str) -> Result<Step, String> {
+    if let Some(title) = action.strip_prefix("tab:") {
+        return Ok(Step::Tab(title.to_owned()));
+    }
+    if let Some(status) = action.strip_prefix("filter:") {
+        return match status.to_lowercase().as_str() {
+            "all" => Ok(Step::StatusFilter(None)),
+            "running" => Ok(Step::StatusFilter(Some(FiberStatus::Running))),
+            "suspended" => Ok(Step::StatusFilter(Some(FiberStatus::Suspended))),
+            "finishing" => Ok(Step::StatusFilter(Some(FiberStatus::Finishing))),
+            "done" => Ok(Step::StatusFilter(Some(FiberStatus::Done))),
+            _ => Err(format!("unknown fiber status {}", status)),
+        };
+    }
+    if let Some(column) = action.strip_prefix("sort:") {
+        return Ok(match column.strip_suffix(" desc") {
+            Some(column) => Step::Sort(column.trim().to_owned(), true),
+            None => Step::Sort(column.trim_end_matches(" asc").trim().to_owned(), false),
+        });
+    }
+    let key = match action {
+        "<Enter>" => KeyCode::Enter,
+        "<Tab>" => KeyCode::Tab,
+        "<Esc>" => KeyCode::Esc,
+        "<Up>" => KeyCode::Up,
+        "<Down>" => KeyCode::Down,
+        "<Left>" => KeyCode::Left,
+        "<Right>" => KeyCode::Right,
+        "<PageUp>" => KeyCode::PageUp,
+        "<PageDown>" => KeyCode::PageDown,
+        _ if action.chars().count() == 1 => KeyCode::Char(action.chars().next().unwrap()),
+        _ => return Err(format!("unknown action {}", action)),
+    };
+    Ok(Step::Key(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::quick_actions::{bindings, QuickAction, Step};
+    use crate::zio::model::FiberStatus;
+
+    #[test]
+    fn binds_steps_to_function_keys() {
+        let action = |key: &str, actions: &[&str]| QuickAction {
+            key: key.to_owned(),
+            actions: actions.iter().map(|a| a.to_string()).collect(),
+        };
+
+        let bound = bindings(&[action("F5", &["tab:ZIO", "<Enter>", "filter:suspended", "sort:Executions desc", "s"])]).unwrap();
+        assert_eq!(bound[&5], vec![
+            Step::Tab("ZIO".to_owned()),
+            Step::Key(KeyCode::Enter),
+            Step::StatusFilter(Some(FiberStatus::Suspended)),
+            Step::Sort("Executions".to_owned(), true),
+            Step::Key(KeyCode::Char('s')),
+        ]);
+
+        assert!(bindings(&[action("x", &["s"])]).is_err());
+        assert!(bindings(&[action("F13", &["s"])]).is_err());
+        assert!(bindings(&[action("F5", &["filter:sleeping"])]).is_err());
+        assert!(bindings(&[action("F5", &["snapshot"])]).is_err());
+        assert!(bindings(&[action("F5", &["s"]), action("F5", &["S"])]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+
+use bytes::BytesMut;
+use redis_protocol::types::Frame;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+use crate::redis::info_parser;
+use crate::redis::model::RedisStats;
+
+pub trait RedisClient {
+    fn address(&self) -> String;
+    fn get_stats(&self) -> Result<RedisStats, String>;
+}
+
+pub struct NetworkRedisClient {
+    address: String
+}
+
+impl NetworkRedisClient {
+    /// Amount of the most recent slowlog entries to fetch
+    pub const SLOWLOG_ENTRIES: &'static str = "25";
+
+    pub fn new(address: String) -> NetworkRedisClient { NetworkRedisClient { address } }
+
+    #[tokio::main]
+    async fn get_stats_async(&self) -> Result<RedisStats, Box<dyn Error>> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+
+        let info = match NetworkRedisClient::command(&mut stream, &["INFO"]).await? {
+            Frame::Error(e) => Err(format!("INFO failed: {}", e)),
+            f => f.as_str()
+                .map(|s| s.to_owned())
+                .ok_or(format!("Unexpected INFO response: {:?}", f)),
+        }?;
+
+        let slowlog = match NetworkRedisClient::command(&mut stream, &["SLOWLOG", "GET", NetworkRedisClient::SLOWLOG_ENTRIES]).await? {
+            Frame::Error(e) => Err(format!("SLOWLOG failed: {}", e)),
+            f => info_parser::parse_slowlog(&f),
+        }?;
+
+        Ok(RedisStats { info: info_parser::parse_info(&info)?, slowlog })
+    }
+
+    /// Sends a command and reads until a complete response frame arrives
+    async fn command(stream: &mut TcpStream, args: &[&str]) -> Result<Frame, Box<dyn Error>> {
+        let frame = Frame::Array(args.iter().map(|a| Frame::BulkString(a.to_string().into())).collect());
+        let mut buf = BytesMut::new();
+        redis_protocol::prelude::encode_bytes(&mut buf, &frame)
+            .map_err(|e| format!("Error encoding frame: {:?}", e))?;
+        stream.write_all(&buf).await?;
+
+        let mut received: Vec<u8> = vec![];
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                return Err(Box::from("Connection closed before a complete response was received"));
+            }
+            received.extend_from_slice(&chunk[..n]);
+
+            let buf = BytesMut::from(received.clone());
+            match redis_protocol::prelude::decode_bytes(&buf) {
+                Ok((Some(frame), _)) => return Ok(frame),
+                Ok((None, _)) => continue,
+                Err(e) => return Err(Box::from(format!("Error parsing bytes: {:?}", e))),
+            }
+        }
+    }
+}
+
+impl RedisClient for NetworkRedisClient {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    fn get_stats(&self) -> Result<RedisStats, String> {
+        self.get_stats_async().map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use redis_protocol::types::Frame;
+
+use crate::redis::model::{RedisInfo, SlowlogEntry};
+
+///
+/// Parses the response of the `INFO` command.
+///
+/// Expects `field:value` lines, grouped in sections:
+///
+///   ```text
+///   # Clients
+///   connected_clients:1
+///   ```
+pub fn parse_info(info: &str) -> Result<RedisInfo, String> {
+    let fields: HashMap<&str, &str> = info.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| l.find(':').map(|i| (&l[..i], &l[i + 1..])))
+        .collect();
+
+    let field = |name: &str| -> Result<u64, String> {
+        fields.get(name)
+            .ok_or(format!("Missing {} in INFO response", name))?
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid {} in INFO response: {}", name, e))
+    };
+
+    Ok(RedisInfo {
+        used_memory: field("used_memory")?,
+        connected_clients: field("connected_clients")?,
+        ops_per_sec: field("instantaneous_ops_per_sec")?,
+        keyspace_hits: field("keyspace_hits")?,
+        keyspace_misses: field("keyspace_misses")?,
+    })
+}
+
+///
+/// Parses the response of the `SLOWLOG GET` command.
+///
+/// Each entry is an array of id, unix timestamp, duration in microseconds and command arguments,
+/// optionally followed by client address and name (Redis 4.0+).
+pub fn parse_slowlog(frame: &Frame) -> Result<Vec<SlowlogEntry>, String> {
+    match frame {
+        Frame::Array(entries) => entries.iter().map(parse_slowlog_entry).collect(),
+        _ => Err(format!("Unexpected SLOWLOG response: {:?}", frame)),
+    }
+}
+
+fn parse_slowlog_entry(frame: &Frame) -> Result<SlowlogEntry, String> {
+    if let Frame::Array(fields) = frame {
+        if let [Frame::Integer(id), Frame::Integer(timestamp), Frame::Integer(duration), Frame::Array(args), ..] = fields.as_slice() {
+            return Ok(SlowlogEntry {
+                id: *id,
+                timestamp: *timestamp,
+                duration_micros: *duration,
+                command: args.iter().filter_map(|a| a.as_str()).collect::<Vec<&str>>().join(" "),
+            });
+        }
+    }
+    Err(format!("Unexpected SLOWLOG entry: {:?}", frame))
+}
@@ -0,0 +1,4 @@
+pub mod model;
+pub mod client;
+mod info_parser;
+mod tests;
@@ -0,0 +1,50 @@
+use serde::Serialize;
+
+use crate::history::Downsample;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RedisInfo {
+    pub used_memory: u64,
+    pub connected_clients: u64,
+    pub ops_per_sec: u64,
+    pub keyspace_hits: u64,
+    pub keyspace_misses: u64,
+}
+
+impl RedisInfo {
+    /// Percentage of key lookups that were hits since the previous sample
+    pub fn hit_rate_since(&self, prev: &RedisInfo) -> Option<f64> {
+        let hits = self.keyspace_hits.saturating_sub(prev.keyspace_hits);
+        let misses = self.keyspace_misses.saturating_sub(prev.keyspace_misses);
+        if hits + misses == 0 {
+            None
+        } else {
+            Some(hits as f64 * 100.0 / (hits + misses) as f64)
+        }
+    }
+}
+
+impl Downsample for RedisInfo {
+    fn merge(&mut self, later: RedisInfo) {
+        self.used_memory = self.used_memory.max(later.used_memory);
+        self.connected_clients = self.connected_clients.max(later.connected_clients);
+        self.ops_per_sec = self.ops_per_sec.max(later.ops_per_sec);
+        // counters, the latest is the highest unless the server restarted
+        self.keyspace_hits = later.keyspace_hits;
+        self.keyspace_misses = later.keyspace_misses;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlowlogEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    pub duration_micros: i64,
+    pub command: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RedisStats {
+    pub info: RedisInfo,
+    pub slowlog: Vec<SlowlogEntry>,
+}
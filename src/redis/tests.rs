@@ -0,0 +1,80 @@
+#[test]
+fn info_parser_reads_stats() {
+    use crate::redis::info_parser::parse_info;
+    use crate::redis::model::RedisInfo;
+
+    let info = "# Server\r
+redis_version:5.0.7\r
+\r
+# Clients\r
+connected_clients:12\r
+\r
+# Memory\r
+used_memory:1048576\r
+used_memory_human:1.00M\r
+\r
+# Stats\r
+instantaneous_ops_per_sec:340\r
+keyspace_hits:90\r
+keyspace_misses:10\r
+";
+
+    let result = RedisInfo {
+        used_memory: 1048576,
+        connected_clients: 12,
+        ops_per_sec: 340,
+        keyspace_hits: 90,
+        keyspace_misses: 10,
+    };
+    assert_eq!(parse_info(info), Ok(result));
+}
+
+#[test]
+fn info_parser_missing_field() {
+    use crate::redis::info_parser::parse_info;
+
+    assert_eq!(
+        parse_info("connected_clients:12"),
+        Err("Missing used_memory in INFO response".to_owned())
+    );
+}
+
+#[test]
+fn slowlog_parser_reads_entries() {
+    use redis_protocol::types::Frame;
+    use crate::redis::info_parser::parse_slowlog;
+    use crate::redis::model::SlowlogEntry;
+
+    let frame = Frame::Array(vec![
+        Frame::Array(vec![
+            Frame::Integer(14),
+            Frame::Integer(1309448221),
+            Frame::Integer(15),
+            Frame::Array(vec![Frame::BulkString("ping".into())]),
+        ]),
+        Frame::Array(vec![
+            Frame::Integer(13),
+            Frame::Integer(1309448128),
+            Frame::Integer(30),
+            Frame::Array(vec![Frame::BulkString("slowlog".into()), Frame::BulkString("get".into())]),
+            Frame::BulkString("127.0.0.1:58217".into()),
+            Frame::BulkString("worker-123".into()),
+        ]),
+    ]);
+
+    assert_eq!(parse_slowlog(&frame), Ok(vec![
+        SlowlogEntry { id: 14, timestamp: 1309448221, duration_micros: 15, command: "ping".to_owned() },
+        SlowlogEntry { id: 13, timestamp: 1309448128, duration_micros: 30, command: "slowlog get".to_owned() },
+    ]));
+    assert!(parse_slowlog(&Frame::Integer(1)).is_err());
+}
+
+#[test]
+fn hit_rate_since_previous_sample() {
+    use crate::redis::model::RedisInfo;
+
+    let prev = RedisInfo { used_memory: 0, connected_clients: 0, ops_per_sec: 0, keyspace_hits: 90, keyspace_misses: 10 };
+    let next = RedisInfo { keyspace_hits: 120, keyspace_misses: 20, ..prev.clone() };
+    assert_eq!(next.hit_rate_since(&prev), Some(75.0));
+    assert_eq!(prev.hit_rate_since(&prev), None);
+}
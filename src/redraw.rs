@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+
+///
+/// When the terminal is redrawn: once an event other than a tick has been handled, or once in a while to refresh durations.
+/// Events that queued up during a redraw are handled before the next one, but for a tick at most,
+/// so that a flood of events, e.g. the dumps of a burst capture, doesn't keep the screen from updating.
+pub struct Redraw {
+    interval: Duration,
+    tick_rate: Duration,
+    last_draw: Option<Instant>,
+    dirty: bool,
+}
+
+impl Redraw {
+    pub fn new(interval: Duration, tick_rate: Duration) -> Redraw {
+        Redraw { interval, tick_rate, last_draw: None, dirty: true }
+    }
+
+    pub fn on_event(&mut self, is_tick: bool) {
+        self.dirty = self.dirty || !is_tick;
+    }
+
+    /// Whether to redraw before handling the next event, `queued` telling whether it's already waiting
+    pub fn is_due(&self, now: Instant, queued: bool) -> bool {
+        match self.last_draw.map(|t| now.duration_since(t)) {
+            None => true,
+            Some(since) => (self.dirty || since >= self.interval) && (!queued || since >= self.tick_rate),
+        }
+    }
+
+    pub fn drawn(&mut self, now: Instant) {
+        self.last_draw = Some(now);
+        self.dirty = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::redraw::Redraw;
+
+    #[test]
+    fn redraws_once_per_tick_under_event_flood() {
+        let tick = Duration::from_millis(250);
+        let mut redraw = Redraw::new(Duration::from_secs(1), tick);
+        let start = Instant::now();
+        assert!(redraw.is_due(start, true));
+        redraw.drawn(start);
+
+        // a constant flood of fetcher responses, each queued behind the previous one
+        let mut draws = 0;
+        for ms in (10..=1000).step_by(10) {
+            redraw.on_event(false);
+            let now = start + Duration::from_millis(ms);
+            if redraw.is_due(now, true) {
+                redraw.drawn(now);
+                draws += 1;
+            }
+        }
+        assert_eq!(draws, 4);
+
+        // nothing queued, the changes are drawn right away
+        redraw.on_event(false);
+        assert!(redraw.is_due(start + Duration::from_millis(1010), false));
+        redraw.drawn(start + Duration::from_millis(1010));
+        // ticks alone only redraw once the interval is over
+        redraw.on_event(true);
+        assert!(!redraw.is_due(start + Duration::from_millis(1500), false));
+        assert!(redraw.is_due(start + Duration::from_millis(2010), false));
+    }
+}
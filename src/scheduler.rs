@@ -0,0 +1,213 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, mpsc};
+use std::sync::mpsc::{RecvTimeoutError, SendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::fetcher::{Fetcher, FetcherRequest, FetcherResponse};
+
+///
+/// Runs fetcher requests on a fixed pool of worker threads.
+///
+/// Each source is pinned to a worker, which owns the connection to it, so a slow source
+/// only holds up the sources sharing its worker. Sources are keyed by `FetcherRequest::worker`,
+/// each data source being a source of its own. Regular polls of the sources are staggered
+/// evenly over the poll interval instead of all firing at the start of a tick.
+pub struct Scheduler {
+    workers: Vec<Worker>,
+    sources: Vec<String>,
+    interval: Duration,
+    spawn: Spawn,
+}
+
+/// Queue of a worker, each request being run once it's due
+type Worker = mpsc::Sender<(Instant, FetcherRequest)>;
+
+/// Starts the workers of the given sources
+type Spawn = Box<dyn Fn(&[String]) -> Vec<Worker> + Send>;
+
+impl Scheduler {
+    ///
+    /// Starts `pool_size` workers (but no more than there are sources). Each of them builds its
+    /// own fetcher with `connect`, passing the sources assigned to it, and passes every response
+    /// along with when it was captured and the time it took to fetch to `respond`.
+    pub fn start<C, R>(sources: Vec<String>, pool_size: usize, interval: Duration, connect: C, respond: R) -> Scheduler
+        where C: Fn(&[String]) -> Result<Fetcher, String> + Send + Sync + 'static,
+              R: Fn(FetcherResponse, Instant, Duration) + Send + Clone + 'static {
+        let connect = Arc::new(connect);
+        let spawn = move |sources: &[String]| -> Vec<Worker> {
+            let pool_size = pool_size.max(1).min(sources.len().max(1));
+            (0..pool_size)
+                .map(|w| {
+                    let assigned: Vec<String> = sources.iter()
+                        .enumerate()
+                        .filter(|(slot, _)| slot % pool_size == w)
+                        .map(|(_, s)| s.to_owned())
+                        .collect();
+                    let (tx, rx) = mpsc::channel();
+                    let connect = connect.clone();
+                    let respond = respond.clone();
+                    thread::spawn(move || run_worker(connect(&assigned), rx, respond));
+                    tx
+                })
+                .collect()
+        };
+        let workers = spawn(&sources);
+        Scheduler { workers, sources, interval, spawn: Box::new(spawn) }
+    }
+
+    ///
+    /// Replaces the workers with new ones, connecting to their sources again, e.g. after they couldn't at startup.
+    /// The previous workers stop once they run out of requests.
+    pub fn reconnect(&mut self) {
+        self.workers = (self.spawn)(&self.sources);
+    }
+
+    ///
+    /// Adds a source connected while running and spreads the sources over the workers again.
+    /// As with `reconnect`, the workers are replaced, so every source is connected to again.
+    pub fn add_source(&mut self, source: &str) {
+        if !self.sources.iter().any(|s| s == source) {
+            self.sources.push(source.to_owned());
+        }
+        self.reconnect();
+    }
+
+    /// Sends a request right away, e.g. one triggered by the user
+    pub fn send(&self, request: FetcherRequest) -> Result<(), SendError<(Instant, FetcherRequest)>> {
+        self.dispatch(request, Duration::from_millis(0))
+    }
+
+    /// Sends a regular poll, delayed by the phase of its source within the poll interval
+    pub fn poll(&self, request: FetcherRequest) -> Result<(), SendError<(Instant, FetcherRequest)>> {
+        let slot = self.slot(&request.worker());
+        self.dispatch(request, phase(slot, self.sources.len(), self.interval))
+    }
+
+    fn slot(&self, source: &str) -> usize {
+        self.sources.iter().position(|s| *s == source).unwrap_or(0)
+    }
+
+    fn dispatch(&self, request: FetcherRequest, delay: Duration) -> Result<(), SendError<(Instant, FetcherRequest)>> {
+        let worker = self.slot(&request.worker()) % self.workers.len();
+        self.workers[worker].send((Instant::now() + delay, request))
+    }
+}
+
+/// Offset of a source's polls within the interval, spreading `count` sources evenly
+fn phase(slot: usize, count: usize, interval: Duration) -> Duration {
+    if count == 0 {
+        Duration::from_millis(0)
+    } else {
+        interval * slot as u32 / count as u32
+    }
+}
+
+/// Requests waiting for their due time, in the order they are due, keeping the send order for equal ones
+struct DueQueue<T> {
+    items: VecDeque<(Instant, T)>,
+}
+
+impl<T> DueQueue<T> {
+    fn new() -> DueQueue<T> {
+        DueQueue { items: VecDeque::new() }
+    }
+
+    fn push(&mut self, due: Instant, item: T) {
+        let i = self.items.iter().position(|(d, _)| *d > due).unwrap_or(self.items.len());
+        self.items.insert(i, (due, item));
+    }
+
+    fn pop_due(&mut self, now: Instant) -> Option<T> {
+        match self.items.front() {
+            Some((due, _)) if *due <= now => self.items.pop_front().map(|(_, x)| x),
+            _ => None,
+        }
+    }
+
+    fn time_to_next(&self, now: Instant) -> Option<Duration> {
+        self.items.front().map(|(due, _)| due.saturating_duration_since(now))
+    }
+}
+
+fn run_worker<R: Fn(FetcherResponse, Instant, Duration)>(fetcher: Result<Fetcher, String>, rx: mpsc::Receiver<(Instant, FetcherRequest)>, respond: R) {
+    let mut queue = DueQueue::new();
+    loop {
+        let received = match queue.time_to_next(Instant::now()) {
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(timeout) => rx.recv_timeout(timeout),
+        };
+        match received {
+            Ok((due, request)) => queue.push(due, request),
+            Err(RecvTimeoutError::Timeout) => {}
+            // replaced by new workers: the requests already queued are still run, their sources wait for them to poll again
+            Err(RecvTimeoutError::Disconnected) => match queue.time_to_next(Instant::now()) {
+                Some(timeout) => thread::sleep(timeout),
+                None => return,
+            },
+        }
+        while let Some(request) = queue.pop_due(Instant::now()) {
+            match &fetcher {
+                Ok(f) => {
+                    // samples are stamped as they come in, not once the UI thread gets to them
+                    let started = Instant::now();
+                    let response = f.fetch(request);
+                    let captured_at = Instant::now();
+                    respond(response, captured_at, captured_at - started)
+                }
+                Err(e) => respond(FetcherResponse::FatalFailure(request.source(), e.to_owned()), Instant::now(), Duration::from_millis(0)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    use crate::fetcher::{FetcherRequest, FetcherResponse};
+    use crate::scheduler::{DueQueue, phase, Scheduler};
+
+    #[test]
+    fn staggers_sources_over_interval() {
+        let interval = Duration::from_millis(2000);
+        assert_eq!(phase(0, 4, interval), Duration::from_millis(0));
+        assert_eq!(phase(1, 4, interval), Duration::from_millis(500));
+        assert_eq!(phase(3, 4, interval), Duration::from_millis(1500));
+        assert_eq!(phase(0, 0, interval), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn pops_requests_when_due_in_order() {
+        let now = Instant::now();
+        let mut queue = DueQueue::new();
+        queue.push(now + Duration::from_millis(500), "redis");
+        queue.push(now, "hikari");
+        queue.push(now, "slick");
+
+        assert_eq!(queue.time_to_next(now), Some(Duration::from_millis(0)));
+        assert_eq!(queue.pop_due(now), Some("hikari"));
+        assert_eq!(queue.pop_due(now), Some("slick"));
+        assert_eq!(queue.pop_due(now), None);
+        assert_eq!(queue.time_to_next(now), Some(Duration::from_millis(500)));
+        assert_eq!(queue.pop_due(now + Duration::from_millis(500)), Some("redis"));
+    }
+
+    #[test]
+    fn runs_queued_polls_of_replaced_workers() {
+        let (tx, rx) = mpsc::channel();
+        let respond = move |response: FetcherResponse, _: Instant, _: Duration| { let _ = tx.send(response); };
+        let sources = vec!["zmx".to_owned(), "jmx".to_owned()];
+        let mut scheduler = Scheduler::start(sources, 2, Duration::from_millis(400), |_: &[String]| Err("Connection refused".to_owned()), respond);
+
+        // jmx is polled half an interval in, redis gets connected meanwhile
+        scheduler.poll(FetcherRequest::JVMRuntime).unwrap();
+        scheduler.add_source("redis");
+
+        match rx.recv_timeout(Duration::from_secs(2)) {
+            Ok(FetcherResponse::FatalFailure(source, e)) => assert_eq!((source, e.as_str()), ("jmx", "Connection refused")),
+            _ => panic!("the queued poll of jmx was dropped"),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::history::{History, Retention};
+use crate::script::expr::{self, Expr};
+
+///
+/// Series computed from other metrics, defined with `derived` in the config file.
+///
+/// eg.
+///   ```text
+///   { "name": "hikari_wait_ratio", "expr": "hikari_waiting / hikari_total" }
+///   ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "DerivedMetricDef")]
+pub struct DerivedMetric {
+    pub name: String,
+    pub source: String,
+    pub expr: Expr,
+}
+
+#[derive(Deserialize)]
+struct DerivedMetricDef {
+    name: String,
+    expr: String,
+}
+
+impl TryFrom<DerivedMetricDef> for DerivedMetric {
+    type Error = String;
+
+    fn try_from(def: DerivedMetricDef) -> Result<DerivedMetric, String> {
+        let expr = expr::parse(&def.expr).map_err(|e| format!("derived metric {}: {}", def.name, e))?;
+        Ok(DerivedMetric { name: def.name, source: def.expr, expr })
+    }
+}
+
+/// History of the derived metrics, evaluated on each new sample
+pub struct DerivedSeries {
+    pub metrics: Vec<DerivedMetric>,
+    pub values: Vec<History<f64>>,
+    pub latest: Vec<Option<f64>>,
+}
+
+impl DerivedSeries {
+    pub fn new(metrics: Vec<DerivedMetric>, retention: Retention) -> DerivedSeries {
+        DerivedSeries {
+            values: metrics.iter().map(|_| History::new(retention)).collect(),
+            latest: metrics.iter().map(|_| None).collect(),
+            metrics,
+        }
+    }
+
+    ///
+    /// Evaluates the metrics in the config order and adds their values to `vars`,
+    /// so a metric can refer to the ones defined before it.
+    /// Metrics that can't be computed yet are left out of the history.
+    pub fn on_sample(&mut self, vars: &mut HashMap<String, f64>, at: Instant) {
+        for (i, m) in self.metrics.iter().enumerate() {
+            let value = m.expr.eval(vars);
+            if let Some(x) = value {
+                self.values[i].push_back_at(x, at);
+                vars.insert(m.name.to_owned(), x);
+            }
+            self.latest[i] = value;
+        }
+    }
+
+    /// Latest computed values by metric name
+    pub fn vars(&self) -> Vec<(&str, f64)> {
+        self.metrics.iter()
+            .zip(self.latest.iter())
+            .filter_map(|(m, v)| v.map(|x| (m.name.as_str(), x)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    use crate::history::Retention;
+    use crate::script::derived::{DerivedMetric, DerivedSeries};
+
+    #[test]
+    fn evaluates_metrics_in_order() {
+        let metrics: Vec<DerivedMetric> = serde_json::from_str(r#"[
+            { "name": "wait_ratio", "expr": "waiting / total" },
+            { "name": "wait_percent", "expr": "wait_ratio * 100" }
+        ]"#).unwrap();
+        let mut series = DerivedSeries::new(metrics, Retention::default());
+        let start = Instant::now();
+        let mut vars = HashMap::new();
+
+        vars.insert("waiting".to_owned(), 1.0);
+        vars.insert("total".to_owned(), 4.0);
+        series.on_sample(&mut vars, start);
+        assert_eq!(series.latest, vec![Some(0.25), Some(25.0)]);
+        assert_eq!(vars["wait_percent"], 25.0);
+
+        let mut vars = HashMap::new();
+        vars.insert("waiting".to_owned(), 1.0);
+        series.on_sample(&mut vars, start + Duration::from_secs(2));
+        assert_eq!(series.latest, vec![None, None]);
+        assert_eq!(series.values[0].len(), 1);
+        assert!(series.vars().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_expression() {
+        let result = serde_json::from_str::<Vec<DerivedMetric>>(r#"[{ "name": "x", "expr": "a +" }]"#);
+
+        assert!(result.unwrap_err().to_string().starts_with("derived metric x:"));
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+///
+/// Arithmetic/boolean expression over named metric values, e.g. `suspended / running > 3`.
+///
+/// Booleans are represented as 1.0 (true) and 0.0 (false).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, Op, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl Expr {
+    ///
+    /// Evaluates the expression against given variables.
+    ///
+    /// Returns None if some variable is not known yet or on division by zero,
+    /// so that hooks don't fire on incomplete data.
+    pub fn eval(&self, vars: &HashMap<String, f64>) -> Option<f64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Var(name) => vars.get(name).cloned(),
+            Expr::Neg(e) => e.eval(vars).map(|x| -x),
+            Expr::Binary(l, op, r) => {
+                let a = l.eval(vars)?;
+                let b = r.eval(vars)?;
+                let bool = |x: bool| if x { 1.0 } else { 0.0 };
+                match op {
+                    Op::Add => Some(a + b),
+                    Op::Sub => Some(a - b),
+                    Op::Mul => Some(a * b),
+                    Op::Div => if b == 0.0 { None } else { Some(a / b) },
+                    Op::Gt => Some(bool(a > b)),
+                    Op::Ge => Some(bool(a >= b)),
+                    Op::Lt => Some(bool(a < b)),
+                    Op::Le => Some(bool(a <= b)),
+                    Op::Eq => Some(bool(a == b)),
+                    Op::Ne => Some(bool(a != b)),
+                    Op::And => Some(bool(a != 0.0 && b != 0.0)),
+                    Op::Or => Some(bool(a != 0.0 || b != 0.0)),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(Op),
+    LParen,
+    RParen,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).cloned();
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let n: String = chars[start..i].iter().collect();
+            let n = n.parse::<f64>().map_err(|_| format!("Invalid number: {}", n))?;
+            tokens.push(Token::Number(n));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let (token, len) = match (c, next) {
+                ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+                ('<', Some('=')) => (Token::Op(Op::Le), 2),
+                ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+                ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+                ('&', Some('&')) => (Token::Op(Op::And), 2),
+                ('|', Some('|')) => (Token::Op(Op::Or), 2),
+                ('>', _) => (Token::Op(Op::Gt), 1),
+                ('<', _) => (Token::Op(Op::Lt), 1),
+                ('+', _) => (Token::Op(Op::Add), 1),
+                ('-', _) => (Token::Op(Op::Sub), 1),
+                ('*', _) => (Token::Op(Op::Mul), 1),
+                ('/', _) => (Token::Op(Op::Div), 1),
+                ('(', _) => (Token::LParen, 1),
+                (')', _) => (Token::RParen, 1),
+                _ => return Err(format!("Unexpected character '{}'", c)),
+            };
+            tokens.push(token);
+            i += len;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Binary operators grouped by precedence, loosest first
+const PRECEDENCE: [&[Op]; 5] = [
+    &[Op::Or],
+    &[Op::And],
+    &[Op::Gt, Op::Ge, Op::Lt, Op::Le, Op::Eq, Op::Ne],
+    &[Op::Add, Op::Sub],
+    &[Op::Mul, Op::Div],
+];
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Op(op)) = self.peek().cloned() {
+            if !PRECEDENCE[level].contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(Box::new(left), op, Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if let Some(Token::Op(Op::Sub)) = self.peek() {
+            self.pos += 1;
+            return self.unary().map(|e| Expr::Neg(Box::new(e)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Ident(name)) => Ok(Expr::Var(name)),
+            Some(Token::LParen) => {
+                let e = self.binary(0)?;
+                match self.peek() {
+                    Some(Token::RParen) => {
+                        self.pos += 1;
+                        Ok(e)
+                    }
+                    _ => Err("Missing closing parenthesis".to_owned()),
+                }
+            }
+            Some(t) => Err(format!("Unexpected token {:?}", t)),
+            None => Err("Unexpected end of expression".to_owned()),
+        }
+    }
+}
+
+pub fn parse(s: &str) -> Result<Expr, String> {
+    let mut parser = Parser { tokens: tokenize(s)?, pos: 0 };
+    let e = parser.binary(0)?;
+    match parser.peek() {
+        None => Ok(e),
+        Some(t) => Err(format!("Unexpected token {:?}", t)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::script::expr::{Expr, Op, parse};
+
+    fn vars() -> HashMap<String, f64> {
+        let mut vars = HashMap::new();
+        vars.insert("running".to_owned(), 2.0);
+        vars.insert("suspended".to_owned(), 7.0);
+        vars
+    }
+
+    #[test]
+    fn parses_with_precedence() {
+        assert_eq!(parse("1 + 2 * x").unwrap(), Expr::Binary(
+            Box::new(Expr::Number(1.0)),
+            Op::Add,
+            Box::new(Expr::Binary(Box::new(Expr::Number(2.0)), Op::Mul, Box::new(Expr::Var("x".to_owned())))),
+        ));
+        assert_eq!(parse("(1 + 2) * 3").unwrap().eval(&vars()), Some(9.0));
+        assert_eq!(parse("-2 - -3").unwrap().eval(&vars()), Some(1.0));
+        assert_eq!(parse("8 / 2 / 2").unwrap().eval(&vars()), Some(2.0));
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        assert_eq!(parse("suspended / running > 3").unwrap().eval(&vars()), Some(1.0));
+        assert_eq!(parse("suspended / running > 4").unwrap().eval(&vars()), Some(0.0));
+        assert_eq!(parse("running == 2 && suspended >= 7 || 0").unwrap().eval(&vars()), Some(1.0));
+    }
+
+    #[test]
+    fn undefined_values() {
+        assert_eq!(parse("unknown + 1").unwrap().eval(&vars()), None);
+        assert_eq!(parse("running / 0").unwrap().eval(&vars()), None);
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert!(parse("1 +").is_err());
+        assert!(parse("(1 + 2").is_err());
+        assert!(parse("1 2").is_err());
+        assert!(parse("a $ b").is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+
+use regex::Regex;
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use crate::logging::utc_time;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HookKind {
+    /// Fires while the condition holds
+    Alert,
+    /// Computes a value on each sample
+    Series,
+    /// Adds a note to the log each time the condition starts to hold
+    Annotate,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hook {
+    pub kind: HookKind,
+    pub name: String,
+    pub source: String,
+    pub script: AST,
+    /// Value of the script on the latest sample, None while some metric it refers to isn't known
+    pub value: Option<f64>,
+}
+
+impl Hook {
+    /// Names the script refers to, some of them being metrics
+    pub fn vars(&self) -> Vec<&str> {
+        let identifier = Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap();
+        identifier.find_iter(&self.source).map(|m| m.as_str()).collect()
+    }
+
+    pub fn is_active(&self) -> bool {
+        match self.kind {
+            HookKind::Series => false,
+            _ => matches!(self.value, Some(v) if v != 0.0),
+        }
+    }
+}
+
+///
+/// User-defined hooks, evaluated on each new sample.
+///
+/// A script contains one hook per line (lines starting with `#` are comments), each hook being
+/// a Rhai script with the latest metric values as variables:
+///
+///   ```text
+///   alert too many suspended: fibers_suspended / fibers_running > 3
+///   series wait_ratio = hikari_waiting / hikari_total
+///   annotate pool exhausted: hikari_idle == 0
+///   alert slow and busy: let busy = hikari_active / hikari_total; busy > 0.9 && latency_jmx_ms > 500
+///   ```
+pub struct Hooks {
+    pub hooks: Vec<Hook>,
+    pub log: VecDeque<String>,
+    engine: Engine,
+}
+
+impl Hooks {
+    pub const MAX_LOG_ENTRIES: usize = 100;
+    /// Hooks run on the UI thread, a script running longer than this is stopped
+    const MAX_OPERATIONS: u64 = 10_000;
+
+    pub fn new(hooks: Vec<Hook>) -> Hooks {
+        let mut engine = Engine::new();
+        engine.set_max_operations(Hooks::MAX_OPERATIONS);
+        Hooks { hooks, log: VecDeque::new(), engine }
+    }
+
+    pub fn load(path: &str) -> Result<Hooks, String> {
+        let script = fs::read_to_string(path)
+            .map_err(|e| format!("Couldn't read script {}: {}", path, e))?;
+        parse_script(&script)
+            .map(Hooks::new)
+            .map_err(|e| format!("Invalid script {}: {}", path, e))
+    }
+
+    ///
+    /// Re-evaluates all hooks against the latest metric values, returns the names of alerts that started firing.
+    /// A hook referring to a metric that isn't known has no value, so an alert stops firing while its metrics are unavailable.
+    pub fn on_sample(&mut self, vars: &HashMap<String, f64>) -> Vec<String> {
+        let mut scope = Scope::new();
+        for (name, value) in vars {
+            scope.push_constant(name.as_str(), *value);
+        }
+        let mut fired: Vec<String> = vec![];
+        let mut alerts: Vec<String> = vec![];
+        for hook in self.hooks.iter_mut() {
+            let was_active = hook.is_active();
+            hook.value = self.engine.eval_ast_with_scope::<Dynamic>(&mut scope.clone(), &hook.script)
+                .ok()
+                .and_then(to_number);
+            if !was_active && hook.is_active() {
+                match hook.kind {
+                    HookKind::Alert => {
+                        fired.push(format!("ALERT {}", hook.name));
+                        alerts.push(hook.name.to_owned());
+                    }
+                    HookKind::Annotate => fired.push(hook.name.to_owned()),
+                    HookKind::Series => {}
+                }
+            }
+        }
+        for msg in fired {
+            self.append_log(msg);
+        }
+        alerts
+    }
+
+    pub fn active_alerts(&self) -> Vec<&str> {
+        self.hooks.iter()
+            .filter(|h| h.kind == HookKind::Alert && h.is_active())
+            .map(|h| h.name.as_str())
+            .collect()
+    }
+
+    fn append_log(&mut self, msg: String) {
+        if self.log.len() >= Hooks::MAX_LOG_ENTRIES {
+            self.log.pop_front();
+        }
+        self.log.push_back(format!("{} {}", utc_time(), msg));
+    }
+}
+
+/// Booleans are 1.0 (true) and 0.0 (false), a division by zero has no value
+fn to_number(value: Dynamic) -> Option<f64> {
+    let number = if let Ok(b) = value.as_bool() {
+        if b { 1.0 } else { 0.0 }
+    } else if let Ok(i) = value.as_int() {
+        i as f64
+    } else {
+        value.as_float().ok()?
+    };
+    Some(number).filter(|x| x.is_finite())
+}
+
+pub fn parse_script(script: &str) -> Result<Vec<Hook>, String> {
+    let engine = Engine::new();
+    script.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim().starts_with('#'))
+        .map(|(n, l)| parse_hook(&engine, l.trim()).map_err(|e| format!("line {}: {}", n + 1, e)))
+        .collect()
+}
+
+fn parse_hook(engine: &Engine, line: &str) -> Result<Hook, String> {
+    let (keyword, rest) = match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], line[i..].trim()),
+        None => (line, ""),
+    };
+    let (kind, separator) = match keyword {
+        "alert" => (HookKind::Alert, ':'),
+        "series" => (HookKind::Series, '='),
+        "annotate" => (HookKind::Annotate, ':'),
+        _ => return Err(format!("unknown hook '{}', expected alert, series or annotate", keyword)),
+    };
+    let i = rest.find(separator)
+        .ok_or(format!("expected '{} <name> {} <expression>'", keyword, separator))?;
+    let name = rest[..i].trim();
+    let source = rest[i + 1..].trim();
+    if name.is_empty() {
+        return Err("hook name is empty".to_owned());
+    }
+    let script = engine.compile(source).map_err(|e| e.to_string())?;
+    Ok(Hook { kind, name: name.to_owned(), source: source.to_owned(), script, value: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::script::hooks::{HookKind, Hooks, parse_script};
+
+    #[test]
+    fn parses_script() {
+        let hooks = parse_script("
+            # comment
+            alert too many suspended: suspended / running > 3
+            series ratio = suspended / running
+        ").unwrap();
+
+        assert_eq!(hooks.len(), 2);
+        assert_eq!(hooks[0].kind, HookKind::Alert);
+        assert_eq!(hooks[0].name, "too many suspended");
+        assert_eq!(hooks[0].source, "suspended / running > 3");
+        assert_eq!(hooks[1].kind, HookKind::Series);
+        assert_eq!(hooks[1].name, "ratio");
+    }
+
+    #[test]
+    fn reports_line_of_invalid_hook() {
+        assert_eq!(parse_script("\nwatch x: 1").err(), Some("line 2: unknown hook 'watch', expected alert, series or annotate".to_owned()));
+        assert!(parse_script("alert x 1").is_err());
+        assert!(parse_script("series x = 1 +").is_err());
+    }
+
+    #[test]
+    fn fires_alerts_once_per_activation() {
+        let mut hooks = Hooks::new(parse_script("alert high: x > 3\nseries double = x * 2").unwrap());
+        let mut vars = HashMap::new();
+
+        vars.insert("x".to_owned(), 1.0);
+        assert!(hooks.on_sample(&vars).is_empty());
+        assert!(hooks.active_alerts().is_empty());
+        assert_eq!(hooks.hooks[1].value, Some(2.0));
+
+        vars.insert("x".to_owned(), 5.0);
+        assert_eq!(hooks.on_sample(&vars), vec!["high"]);
+        assert!(hooks.on_sample(&vars).is_empty());
+        assert_eq!(hooks.active_alerts(), vec!["high"]);
+        assert_eq!(hooks.log.len(), 1);
+        assert!(hooks.log[0].ends_with("ALERT high"));
+
+        vars.insert("x".to_owned(), 1.0);
+        hooks.on_sample(&vars);
+        assert!(hooks.active_alerts().is_empty());
+        vars.insert("x".to_owned(), 5.0);
+        assert_eq!(hooks.on_sample(&vars), vec!["high"]);
+    }
+
+    #[test]
+    fn has_no_value_while_metrics_are_missing() {
+        let mut hooks = Hooks::new(parse_script("alert high: x > 3\nseries ratio = x / y").unwrap());
+        let mut vars = HashMap::new();
+        vars.insert("x".to_owned(), 5.0);
+        vars.insert("y".to_owned(), 2.0);
+        assert_eq!(hooks.on_sample(&vars), vec!["high"]);
+        assert_eq!(hooks.hooks[1].value, Some(2.5));
+
+        vars.clear();
+        hooks.on_sample(&vars);
+        assert!(hooks.active_alerts().is_empty());
+        assert_eq!(hooks.hooks[0].value, None);
+        assert_eq!(hooks.hooks[1].value, None);
+
+        vars.insert("x".to_owned(), 5.0);
+        vars.insert("y".to_owned(), 0.0);
+        assert_eq!(hooks.on_sample(&vars), vec!["high"]);
+        assert_eq!(hooks.hooks[1].value, None);
+    }
+
+    #[test]
+    fn runs_rhai_scripts() {
+        let hooks = parse_script("alert busy: let busy = active / total; busy > 0.9 && latency_jmx_ms > 500").unwrap();
+        let mut hooks = Hooks::new(hooks);
+        let mut vars = HashMap::new();
+        vars.insert("active".to_owned(), 19.0);
+        vars.insert("total".to_owned(), 20.0);
+        vars.insert("latency_jmx_ms".to_owned(), 800.0);
+
+        assert_eq!(hooks.on_sample(&vars), vec!["busy"]);
+        assert!(hooks.hooks[0].vars().contains(&"latency_jmx_ms"));
+    }
+}
@@ -0,0 +1,3 @@
+pub mod derived;
+pub mod expr;
+pub mod hooks;
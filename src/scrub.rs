@@ -0,0 +1,117 @@
+use std::convert::TryFrom;
+
+use regex::Regex;
+use serde::Deserialize;
+
+///
+/// Pattern scrubbed from fiber and thread dumps before they're shared, replaced by `replacement`.
+///
+/// eg. `{ "pattern": "customer-\\d+", "replacement": "customer-<id>" }`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "ScrubRuleDef")]
+pub struct ScrubRule {
+    pub pattern: Regex,
+    /// May refer to capture groups of the pattern, e.g. `$1`
+    pub replacement: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScrubRuleDef {
+    pattern: String,
+    #[serde(default = "default_replacement")]
+    replacement: String,
+}
+
+fn default_replacement() -> String {
+    "***".to_owned()
+}
+
+impl TryFrom<ScrubRuleDef> for ScrubRule {
+    type Error = String;
+
+    fn try_from(def: ScrubRuleDef) -> Result<ScrubRule, String> {
+        let pattern = Regex::new(&def.pattern).map_err(|e| format!("invalid scrub pattern: {}", e))?;
+        Ok(ScrubRule { pattern, replacement: def.replacement })
+    }
+}
+
+impl PartialEq for ScrubRule {
+    fn eq(&self, other: &ScrubRule) -> bool {
+        self.pattern.as_str() == other.pattern.as_str() && self.replacement == other.replacement
+    }
+}
+
+/// Scrubs exported and uploaded dumps while it's turned on with `x`
+#[derive(Default)]
+pub struct Scrubber {
+    rules: Vec<ScrubRule>,
+    pub enabled: bool,
+}
+
+impl Scrubber {
+    pub fn new(rules: Vec<ScrubRule>) -> Scrubber {
+        Scrubber { rules, enabled: false }
+    }
+
+    /// Turns scrubbing on or off, returning a notice of the outcome
+    pub fn toggle(&mut self) -> String {
+        if self.rules.is_empty() {
+            return "No patterns to scrub, set `scrub` in the config file".to_owned();
+        }
+        self.enabled = !self.enabled;
+        if self.enabled {
+            format!("Scrubbing {} patterns from exported dumps", self.rules.len())
+        } else {
+            "Exporting dumps as they are".to_owned()
+        }
+    }
+
+    /// The text with every match of each pattern replaced in turn, or as is when scrubbing is off
+    pub fn scrub(&self, text: &str) -> String {
+        if !self.enabled {
+            return text.to_owned();
+        }
+        self.rules.iter().fold(text.to_owned(), |text, rule| {
+            rule.pattern.replace_all(&text, rule.replacement.as_str()).into_owned()
+        })
+    }
+
+    /// Marks the name of an exported file as scrubbed, e.g. `thread-dump-1600000000-scrubbed`
+    pub fn file_name(&self, name: &str) -> String {
+        if self.enabled { format!("{}-scrubbed", name) } else { name.to_owned() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scrub::{ScrubRule, Scrubber};
+
+    #[test]
+    fn scrubs_patterns_only_when_enabled() {
+        let rules: Vec<ScrubRule> = serde_json::from_str(r#"[
+            { "pattern": "[\\w-]+\\.internal(:\\d+)?", "replacement": "<host>$1" },
+            { "pattern": "customer-\\d+" },
+            { "pattern": "'[^']*'", "replacement": "'?'" }
+        ]"#).unwrap();
+        let mut scrubber = Scrubber::new(rules);
+        let dump = "at db.internal:5432 for customer-1234: select * from orders where email = 'jane@example.com'";
+
+        assert_eq!(scrubber.scrub(dump), dump);
+        assert_eq!(scrubber.file_name("thread-dump-1"), "thread-dump-1");
+
+        scrubber.toggle();
+        assert_eq!(scrubber.scrub(dump), "at <host>:5432 for ***: select * from orders where email = '?'");
+        assert_eq!(scrubber.file_name("thread-dump-1"), "thread-dump-1-scrubbed");
+
+        assert!(serde_json::from_str::<Vec<ScrubRule>>(r#"[{ "pattern": "(" }]"#).is_err());
+        assert!(serde_json::from_str::<Vec<ScrubRule>>(r#"[{ "pattern": "x", "with": "y" }]"#).is_err());
+    }
+
+    #[test]
+    fn cannot_be_enabled_without_patterns() {
+        let mut scrubber = Scrubber::new(vec![]);
+        scrubber.toggle();
+        assert!(!scrubber.enabled);
+    }
+}
@@ -0,0 +1,426 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::app::Panel;
+use crate::history::History;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SeriesKind {
+    Raw,
+    MovingAverage,
+    RateOfChange,
+}
+
+/// Span of the time axis of a chart
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimeRange {
+    /// The last `SeriesView::TIME_WINDOW` ticks, or the whole histories in the long range view
+    Shared,
+    Last(Duration),
+    /// The whole histories, whether the long range view is on or not
+    All,
+}
+
+impl TimeRange {
+    /// Range after this one when cycling through them: the last 5 minutes, the last 15 minutes, everything
+    pub fn next(self) -> TimeRange {
+        const FIVE_MINUTES: Duration = Duration::from_secs(5 * 60);
+        match self {
+            TimeRange::Shared => TimeRange::Last(FIVE_MINUTES),
+            TimeRange::Last(d) if d <= FIVE_MINUTES => TimeRange::Last(Duration::from_secs(15 * 60)),
+            TimeRange::Last(_) => TimeRange::All,
+            TimeRange::All => TimeRange::Shared,
+        }
+    }
+}
+
+///
+/// Time range and freeze of a single chart, on top of the shared view.
+/// A frozen chart keeps ending at the time it was frozen while the other charts move on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PanelView {
+    pub range: TimeRange,
+    pub frozen_at: Option<Instant>,
+}
+
+impl Default for PanelView {
+    fn default() -> PanelView {
+        PanelView { range: TimeRange::Shared, frozen_at: None }
+    }
+}
+
+///
+/// Describes how sampled series are transformed before being charted.
+///
+/// All charts share the same time axis, ending at `now`: samples of histories are placed at the time they
+/// were captured, e.g. a sample captured 5s ago at `-5`, and other series at `-n * sample_interval` seconds
+/// for the value taken `n` ticks ago, so that series of different sources line up.
+/// The cursor, if set, points at the sample taken `cursor` ticks ago in every chart.
+/// In the long range view the axis spans `history_span` ticks, showing downsampled samples of the histories.
+#[derive(Clone, Copy, Debug)]
+pub struct SeriesView {
+    pub kind: SeriesKind,
+    pub sample_interval: Duration,
+    pub cursor: Option<usize>,
+    pub long_range: bool,
+    /// Ticks covered by the histories, see `Retention::span`
+    pub history_span: usize,
+    /// End of the time axis, see `as_of`
+    pub now: Instant,
+    pub range: TimeRange,
+    /// Whether the chart is frozen, `now` being the time it was frozen at
+    pub frozen: bool,
+}
+
+impl SeriesView {
+    /// Amount of samples averaged by the moving average view
+    pub const MOVING_AVERAGE_WINDOW: usize = 5;
+    /// Amount of most recent samples shown on the time axis
+    pub const TIME_WINDOW: usize = 100;
+
+    pub fn new(sample_interval: Duration) -> SeriesView {
+        SeriesView {
+            kind: SeriesKind::Raw,
+            sample_interval,
+            cursor: None,
+            long_range: false,
+            history_span: SeriesView::TIME_WINDOW,
+            now: Instant::now(),
+            range: TimeRange::Shared,
+            frozen: false,
+        }
+    }
+
+    /// Same view with the time axis ending at the given time, usually when drawing
+    pub fn as_of(self, now: Instant) -> SeriesView {
+        SeriesView { now, ..self }
+    }
+
+    /// View of a chart with its own time range, ending at the time it was frozen if it is
+    pub fn for_panel(self, panel: &PanelView) -> SeriesView {
+        match panel.frozen_at {
+            Some(at) => SeriesView { range: panel.range, now: at, frozen: true, ..self },
+            None => SeriesView { range: panel.range, ..self },
+        }
+    }
+
+    pub fn toggle_long_range(&mut self) {
+        self.long_range = !self.long_range;
+    }
+
+    /// Amount of ticks shown on the time axis
+    fn window(&self) -> usize {
+        match self.range {
+            TimeRange::Shared if !self.long_range => SeriesView::TIME_WINDOW,
+            TimeRange::Shared | TimeRange::All => self.history_span.max(SeriesView::TIME_WINDOW),
+            TimeRange::Last(d) => ((d.as_secs_f64() / self.sample_interval.as_secs_f64()).round() as usize + 1).max(2),
+        }
+    }
+
+    /// Switches to the given kind, or back to raw values if it's already active
+    pub fn toggle(&mut self, kind: SeriesKind) {
+        self.kind = if self.kind == kind { SeriesKind::Raw } else { kind };
+    }
+
+    pub fn apply(&self, data: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+        match self.kind {
+            SeriesKind::Raw => data,
+            SeriesKind::MovingAverage => moving_average(&data, SeriesView::MOVING_AVERAGE_WINDOW),
+            SeriesKind::RateOfChange => rate_of_change(&data),
+        }
+    }
+
+    pub fn title_suffix(&self) -> String {
+        let kind = match self.kind {
+            SeriesKind::Raw => "",
+            SeriesKind::MovingAverage => " [moving avg]",
+            SeriesKind::RateOfChange => " [rate/s]",
+        };
+        let range = match self.range {
+            TimeRange::Shared if self.long_range => " [long range]".to_owned(),
+            TimeRange::Shared => "".to_owned(),
+            TimeRange::Last(d) => format!(" [last {}m]", d.as_secs() / 60),
+            TimeRange::All => " [all]".to_owned(),
+        };
+        let frozen = if self.frozen { " [frozen]" } else { "" };
+        match self.cursor {
+            Some(n) => format!("{}{}{} @ {}", kind, range, frozen, self.time_label(n)),
+            None => format!("{}{}{}", kind, range, frozen),
+        }
+    }
+
+    /// Places the samples of a history shown on the time axis at their capture time, downsampled ones included in the long range view
+    pub fn history_timeline<T, F>(&self, history: &History<T>, f: F) -> Vec<(f64, f64)>
+        where F: Fn(&T) -> f64, {
+        self.history_events(history, |x| Some(f(x)))
+    }
+
+    /// Capture times on the time axis of the shown samples of a history that `f` picks, along with what it makes of them
+    pub fn history_events<T, E, F>(&self, history: &History<T>, f: F) -> Vec<(f64, E)>
+        where F: Fn(&T) -> Option<E>, {
+        let from = self.x_bounds()[0];
+        history.points().into_iter()
+            .filter(|(at, _)| *at <= self.now)
+            .map(|(at, x)| (-self.now.saturating_duration_since(at).as_secs_f64(), x))
+            .filter(|(secs, _)| *secs >= from)
+            .filter_map(|(secs, x)| f(x).map(|e| (secs, e)))
+            .collect()
+    }
+
+    pub fn x_bounds(&self) -> [f64; 2] {
+        [-((self.window() - 1) as f64) * self.sample_interval.as_secs_f64(), 0.0]
+    }
+
+    pub fn x_labels(&self) -> [String; 3] {
+        let n = self.window() - 1;
+        [self.time_label(n), self.time_label(n / 2), self.time_label(0)]
+    }
+
+    fn time_label(&self, ticks_ago: usize) -> String {
+        let secs = ticks_ago as f64 * self.sample_interval.as_secs_f64();
+        if ticks_ago == 0 {
+            "now".to_owned()
+        } else if secs >= 600.0 {
+            format!("-{}m", axis_label((secs / 60.0).round()))
+        } else {
+            format!("-{}s", axis_label(secs))
+        }
+    }
+
+    /// Moves the cursor one sample back in time
+    pub fn cursor_back(&mut self) {
+        self.cursor = Some(self.cursor.map_or(0, |n| (n + 1).min(SeriesView::TIME_WINDOW - 1)));
+    }
+
+    /// Moves the cursor one sample forward in time, hiding it after the most recent sample
+    pub fn cursor_forward(&mut self) {
+        self.cursor = match self.cursor {
+            Some(0) | None => None,
+            Some(n) => Some(n - 1),
+        };
+    }
+
+    /// Sample under the cursor (or the latest one shown), the one captured nearest to where the cursor line is drawn
+    pub fn cursor_sample<'a, T>(&self, history: &'a History<T>) -> Option<&'a T> {
+        let secs_ago = self.cursor.unwrap_or(0) as f64 * self.sample_interval.as_secs_f64();
+        history.points().into_iter()
+            .filter(|(at, _)| *at <= self.now)
+            .map(|(at, x)| ((self.now.saturating_duration_since(at).as_secs_f64() - secs_ago).abs(), x))
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))
+            .map(|(_, x)| x)
+    }
+
+    /// Points of a vertical line marking the cursor position between given y bounds
+    pub fn cursor_line(&self, y_bounds: [f64; 2]) -> Vec<(f64, f64)> {
+        match self.cursor {
+            Some(n) => vertical_line(-(n as f64) * self.sample_interval.as_secs_f64(), y_bounds),
+            None => vec![],
+        }
+    }
+}
+
+/// Points of a vertical line at the given time between given y bounds, e.g. to mark an event on a chart
+pub fn vertical_line(x: f64, y_bounds: [f64; 2]) -> Vec<(f64, f64)> {
+    const POINTS: usize = 50;
+    (0..=POINTS)
+        .map(|i| (x, y_bounds[0] + (y_bounds[1] - y_bounds[0]) * i as f64 / POINTS as f64))
+        .collect()
+}
+
+/// Views of the charts: the shared one, and the ones of panels with their own time range or freeze
+pub struct ChartViews {
+    pub shared: SeriesView,
+    pub panels: HashMap<Panel, PanelView>,
+}
+
+impl ChartViews {
+    pub fn of(&self, panel: Panel) -> SeriesView {
+        self.panels.get(&panel).map_or(self.shared, |p| self.shared.for_panel(p))
+    }
+}
+
+/// Averages each point with up to `window - 1` preceding points
+pub fn moving_average(data: &[(f64, f64)], window: usize) -> Vec<(f64, f64)> {
+    let window = window.max(1);
+    data.iter().enumerate()
+        .map(|(i, (x, _))| {
+            let from = (i + 1).saturating_sub(window);
+            let points = &data[from..=i];
+            (*x, points.iter().map(|p| p.1).sum::<f64>() / points.len() as f64)
+        })
+        .collect()
+}
+
+/// Per-second change between consecutive points over the seconds between them, e.g. more of them for downsampled points.
+/// Points at the same time as the previous one are skipped.
+pub fn rate_of_change(data: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    data.windows(2)
+        .filter(|w| w[1].0 > w[0].0)
+        .map(|w| (w[1].0, (w[1].1 - w[0].1) / (w[1].0 - w[0].0)))
+        .collect()
+}
+
+/// Minimal and maximal values across all given series, (0, 0) if there is no data
+pub fn y_bounds(series: &[&[(f64, f64)]]) -> (f64, f64) {
+    series.iter()
+        .flat_map(|s| s.iter().map(|p| p.1))
+        .fold(None, |acc: Option<(f64, f64)>, y| match acc {
+            None => Some((y, y)),
+            Some((lo, hi)) => Some((lo.min(y), hi.max(y))),
+        })
+        .unwrap_or((0.0, 0.0))
+}
+
+/// Formats a value for a chart axis, omitting fractions for whole numbers
+pub fn axis_label(v: f64) -> String {
+    if v.fract() == 0.0 {
+        v.to_string()
+    } else {
+        format!("{:.2}", v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::history::{History, Retention};
+    use crate::series::{moving_average, PanelView, rate_of_change, SeriesKind, SeriesView, TimeRange, y_bounds};
+
+    #[test]
+    fn moving_average_uses_available_points() {
+        let data = vec![(0.0, 2.0), (1.0, 4.0), (2.0, 6.0), (3.0, 10.0)];
+        assert_eq!(moving_average(&data, 2), vec![(0.0, 2.0), (1.0, 3.0), (2.0, 5.0), (3.0, 8.0)]);
+        assert_eq!(moving_average(&data, 1), data);
+    }
+
+    #[test]
+    fn rate_of_change_is_per_second() {
+        let data = vec![(0.0, 2.0), (2.0, 6.0), (2.0, 7.0), (6.0, 4.0)];
+        assert_eq!(rate_of_change(&data), vec![(2.0, 2.0), (6.0, -0.75)]);
+        assert_eq!(rate_of_change(&data[..1]), vec![]);
+    }
+
+    #[test]
+    fn series_view_toggles_back_to_raw() {
+        let mut view = SeriesView::new(Duration::from_secs(1));
+        view.toggle(SeriesKind::MovingAverage);
+        assert_eq!(view.kind, SeriesKind::MovingAverage);
+        view.toggle(SeriesKind::RateOfChange);
+        assert_eq!(view.kind, SeriesKind::RateOfChange);
+        view.toggle(SeriesKind::RateOfChange);
+        assert_eq!(view.kind, SeriesKind::Raw);
+    }
+
+    #[test]
+    fn timeline_ends_at_now() {
+        let start = Instant::now();
+        let mut history = History::new(Retention::default());
+        for x in 0..3 {
+            history.push_back_at(5.0 + x as f64, start + Duration::from_secs(2 * x));
+        }
+        let view = SeriesView::new(Duration::from_secs(2)).as_of(start + Duration::from_secs(4));
+        assert_eq!(view.history_timeline(&history, |x| *x), vec![(-4.0, 5.0), (-2.0, 6.0), (0.0, 7.0)]);
+        assert_eq!(view.x_bounds(), [-198.0, 0.0]);
+        assert_eq!(view.x_labels(), ["-198s".to_owned(), "-98s".to_owned(), "now".to_owned()]);
+    }
+
+    #[test]
+    fn cursor_moves_through_history() {
+        let start = Instant::now();
+        let mut history = History::new(Retention::default());
+        // the polls between 4s and 10s were missed
+        for secs in [0, 2, 4, 10].iter() {
+            history.push_back_at(*secs, start + Duration::from_secs(*secs));
+        }
+        let mut view = SeriesView::new(Duration::from_secs(2)).as_of(start + Duration::from_secs(10));
+        assert_eq!(view.cursor_sample(&history), Some(&10));
+        assert!(view.cursor_line([0.0, 10.0]).is_empty());
+
+        view.cursor_back();
+        view.cursor_back();
+        assert_eq!(view.cursor, Some(1));
+        assert_eq!(view.cursor_sample(&history), Some(&10));
+        assert_eq!(view.title_suffix(), " @ -2s");
+        assert!(view.cursor_line([0.0, 10.0]).iter().all(|p| p.0 == -2.0));
+
+        view.cursor_back();
+        view.cursor_back();
+        assert_eq!(view.cursor_sample(&history), Some(&4));
+
+        view.cursor_forward();
+        view.cursor_forward();
+        view.cursor_forward();
+        view.cursor_forward();
+        assert_eq!(view.cursor, None);
+        assert_eq!(view.cursor_sample(&History::<u64>::new(Retention::default())), None);
+    }
+
+    #[test]
+    fn long_range_shows_downsampled_history() {
+        let start = Instant::now();
+        let mut history = History::new(Retention { full_resolution: 100, downsample_every: 10, downsampled: 10 });
+        for x in 0..200 {
+            history.push_back_at(x as f64, start + Duration::from_secs(6 * x));
+        }
+        let now = start + Duration::from_secs(6 * 199);
+        let mut view = SeriesView { history_span: 200, ..SeriesView::new(Duration::from_secs(6)) }.as_of(now);
+        assert_eq!(view.history_timeline(&history, |x| *x).len(), 100);
+
+        view.toggle_long_range();
+        let timeline = view.history_timeline(&history, |x| *x);
+        assert_eq!(timeline.len(), 110);
+        // a bucket is charted at the time of its first sample, with the highest of them
+        assert_eq!(timeline[0], (-1194.0, 9.0));
+        assert_eq!(timeline[10], (-594.0, 100.0));
+        assert_eq!(view.x_labels(), ["-20m".to_owned(), "-594s".to_owned(), "now".to_owned()]);
+        // the last bucket peaks at 99, a minute before the first sample at full resolution
+        assert_eq!(rate_of_change(&timeline[8..11]), vec![(-654.0, 1.0 / 6.0), (-594.0, 1.0 / 60.0)]);
+    }
+
+    #[test]
+    fn history_is_charted_at_capture_time() {
+        let start = Instant::now();
+        let mut history = History::new(Retention::default());
+        history.push_back_at(1.0, start);
+        history.push_back_at(2.0, start + Duration::from_secs(2));
+        // a missed poll
+        history.push_back_at(3.0, start + Duration::from_secs(7));
+        let view = SeriesView::new(Duration::from_secs(2)).as_of(start + Duration::from_secs(8));
+
+        assert_eq!(view.history_timeline(&history, |x| *x), vec![(-8.0, 1.0), (-6.0, 2.0), (-1.0, 3.0)]);
+        assert_eq!(view.history_events(&history, |x| if *x > 1.5 { Some(*x as i32) } else { None }), vec![(-6.0, 2), (-1.0, 3)]);
+        assert_eq!(view.as_of(start + Duration::from_secs(300)).history_timeline(&history, |x| *x), vec![]);
+    }
+
+    #[test]
+    fn panel_has_own_time_range_and_freeze() {
+        let start = Instant::now();
+        let mut history = History::new(Retention::default());
+        for x in 0..10 {
+            history.push_back_at(x as f64, start + Duration::from_secs(2 * x));
+        }
+        let view = SeriesView::new(Duration::from_secs(2)).as_of(start + Duration::from_secs(18));
+        let panel = view.for_panel(&PanelView { range: TimeRange::Last(Duration::from_secs(300)), frozen_at: Some(start + Duration::from_secs(10)) });
+
+        assert_eq!(panel.x_bounds(), [-300.0, 0.0]);
+        assert_eq!(panel.title_suffix(), " [last 5m] [frozen]");
+        // values taken after the freeze are left out
+        assert_eq!(panel.history_timeline(&history, |x| *x).last(), Some(&(0.0, 5.0)));
+        assert_eq!(panel.cursor_sample(&history), Some(&5.0));
+        // while the other charts move on
+        assert_eq!(view.history_timeline(&history, |x| *x).last(), Some(&(0.0, 9.0)));
+
+        assert_eq!(TimeRange::Shared.next().next(), TimeRange::Last(Duration::from_secs(900)));
+        assert_eq!(TimeRange::Last(Duration::from_secs(900)).next().next(), TimeRange::Shared);
+    }
+
+    #[test]
+    fn y_bounds_across_series() {
+        let a = vec![(0.0, 2.0), (1.0, -3.0)];
+        let b = vec![(0.0, 7.5)];
+        assert_eq!(y_bounds(&[&a, &b]), (-3.0, 7.5));
+        assert_eq!(y_bounds(&[]), (0.0, 0.0));
+    }
+}
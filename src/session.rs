@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+
+use serde::{Deserialize, Serialize};
+
+///
+/// A sample of a recorded session, stored as a JSON line.
+///
+/// eg. `{"t":1590000000000,"metrics":{"fibers_total":12.0},"suspended_fibers":[3,7]}`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionSample {
+    /// Milliseconds since the Unix epoch
+    pub t: u64,
+    /// Metric values, named as in hooks
+    pub metrics: HashMap<String, f64>,
+    /// Ids of the fibers suspended in the last fiber dump
+    #[serde(default)]
+    pub suspended_fibers: Vec<usize>,
+}
+
+/// Appends samples to a session file passed with `--record`
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> Result<Recorder, String> {
+        OpenOptions::new().create(true).append(true).open(path)
+            .map(|file| Recorder { file })
+            .map_err(|e| format!("Couldn't open session file {}: {}", path, e))
+    }
+
+    pub fn record(&mut self, sample: &SessionSample) -> Result<(), String> {
+        let line = serde_json::to_string(sample).map_err(|e| e.to_string())?;
+        writeln!(self.file, "{}", line).map_err(|e| e.to_string())
+    }
+}
+
+/// Min, max and average of a metric over the session
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// A continuous period, in seconds since the start of the session
+#[derive(Debug, PartialEq)]
+pub struct Period {
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StuckFiber {
+    pub id: usize,
+    /// Longest continuous time the fiber stayed suspended, in seconds
+    pub suspended_for: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Summary {
+    pub samples: usize,
+    /// Length of the session in seconds
+    pub duration: f64,
+    pub fibers_total: Option<Stats>,
+    pub fibers_suspended: Option<Stats>,
+    /// Periods when all Hikari connections were active or threads were waiting for one
+    pub hikari_saturation: Vec<Period>,
+    pub actor_growth_per_minute: Option<f64>,
+    pub stuck_fibers: Vec<StuckFiber>,
+}
+
+impl Summary {
+    pub const STUCK_FIBERS_SHOWN: usize = 5;
+
+    pub fn of(samples: &[SessionSample]) -> Summary {
+        let start = samples.first().map_or(0, |s| s.t);
+        let secs = |t: u64| t.saturating_sub(start) as f64 / 1000.0;
+        let duration = samples.last().map_or(0.0, |s| secs(s.t));
+
+        let saturated = |s: &SessionSample| {
+            let m = |k: &str| s.metrics.get(k).copied();
+            matches!(m("hikari_waiting"), Some(w) if w > 0.0) ||
+                matches!((m("hikari_active"), m("hikari_total")), (Some(a), Some(t)) if t > 0.0 && a >= t)
+        };
+        let mut hikari_saturation: Vec<Period> = vec![];
+        let mut open = false;
+        for s in samples {
+            match (saturated(s), open) {
+                (true, true) => hikari_saturation.last_mut().unwrap().end = secs(s.t),
+                (true, false) => hikari_saturation.push(Period { start: secs(s.t), end: secs(s.t) }),
+                _ => {}
+            }
+            open = saturated(s);
+        }
+
+        let actors: Vec<(f64, f64)> = samples.iter()
+            .filter_map(|s| s.metrics.get("actor_count").map(|c| (secs(s.t), *c)))
+            .collect();
+        let actor_growth_per_minute = match (actors.first(), actors.last()) {
+            (Some((t0, c0)), Some((t1, c1))) if t1 > t0 => Some((c1 - c0) / (t1 - t0) * 60.0),
+            _ => None,
+        };
+
+        Summary {
+            samples: samples.len(),
+            duration,
+            fibers_total: stats(samples, "fibers_total"),
+            fibers_suspended: stats(samples, "fibers_suspended"),
+            hikari_saturation,
+            actor_growth_per_minute,
+            stuck_fibers: stuck_fibers(samples, Summary::STUCK_FIBERS_SHOWN),
+        }
+    }
+
+    pub fn report(&self) -> String {
+        let stats = |name: &str, s: &Option<Stats>| match s {
+            Some(s) => format!("{}: max {}, avg {:.1}, min {}", name, s.max, s.avg, s.min),
+            None => format!("{}: not recorded", name),
+        };
+        let mut lines = vec![
+            format!("Samples: {} over {:.0}s", self.samples, self.duration),
+            stats("Fibers", &self.fibers_total),
+            stats("Suspended fibers", &self.fibers_suspended),
+        ];
+        if self.hikari_saturation.is_empty() {
+            lines.push("Hikari saturation: none".to_owned());
+        } else {
+            lines.push(format!("Hikari saturation: {} period(s)", self.hikari_saturation.len()));
+            lines.extend(self.hikari_saturation.iter()
+                .map(|p| format!("  {:.0}s - {:.0}s ({:.0}s)", p.start, p.end, p.end - p.start)));
+        }
+        lines.push(match self.actor_growth_per_minute {
+            Some(g) => format!("Actor count growth: {:.2} actors/min", g),
+            None => "Actor count growth: not recorded".to_owned(),
+        });
+        if self.stuck_fibers.is_empty() {
+            lines.push("Stuck fibers: none".to_owned());
+        } else {
+            lines.push("Stuck fibers:".to_owned());
+            lines.extend(self.stuck_fibers.iter()
+                .map(|f| format!("  #{} suspended for {:.0}s", f.id, f.suspended_for)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Reads a recorded session and summarizes it
+pub fn analyze(path: &str) -> Result<String, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read session {}: {}", path, e))?;
+    let samples = content.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(n, l)| serde_json::from_str(l).map_err(|e| format!("Invalid session {}, line {}: {}", path, n + 1, e)))
+        .collect::<Result<Vec<SessionSample>, String>>()?;
+    Ok(Summary::of(&samples).report())
+}
+
+fn stats(samples: &[SessionSample], metric: &str) -> Option<Stats> {
+    let values: Vec<f64> = samples.iter().filter_map(|s| s.metrics.get(metric).copied()).collect();
+    if values.is_empty() {
+        return None;
+    }
+    Some(Stats {
+        min: values.iter().cloned().fold(f64::MAX, f64::min),
+        max: values.iter().cloned().fold(f64::MIN, f64::max),
+        avg: values.iter().sum::<f64>() / values.len() as f64,
+    })
+}
+
+/// Fibers that stayed suspended the longest in consecutive samples, the longest first
+fn stuck_fibers(samples: &[SessionSample], top: usize) -> Vec<StuckFiber> {
+    // fiber id -> (time it got suspended, longest time suspended so far)
+    let mut runs: HashMap<usize, (u64, u64)> = HashMap::new();
+    let mut prev: &[usize] = &[];
+    for s in samples {
+        for id in s.suspended_fibers.iter() {
+            let run = runs.entry(*id).or_insert((s.t, 0));
+            if !prev.contains(id) {
+                run.0 = s.t;
+            }
+            // the clock may step back, or sessions be appended to the same file
+            run.1 = run.1.max(s.t.saturating_sub(run.0));
+        }
+        prev = &s.suspended_fibers;
+    }
+    let mut stuck: Vec<StuckFiber> = runs.into_iter()
+        .filter(|(_, (_, longest))| *longest > 0)
+        .map(|(id, (_, longest))| StuckFiber { id, suspended_for: longest as f64 / 1000.0 })
+        .collect();
+    stuck.sort_by(|a, b| b.suspended_for.partial_cmp(&a.suspended_for).unwrap().then(a.id.cmp(&b.id)));
+    stuck.truncate(top);
+    stuck
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::session::{Period, SessionSample, Stats, StuckFiber, Summary};
+
+    fn sample(t: u64, metrics: &[(&str, f64)], suspended_fibers: Vec<usize>) -> SessionSample {
+        let metrics: HashMap<String, f64> = metrics.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        SessionSample { t, metrics, suspended_fibers }
+    }
+
+    #[test]
+    fn summarizes_session() {
+        let samples = vec![
+            sample(0, &[("fibers_total", 10.0), ("hikari_active", 2.0), ("hikari_total", 10.0), ("actor_count", 100.0)], vec![1]),
+            sample(2000, &[("fibers_total", 20.0), ("hikari_active", 10.0), ("hikari_total", 10.0)], vec![1, 2]),
+            sample(4000, &[("fibers_total", 30.0), ("hikari_waiting", 3.0)], vec![1, 2]),
+            sample(6000, &[("fibers_total", 20.0), ("hikari_active", 1.0), ("hikari_total", 10.0)], vec![2]),
+            sample(8000, &[("hikari_waiting", 1.0), ("actor_count", 104.0)], vec![1]),
+        ];
+        let summary = Summary::of(&samples);
+
+        assert_eq!(summary.samples, 5);
+        assert_eq!(summary.duration, 8.0);
+        assert_eq!(summary.fibers_total, Some(Stats { min: 10.0, max: 30.0, avg: 20.0 }));
+        assert_eq!(summary.fibers_suspended, None);
+        assert_eq!(summary.hikari_saturation, vec![Period { start: 2.0, end: 4.0 }, Period { start: 8.0, end: 8.0 }]);
+        assert_eq!(summary.actor_growth_per_minute, Some(30.0));
+        assert_eq!(summary.stuck_fibers, vec![
+            StuckFiber { id: 1, suspended_for: 4.0 },
+            StuckFiber { id: 2, suspended_for: 4.0 },
+        ]);
+    }
+
+    #[test]
+    fn stuck_fibers_ignore_time_going_back() {
+        let samples = vec![
+            sample(5000, &[], vec![1]),
+            sample(1000, &[], vec![1]),
+            sample(3000, &[], vec![1]),
+        ];
+        assert_eq!(Summary::of(&samples).stuck_fibers, vec![]);
+    }
+
+    #[test]
+    fn reads_sample_without_fibers() {
+        let s: SessionSample = serde_json::from_str(r#"{"t":5,"metrics":{"actor_count":3.0}}"#).unwrap();
+        assert_eq!(s, sample(5, &[("actor_count", 3.0)], vec![]));
+    }
+}
@@ -0,0 +1,84 @@
+use std::process::Command;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[cfg(feature = "command-source")]
+use crate::config::Config;
+use crate::process;
+use crate::source::{DataSource, PanelSpec, PanelStyle, Sample};
+#[cfg(feature = "command-source")]
+use crate::source::{builder, Builder, SourceKind};
+
+/// Shell command run on each poll, printing a `<series> <value>` line per series
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CommandSettings {
+    pub name: String,
+    pub command: String,
+    #[serde(default = "default_style")]
+    pub panel: PanelStyle,
+}
+
+fn default_style() -> PanelStyle {
+    PanelStyle::Sparklines
+}
+
+/// A tab of each command of the config file
+#[cfg(feature = "command-source")]
+pub const KIND: SourceKind = SourceKind { name: "commands", builders };
+
+#[cfg(feature = "command-source")]
+fn builders(config: &Config) -> Result<Vec<Builder>, String> {
+    Ok(config.commands.iter()
+        .map(|c| {
+            let settings = c.clone();
+            builder(move || CommandSource::new(settings.clone()))
+        })
+        .collect())
+}
+
+pub struct CommandSource {
+    settings: CommandSettings,
+}
+
+impl CommandSource {
+    pub fn new(settings: CommandSettings) -> CommandSource {
+        CommandSource { settings }
+    }
+}
+
+impl DataSource for CommandSource {
+    fn name(&self) -> &str {
+        &self.settings.name
+    }
+
+    /// The command is killed once the timeout is over, so that a hung one doesn't hold up the polls of its worker
+    fn poll(&self, timeout: u64) -> Result<Vec<Sample>, String> {
+        let output = process::output_within(Command::new("sh").arg("-c").arg(&self.settings.command), Duration::from_millis(timeout))
+            .map_err(|e| format!("Couldn't run {}: {}", self.settings.command, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed with {}: {}",
+                self.settings.command, output.status, String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        parse_samples(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn panels(&self) -> Vec<PanelSpec> {
+        vec![PanelSpec { title: self.settings.name.to_owned(), style: self.settings.panel, series: vec![] }]
+    }
+}
+
+/// Parses `<series> <value>` lines, the series is everything before the last space
+pub fn parse_samples(output: &str) -> Result<Vec<Sample>, String> {
+    output.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let i = l.rfind(char::is_whitespace).ok_or(format!("expected '<series> <value>', got '{}'", l))?;
+            let value = l[i + 1..].parse::<f64>().map_err(|_| format!("invalid value in '{}'", l))?;
+            Ok(Sample { series: l[..i].trim().to_owned(), value })
+        })
+        .collect()
+}
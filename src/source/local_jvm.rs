@@ -0,0 +1,291 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::convert::{TryFrom, TryInto};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+#[cfg(feature = "local-jvm-source")]
+use crate::config::Config;
+use crate::source::{DataSource, PanelSpec, PanelStyle, Sample};
+#[cfg(feature = "local-jvm-source")]
+use crate::source::{builder, Builder, SourceKind};
+
+///
+/// JVM running on the same host, read from the perf data file it keeps in `hsperfdata_<user>` (the one `jstat`
+/// reads) rather than over JMX, so that it can be looked at without restarting it with a JMX port.
+/// The JVM is either given by its pid or by a part of its main class or jar, followed across restarts.
+///
+/// eg. `{ "main_class": "com.example.OrdersApp" }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "LocalJvmSettingsDef")]
+pub struct LocalJvmSettings {
+    pub name: String,
+    pub jvm: LocalJvm,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LocalJvm {
+    Pid(u32),
+    /// Part of the command line of the JVM, e.g. its main class or jar
+    MainClass(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LocalJvmSettingsDef {
+    #[serde(default = "default_name")]
+    name: String,
+    pid: Option<u32>,
+    main_class: Option<String>,
+}
+
+fn default_name() -> String {
+    "Local JVM".to_owned()
+}
+
+impl TryFrom<LocalJvmSettingsDef> for LocalJvmSettings {
+    type Error = String;
+
+    fn try_from(def: LocalJvmSettingsDef) -> Result<LocalJvmSettings, String> {
+        let jvm = match (def.pid, def.main_class) {
+            (Some(pid), None) => LocalJvm::Pid(pid),
+            (None, Some(c)) => LocalJvm::MainClass(c),
+            _ => return Err("local_jvm needs either a pid or a main_class".to_owned()),
+        };
+        Ok(LocalJvmSettings { name: def.name, jvm })
+    }
+}
+
+/// Value of a perf data counter, either a number or a string such as the name of a collector
+#[derive(Clone, Debug, PartialEq)]
+pub enum PerfValue {
+    Long(i64),
+    Text(String),
+}
+
+pub type PerfCounters = BTreeMap<String, PerfValue>;
+
+const MAGIC: [u8; 4] = [0xca, 0xfe, 0xc0, 0xc0];
+const PROLOGUE_SIZE: usize = 32;
+const ENTRY_HEADER_SIZE: usize = 20;
+
+///
+/// Counters of a perf data file, laid out as a prologue followed by entries of a name and a value.
+/// Only numbers (`J`) and strings (vectors of `B`) are read, other types are skipped.
+///
+/// See `src/hotspot/share/runtime/perfData.hpp` and `perfMemory.hpp` of the JDK for the layout.
+pub fn parse_perf_data(data: &[u8]) -> Result<PerfCounters, String> {
+    if data.len() < PROLOGUE_SIZE || data[..4] != MAGIC {
+        return Err("Not a perf data file".to_owned());
+    }
+    let big_endian = data[4] == 0;
+    let read_int = |at: usize| -> Result<usize, String> {
+        let bytes: [u8; 4] = data.get(at..at + 4).and_then(|b| b.try_into().ok()).ok_or("Truncated perf data")?;
+        let x = if big_endian { i32::from_be_bytes(bytes) } else { i32::from_le_bytes(bytes) };
+        usize::try_from(x).map_err(|_| "Malformed perf data".to_owned())
+    };
+    let read_long = |at: usize| -> Result<i64, String> {
+        let bytes: [u8; 8] = data.get(at..at + 8).and_then(|b| b.try_into().ok()).ok_or("Truncated perf data")?;
+        Ok(if big_endian { i64::from_be_bytes(bytes) } else { i64::from_le_bytes(bytes) })
+    };
+    let read_string = |from: usize, max_len: usize| -> Result<String, String> {
+        let bytes = data.get(from..from.saturating_add(max_len).min(data.len())).ok_or("Truncated perf data")?;
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    };
+
+    let mut counters = BTreeMap::new();
+    let mut start = read_int(24)?;
+    for _ in 0..read_int(28)? {
+        let length = read_int(start)?;
+        if length < ENTRY_HEADER_SIZE {
+            return Err("Malformed perf data".to_owned());
+        }
+        let name = read_string(start + read_int(start + 4)?, length)?;
+        let vector_length = read_int(start + 8)?;
+        let value_at = start + read_int(start + 16)?;
+        let value = match (data.get(start + 12).copied(), vector_length) {
+            (Some(b'J'), 0) => Some(PerfValue::Long(read_long(value_at)?)),
+            (Some(b'B'), n) if n > 0 => Some(PerfValue::Text(read_string(value_at, n)?)),
+            _ => None,
+        };
+        if let Some(v) = value {
+            counters.insert(name, v);
+        }
+        start += length;
+    }
+    Ok(counters)
+}
+
+fn long(counters: &PerfCounters, name: &str) -> Option<i64> {
+    match counters.get(name) {
+        Some(PerfValue::Long(x)) => Some(*x),
+        _ => None,
+    }
+}
+
+fn text<'a>(counters: &'a PerfCounters, name: &str) -> Option<&'a str> {
+    match counters.get(name) {
+        Some(PerfValue::Text(x)) => Some(x),
+        _ => None,
+    }
+}
+
+/// Sum of the counters whose name starts with the prefix and ends with the suffix
+fn sum(counters: &PerfCounters, prefix: &str, suffix: &str) -> i64 {
+    counters.iter()
+        .filter(|(n, _)| n.starts_with(prefix) && n.ends_with(suffix))
+        .filter_map(|(_, v)| match v {
+            PerfValue::Long(x) => Some(*x),
+            _ => None,
+        })
+        .sum()
+}
+
+const MB: f64 = 1024.0 * 1024.0;
+
+///
+/// Series of the counters: heap and metaspace usage, live threads and loaded classes, and for each collector
+/// the share of the time spent collecting and the collections since the previous counters, if any.
+///
+/// eg. `heap_used_mb`, `gc_time_percent{G1 Young Generation}` and `gc_collections{G1 Young Generation}`
+pub fn jvm_samples(counters: &PerfCounters, previous: Option<&PerfCounters>) -> Vec<Sample> {
+    let mut samples = vec![];
+    let mut push = |series: String, value: f64| samples.push(Sample { series, value });
+    let generations = (0..).map(|g| format!("sun.gc.generation.{}.", g)).take_while(|g| counters.contains_key(&format!("{}capacity", g)));
+    let (mut used, mut committed) = (0, 0);
+    for g in generations {
+        used += sum(counters, &format!("{}space.", g), ".used");
+        committed += long(counters, &format!("{}capacity", g)).unwrap_or(0);
+    }
+    push("heap_used_mb".to_owned(), used as f64 / MB);
+    push("heap_committed_mb".to_owned(), committed as f64 / MB);
+    if let Some(x) = long(counters, "sun.gc.metaspace.used") {
+        push("metaspace_used_mb".to_owned(), x as f64 / MB);
+    }
+    if let Some(x) = long(counters, "java.threads.live") {
+        push("threads_live".to_owned(), x as f64);
+    }
+    if let Some(x) = long(counters, "java.threads.daemon") {
+        push("threads_daemon".to_owned(), x as f64);
+    }
+    if let Some(x) = long(counters, "java.cls.loadedClasses") {
+        push("classes_loaded".to_owned(), x as f64);
+    }
+
+    let previous = match previous {
+        Some(p) => p,
+        None => return samples,
+    };
+    let elapsed = long(counters, "sun.os.hrt.ticks").unwrap_or(0) - long(previous, "sun.os.hrt.ticks").unwrap_or(0);
+    let collectors = (0..).map(|c| format!("sun.gc.collector.{}.", c)).take_while(|c| counters.contains_key(&format!("{}name", c)));
+    for c in collectors {
+        let name = text(counters, &format!("{}name", c)).unwrap_or("");
+        let delta = |counter: &str| {
+            let key = format!("{}{}", c, counter);
+            long(counters, &key).unwrap_or(0) - long(previous, &key).unwrap_or(0)
+        };
+        // counters going down belong to a restarted JVM
+        let (collections, ticks) = (delta("invocations"), delta("time"));
+        if collections >= 0 && ticks >= 0 && elapsed > 0 {
+            push(format!("gc_time_percent{{{}}}", name), ticks as f64 * 100.0 / elapsed as f64);
+            push(format!("gc_collections{{{}}}", name), collections as f64);
+        }
+    }
+    samples
+}
+
+/// Directories of the perf data files, one per user
+fn perf_data_dirs() -> Vec<PathBuf> {
+    // HotSpot ignores TMPDIR on Linux
+    let tmp = if cfg!(target_os = "linux") { PathBuf::from("/tmp") } else { env::temp_dir() };
+    fs::read_dir(tmp)
+        .map(|entries| entries
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with("hsperfdata_"))
+            .map(|e| e.path())
+            .collect())
+        .unwrap_or_default()
+}
+
+/// The tab of a JVM on this host, when the config file names one
+#[cfg(feature = "local-jvm-source")]
+pub const KIND: SourceKind = SourceKind { name: "local_jvm", builders };
+
+#[cfg(feature = "local-jvm-source")]
+fn builders(config: &Config) -> Result<Vec<Builder>, String> {
+    Ok(config.local_jvm.iter()
+        .map(|j| {
+            let settings = j.clone();
+            builder(move || LocalJvmSource::new(settings.clone()))
+        })
+        .collect())
+}
+
+pub struct LocalJvmSource {
+    settings: LocalJvmSettings,
+    /// Counters of the previous poll, to chart the collections in between
+    previous: RefCell<Option<PerfCounters>>,
+}
+
+impl LocalJvmSource {
+    pub fn new(settings: LocalJvmSettings) -> LocalJvmSource {
+        LocalJvmSource { settings, previous: RefCell::new(None) }
+    }
+
+    fn read(path: &Path) -> Result<PerfCounters, String> {
+        let data = fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+        parse_perf_data(&data)
+    }
+
+    /// Counters of the JVM, looked up again on each poll as a restarted JVM gets a new pid
+    fn counters(&self) -> Result<PerfCounters, String> {
+        match &self.settings.jvm {
+            LocalJvm::Pid(pid) => {
+                let path = perf_data_dirs().into_iter()
+                    .map(|d| d.join(pid.to_string()))
+                    .find(|p| p.exists())
+                    .ok_or(format!("No perf data for pid {}, is it a JVM running with -XX:+UsePerfData?", pid))?;
+                LocalJvmSource::read(&path)
+            }
+            LocalJvm::MainClass(class) => perf_data_dirs().into_iter()
+                .flat_map(|d| fs::read_dir(d).into_iter().flatten().flatten().map(|e| e.path()))
+                .filter_map(|p| LocalJvmSource::read(&p).ok())
+                .find(|c| text(c, "sun.rt.javaCommand").map_or(false, |cmd| cmd.contains(class.as_str())))
+                .ok_or(format!("No local JVM running {}", class)),
+        }
+    }
+}
+
+impl DataSource for LocalJvmSource {
+    fn name(&self) -> &str {
+        &self.settings.name
+    }
+
+    fn poll(&self, _timeout: u64) -> Result<Vec<Sample>, String> {
+        let counters = self.counters()?;
+        let samples = jvm_samples(&counters, self.previous.borrow().as_ref());
+        self.previous.replace(Some(counters));
+        Ok(samples)
+    }
+
+    fn panels(&self) -> Vec<PanelSpec> {
+        vec![
+            PanelSpec {
+                title: "Memory (MB)".to_owned(),
+                style: PanelStyle::Sparklines,
+                series: vec!["heap_used_mb".to_owned(), "heap_committed_mb".to_owned(), "metaspace_used_mb".to_owned()],
+            },
+            PanelSpec { title: "GC time (% of wall time)".to_owned(), style: PanelStyle::Sparklines, series: vec!["gc_time_percent{*".to_owned()] },
+            PanelSpec { title: "Collections per poll".to_owned(), style: PanelStyle::Sparklines, series: vec!["gc_collections{*".to_owned()] },
+            PanelSpec {
+                title: "Threads and classes".to_owned(),
+                style: PanelStyle::Table,
+                series: vec!["threads_live".to_owned(), "threads_daemon".to_owned(), "classes_loaded".to_owned()],
+            },
+        ]
+    }
+}
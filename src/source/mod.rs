@@ -0,0 +1,123 @@
+//!
+//! Extension point for data sources that don't need changes to the app, the fetcher or the event loop.
+//!
+//! A source implements `DataSource` and gets its own tab, polled like the built-in sources and laid out
+//! from the panels the source describes. Sources are set up from the config file by their `SourceKind`,
+//! and `kinds` lists the kinds of the enabled cargo features, the built-in ones being on by default.
+//! A companion crate is an optional dependency enabled by a feature of its own, which adds its kind, e.g.
+//!
+//!   ```text
+//!   #[cfg(feature = "acme")]
+//!   kinds.push(acme_panopticon::KIND);
+//!   ```
+//!
+//! Its settings are read from `sources` in the config file, under the name of its kind.
+// the sources of a kind whose feature is disabled are still tested, just never set up
+#[cfg_attr(not(feature = "command-source"), allow(dead_code))]
+pub mod command;
+#[cfg_attr(not(feature = "local-jvm-source"), allow(dead_code))]
+pub mod local_jvm;
+#[cfg_attr(not(feature = "pulsar-source"), allow(dead_code))]
+pub mod pulsar;
+#[cfg_attr(not(feature = "tcp-probe-source"), allow(dead_code))]
+pub mod tcp;
+mod tests;
+
+use serde::Deserialize;
+
+use crate::config::Config;
+
+/// Value of a single series of a data source, e.g. `queue_depth{queue="orders"}`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub series: String,
+    pub value: f64,
+}
+
+/// How a panel shows the series of a data source
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelStyle {
+    /// A sparkline of each series
+    Sparklines,
+    /// The latest value of each series
+    Table,
+}
+
+/// A panel of a data source's tab, the panels of a tab are stacked vertically
+#[derive(Clone, Debug, PartialEq)]
+pub struct PanelSpec {
+    pub title: String,
+    pub style: PanelStyle,
+    /// Series shown on the panel, all of them if empty. A trailing `*` matches any series starting with the rest, e.g. `lag{*`
+    pub series: Vec<String>,
+}
+
+impl PanelSpec {
+    pub fn shows(&self, series: &str) -> bool {
+        self.series.is_empty() || self.series.iter().any(|s| match s.strip_suffix('*') {
+            Some(prefix) => series.starts_with(prefix),
+            None => s == series,
+        })
+    }
+}
+
+pub trait DataSource {
+    /// Title of the source's tab, also used in logs and the status bar
+    fn name(&self) -> &str;
+    /// Current values of the source's series, called on each poll
+    fn poll(&self, timeout: u64) -> Result<Vec<Sample>, String>;
+    /// Panels of the source's tab
+    fn panels(&self) -> Vec<PanelSpec> {
+        vec![PanelSpec { title: self.name().to_owned(), style: PanelStyle::Sparklines, series: vec![] }]
+    }
+}
+
+///
+/// Builds a data source on the worker polling it, or for its tab. Sources aren't shared between threads,
+/// so each worker builds its own.
+pub type Builder = Box<dyn Fn() -> Box<dyn DataSource> + Send + Sync>;
+
+/// Kind of data source, setting up the sources of its kind the config file asks for
+#[derive(Clone, Copy)]
+pub struct SourceKind {
+    /// Name of the kind, under which `sources` in the config file holds the settings of a companion crate's kind
+    pub name: &'static str,
+    pub builders: fn(&Config) -> Result<Vec<Builder>, String>,
+}
+
+/// Builder of the sources `build` makes
+#[cfg_attr(not(any(feature = "command-source", feature = "local-jvm-source", feature = "pulsar-source", feature = "tcp-probe-source")), allow(dead_code))]
+pub fn builder<S, F>(build: F) -> Builder where S: DataSource + 'static, F: Fn() -> S + Send + Sync + 'static {
+    Box::new(move || -> Box<dyn DataSource> { Box::new(build()) })
+}
+
+/// Kinds of data sources of the enabled cargo features, in the order of their tabs
+#[allow(clippy::vec_init_then_push, unused_mut)]
+pub fn kinds() -> Vec<SourceKind> {
+    let mut kinds = vec![];
+    #[cfg(feature = "pulsar-source")]
+    kinds.push(pulsar::KIND);
+    #[cfg(feature = "tcp-probe-source")]
+    kinds.push(tcp::KIND);
+    #[cfg(feature = "local-jvm-source")]
+    kinds.push(local_jvm::KIND);
+    #[cfg(feature = "command-source")]
+    kinds.push(command::KIND);
+    kinds
+}
+
+///
+/// Data sources beyond the built-in ones the config sets up, in the order of their tabs.
+/// Settings of a kind whose feature isn't enabled are an error rather than a tab that never shows up.
+pub fn registered(config: &Config) -> Result<Vec<Builder>, String> {
+    let kinds = kinds();
+    if let Some(name) = config.sources.keys().find(|n| !kinds.iter().any(|k| k.name == n.as_str())) {
+        return Err(format!("Unknown data source kind {}, is the cargo feature adding it enabled?", name));
+    }
+    let mut builders = vec![];
+    for kind in kinds {
+        builders.extend((kind.builders)(config).map_err(|e| format!("Invalid {} settings: {}", kind.name, e))?);
+    }
+    Ok(builders)
+}
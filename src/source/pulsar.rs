@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+#[cfg(feature = "pulsar-source")]
+use crate::config::Config;
+use crate::config::Proxy;
+use crate::http;
+use crate::source::{DataSource, PanelSpec, PanelStyle, Sample};
+#[cfg(feature = "pulsar-source")]
+use crate::source::{builder, Builder, SourceKind};
+
+///
+/// Pulsar cluster polled over the admin REST API of one of its brokers.
+///
+/// eg. `{ "url": "http://localhost:8080", "cluster": "standalone", "topics": ["persistent://public/default/orders"] }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PulsarSettings {
+    /// Web service URL of a broker
+    pub url: String,
+    pub cluster: String,
+    /// Topics whose backlog, subscription lag and throughput are charted
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Sent as a bearer token, when the admin API requires authentication
+    pub token: Option<String>,
+}
+
+/// Stats of a topic as returned by `/admin/v2/<domain>/<tenant>/<namespace>/<topic>/stats`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TopicStats {
+    pub msg_rate_in: f64,
+    pub msg_rate_out: f64,
+    pub subscriptions: HashMap<String, SubscriptionStats>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SubscriptionStats {
+    /// Messages not acknowledged by the subscription yet
+    pub msg_backlog: f64,
+}
+
+/// The Pulsar tab, when the config file sets up a cluster
+#[cfg(feature = "pulsar-source")]
+pub const KIND: SourceKind = SourceKind { name: "pulsar", builders };
+
+#[cfg(feature = "pulsar-source")]
+fn builders(config: &Config) -> Result<Vec<Builder>, String> {
+    Ok(config.pulsar.iter()
+        .map(|p| {
+            let (settings, proxy) = (p.clone(), config.proxies.pulsar.clone());
+            builder(move || PulsarSource::new(settings.clone(), proxy.clone()))
+        })
+        .collect())
+}
+
+pub struct PulsarSource {
+    settings: PulsarSettings,
+    proxy: Option<Proxy>,
+}
+
+impl PulsarSource {
+    pub fn new(settings: PulsarSettings, proxy: Option<Proxy>) -> PulsarSource {
+        PulsarSource { settings, proxy }
+    }
+}
+
+impl DataSource for PulsarSource {
+    fn name(&self) -> &str {
+        "Pulsar"
+    }
+
+    fn poll(&self, timeout: u64) -> Result<Vec<Sample>, String> {
+        poll_async(&self.settings, self.proxy.as_ref(), timeout)
+            .map_err(|e| format!("Couldn't get stats from pulsar at {}. Underlying error: {}", self.settings.url, e))
+    }
+
+    fn panels(&self) -> Vec<PanelSpec> {
+        let panel = |title: &str, style, series: &[&str]| PanelSpec {
+            title: title.to_owned(),
+            style,
+            series: series.iter().map(|s| s.to_string()).collect(),
+        };
+        vec![
+            panel("Brokers (owned namespace bundles)", PanelStyle::Table, &["broker{*"]),
+            panel("Topic backlog (messages)", PanelStyle::Sparklines, &["backlog{*"]),
+            panel("Subscription lag (messages)", PanelStyle::Sparklines, &["lag{*"]),
+            panel("Throughput (msg/s)", PanelStyle::Sparklines, &["rate_in{*", "rate_out{*"]),
+        ]
+    }
+}
+
+#[tokio::main]
+async fn poll_async(settings: &PulsarSettings, proxy: Option<&Proxy>, timeout: u64) -> Result<Vec<Sample>, String> {
+    let client = http::client(proxy)?;
+    let get = |path: String| {
+        let request = client.get(&format!("{}{}", settings.url.trim_end_matches('/'), path))
+            .timeout(Duration::from_millis(timeout));
+        match &settings.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    };
+
+    let mut samples = vec![];
+    let brokers: Vec<String> = json(get(format!("/admin/v2/brokers/{}", settings.cluster))).await?;
+    for broker in brokers {
+        let bundles: HashMap<String, serde_json::Value> = json(get(format!("/admin/v2/brokers/{}/{}/ownedNamespaces", settings.cluster, broker))).await?;
+        samples.push(Sample { series: format!("broker{{{}}}", broker), value: bundles.len() as f64 });
+    }
+    for topic in settings.topics.iter() {
+        let stats: TopicStats = json(get(stats_path(topic)?)).await?;
+        samples.extend(topic_samples(topic, &stats));
+    }
+    Ok(samples)
+}
+
+async fn json<T: serde::de::DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Request to {} failed with status {}", response.url(), response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Path of the stats of a topic, e.g. `/admin/v2/persistent/public/default/orders/stats`
+pub fn stats_path(topic: &str) -> Result<String, String> {
+    match topic.find("://") {
+        Some(i) => Ok(format!("/admin/v2/{}/{}/stats", &topic[..i], &topic[i + 3..])),
+        None => Err(format!("Invalid topic {}, expected e.g. persistent://public/default/orders", topic)),
+    }
+}
+
+///
+/// Series of a topic, named after the topic without its domain, its subscriptions sorted by name.
+///
+/// eg. `backlog{public/default/orders}`, `lag{public/default/orders:billing}` and `rate_in{public/default/orders}`
+pub fn topic_samples(topic: &str, stats: &TopicStats) -> Vec<Sample> {
+    let name = topic.splitn(2, "://").last().unwrap_or(topic);
+    let mut subscriptions: Vec<(&String, &SubscriptionStats)> = stats.subscriptions.iter().collect();
+    subscriptions.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut samples = vec![Sample {
+        series: format!("backlog{{{}}}", name),
+        value: subscriptions.iter().map(|(_, s)| s.msg_backlog).sum(),
+    }];
+    for (subscription, s) in subscriptions {
+        samples.push(Sample { series: format!("lag{{{}:{}}}", name, subscription), value: s.msg_backlog });
+    }
+    samples.push(Sample { series: format!("rate_in{{{}}}", name), value: stats.msg_rate_in });
+    samples.push(Sample { series: format!("rate_out{{{}}}", name), value: stats.msg_rate_out });
+    samples
+}
@@ -0,0 +1,107 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+#[cfg(feature = "tcp-probe-source")]
+use crate::config::Config;
+use crate::source::{DataSource, PanelSpec, PanelStyle, Sample};
+#[cfg(feature = "tcp-probe-source")]
+use crate::source::{builder, Builder, SourceKind};
+
+///
+/// `host:port` targets probed with a TCP connect on each poll, for dependencies without a metrics endpoint.
+///
+/// eg. `{ "targets": ["db.internal:5432", "kafka-1.internal:9092"] }`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TcpProbeSettings {
+    /// Title of the tab
+    #[serde(default = "default_name")]
+    pub name: String,
+    pub targets: Vec<String>,
+}
+
+fn default_name() -> String {
+    "TCP".to_owned()
+}
+
+/// The TCP probe tab, when the config file lists targets
+#[cfg(feature = "tcp-probe-source")]
+pub const KIND: SourceKind = SourceKind { name: "tcp_probe", builders };
+
+#[cfg(feature = "tcp-probe-source")]
+fn builders(config: &Config) -> Result<Vec<Builder>, String> {
+    Ok(config.tcp_probe.iter()
+        .map(|t| {
+            let settings = t.clone();
+            builder(move || TcpProbeSource::new(settings.clone()))
+        })
+        .collect())
+}
+
+pub struct TcpProbeSource {
+    settings: TcpProbeSettings,
+}
+
+impl TcpProbeSource {
+    pub fn new(settings: TcpProbeSettings) -> TcpProbeSource {
+        TcpProbeSource { settings }
+    }
+}
+
+impl DataSource for TcpProbeSource {
+    fn name(&self) -> &str {
+        &self.settings.name
+    }
+
+    /// Targets are probed in parallel, an unreachable target is a sample rather than an error
+    fn poll(&self, timeout: u64) -> Result<Vec<Sample>, String> {
+        let timeout = Duration::from_millis(timeout);
+        let probes: Vec<(String, thread::JoinHandle<Result<f64, String>>)> = self.settings.targets.iter()
+            .map(|t| {
+                let target = t.to_owned();
+                (t.to_owned(), thread::spawn(move || connect(&target, timeout)))
+            })
+            .collect();
+        let mut samples = vec![];
+        for (target, probe) in probes {
+            let latency = probe.join().map_err(|_| format!("Probe of {} panicked", target))?;
+            samples.extend(probe_samples(&target, latency));
+        }
+        Ok(samples)
+    }
+
+    fn panels(&self) -> Vec<PanelSpec> {
+        vec![
+            PanelSpec { title: "Reachable (1 = connected)".to_owned(), style: PanelStyle::Table, series: vec!["up{*".to_owned()] },
+            PanelSpec { title: "Connect latency (ms)".to_owned(), style: PanelStyle::Sparklines, series: vec!["latency_ms{*".to_owned()] },
+        ]
+    }
+}
+
+/// Time (in ms) to connect to the first address the target resolves to
+fn connect(target: &str, timeout: Duration) -> Result<f64, String> {
+    let address = target.to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or(format!("{} resolves to no address", target))?;
+    let started = Instant::now();
+    TcpStream::connect_timeout(&address, timeout).map_err(|e| e.to_string())?;
+    Ok(started.elapsed().as_secs_f64() * 1000.0)
+}
+
+///
+/// Series of a probe: whether the target was reached, and if so how long connecting took.
+///
+/// eg. `up{db.internal:5432}` and `latency_ms{db.internal:5432}`
+pub fn probe_samples(target: &str, latency: Result<f64, String>) -> Vec<Sample> {
+    match latency {
+        Ok(ms) => vec![
+            Sample { series: format!("up{{{}}}", target), value: 1.0 },
+            Sample { series: format!("latency_ms{{{}}}", target), value: ms },
+        ],
+        Err(_) => vec![Sample { series: format!("up{{{}}}", target), value: 0.0 }],
+    }
+}
@@ -0,0 +1,172 @@
+#[test]
+fn parses_command_output() {
+    use crate::source::command::parse_samples;
+    use crate::source::Sample;
+
+    let samples = parse_samples("queue_depth{queue=\"orders\"} 3\n\n  workers  12.5\n").unwrap();
+
+    assert_eq!(samples, vec![
+        Sample { series: "queue_depth{queue=\"orders\"}".to_owned(), value: 3.0 },
+        Sample { series: "workers".to_owned(), value: 12.5 },
+    ]);
+    assert!(parse_samples("workers").is_err());
+    assert!(parse_samples("workers many").is_err());
+}
+
+#[test]
+fn polls_command() {
+    use crate::source::{DataSource, PanelStyle};
+    use crate::source::command::{CommandSettings, CommandSource};
+
+    let source = CommandSource::new(CommandSettings {
+        name: "queues".to_owned(),
+        command: "echo orders 3".to_owned(),
+        panel: PanelStyle::Table,
+    });
+
+    assert_eq!(source.poll(1000).unwrap()[0].value, 3.0);
+    assert_eq!(source.panels()[0].style, PanelStyle::Table);
+
+    let hung = CommandSource::new(CommandSettings {
+        name: "hung".to_owned(),
+        command: "exec sleep 10".to_owned(),
+        panel: PanelStyle::Sparklines,
+    });
+    assert_eq!(hung.poll(100), Err("Couldn't run exec sleep 10: timed out after 100ms".to_owned()));
+}
+
+#[test]
+fn registers_sources_of_enabled_kinds() {
+    use crate::config::Config;
+    use crate::source::registered;
+
+    let config = Config::parse(r#"{
+        "commands": [{ "name": "Queues", "command": "./queue-depths.sh" }],
+        "tcp_probe": { "targets": ["db.internal:5432"] }
+    }"#).unwrap();
+    let names: Vec<String> = registered(&config).unwrap().iter().map(|build| build().name().to_owned()).collect();
+    assert_eq!(names, vec!["TCP", "Queues"]);
+
+    let unknown = Config::parse(r#"{ "sources": { "acme": { "queues": ["orders"] } } }"#).unwrap();
+    assert_eq!(registered(&unknown).err(), Some("Unknown data source kind acme, is the cargo feature adding it enabled?".to_owned()));
+}
+
+#[test]
+fn reads_pulsar_topic_stats() {
+    use crate::source::{DataSource, Sample};
+    use crate::source::pulsar::{stats_path, topic_samples, PulsarSettings, PulsarSource, TopicStats};
+
+    assert_eq!(stats_path("persistent://public/default/orders").unwrap(), "/admin/v2/persistent/public/default/orders/stats");
+    assert!(stats_path("orders").is_err());
+
+    let stats: TopicStats = serde_json::from_str(r#"{
+        "msgRateIn": 120.5, "msgRateOut": 98.0, "backlogSize": 4096,
+        "subscriptions": { "shipping": { "msgBacklog": 2, "msgRateOut": 48.0 }, "billing": { "msgBacklog": 40, "msgRateOut": 50.0 } }
+    }"#).unwrap();
+    let series = |name: &str, value: f64| Sample { series: name.to_owned(), value };
+    assert_eq!(topic_samples("persistent://public/default/orders", &stats), vec![
+        series("backlog{public/default/orders}", 42.0),
+        series("lag{public/default/orders:billing}", 40.0),
+        series("lag{public/default/orders:shipping}", 2.0),
+        series("rate_in{public/default/orders}", 120.5),
+        series("rate_out{public/default/orders}", 98.0),
+    ]);
+
+    let source = PulsarSource::new(PulsarSettings {
+        url: "http://localhost:8080".to_owned(),
+        cluster: "standalone".to_owned(),
+        topics: vec![],
+        token: None,
+    }, None);
+    let throughput = &source.panels()[3];
+    assert!(throughput.shows("rate_out{public/default/orders}"));
+    assert!(!throughput.shows("lag{public/default/orders:billing}"));
+}
+
+#[test]
+fn probes_tcp_targets() {
+    use std::net::TcpListener;
+
+    use crate::source::DataSource;
+    use crate::source::tcp::{TcpProbeSettings, TcpProbeSource};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let open = listener.local_addr().unwrap().to_string();
+    // a port that was just released is very unlikely to be taken again right away
+    let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+
+    let settings: TcpProbeSettings = serde_json::from_str(&format!(r#"{{ "targets": ["{}", "{}"] }}"#, open, closed)).unwrap();
+    assert_eq!(settings.name, "TCP");
+    let source = TcpProbeSource::new(settings);
+    let samples = source.poll(1000).unwrap();
+
+    let series: Vec<&str> = samples.iter().map(|s| s.series.as_str()).collect();
+    assert_eq!(series, vec![format!("up{{{}}}", open), format!("latency_ms{{{}}}", open), format!("up{{{}}}", closed)]);
+    assert_eq!(samples[0].value, 1.0);
+    assert_eq!(samples[2].value, 0.0);
+    assert!(source.panels()[1].shows(&format!("latency_ms{{{}}}", open)));
+}
+
+#[test]
+fn reads_local_jvm_perf_data() {
+    use crate::source::local_jvm::{jvm_samples, parse_perf_data, LocalJvm, LocalJvmSettings, PerfValue};
+    use crate::source::Sample;
+
+    // a little endian perf data file, each entry being a 20 bytes header, its name and its value
+    let perf_data = |counters: &[(&str, PerfValue)]| {
+        let mut data = vec![0xca, 0xfe, 0xc0, 0xc0, 1, 2, 0, 1];
+        data.extend(vec![0; 16]);
+        data.extend(&32i32.to_le_bytes());
+        data.extend(&(counters.len() as i32).to_le_bytes());
+        for (name, value) in counters {
+            let (kind, vector_length, bytes) = match value {
+                PerfValue::Long(x) => (b'J', 0, x.to_le_bytes().to_vec()),
+                PerfValue::Text(s) => (b'B', s.len() as i32 + 1, [s.as_bytes(), &[0]].concat()),
+            };
+            let name = [name.as_bytes(), &[0]].concat();
+            data.extend(&((20 + name.len() + bytes.len()) as i32).to_le_bytes());
+            data.extend(&20i32.to_le_bytes());
+            data.extend(&vector_length.to_le_bytes());
+            data.extend(&[kind, 0, 1, 3]);
+            data.extend(&((20 + name.len()) as i32).to_le_bytes());
+            data.extend(name);
+            data.extend(bytes);
+        }
+        data
+    };
+    let counters = |ticks: i64, gc_time: i64| perf_data(&[
+        ("sun.os.hrt.ticks", PerfValue::Long(ticks)),
+        ("sun.gc.generation.0.capacity", PerfValue::Long(64 << 20)),
+        ("sun.gc.generation.0.space.0.used", PerfValue::Long(24 << 20)),
+        ("sun.gc.generation.0.space.1.used", PerfValue::Long(8 << 20)),
+        ("sun.gc.generation.1.capacity", PerfValue::Long(128 << 20)),
+        ("sun.gc.generation.1.space.0.used", PerfValue::Long(40 << 20)),
+        ("sun.gc.collector.0.name", PerfValue::Text("G1 Young Generation".to_owned())),
+        ("sun.gc.collector.0.invocations", PerfValue::Long(gc_time / 10)),
+        ("sun.gc.collector.0.time", PerfValue::Long(gc_time)),
+        ("java.threads.live", PerfValue::Long(42)),
+    ]);
+
+    let before = parse_perf_data(&counters(1000, 50)).unwrap();
+    let after = parse_perf_data(&counters(2000, 100)).unwrap();
+    assert_eq!(after.get("sun.gc.collector.0.name"), Some(&PerfValue::Text("G1 Young Generation".to_owned())));
+
+    let series = |name: &str, value: f64| Sample { series: name.to_owned(), value };
+    assert_eq!(jvm_samples(&after, None), vec![
+        series("heap_used_mb", 72.0),
+        series("heap_committed_mb", 192.0),
+        series("threads_live", 42.0),
+    ]);
+    assert_eq!(jvm_samples(&after, Some(&before))[3..], [
+        series("gc_time_percent{G1 Young Generation}", 5.0),
+        series("gc_collections{G1 Young Generation}", 5.0),
+    ]);
+    assert!(parse_perf_data(&counters(1000, 50)[..40]).is_err());
+    assert!(parse_perf_data(b"not perf data, but long enough to be").is_err());
+
+    let settings: LocalJvmSettings = serde_json::from_str(r#"{ "main_class": "com.example.OrdersApp" }"#).unwrap();
+    assert_eq!(settings.jvm, LocalJvm::MainClass("com.example.OrdersApp".to_owned()));
+    assert_eq!(settings.name, "Local JVM");
+    assert!(serde_json::from_str::<LocalJvmSettings>(r#"{ "pid": 4242, "main_class": "OrdersApp" }"#).is_err());
+    assert!(serde_json::from_str::<LocalJvmSettings>(r#"{}"#).is_err());
+}
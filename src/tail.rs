@@ -0,0 +1,44 @@
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Reads lines appended to a log file since the previous read, starting from the beginning of the file
+pub struct LogTail {
+    pub path: String,
+    offset: Cell<u64>,
+    /// The last line, if it isn't complete yet. It's kept as bytes, as a read may end in the middle of a character.
+    partial: RefCell<Vec<u8>>,
+}
+
+impl LogTail {
+    pub fn new(path: String) -> LogTail {
+        LogTail { path, offset: Cell::new(0), partial: RefCell::new(vec![]) }
+    }
+
+    /// Complete lines appended since the previous read, a partial last line being kept for the next one
+    pub fn read_lines(&self) -> Result<Vec<String>, String> {
+        let mut file = File::open(&self.path).map_err(|e| e.to_string())?;
+        let len = file.metadata().map_err(|e| e.to_string())?.len();
+        if len < self.offset.get() {
+            // the log was rotated or truncated
+            self.offset.set(0);
+            self.partial.borrow_mut().clear();
+        }
+        file.seek(SeekFrom::Start(self.offset.get())).map_err(|e| e.to_string())?;
+        let mut bytes = self.partial.replace(vec![]);
+        let read = file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        self.offset.set(self.offset.get() + read as u64);
+
+        // a newline is never part of a multibyte character, so complete lines are complete UTF-8 sequences
+        match bytes.iter().rposition(|b| *b == b'\n') {
+            Some(i) => {
+                *self.partial.borrow_mut() = bytes.split_off(i + 1);
+                Ok(String::from_utf8_lossy(&bytes).lines().map(str::to_owned).collect())
+            }
+            None => {
+                *self.partial.borrow_mut() = bytes;
+                Ok(vec![])
+            }
+        }
+    }
+}
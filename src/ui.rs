@@ -1,4 +1,5 @@
 use std::io;
+use std::time::Instant;
 
 use tui::{
     backend::Backend,
@@ -7,20 +8,50 @@ use tui::{
     style::{Color, Modifier, Style},
     symbols::Marker,
     Terminal,
-    widgets::{Axis, BarChart, Block, Borders, Chart, Dataset, List, Paragraph, Tabs, Text},
+    widgets::{Axis, BarChart, Block, Borders, Chart, Dataset, Gauge, List, ListState, Paragraph, Row, Sparkline, Table, Tabs, Text},
 };
 
-use crate::app::{AkkaActorTreeTab, App, SlickTab, TabKind, ZMXTab};
-use crate::jmx::model::HikariMetrics;
-use crate::zio::model::FiberCount;
+use crate::akka::model::{AkkaHealth, HealthProbe};
+use crate::app::{AkkaActorTreeTab, App, CompareEnv, CompareTab, ConsulTab, CustomTab, GcLogTab, JVMTab, Panel, PollLatency, PrometheusTab, RedisTab, SlickTab, SourceTab, TabKind, ZMXTab, ZookeeperConsole, ZookeeperStatus};
+use crate::consul::model::HealthStatus;
+use crate::dashboard;
+use crate::diagnostics::{self, Diagnostics};
+use crate::history::History;
+use crate::jmx::model::{HikariMetrics, PoolErrorRates};
+use crate::keymap;
+use crate::logging::{Logger, LogLevel};
+use crate::script::derived::DerivedSeries;
+use crate::script::hooks::{HookKind, Hooks};
+use crate::series::{self, ChartViews, SeriesKind, SeriesView};
+use crate::source::PanelStyle;
+use crate::widgets::stacked::{Layer, StackedAreaChart};
+use crate::widgets::stale::StaleOverlay;
+use crate::widgets::table::{SortableTable, TableSort};
+use crate::zio::diff::LineChange;
+use crate::zio::model::{FiberCount, FiberStatus, FiberTreeShape};
+use crate::zookeeper::model::config_drift;
 
 pub fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), io::Error> {
+    let mut status = app.availability_labels();
+    if app.scrub.enabled {
+        status.push("SCRUBBING".to_owned());
+    }
+    status.extend(app.clock.warnings());
+    status.extend(app.notice.clone());
+    let availability = status.join(" | ");
+    let alerts = app.active_alerts().iter().map(|a| format!(" | ALERT: {}", a)).collect::<String>();
+    let chart_views = app.chart_views(Instant::now());
+    let focused = app.focused_panel();
+    let zoomed = app.tabs.current().zoomed;
+    let sort = app.tabs.current().sort;
+    let stale_for = app.current_stale_for();
+    let command_line = app.command_line.clone();
     terminal.draw(|mut f| {
         let chunks = Layout::default()
-            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3), Constraint::Length(1)].as_ref())
             .split(f.size());
         let tabs = app.tabs.to_owned();
-        let titles = tabs.titles();
+        let titles = app.tab_titles();
         let tabs_widget = Tabs::default()
             .block(Block::default()
                 .borders(Borders::ALL)
@@ -31,18 +62,57 @@ pub fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(),
             .highlight_style(Style::default().fg(Color::Yellow))
             .select(tabs.index);
         f.render_widget(tabs_widget, chunks[0]);
+        if app.diagnostics.is_shown() {
+            draw_diagnostics(&mut f, &app.diagnostics, chunks[1]);
+            draw_text(&mut f, &availability, &alerts, chunks[2]);
+            draw_key_hints(&mut f, &tabs.current().kind, &command_line, chunks[3]);
+            return;
+        }
         match tabs.current().kind {
-            TabKind::ZMX => &app.zmx.as_mut().map(|mut t| draw_zio_tab(&mut f, &mut t, chunks[1])),
-            TabKind::Slick => &app.slick.as_ref().map(|t| draw_slick_tab(&mut f, t, chunks[1])),
-            TabKind::AkkaActorTree => &app.actor_tree.as_mut().map(|t| draw_actor_tree_tab(&mut f, t, chunks[1])),
+            TabKind::ZMX => &app.zmx.as_mut().map(|mut t| draw_zio_tab(&mut f, &mut t, &chart_views, focused, zoomed, chunks[1])),
+            TabKind::Slick => &app.slick.as_ref().map(|t| draw_slick_tab(&mut f, t, &chart_views, focused, zoomed, sort, chunks[1])),
+            TabKind::JVM => &app.jvm.as_ref().map(|t| draw_jvm_tab(&mut f, t, focused, zoomed, sort, chunks[1])),
+            TabKind::Custom => &app.custom.as_ref().map(|t| draw_custom_tab(&mut f, t, chunks[1])),
+            TabKind::AkkaActorTree => &app.actor_tree.as_mut().map(|t| draw_actor_tree_tab(&mut f, t, focused, zoomed, sort, chunks[1])),
+            TabKind::Redis => &app.redis.as_ref().map(|t| draw_redis_tab(&mut f, t, focused, zoomed, sort, chunks[1])),
+            TabKind::Consul => &app.consul.as_ref().map(|t| draw_consul_tab(&mut f, t, chunks[1])),
+            TabKind::GcLog => &app.gc_log.as_ref().map(|t| draw_gc_log_tab(&mut f, t, focused, zoomed, chunks[1])),
+            TabKind::Prometheus => &app.prometheus.as_mut().map(|t| draw_prometheus_tab(&mut f, t, chunks[1])),
+            TabKind::Source(i) => &app.sources.get(i).map(|t| draw_source_tab(&mut f, t, chunks[1])),
+            TabKind::Compare => &app.compare.as_ref().map(|t| draw_compare_tab(&mut f, t, &chart_views, focused, zoomed, chunks[1])),
+            TabKind::Derived => &app.derived.as_ref().map(|d| draw_derived_tab(&mut f, d, chunks[1])),
+            TabKind::Hooks => &app.hooks.as_ref().map(|h| draw_hooks_tab(&mut f, h, focused, zoomed, chunks[1])),
+            TabKind::Zookeeper => &app.zookeeper.as_ref().map(|z| draw_zookeeper_tab(&mut f, z, &chart_views, focused, zoomed, chunks[1])),
+            TabKind::Latency => &Some(draw_latency_tab(&mut f, &app.latency, chunks[1])),
+            TabKind::Log => &Some(draw_log_tab(&mut f, &app.logger, chunks[1])),
+            TabKind::Dashboard => &Some(draw_dashboard(&mut f, &mut *app, &chart_views, focused, zoomed, sort, chunks[1])),
         };
+        if let Some(age) = stale_for {
+            f.render_widget(StaleOverlay { age }, chunks[1]);
+        }
+        draw_text(&mut f, &availability, &alerts, chunks[2]);
+        draw_key_hints(&mut f, &tabs.current().kind, &command_line, chunks[3]);
     })
 }
 
-fn draw_text<B>(f: &mut Frame<B>, area: Rect)
+/// Keys of the current tab, from the keymap, or the command being typed while the command line is open
+fn draw_key_hints<B>(f: &mut Frame<B>, kind: &TabKind, command_line: &Option<String>, area: Rect)
+    where B: Backend,
+{
+    let text = match command_line {
+        Some(input) => [Text::styled(format!(":{}_ (<Enter> to run, <Esc> to cancel)", input), Style::default().fg(Color::Yellow))],
+        None => [Text::styled(keymap::footer(kind, area.width as usize), Style::default().fg(Color::DarkGray))],
+    };
+    f.render_widget(Paragraph::new(text.iter()), area);
+}
+
+fn draw_text<B>(f: &mut Frame<B>, status: &str, alerts: &str, area: Rect)
     where B: Backend,
 {
-    let text = [];
+    let text = [
+        Text::raw(status),
+        Text::styled(alerts, Style::default().fg(Color::Red).modifier(Modifier::BOLD)),
+    ];
     let p = Paragraph::new(text.iter())
         .block(
             Block::default()
@@ -54,55 +124,170 @@ fn draw_text<B>(f: &mut Frame<B>, area: Rect)
     f.render_widget(p, area);
 }
 
-fn draw_slick_tab<B>(f: &mut Frame<B>, slick: &SlickTab, area: Rect)
+/// Bordered block of a panel, highlighted when the panel is focused
+fn panel_block(title: &str, focused: bool) -> Block {
+    let border_style = if focused { Style::default().fg(Color::Yellow) } else { Style::default() };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(border_style)
+        .title_style(Style::default().fg(Color::Cyan))
+        .title(title)
+}
+
+/// Shown in place of the tabs while configured sources couldn't be reached since the start
+fn draw_diagnostics<B>(f: &mut Frame<B>, diagnostics: &Diagnostics, area: Rect)
     where B: Backend,
 {
-    let chunks = Layout::default()
-        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
-        .split(area);
+    let mut text = vec![];
+    for d in diagnostics.failures.iter() {
+        text.push(Text::styled(format!("{}\n", d.source), Style::default().fg(Color::Red).modifier(Modifier::BOLD)));
+        if d.addresses.is_empty() {
+            text.push(Text::raw("  no network address configured\n"));
+        }
+        for address in d.addresses.iter() {
+            let dns = match diagnostics::host_port(address).map(|h| (diagnostics.dns.get(&h).cloned().flatten(), h)) {
+                None => "not resolved, not a network address".to_owned(),
+                Some((None, host)) => format!("resolving {}...", host),
+                Some((Some(Ok(ips)), host)) => format!("{} resolves to {}", host, ips.join(", ")),
+                Some((Some(Err(e)), host)) => format!("{} doesn't resolve: {}", host, e),
+            };
+            text.push(Text::raw(format!("  address: {} ({})\n", address, dns)));
+        }
+        text.push(Text::raw(format!("  error: {}\n\n", d.error)));
+    }
+    let p = Paragraph::new(text.iter())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title("Couldn't reach sources (r to retry, <Esc> to show the tabs, q to quit)")
+            .title_style(Style::default().fg(Color::Red).modifier(Modifier::BOLD)))
+        .wrap(true);
+    f.render_widget(p, area);
+}
+
+/// Shown in place of a panel relying on a feature its source doesn't have, with the reason
+fn draw_placeholder<B>(f: &mut Frame<B>, title: &str, reason: &str, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let text = [Text::styled(reason, Style::default().fg(Color::DarkGray))];
+    let p = Paragraph::new(text.iter())
+        .block(panel_block(title, focused))
+        .wrap(true);
+    f.render_widget(p, area);
+}
 
-    draw_database_graphs(f, slick, chunks[0]);
-    draw_text(f, chunks[1]);
+/// Areas of the tab panels, or the whole tab area for the focused panel if it's zoomed
+fn panel_areas(layout: Vec<(Panel, Rect)>, focused: Panel, zoomed: bool, area: Rect) -> Vec<(Panel, Rect)> {
+    if zoomed {
+        vec![(focused, area)]
+    } else {
+        layout
+    }
 }
 
-fn draw_database_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+fn draw_slick_tab<B>(f: &mut Frame<B>, slick: &SlickTab, views: &ChartViews, focused: Panel, zoomed: bool, sort: Option<TableSort>, area: Rect)
     where B: Backend,
 {
-    let constraints: Vec<Constraint> = if db.has_hikari {
+    let shows_hikari = slick.has_hikari || slick.hikari_missing.is_some();
+    let constraints: Vec<Constraint> = if shows_hikari || slick.has_pool_errors {
         vec![Constraint::Percentage(50), Constraint::Percentage(50)]
     } else {
         vec![Constraint::Percentage(100)]
     };
 
+    let (charts_area, statements_area) = if slick.has_statements || slick.statements_missing.is_some() {
+        let rows = Layout::default()
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area);
+        (rows[0], Some(rows[1]))
+    } else {
+        (area, None)
+    };
+
     let chunks = Layout::default()
         .constraints(constraints.as_ref())
         .direction(Direction::Horizontal)
-        .split(area);
-    {
-        draw_slick_graphs(f, db, chunks[0]);
-        if db.has_hikari {
-            draw_hikari_graphs(f, db, chunks[1]);
+        .split(charts_area);
+    let slick_chunks = Layout::default()
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(20), Constraint::Percentage(40)].as_ref())
+        .split(chunks[0]);
+
+    let mut layout = vec![
+        (Panel::SlickThreads, slick_chunks[0]),
+        (Panel::SlickUtilization, slick_chunks[1]),
+        (Panel::SlickQueue, slick_chunks[2]),
+    ];
+    match (shows_hikari, slick.has_pool_errors) {
+        (true, true) => {
+            let pool_chunks = Layout::default()
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                .split(chunks[1]);
+            layout.push((Panel::Hikari, pool_chunks[0]));
+            layout.push((Panel::PoolErrors, pool_chunks[1]));
         }
+        (true, false) => layout.push((Panel::Hikari, chunks[1])),
+        (false, true) => layout.push((Panel::PoolErrors, chunks[1])),
+        (false, false) => {}
+    }
+    if let Some(a) = statements_area {
+        layout.push((Panel::Statements, a));
+    }
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_slick_panel(f, slick, views, sort, panel, panel == focused, area);
     }
 }
 
-fn draw_slick_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+fn draw_slick_panel<B>(f: &mut Frame<B>, slick: &SlickTab, views: &ChartViews, sort: Option<TableSort>, panel: Panel, is_focused: bool, area: Rect)
     where B: Backend,
 {
-    let chunks = Layout::default()
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(area);
+    let view = &views.of(panel);
+    match panel {
+        Panel::SlickThreads => draw_slick_threads(f, slick, is_focused, area),
+        Panel::SlickUtilization => draw_slick_utilization(f, slick, is_focused, area),
+        Panel::SlickQueue => draw_slick_queue(f, slick, is_focused, area),
+        Panel::Hikari => match &slick.hikari_missing {
+            Some(reason) => draw_placeholder(f, "HikariCP", reason, is_focused, area),
+            None => draw_hikari_graphs(f, slick, view, is_focused, area),
+        },
+        Panel::PoolErrors => if slick.has_pool_errors {
+            draw_pool_errors(f, slick, view, is_focused, area)
+        } else {
+            draw_placeholder(f, "Pool errors", "No failure counters, see `pool_errors` in the config file", is_focused, area)
+        },
+        Panel::Statements => match &slick.statements_missing {
+            Some(reason) => draw_placeholder(f, "Slowest statements", reason, is_focused, area),
+            None => draw_statements(f, slick, is_focused, sort, area),
+        },
+        _ => {}
+    }
+}
+
+fn draw_statements<B>(f: &mut Frame<B>, db: &SlickTab, focused: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let rows = db.statements.iter().map(|s| vec![
+        s.name.to_owned(),
+        s.count.to_string(),
+        format!("{:.2}", s.mean_ms),
+    ]).collect();
+    let title = format!("Slowest statements ({}, press s/S to sort)", db.statements.len());
+    let table = SortableTable::new(Panel::Statements.table_columns(), rows)
+        .sort(sort)
+        .block(panel_block(&title, focused))
+        .widths(&[Constraint::Percentage(70), Constraint::Percentage(15), Constraint::Percentage(15)]);
+    f.render_widget(table, area);
+}
 
-    let slick_threads_barchart: Vec<(&str, u64)> = db.slick_metrics.iter()
+fn draw_slick_threads<B>(f: &mut Frame<B>, db: &SlickTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let slick_threads_barchart: Vec<(&str, u64)> = db.slick_metrics.latest(SlickTab::MAX_SLICK_MEASURES)
         .map(|x| ("", x.active_threads as u64))
         .collect();
     let active_threads = db.slick_metrics.back().map_or(0, |x| x.active_threads);
     let active_threads_title = format!("Slick active threads: {} (max: {})", active_threads, db.slick_config.max_threads);
     let active_threads_bc = BarChart::default()
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title_style(Style::default().fg(Color::Cyan))
-            .title(&active_threads_title))
+        .block(panel_block(&active_threads_title, focused))
         .data(&slick_threads_barchart)
         .max(db.slick_config.max_threads as u64)
         .bar_width(3)
@@ -113,18 +298,56 @@ fn draw_slick_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
                 .bg(Color::Green)
         )
         .style(Style::default().fg(Color::Green));
-    f.render_widget(active_threads_bc, chunks[0]);
+    f.render_widget(active_threads_bc, area);
+}
+
+/// Gauge of the share of the executor threads in use, above its history
+fn draw_slick_utilization<B>(f: &mut Frame<B>, db: &SlickTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let utilization = db.thread_utilization.back().cloned();
+    let (title, color) = match utilization {
+        Some(u) if u >= 90.0 => (format!("Slick thread utilization: {:.0}%", u), Color::Red),
+        Some(u) if u >= 70.0 => (format!("Slick thread utilization: {:.0}%", u), Color::Yellow),
+        Some(u) => (format!("Slick thread utilization: {:.0}%", u), Color::Green),
+        None => ("Slick thread utilization: n/a".to_owned(), Color::Gray),
+    };
+    let block = panel_block(&title, focused);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let active_threads = db.slick_metrics.back().map_or(0, |x| x.active_threads);
+    let label = format!("{} of {} threads", active_threads, db.slick_config.max_threads);
+    let gauge = Gauge::default()
+        .style(Style::default().fg(color))
+        .percent(utilization.map_or(0.0, |u| u.min(100.0)).round() as u16)
+        .label(&label);
+    f.render_widget(gauge, chunks[0]);
+
+    // the most recent samples that fit
+    let history: Vec<u64> = db.thread_utilization.iter().map(|u| u.round() as u64).collect();
+    let shown = &history[history.len().saturating_sub(chunks[1].width as usize)..];
+    let sparkline = Sparkline::default()
+        .data(shown)
+        .max(100)
+        .style(Style::default().fg(color));
+    f.render_widget(sparkline, chunks[1]);
+}
 
-    let slick_queue_data: Vec<(&str, u64)> = db.slick_metrics.iter()
+fn draw_slick_queue<B>(f: &mut Frame<B>, db: &SlickTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let slick_queue_data: Vec<(&str, u64)> = db.slick_metrics.latest(SlickTab::MAX_SLICK_MEASURES)
         .map(|x| ("", x.queue_size as u64))
         .collect();
     let queue_size = db.slick_metrics.back().map_or(0, |x| x.queue_size);
     let queue_size_title = format!("Slick queue size: {} (max: {})", queue_size, db.slick_config.max_queue_size);
     let slick_queue_bc = BarChart::default()
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title_style(Style::default().fg(Color::Cyan))
-            .title(&queue_size_title))
+        .block(panel_block(&queue_size_title, focused))
         .data(&slick_queue_data)
         .max(db.slick_config.max_queue_size as u64)
         .bar_width(3)
@@ -135,30 +358,34 @@ fn draw_slick_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
                 .bg(Color::Blue)
         )
         .style(Style::default().fg(Color::Blue));
-    f.render_widget(slick_queue_bc, chunks[1]);
+    f.render_widget(slick_queue_bc, area);
 }
 
-fn hikari_chart<F>(db: &SlickTab, f: F) -> Vec<(f64, f64)>
+fn hikari_chart<F>(db: &SlickTab, view: &SeriesView, f: F) -> Vec<(f64, f64)>
     where F: Fn(&HikariMetrics) -> i32, {
-    db.hikari_metrics.iter().enumerate()
-        .map(|(i, x)| (i as f64, f(x) as f64))
-        .collect()
+    view.apply(view.history_timeline(&db.hikari_metrics, |x| f(x) as f64))
 }
 
-fn draw_hikari_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+/// Y axis bounds and labels for the charted series.
+/// Raw values are charted from zero up to the given maximum, derived ones span their actual range.
+fn y_axis(view: &SeriesView, raw_max: f64, series: &[&[(f64, f64)]]) -> ([f64; 2], [String; 3]) {
+    let (lo, hi) = match view.kind {
+        SeriesKind::Raw => (0.0, raw_max),
+        _ => series::y_bounds(series),
+    };
+    let labels = [series::axis_label(lo), series::axis_label((lo + hi) / 2.0), series::axis_label(hi)];
+    ([lo - 1.0, hi + 1.0], labels)
+}
+
+fn draw_hikari_graphs<B>(f: &mut Frame<B>, db: &SlickTab, view: &SeriesView, focused: bool, area: Rect)
     where B: Backend,
 {
-    let total_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.total);
-    let active_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.active);
-    let idle_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.idle);
-    let waiting_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.waiting);
+    let total_chart: Vec<(f64, f64)> = hikari_chart(db, view, |x| x.total);
+    let active_chart: Vec<(f64, f64)> = hikari_chart(db, view, |x| x.active);
+    let idle_chart: Vec<(f64, f64)> = hikari_chart(db, view, |x| x.idle);
+    let waiting_chart: Vec<(f64, f64)> = hikari_chart(db, view, |x| x.waiting);
 
-    let datasets = [
-        Dataset::default()
-            .name("total")
-            .marker(Marker::Braille)
-            .style(Style::default().fg(Color::Blue))
-            .data(&total_chart),
+    let mut datasets = vec![
         Dataset::default()
             .name("active")
             .marker(Marker::Braille)
@@ -175,228 +402,970 @@ fn draw_hikari_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
             .style(Style::default().fg(Color::Green))
             .data(&idle_chart)
     ];
+    let mut lines = vec![
+        Dataset::default()
+            .name("total")
+            .marker(Marker::Braille)
+            .style(Style::default().fg(Color::Blue))
+            .data(&total_chart),
+    ];
 
-    let max_connections = db.hikari_metrics.back().map_or(99, |x| x.total);
-    let total_connections = db.hikari_metrics.back().map_or(0, |x| x.total);
-    let active_connections = db.hikari_metrics.back().map_or(0, |x| x.active);
-    let waiting_connections = db.hikari_metrics.back().map_or(0, |x| x.waiting);
-    let idle_connections = db.hikari_metrics.back().map_or(0, |x| x.idle);
+    // connections are stacked, so waiting threads can take them above the size of the pool
+    let max_connections = db.hikari_metrics.iter().map(|x| x.total.max(x.active + x.idle + x.waiting)).max().unwrap_or(99);
+    let shown = view.cursor_sample(&db.hikari_metrics);
+    let total_connections = shown.map_or(0, |x| x.total);
+    let active_connections = shown.map_or(0, |x| x.active);
+    let waiting_connections = shown.map_or(0, |x| x.waiting);
+    let idle_connections = shown.map_or(0, |x| x.idle);
 
     let title = format!(
-        "HikariCP (total={}, active={}, idle={}, waiting={})",
+        "HikariCP (total={}, active={}, idle={}, waiting={}){}",
         total_connections,
         active_connections,
         idle_connections,
-        waiting_connections
+        waiting_connections,
+        view.title_suffix()
     );
-    let label = &["0".to_owned(), ((max_connections as f64) / 2.0).to_string(), max_connections.to_string()];
+    let (y_bounds, label) = y_axis(
+        view,
+        max_connections as f64,
+        &[&total_chart, &active_chart, &idle_chart, &waiting_chart],
+    );
+    let x_labels = view.x_labels();
+    let cursor_line = view.cursor_line(y_bounds);
+    lines.push(cursor_dataset(&cursor_line));
+    if view.kind == SeriesKind::Raw {
+        let layers = [
+            Layer { name: "active", color: Color::Red, data: &active_chart },
+            Layer { name: "idle", color: Color::Green, data: &idle_chart },
+            Layer { name: "waiting", color: Color::Yellow, data: &waiting_chart },
+        ];
+        let c = StackedAreaChart::new(&layers)
+            .block(panel_block(&title, focused))
+            .x_axis(view.x_bounds(), &x_labels)
+            .y_axis(y_bounds, &label)
+            .lines(lines);
+        f.render_widget(c, area);
+        return;
+    }
+    datasets.extend(lines);
     let c = Chart::default()
-        .block(
-            Block::default()
-                .title(&title)
-                .title_style(Style::default().fg(Color::Cyan))
-                .borders(Borders::ALL)
-        )
-        .x_axis(
+        .block(panel_block(&title, focused))
+        .x_axis(time_axis(view, &x_labels))
+        .y_axis(
             Axis::default()
                 .style(Style::default().fg(Color::Gray))
                 .labels_style(Style::default().modifier(Modifier::ITALIC))
-                .bounds([0.0, SlickTab::MAX_HIKARI_MEASURES as f64])
-                .labels(&["older", "recent"])
+                .bounds(y_bounds)
+                .labels(&label)
         )
+        .datasets(&datasets);
+    f.render_widget(c, area);
+}
+
+/// Failures per second of the configured counters, only charting the counters that are configured
+fn draw_pool_errors<B>(f: &mut Frame<B>, db: &SlickTab, view: &SeriesView, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let fields: [(&str, Color, fn(&PoolErrorRates) -> Option<f64>); 3] = [
+        ("timeouts", Color::Red, |r| r.connection_timeouts),
+        ("creation failures", Color::Magenta, |r| r.creation_failures),
+        ("rejections", Color::Yellow, |r| r.rejections),
+    ];
+    let configured: Vec<_> = fields.iter()
+        .filter(|(_, _, rate)| db.pool_error_rates.iter().any(|r| rate(r).is_some()))
+        .collect();
+    let charts: Vec<Vec<(f64, f64)>> = configured.iter()
+        .map(|(_, _, rate)| view.apply(view.history_timeline(&db.pool_error_rates, |r| rate(r).unwrap_or(0.0))))
+        .collect();
+
+    let shown = view.cursor_sample(&db.pool_error_rates);
+    let values: Vec<String> = configured.iter()
+        .map(|(name, _, rate)| format!("{}={:.2}/s", name, shown.and_then(|r| rate(r)).unwrap_or(0.0)))
+        .collect();
+    let title = match &db.pool_errors_error {
+        Some(e) => format!("Pool errors ({})", e),
+        None => format!("Pool errors ({}){}", values.join(", "), view.title_suffix()),
+    };
+
+    let raw_max = db.pool_error_rates.iter()
+        .flat_map(|r| configured.iter().filter_map(move |(_, _, rate)| rate(r)))
+        .fold(1.0, f64::max);
+    let series: Vec<&[(f64, f64)]> = charts.iter().map(|c| c.as_slice()).collect();
+    let (y_bounds, label) = y_axis(view, raw_max, &series);
+    let x_labels = view.x_labels();
+    let cursor_line = view.cursor_line(y_bounds);
+    let mut datasets: Vec<Dataset> = configured.iter().zip(charts.iter())
+        .map(|((name, color, _), chart)| Dataset::default()
+            .name(*name)
+            .marker(Marker::Braille)
+            .style(Style::default().fg(*color))
+            .data(chart))
+        .collect();
+    datasets.push(cursor_dataset(&cursor_line));
+    let c = Chart::default()
+        .block(panel_block(&title, focused))
+        .x_axis(time_axis(view, &x_labels))
         .y_axis(
             Axis::default()
                 .style(Style::default().fg(Color::Gray))
                 .labels_style(Style::default().modifier(Modifier::ITALIC))
-                .bounds([-1.0, (max_connections + 1) as f64])
-                .labels(label)
+                .bounds(y_bounds)
+                .labels(&label)
         )
         .datasets(&datasets);
     f.render_widget(c, area);
 }
 
-
-fn draw_zio_tab<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, area: Rect)
+fn draw_jvm_tab<B>(f: &mut Frame<B>, jvm: &JVMTab, focused: Panel, zoomed: bool, sort: Option<TableSort>, area: Rect)
     where B: Backend,
 {
+    if let Some(e) = &jvm.error {
+        let text = [Text::styled(e, Style::default().fg(Color::Red))];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("JVM"))
+            .wrap(true);
+        f.render_widget(p, area);
+        return;
+    }
+
     let chunks = Layout::default()
-        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
         .split(area);
-    draw_fiber_list(f, zmx, chunks[0]);
-    draw_text(f, chunks[1]);
+    let chart_chunks = Layout::default()
+        .constraints([Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[1]);
+    let layout = vec![
+        (Panel::JVMAttributes, chunks[0]),
+        (Panel::JVMClasses, chart_chunks[0]),
+        (Panel::JVMThreads, chart_chunks[1]),
+        (Panel::JVMDaemonThreads, chart_chunks[2]),
+    ];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_jvm_panel(f, jvm, sort, panel, panel == focused, area);
+    }
 }
 
-fn fiber_count_chart<F>(db: &ZMXTab, f: F) -> Vec<(f64, f64)>
-    where F: Fn(&FiberCount) -> i32, {
-    db.fiber_counts.iter().enumerate()
-        .map(|(i, x)| (i as f64, f(x) as f64))
-        .collect()
+fn draw_jvm_panel<B>(f: &mut Frame<B>, jvm: &JVMTab, sort: Option<TableSort>, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    let last = jvm.runtime.back();
+    match panel {
+        Panel::JVMAttributes => draw_jvm_attributes(f, jvm, sort, area),
+        Panel::JVMClasses => {
+            let title = format!("Loaded classes: {}", last.map_or(0, |m| m.loaded_classes));
+            let data: Vec<u64> = jvm.runtime.iter().map(|m| m.loaded_classes as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Magenta, is_focused, area)
+        }
+        Panel::JVMThreads => {
+            let title = format!(
+                "Live threads: {} (peak: {}, press T for a thread dump)",
+                last.map_or(0, |m| m.live_threads), last.map_or(0, |m| m.peak_threads)
+            );
+            let data: Vec<u64> = jvm.runtime.iter().map(|m| m.live_threads as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Green, is_focused, area)
+        }
+        Panel::JVMDaemonThreads => {
+            let title = format!("Daemon threads: {}", last.map_or(0, |m| m.daemon_threads));
+            let data: Vec<u64> = jvm.runtime.iter().map(|m| m.daemon_threads as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Blue, is_focused, area)
+        }
+        _ => {}
+    }
 }
 
-fn draw_fiber_list<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, area: Rect)
+fn draw_jvm_attributes<B>(f: &mut Frame<B>, jvm: &JVMTab, sort: Option<TableSort>, area: Rect)
     where B: Backend,
 {
-    let constraints = vec![Constraint::Percentage(100)];
+    let n = jvm.attributes.len().max(1);
+    let constraints: Vec<Constraint> = jvm.attributes.iter()
+        .map(|_| Constraint::Percentage((100 / n) as u16))
+        .collect();
     let chunks = Layout::default()
         .constraints(constraints)
         .direction(Direction::Horizontal)
         .split(area);
-    {
+
+    for ((name, value), chunk) in jvm.attributes.iter().zip(chunks) {
+        let rows = value.table_rows().into_iter().map(|(k, v)| vec![k, v]).collect();
+        let title = if name == "Heap memory" { format!("{} (press H for a heap dump)", name) } else { name.to_owned() };
+        let table = SortableTable::new(Panel::JVMAttributes.table_columns(), rows)
+            .sort(sort)
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title(&title))
+            .widths(&[Constraint::Percentage(60), Constraint::Percentage(40)]);
+        f.render_widget(table, chunk);
+    }
+}
+
+fn draw_custom_tab<B>(f: &mut Frame<B>, custom: &CustomTab, area: Rect)
+    where B: Backend,
+{
+    if let Some(e) = &custom.error {
+        let text = [Text::styled(e, Style::default().fg(Color::Red))];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("Custom"))
+            .wrap(true);
+        f.render_widget(p, area);
+        return;
+    }
+
+    let sparklines: Vec<(String, Vec<f64>)> = custom.labels.iter().zip(custom.values.iter()).zip(custom.latest.iter())
+        .map(|((label, values), latest)| (format!("{}: {}", label, latest.map_or("n/a".to_owned(), series::axis_label)), values.iter().copied().collect()))
+        .collect();
+    draw_sparkline_grid(f, &sparklines, area);
+}
+
+fn draw_derived_tab<B>(f: &mut Frame<B>, derived: &DerivedSeries, area: Rect)
+    where B: Backend,
+{
+    let sparklines: Vec<(String, Vec<f64>)> = derived.metrics.iter().zip(derived.values.iter()).zip(derived.latest.iter())
+        .map(|((m, values), latest)| (format!("{} = {}: {}", m.name, m.source, latest.map_or("n/a".to_owned(), series::axis_label)), values.iter().copied().collect()))
+        .collect();
+    draw_sparkline_grid(f, &sparklines, area);
+}
+
+fn draw_consul_tab<B>(f: &mut Frame<B>, consul: &ConsulTab, area: Rect)
+    where B: Backend,
+{
+    let style = |status: HealthStatus| match status {
+        HealthStatus::Passing => Style::default().fg(Color::Green),
+        HealthStatus::Warning => Style::default().fg(Color::Yellow),
+        HealthStatus::Critical => Style::default().fg(Color::Red),
+    };
+    let width = consul.services.iter().map(|s| s.name.chars().count()).max().unwrap_or(0);
+    let mut text = vec![];
+    for s in consul.services.iter() {
+        text.push(Text::styled(format!("{:width$} {:8}", s.name, format!("{:?}", s.status), width = width), style(s.status)));
+        text.push(Text::raw(format!(" passing={} warning={} critical={} ", s.passing, s.warning, s.critical)));
+        for h in consul.history.get(&s.name).into_iter().flat_map(|h| h.latest(ConsulTab::MAX_CONSUL_MEASURES)) {
+            text.push(Text::styled("■", style(*h)));
+        }
+        text.push(Text::raw("\n"));
+    }
+    if text.is_empty() {
+        text.push(Text::raw("No services registered"));
+    }
+    let title = format!(
+        "Services (passing={}, warning={}, critical={}, history oldest first)",
+        consul.count(HealthStatus::Passing),
+        consul.count(HealthStatus::Warning),
+        consul.count(HealthStatus::Critical)
+    );
+    let p = Paragraph::new(text.iter())
+        .block(panel_block(&title, true));
+    f.render_widget(p, area);
+}
+
+fn draw_prometheus_tab<B>(f: &mut Frame<B>, prometheus: &mut PrometheusTab, area: Rect)
+    where B: Backend,
+{
+    let area = if prometheus.search.is_some() {
         let chunks = Layout::default()
-            .constraints([Constraint::Percentage(100)].as_ref())
-            .split(chunks[0]);
-        {
-            let chunks = Layout::default()
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                .direction(Direction::Horizontal)
-                .split(chunks[0]);
-            {
-                let chunks = Layout::default()
-                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                    .split(chunks[0]);
-
-                let items = zmx.fibers.items.iter().map(|i| Text::raw(i));
-
-                let list = List::new(items)
-                    .block(Block::default()
-                        .borders(Borders::ALL)
-                        .title_style(Style::default().fg(Color::Cyan))
-                        .title("Fibers (press <Enter> to take a snapshot)"))
-                    .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
-                    .highlight_symbol(">");
-                f.render_stateful_widget(list, chunks[0], &mut zmx.fibers.state);
-
-                let running_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.running);
-                let done_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.done);
-                let finishing_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.finishing);
-                let suspended_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.suspended);
-
-                let datasets = [
-                    Dataset::default()
-                        .name("running")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Green))
-                        .data(&running_chart),
-                    Dataset::default()
-                        .name("done")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::LightBlue))
-                        .data(&done_chart),
-                    Dataset::default()
-                        .name("finishing")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::White))
-                        .data(&finishing_chart),
-                    Dataset::default()
-                        .name("suspended")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Yellow))
-                        .data(&suspended_chart)
-                ];
-
-                let max_fibers = zmx.fiber_counts.iter().map(|x| x.total()).max().unwrap_or(0);
-                let total_fibers = zmx.fiber_counts.back().map_or(0, |x| x.total());
-                let running_fibers = zmx.fiber_counts.back().map_or(0, |x| x.running);
-                let done_fibers = zmx.fiber_counts.back().map_or(0, |x| x.done);
-                let finishing_fibers = zmx.fiber_counts.back().map_or(0, |x| x.finishing);
-                let suspended_fibers = zmx.fiber_counts.back().map_or(0, |x| x.suspended);
-
-                let title = format!(
-                    "Fibers (total={}, running={}, done={}, finishing={}, suspended={})",
-                    total_fibers,
-                    running_fibers,
-                    done_fibers,
-                    finishing_fibers,
-                    suspended_fibers
-                );
-                let label = &["0".to_owned(), ((max_fibers as f64) / 2.0).to_string(), max_fibers.to_string()];
-                let c = Chart::default()
-                    .block(
-                        Block::default()
-                            .title(&title)
-                            .title_style(Style::default().fg(Color::Cyan))
-                            .borders(Borders::ALL)
-                    )
-                    .x_axis(
-                        Axis::default()
-                            .style(Style::default().fg(Color::Gray))
-                            .labels_style(Style::default().modifier(Modifier::ITALIC))
-                            .bounds([0.0, ZMXTab::MAX_FIBER_COUNT_MEASURES as f64])
-                            .labels(&["older", "recent"])
-                    )
-                    .y_axis(
-                        Axis::default()
-                            .style(Style::default().fg(Color::Gray))
-                            .labels_style(Style::default().modifier(Modifier::ITALIC))
-                            .bounds([-1.0, (max_fibers + 1) as f64])
-                            .labels(label)
-                    )
-                    .datasets(&datasets);
-                f.render_widget(c, chunks[1]);
-            }
+            .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(area);
+        draw_metric_explorer(f, prometheus, chunks[0]);
+        chunks[1]
+    } else {
+        area
+    };
+
+    let sparklines: Vec<(String, Vec<f64>)> = prometheus.series.iter().zip(prometheus.values.iter())
+        .map(|(name, values)| (format!("{}: {}", name, values.back().map_or("n/a".to_owned(), |v| series::axis_label(*v))), values.iter().copied().collect()))
+        .collect();
+    if sparklines.is_empty() {
+        let text = [Text::raw("None of the configured metrics has been scraped yet, press / to search for metrics to pin")];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("Prometheus"));
+        f.render_widget(p, area);
+        return;
+    }
+    draw_sparkline_grid(f, &sparklines, area);
+}
+
+/// Panels described by the data source, stacked vertically
+fn draw_source_tab<B>(f: &mut Frame<B>, source: &SourceTab, area: Rect)
+    where B: Backend,
+{
+    if source.series.is_empty() {
+        let text = [Text::raw("No samples yet")];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title(&source.name));
+        f.render_widget(p, area);
+        return;
+    }
 
-            let text = [Text::raw(zmx.selected_fiber_dump.0.to_owned())];
-
-            let p = Paragraph::new(text.iter())
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Fiber dump (press <PageUp>/<PageDown> to scroll)")
-                        .title_style(Style::default().fg(Color::Cyan)),
-                )
-                .wrap(true)
-                .scroll(zmx.scroll);
-            f.render_widget(p, chunks[1]);
+    let count = source.panels.len().max(1);
+    let chunks = Layout::default()
+        .constraints(vec![Constraint::Percentage((100 / count) as u16); count])
+        .split(area);
+    for (panel, area) in source.panels.iter().zip(chunks.into_iter()) {
+        let series = source.panel_series(panel);
+        let block = Block::default().borders(Borders::ALL).title(&panel.title);
+        match panel.style {
+            PanelStyle::Sparklines => {
+                let inner = block.inner(area);
+                f.render_widget(block, area);
+                let sparklines: Vec<(String, Vec<f64>)> = series.into_iter()
+                    .map(|(name, values)| (format!("{}: {}", name, values.back().map_or("n/a".to_owned(), |v| series::axis_label(*v))), values.iter().copied().collect()))
+                    .collect();
+                draw_sparkline_grid(f, &sparklines, inner);
+            }
+            PanelStyle::Table => {
+                let rows = series.into_iter().map(|(name, values)| Row::Data(vec![
+                    name.to_owned(),
+                    values.back().map_or("n/a".to_owned(), |v| series::axis_label(*v)),
+                ].into_iter()));
+                let table = Table::new(["Series", "Value"].iter(), rows)
+                    .block(block)
+                    .header_style(Style::default().fg(Color::Yellow))
+                    .widths(&[Constraint::Percentage(80), Constraint::Percentage(20)]);
+                f.render_widget(table, area);
+            }
         }
     }
 }
 
-fn draw_actor_tree_tab<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, area: Rect)
+fn draw_compare_tab<B>(f: &mut Frame<B>, compare: &CompareTab, views: &ChartViews, focused: Panel, zoomed: bool, area: Rect)
     where B: Backend,
 {
     let chunks = Layout::default()
-        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .constraints([Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref())
         .split(area);
-    {
-        let chunks = Layout::default()
-            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
-            .split(chunks[0]);
-        draw_actor_tree(f, tab, chunks[0]);
-        draw_actor_count_chart(f, tab, chunks[1]);
+    let layout = vec![
+        (Panel::CompareFibers, chunks[0]),
+        (Panel::CompareHikari, chunks[1]),
+        (Panel::CompareActors, chunks[2]),
+    ];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_compare_panel(f, compare, views, panel, panel == focused, area);
+    }
+}
+
+fn draw_compare_panel<B>(f: &mut Frame<B>, compare: &CompareTab, views: &ChartViews, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    let view = &views.of(panel);
+    match panel {
+        Panel::CompareFibers => draw_compare_chart(f, compare, "Fibers", |e| &e.fibers_total, view, is_focused, area),
+        Panel::CompareHikari => draw_compare_chart(f, compare, "Active Hikari connections", |e| &e.hikari_active, view, is_focused, area),
+        Panel::CompareActors => draw_compare_chart(f, compare, "Actors", |e| &e.actor_count, view, is_focused, area),
+        _ => {}
+    }
+}
+
+/// A metric of every compared environment on the same axes
+fn draw_compare_chart<B>(
+    f: &mut Frame<B>,
+    compare: &CompareTab,
+    metric: &str,
+    series: fn(&CompareEnv) -> &History<f64>,
+    view: &SeriesView,
+    focused: bool,
+    area: Rect)
+    where B: Backend,
+{
+    const COLORS: [Color; 2] = [Color::Cyan, Color::Yellow];
+    let charts: Vec<Vec<(f64, f64)>> = compare.envs.iter()
+        .map(|e| view.apply(view.history_timeline(series(e), |v| *v)))
+        .collect();
+    let names: Vec<String> = compare.envs.iter()
+        .map(|e| if e.error.is_some() { format!("{} (not polled, see Log)", e.name) } else { e.name.to_owned() })
+        .collect();
+    let mut datasets: Vec<Dataset> = charts.iter().zip(names.iter()).enumerate()
+        .map(|(i, (chart, name))| Dataset::default()
+            .name(name)
+            .marker(Marker::Braille)
+            .style(Style::default().fg(COLORS[i % COLORS.len()]))
+            .data(chart))
+        .collect();
+
+    let shown: Vec<String> = compare.envs.iter()
+        .map(|e| {
+            let value = view.cursor_sample(series(e));
+            format!("{}={}", e.name, value.map_or("n/a".to_owned(), |v| series::axis_label(*v)))
+        })
+        .collect();
+    let title = format!("{} ({}){}", metric, shown.join(", "), view.title_suffix());
+
+    let raw_max = compare.envs.iter().flat_map(|e| series(e).iter()).fold(0.0, |m: f64, v| m.max(*v));
+    let chart_refs: Vec<&[(f64, f64)]> = charts.iter().map(|c| c.as_slice()).collect();
+    let (y_bounds, label) = y_axis(view, raw_max, &chart_refs);
+    let x_labels = view.x_labels();
+    let cursor_line = view.cursor_line(y_bounds);
+    datasets.push(cursor_dataset(&cursor_line));
+    let c = Chart::default()
+        .block(panel_block(&title, focused))
+        .x_axis(time_axis(view, &x_labels))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .labels_style(Style::default().modifier(Modifier::ITALIC))
+                .bounds(y_bounds)
+                .labels(&label)
+        )
+        .datasets(&datasets);
+    f.render_widget(c, area);
+}
+
+/// Titled sparklines laid out in rows of three
+fn draw_sparkline_grid<B>(f: &mut Frame<B>, sparklines: &[(String, Vec<f64>)], area: Rect)
+    where B: Backend,
+{
+    const COLUMNS: usize = 3;
+    let rows = (sparklines.len() + COLUMNS - 1) / COLUMNS;
+    let row_chunks = Layout::default()
+        .constraints(vec![Constraint::Percentage((100 / rows.max(1)) as u16); rows])
+        .split(area);
+
+    for (i, (title, values)) in sparklines.iter().enumerate() {
+        let cells = Layout::default()
+            .constraints(vec![Constraint::Percentage((100 / COLUMNS) as u16); COLUMNS])
+            .direction(Direction::Horizontal)
+            .split(row_chunks[i / COLUMNS]);
+        let (lo, hi) = values.iter().fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+        // sparklines only take integers, so values are scaled into 1..=100 to keep fractions and small changes visible
+        let data: Vec<u64> = values.iter()
+            .map(|v| if hi > lo { 1 + ((v - lo) / (hi - lo) * 99.0) as u64 } else { 50 })
+            .collect();
+        draw_sparkline(f, title, &data, Color::Cyan, false, cells[i % COLUMNS]);
+    }
+}
+
+fn draw_zio_tab<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, views: &ChartViews, focused: Panel, zoomed: bool, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(area);
+    let list_chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[0]);
+    let dump_chunks = Layout::default()
+        .constraints([Constraint::Min(0), Constraint::Length(8)].as_ref())
+        .split(chunks[1]);
+
+    let layout = vec![
+        (Panel::Fibers, list_chunks[0]),
+        (Panel::FiberCounts, list_chunks[1]),
+        (Panel::FiberDump, dump_chunks[0]),
+        (Panel::FiberTree, dump_chunks[1]),
+    ];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_zio_panel(f, zmx, views, panel, panel == focused, area);
     }
-    draw_text(f, chunks[1]);
 }
 
+fn draw_zio_panel<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, views: &ChartViews, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    match panel {
+        Panel::Fibers => draw_fiber_list(f, zmx, is_focused, area),
+        Panel::FiberCounts => draw_fiber_counts(f, zmx, &views.of(panel), is_focused, area),
+        Panel::FiberDump => draw_fiber_dump(f, zmx, is_focused, area),
+        Panel::FiberTree => draw_fiber_tree_shape(f, zmx, is_focused, area),
+        _ => {}
+    }
+}
+
+fn fiber_count_chart<F>(db: &ZMXTab, view: &SeriesView, f: F) -> Vec<(f64, f64)>
+    where F: Fn(&FiberCount) -> i32, {
+    view.apply(view.history_timeline(&db.fiber_counts, |x| f(x) as f64))
+}
+
+/// Shared time axis of all line charts
+fn time_axis<'a>(view: &SeriesView, labels: &'a [String; 3]) -> Axis<'a, String> {
+    Axis::default()
+        .style(Style::default().fg(Color::Gray))
+        .labels_style(Style::default().modifier(Modifier::ITALIC))
+        .bounds(view.x_bounds())
+        .labels(labels)
+}
+
+/// Vertical line at the cursor position, shown on top of the charted series
+fn cursor_dataset(cursor_line: &[(f64, f64)]) -> Dataset {
+    Dataset::default()
+        .marker(Marker::Dot)
+        .style(Style::default().fg(Color::Magenta))
+        .data(cursor_line)
+}
 
-fn draw_actor_tree<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, area: Rect)
+fn draw_fiber_list<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, focused: bool, area: Rect)
     where B: Backend,
 {
-    let items = tab.actors.items.iter().map(|i| Text::raw(i));
+    let title = match &zmx.fiber_prompt {
+        Some(input) => format!("Jump to fiber #{}_ (<Enter> to jump, <Esc> to cancel)", input),
+        None => format!(
+            "Fibers by {} (press <Enter> to take a snapshot, p/c/n for parent/child/next sibling, g to filter by status, o to sort, : to jump to a fiber)",
+            zmx.fiber_sort,
+        ),
+    };
+    let block = panel_block(&title, focused);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let banner = zmx.grouped_fibers().map(|n| format!(
+        "{} fibers are more than {} to list one by one: fibers are grouped by status line, each group showing the dump of a sample (exports cover every fiber)",
+        n, zmx.fiber_limit,
+    ));
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Length(if banner.is_some() { 1 } else { 0 }), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    // the status the list is narrowed to stands out of the counts of the snapshot
+    let mut summary = vec![];
+    for (i, (status, count)) in zmx.status_counts().into_iter().enumerate() {
+        if i > 0 {
+            summary.push(Text::raw(" | "));
+        }
+        let style = if zmx.status_filter.as_ref() == Some(&status) {
+            Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        summary.push(Text::styled(format!("{} {}", status, count), style));
+    }
+    f.render_widget(Paragraph::new(summary.iter()), chunks[0]);
+    if let Some(banner) = banner {
+        let text = [Text::styled(banner, Style::default().fg(Color::Black).bg(Color::Yellow))];
+        f.render_widget(Paragraph::new(text.iter()), chunks[1]);
+    }
+
+    let (visible, selected) = zmx.fibers.visible_items(chunks[2].height as usize);
+    let items = visible.iter().map(|i| Text::raw(i));
+    let mut state = ListState::default();
+    state.select(selected);
+
+    let list = List::new(items)
+        .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+    f.render_stateful_widget(list, chunks[2], &mut state);
+}
+
+/// Number of items that fit in a bordered list
+fn draw_metric_explorer<B>(f: &mut Frame<B>, prometheus: &mut PrometheusTab, area: Rect)
+    where B: Backend,
+{
+    let pinned = &prometheus.pinned.metrics;
+    let search = match prometheus.search.as_mut() {
+        Some(s) => s,
+        None => return,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Metrics (type to search, <Enter> to pin, <Esc> to close)");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+    let query = [Text::styled(format!("/{}", search.query), Style::default().fg(Color::Yellow))];
+    f.render_widget(Paragraph::new(query.iter()), chunks[0]);
+
+    let (visible, selected) = search.matches.visible_items(chunks[1].height as usize);
+    let items = visible.iter().map(|m| {
+        let marker = if pinned.contains(m) { "* " } else { "  " };
+        Text::raw(format!("{}{}", marker, m))
+    });
+    let mut state = ListState::default();
+    state.select(selected);
 
     let list = List::new(items)
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title_style(Style::default().fg(Color::Cyan))
-            .title("Actors (press <Enter> to reload the tree)"))
         .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
         .highlight_symbol(">");
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
 
-    f.render_stateful_widget(list, area, &mut tab.actors.state);
+fn list_height(area: Rect) -> usize {
+    area.height.saturating_sub(2) as usize
 }
 
-fn draw_actor_count_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+fn draw_fiber_counts<B>(f: &mut Frame<B>, zmx: &ZMXTab, view: &SeriesView, focused: bool, area: Rect)
     where B: Backend,
 {
-    let data: Vec<(&str, u64)> = tab.actor_counts.iter()
-        .map(|x| ("", x.to_owned()))
-        .collect();
+    let running_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, view, |x| x.running);
+    let done_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, view, |x| x.done);
+    let finishing_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, view, |x| x.finishing);
+    let suspended_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, view, |x| x.suspended);
+    let created_chart: Vec<(f64, f64)> = view.apply(view.history_timeline(&zmx.fiber_lifecycle, |x| x.created as f64));
+    let completed_chart: Vec<(f64, f64)> = view.apply(view.history_timeline(&zmx.fiber_lifecycle, |x| x.completed as f64));
 
-    let title = format!("Running actors: {}", tab.actor_counts.back().unwrap_or(&0));
-    let count_bc = BarChart::default()
-        .block(Block::default()
-            .borders(Borders::ALL)
-            .title_style(Style::default().fg(Color::Cyan))
-            .title(&title))
+    let statuses = [
+        (FiberStatus::Running, "1 running", Color::Green, &running_chart),
+        (FiberStatus::Done, "2 done", Color::LightBlue, &done_chart),
+        (FiberStatus::Finishing, "3 finishing", Color::White, &finishing_chart),
+        (FiberStatus::Suspended, "4 suspended", Color::Yellow, &suspended_chart),
+    ];
+    let mut datasets: Vec<Dataset> = statuses.iter()
+        .filter(|(status, _, _, _)| !zmx.hidden_statuses.contains(status))
+        .map(|(_, name, color, data)| Dataset::default()
+            .name(*name)
+            .marker(Marker::Braille)
+            .style(Style::default().fg(*color))
+            .data(data))
+        .collect();
+    let mut lines = vec![
+        Dataset::default()
+            .name("created")
+            .marker(Marker::Dot)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&created_chart),
+        Dataset::default()
+            .name("completed")
+            .marker(Marker::Dot)
+            .style(Style::default().fg(Color::Magenta))
+            .data(&completed_chart),
+    ];
+
+    let max_fibers = zmx.shown_fibers_max();
+    let shown = view.cursor_sample(&zmx.fiber_counts);
+    let total_fibers = shown.map_or(0, |x| x.total());
+    let running_fibers = shown.map_or(0, |x| x.running);
+    let done_fibers = shown.map_or(0, |x| x.done);
+    let finishing_fibers = shown.map_or(0, |x| x.finishing);
+    let suspended_fibers = shown.map_or(0, |x| x.suspended);
+    let lifecycle = view.cursor_sample(&zmx.fiber_lifecycle);
+    let created_fibers = lifecycle.map_or(0, |x| x.created);
+    let completed_fibers = lifecycle.map_or(0, |x| x.completed);
+
+    let title = format!(
+        "Fibers (total={}, running={}, done={}, finishing={}, suspended={}, created={}, completed={}){}",
+        total_fibers,
+        running_fibers,
+        done_fibers,
+        finishing_fibers,
+        suspended_fibers,
+        created_fibers,
+        completed_fibers,
+        view.title_suffix()
+    );
+    let (y_bounds, label) = y_axis(
+        view,
+        max_fibers as f64,
+        &statuses.iter()
+            .filter(|(status, _, _, _)| !zmx.hidden_statuses.contains(status))
+            .map(|(_, _, _, data)| data.as_slice())
+            .chain(vec![created_chart.as_slice(), completed_chart.as_slice()])
+            .collect::<Vec<&[(f64, f64)]>>(),
+    );
+    let x_labels = view.x_labels();
+    let cursor_line = view.cursor_line(y_bounds);
+    lines.push(cursor_dataset(&cursor_line));
+    // raw counts add up to all the fibers, derived ones such as rates don't
+    if view.kind == SeriesKind::Raw {
+        let layers: Vec<Layer> = statuses.iter()
+            .filter(|(status, _, _, _)| !zmx.hidden_statuses.contains(status))
+            .map(|(_, name, color, data)| Layer { name, color: *color, data })
+            .collect();
+        let c = StackedAreaChart::new(&layers)
+            .block(panel_block(&title, focused))
+            .x_axis(view.x_bounds(), &x_labels)
+            .y_axis(y_bounds, &label)
+            .lines(lines);
+        f.render_widget(c, area);
+        return;
+    }
+    datasets.extend(lines);
+    let c = Chart::default()
+        .block(panel_block(&title, focused))
+        .x_axis(time_axis(view, &x_labels))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .labels_style(Style::default().modifier(Modifier::ITALIC))
+                .bounds(y_bounds)
+                .labels(&label)
+        )
+        .datasets(&datasets);
+    f.render_widget(c, area);
+}
+
+/// Number of fibers by their number of children, along with the depth of the fiber tree
+fn draw_fiber_tree_shape<B>(f: &mut Frame<B>, zmx: &ZMXTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let shape = zmx.tree_shape.clone().unwrap_or_default();
+    let title = format!(
+        "Fibers by children (max depth={}, most children={}, orphans={})",
+        shape.max_depth,
+        shape.max_children,
+        shape.orphans,
+    );
+    let data: Vec<(&str, u64)> = FiberTreeShape::CHILDREN_RANGES.iter()
+        .zip(shape.children.iter())
+        .map(|(range, n)| (*range, *n as u64))
+        .collect();
+
+    let bc = BarChart::default()
+        .block(panel_block(&title, focused))
+        .data(&data)
+        .bar_width(5)
+        .bar_gap(1)
+        .value_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+        )
+        .style(Style::default().fg(Color::Cyan));
+    f.render_widget(bc, area);
+}
+
+fn draw_fiber_dump<B>(f: &mut Frame<B>, zmx: &ZMXTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    // lines that changed since the previous snapshot stay marked until the next one
+    let dump = zmx.visible_dump();
+    let lines: Vec<&str> = dump.split('\n').collect();
+    let text: Vec<Text> = lines.iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let line = if i + 1 < lines.len() { format!("{}\n", l) } else { l.to_string() };
+            match zmx.dump_changes.get(i) {
+                Some(LineChange::Added) => Text::styled(line, Style::default().fg(Color::Green)),
+                Some(LineChange::Removed) => Text::styled(line, Style::default().fg(Color::Red).modifier(Modifier::CROSSED_OUT)),
+                _ => Text::raw(line),
+            }
+        })
+        .collect();
+    let title = if zmx.wrap {
+        "Fiber dump (press <PageUp>/<PageDown> to scroll, f/F to fold, w to unwrap, d to save all dumps)"
+    } else {
+        "Fiber dump (press <PageUp>/<PageDown> or <Left>/<Right> to scroll, f/F to fold, w to wrap, d to save all dumps)"
+    };
+
+    let p = Paragraph::new(text.iter())
+        .block(panel_block(title, focused))
+        .wrap(zmx.wrap)
+        .scroll(zmx.scroll);
+    f.render_widget(p, area);
+}
+
+fn draw_actor_tree_tab<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, focused: Panel, zoomed: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let area = if tab.has_health_checks || tab.health_missing.is_some() {
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(5), Constraint::Min(0)].as_ref())
+            .split(area);
+        match &tab.health_missing {
+            Some(reason) => draw_placeholder(f, "Health checks", reason, false, chunks[0]),
+            None => draw_akka_health(f, tab, chunks[0]),
+        }
+        chunks[1]
+    } else {
+        area
+    };
+
+    let (actors_area, sharding_area) = if tab.has_sharding || tab.sharding_missing.is_some() {
+        let rows = Layout::default()
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area);
+        (rows[0], Some(rows[1]))
+    } else {
+        (area, None)
+    };
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(actors_area);
+    let bottom_constraints = if tab.restarts.is_some() {
+        vec![Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)]
+    } else {
+        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+    };
+    let bottom = Layout::default()
+        .constraints(bottom_constraints.as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[1]);
+    let mut layout = vec![(Panel::Actors, chunks[0]), (Panel::ActorCount, bottom[0]), (Panel::ActorEvents, bottom[1])];
+    if tab.restarts.is_some() {
+        layout.push((Panel::ActorRestarts, bottom[2]));
+    }
+    if let Some(a) = sharding_area {
+        layout.push((Panel::Sharding, a));
+    }
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_actor_tree_panel(f, tab, sort, panel, panel == focused, area);
+    }
+}
+
+fn draw_actor_tree_panel<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, sort: Option<TableSort>, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    match panel {
+        Panel::Actors => draw_actor_tree(f, tab, is_focused, area),
+        Panel::ActorCount => draw_actor_count_chart(f, tab, is_focused, area),
+        Panel::ActorEvents => draw_actor_events(f, tab, is_focused, area),
+        Panel::ActorRestarts => draw_actor_restarts(f, tab, is_focused, sort, area),
+        Panel::Sharding => match &tab.sharding_missing {
+            Some(reason) => draw_placeholder(f, "Cluster sharding", reason, is_focused, area),
+            None => draw_sharding(f, tab, is_focused, sort, area),
+        },
+        _ => {}
+    }
+}
+
+fn draw_sharding<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, focused: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(area);
+
+    let title = match &tab.sharding_error {
+        Some(e) => format!("Cluster sharding: {}", e),
+        None => {
+            let regions: Vec<String> = tab.sharding.entities_per_region().iter()
+                .map(|(entity_type, entities)| format!("{}={}", entity_type, entities))
+                .collect();
+            format!("Cluster sharding, entities per region: {}", regions.join(", "))
+        }
+    };
+    let rows = tab.sharding.distribution().into_iter().map(|((entity_type, node), load)| vec![
+        entity_type.to_owned(),
+        node.to_owned(),
+        load.shards.to_string(),
+        load.entities.to_string(),
+    ]).collect();
+    let table = SortableTable::new(Panel::Sharding.table_columns(), rows)
+        .sort(sort)
+        .block(panel_block(&title, focused))
+        .widths(&[Constraint::Percentage(25), Constraint::Percentage(45), Constraint::Percentage(15), Constraint::Percentage(15)]);
+    f.render_widget(table, chunks[0]);
+
+    let items = tab.rebalances.iter().rev()
+        .map(|(time, m)| Text::raw(format!("{} {} shard {}: {} -> {}", time, m.entity_type, m.shard_id, m.from, m.to)));
+    let list = List::new(items)
+        .block(panel_block(&format!("Rebalances ({}, latest first)", tab.rebalances.len()), focused));
+    f.render_widget(list, chunks[1]);
+}
+
+
+fn draw_akka_health<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(area);
+    let checks: [(&str, fn(&AkkaHealth) -> &HealthProbe); 2] = [
+        ("Alive", |h| &h.alive),
+        ("Ready", |h| &h.ready),
+    ];
+
+    for ((name, probe), chunk) in checks.iter().zip(chunks) {
+        let latencies: Vec<u64> = tab.health.iter().map(|h| probe(h).latency_ms).collect();
+        let shown = recent(&latencies, chunk);
+        let (title, color) = match tab.health.back().map(probe) {
+            Some(p) if p.ok => (format!("{}: ● up, {} ms", name, p.latency_ms), Color::Green),
+            Some(p) => (format!("{}: ● down, {} ms", name, p.latency_ms), Color::Red),
+            None => (format!("{}: n/a", name), Color::Gray),
+        };
+        let sparkline = Sparkline::default()
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(color))
+                .title(&title))
+            .data(shown)
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, chunk);
+    }
+}
+
+fn draw_actor_tree<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let (visible, selected) = tab.actors.visible_items(list_height(area));
+    let items = visible.iter().map(|i| Text::raw(i));
+    let mut state = ListState::default();
+    state.select(selected);
+
+    let list = List::new(items)
+        .block(panel_block("Actors (press <Enter> to reload the tree)", focused))
+        .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_actor_events<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let items = tab.actor_events.iter().rev()
+        .map(|(time, e)| {
+            let (sign, color) = if e.created { ("+", Color::Green) } else { ("-", Color::Red) };
+            Text::styled(format!("{} {} {}", time, sign, e.path), Style::default().fg(color))
+        });
+    let list = List::new(items)
+        .block(panel_block(&format!("Created/terminated actors ({}, latest first)", tab.actor_events.len()), focused));
+    f.render_widget(list, area);
+}
+
+/// Supervisors whose children restarted, the ones restarting the most in the last minute first unless sorted otherwise
+fn draw_actor_restarts<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, focused: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let restarts = match &tab.restarts {
+        Some(r) => r,
+        None => return,
+    };
+    let now = Instant::now();
+    let mut counts: Vec<(&String, u64, usize)> = restarts.totals.iter()
+        .map(|(s, total)| (s, *total, restarts.last_minute(Some(s), now)))
+        .collect();
+    counts.sort_by(|a, b| (b.2, b.1).cmp(&(a.2, a.1)));
+    let rows = counts.into_iter()
+        .map(|(s, total, last_minute)| vec![s.to_owned(), total.to_string(), last_minute.to_string()])
+        .collect();
+    let title = match &tab.restarts_error {
+        Some(e) => format!("Actor restarts (error: {})", e),
+        None => format!("Actor restarts ({} in the last minute, press s/S to sort)", restarts.last_minute(None, now)),
+    };
+    let table = SortableTable::new(Panel::ActorRestarts.table_columns(), rows)
+        .sort(sort)
+        .block(panel_block(&title, focused))
+        .widths(&[Constraint::Percentage(60), Constraint::Percentage(20), Constraint::Percentage(20)]);
+    f.render_widget(table, area);
+}
+
+fn draw_actor_count_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let data: Vec<(&str, u64)> = tab.actor_counts.latest(AkkaActorTreeTab::MAX_ACTOR_COUNT_MEASURES)
+        .map(|x| ("", x.to_owned()))
+        .collect();
+
+    let area = if tab.watches.is_empty() {
+        area
+    } else {
+        let mut constraints = vec![Constraint::Min(5)];
+        constraints.extend(tab.watches.iter().map(|_| Constraint::Length(4)));
+        let chunks = Layout::default()
+            .constraints(constraints)
+            .direction(Direction::Vertical)
+            .split(area);
+        for (i, (watch, counts)) in tab.watches.iter().zip(tab.watch_counts.iter()).enumerate() {
+            let title = format!("{} ({}): {}", watch.name, watch.pattern, counts.back().unwrap_or(&0));
+            let data: Vec<u64> = counts.iter().copied().collect();
+            draw_sparkline(f, &title, &data, Color::Cyan, focused, chunks[i + 1]);
+        }
+        chunks[0]
+    };
+
+    let title = format!("Running actors: {}", tab.actor_counts.back().unwrap_or(&0));
+    let count_bc = BarChart::default()
+        .block(panel_block(&title, focused))
         .data(&data)
         .bar_width(3)
         .bar_gap(1)
@@ -408,3 +1377,515 @@ fn draw_actor_count_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rec
         .style(Style::default().fg(Color::Green));
     f.render_widget(count_bc, area);
 }
+
+fn draw_redis_tab<B>(f: &mut Frame<B>, redis: &RedisTab, focused: Panel, zoomed: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(area);
+    let chart_chunks = Layout::default()
+        .constraints([
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+        ].as_ref())
+        .split(chunks[0]);
+    let layout = vec![
+        (Panel::RedisMemory, chart_chunks[0]),
+        (Panel::RedisClients, chart_chunks[1]),
+        (Panel::RedisOps, chart_chunks[2]),
+        (Panel::RedisHitRate, chart_chunks[3]),
+        (Panel::RedisSlowlog, chunks[1]),
+    ];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_redis_panel(f, redis, sort, panel, panel == focused, area);
+    }
+}
+
+fn draw_redis_panel<B>(f: &mut Frame<B>, redis: &RedisTab, sort: Option<TableSort>, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    let last = redis.info.back();
+    match panel {
+        Panel::RedisMemory => {
+            let title = format!("Used memory: {}", last.map_or("n/a".to_owned(), |i| format_bytes(i.used_memory)));
+            let data: Vec<u64> = redis.info.iter().map(|i| i.used_memory).collect();
+            draw_sparkline(f, &title, &data, Color::Green, is_focused, area)
+        }
+        Panel::RedisClients => {
+            let title = format!("Connected clients: {}", last.map_or(0, |i| i.connected_clients));
+            let data: Vec<u64> = redis.info.iter().map(|i| i.connected_clients).collect();
+            draw_sparkline(f, &title, &data, Color::Blue, is_focused, area)
+        }
+        Panel::RedisOps => {
+            let title = format!("Ops/sec: {}", last.map_or(0, |i| i.ops_per_sec));
+            let data: Vec<u64> = redis.info.iter().map(|i| i.ops_per_sec).collect();
+            draw_sparkline(f, &title, &data, Color::Yellow, is_focused, area)
+        }
+        Panel::RedisHitRate => {
+            let title = format!(
+                "Keyspace hit rate: {}",
+                redis.hit_rates.back().map_or("n/a".to_owned(), |r| format!("{:.1}%", r))
+            );
+            let data: Vec<u64> = redis.hit_rates.iter().map(|r| r.round() as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Magenta, is_focused, area)
+        }
+        Panel::RedisSlowlog => draw_redis_slowlog(f, redis, is_focused, sort, area),
+        _ => {}
+    }
+}
+
+fn draw_gc_log_tab<B>(f: &mut Frame<B>, gc_log: &GcLogTab, focused: Panel, zoomed: bool, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+    let layout = vec![(Panel::GcPauses, chunks[0]), (Panel::GcAllocationRate, chunks[1])];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_gc_log_panel(f, gc_log, panel, panel == focused, area);
+    }
+}
+
+fn draw_gc_log_panel<B>(f: &mut Frame<B>, gc_log: &GcLogTab, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    match panel {
+        Panel::GcPauses => {
+            let max = gc_log.pauses.iter().map(|p| p.duration_ms).fold(0.0, f64::max);
+            let title = match gc_log.pauses.back() {
+                Some(p) => format!(
+                    "GC pauses: last {:.2}ms, max {:.2}ms | {} {:.0}M->{:.0}M({:.0}M)",
+                    p.duration_ms, max, p.name, p.heap_before, p.heap_after, p.heap_total
+                ),
+                None => "GC pauses: none logged yet".to_owned(),
+            };
+            // sparklines only take integers, so durations are charted in microseconds
+            let data: Vec<u64> = gc_log.pauses.iter().map(|p| (p.duration_ms * 1000.0).round() as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Red, is_focused, area)
+        }
+        Panel::GcAllocationRate => {
+            let title = format!(
+                "Allocation rate: {}",
+                gc_log.allocation_rates.back().map_or("n/a".to_owned(), |r| format!("{:.1} MB/s", r))
+            );
+            let data: Vec<u64> = gc_log.allocation_rates.iter().map(|r| r.round() as u64).collect();
+            draw_sparkline(f, &title, &data, Color::Green, is_focused, area)
+        }
+        _ => {}
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, units[unit])
+}
+
+fn draw_sparkline<B>(f: &mut Frame<B>, title: &str, data: &[u64], color: Color, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let sparkline = Sparkline::default()
+        .block(panel_block(title, focused))
+        .data(recent(data, area))
+        .style(Style::default().fg(color));
+    f.render_widget(sparkline, area);
+}
+
+/// The most recent values that fit in a bordered sparkline, which draws the first ones otherwise
+fn recent(data: &[u64], area: Rect) -> &[u64] {
+    &data[data.len().saturating_sub(area.width.saturating_sub(2) as usize)..]
+}
+
+fn draw_redis_slowlog<B>(f: &mut Frame<B>, redis: &RedisTab, focused: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let rows = redis.slowlog.iter().map(|e| vec![
+        e.id.to_string(),
+        e.duration_micros.to_string(),
+        e.command.to_owned(),
+    ]).collect();
+    let table = SortableTable::new(Panel::RedisSlowlog.table_columns(), rows)
+        .sort(sort)
+        .block(panel_block("Slowlog (press s/S to sort)", focused))
+        .widths(&[Constraint::Length(8), Constraint::Length(14), Constraint::Min(10)]);
+    f.render_widget(table, area);
+}
+
+fn draw_hooks_tab<B>(f: &mut Frame<B>, hooks: &Hooks, focused: Panel, zoomed: bool, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+    let layout = vec![(Panel::Hooks, chunks[0]), (Panel::HookLog, chunks[1])];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_hooks_panel(f, hooks, panel, panel == focused, area);
+    }
+}
+
+fn draw_hooks_panel<B>(f: &mut Frame<B>, hooks: &Hooks, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    match panel {
+        Panel::Hooks => draw_hook_list(f, hooks, is_focused, area),
+        Panel::HookLog => draw_hook_log(f, hooks, is_focused, area),
+        _ => {}
+    }
+}
+
+fn draw_hook_list<B>(f: &mut Frame<B>, hooks: &Hooks, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let items = hooks.hooks.iter().map(|h| {
+        let value = h.value.map_or("n/a".to_owned(), series::axis_label);
+        match h.kind {
+            HookKind::Series =>
+                Text::raw(format!("series   {} = {} -> {}", h.name, h.source, value)),
+            HookKind::Alert if h.is_active() =>
+                Text::styled(
+                    format!("alert    {}: {} -> FIRING", h.name, h.source),
+                    Style::default().fg(Color::Red).modifier(Modifier::BOLD),
+                ),
+            HookKind::Alert if h.value.is_none() =>
+                Text::raw(format!("alert    {}: {} -> n/a", h.name, h.source)),
+            HookKind::Alert =>
+                Text::raw(format!("alert    {}: {} -> ok", h.name, h.source)),
+            HookKind::Annotate =>
+                Text::raw(format!("annotate {}: {} -> {}", h.name, h.source, value)),
+        }
+    });
+    let list = List::new(items)
+        .block(panel_block("Hooks", focused));
+    f.render_widget(list, area);
+}
+
+fn draw_hook_log<B>(f: &mut Frame<B>, hooks: &Hooks, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let log = hooks.log.iter().rev().map(|l| Text::raw(l));
+    let list = List::new(log)
+        .block(panel_block("Alerts and annotations", focused));
+    f.render_widget(list, area);
+}
+
+fn draw_zookeeper_tab<B>(f: &mut Frame<B>, zookeeper: &ZookeeperStatus, views: &ChartViews, focused: Panel, zoomed: bool, area: Rect)
+    where B: Backend,
+{
+    let rows = Layout::default()
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(area);
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(rows[0]);
+    let layout = vec![(Panel::ZookeeperNodes, chunks[0]), (Panel::ZookeeperConsole, chunks[1]), (Panel::ZookeeperChart, rows[1])];
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_zookeeper_panel(f, zookeeper, views, panel, panel == focused, area);
+    }
+}
+
+fn draw_zookeeper_panel<B>(f: &mut Frame<B>, zookeeper: &ZookeeperStatus, views: &ChartViews, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    match panel {
+        Panel::ZookeeperNodes => draw_zookeeper_nodes(f, zookeeper, is_focused, area),
+        Panel::ZookeeperConsole => draw_zookeeper_console(f, &zookeeper.console, is_focused, area),
+        Panel::ZookeeperChart => draw_zookeeper_chart(f, zookeeper, &views.of(panel), is_focused, area),
+        _ => {}
+    }
+}
+
+/// Serving voters and the highest latency of the ensemble, with a vertical line on each probe that noticed a leader change
+fn draw_zookeeper_chart<B>(f: &mut Frame<B>, zookeeper: &ZookeeperStatus, view: &SeriesView, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let serving_chart = view.apply(view.history_timeline(&zookeeper.samples, |s| s.serving as f64));
+    let latency_chart = view.apply(view.history_timeline(&zookeeper.samples, |s| s.max_latency));
+    let raw_max = zookeeper.samples.iter().map(|s| s.max_latency).fold(zookeeper.nodes.len() as f64, f64::max);
+    let (y_bounds, label) = y_axis(view, raw_max, &[&serving_chart, &latency_chart]);
+
+    let changes = view.history_events(&zookeeper.samples, |s| s.leader_change.clone());
+    let markers: Vec<(String, Vec<(f64, f64)>)> = changes.into_iter()
+        .map(|(x, leader)| (format!("leader → {}", leader.as_deref().unwrap_or("none")), series::vertical_line(x, y_bounds)))
+        .collect();
+
+    let shown = view.cursor_sample(&zookeeper.samples);
+    let title = format!(
+        "Ensemble (serving voters={}, max latency={}ms, leader changes shown={}){}",
+        shown.map_or(0, |s| s.serving),
+        shown.map_or(0.0, |s| s.max_latency),
+        markers.len(),
+        view.title_suffix()
+    );
+    let x_labels = view.x_labels();
+    let cursor_line = view.cursor_line(y_bounds);
+    let mut datasets = vec![
+        Dataset::default()
+            .name("serving voters")
+            .marker(Marker::Braille)
+            .style(Style::default().fg(Color::Green))
+            .data(&serving_chart),
+        Dataset::default()
+            .name("max latency ms")
+            .marker(Marker::Braille)
+            .style(Style::default().fg(Color::Blue))
+            .data(&latency_chart),
+    ];
+    datasets.extend(markers.iter().map(|(name, line)| Dataset::default()
+        .name(name)
+        .marker(Marker::Dot)
+        .style(Style::default().fg(Color::Yellow))
+        .data(line)));
+    datasets.push(cursor_dataset(&cursor_line));
+    let c = Chart::default()
+        .block(panel_block(&title, focused))
+        .x_axis(time_axis(view, &x_labels))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .labels_style(Style::default().modifier(Modifier::ITALIC))
+                .bounds(y_bounds)
+                .labels(&label)
+        )
+        .datasets(&datasets);
+    f.render_widget(c, area);
+}
+
+fn draw_zookeeper_nodes<B>(f: &mut Frame<B>, zookeeper: &ZookeeperStatus, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let drift = config_drift(&zookeeper.nodes);
+    let mut items: Vec<Text> = zookeeper.nodes.iter().enumerate().map(|(i, n)| {
+        let mut line = match &n.mode {
+            Some(mode) => format!("{} {} {}", n.address, n.state, mode),
+            None => format!("{} {}", n.address, n.state),
+        };
+        if let Some(m) = &n.monitor {
+            line = format!(
+                "{} latency={}ms outstanding={} connections={} znodes={} watches={}",
+                line, m.avg_latency, m.outstanding_requests, m.alive_connections, m.znode_count, m.watch_count,
+            );
+        }
+        if let Some(notice) = n.whitelist_notice() {
+            line = format!("{} ({})", line, notice);
+        }
+        if drift.contains(&n.address.as_str()) {
+            line = format!("{} (config drift)", line);
+        }
+        if i == zookeeper.console.selected {
+            Text::styled(format!("> {}", line), Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        } else if drift.contains(&n.address.as_str()) {
+            Text::styled(format!("  {}", line), Style::default().fg(Color::Red))
+        } else if zookeeper.observers.contains(&n.address) {
+            // observers don't vote, so their state doesn't weigh on the quorum
+            Text::styled(format!("  {}", line), Style::default().fg(Color::Cyan))
+        } else {
+            Text::raw(format!("  {}", line))
+        }
+    }).collect();
+    // the ensemble as the selected node sees it
+    if let Some(config) = zookeeper.nodes.get(zookeeper.console.selected).and_then(|n| n.config.as_ref()) {
+        items.push(Text::raw(""));
+        items.push(Text::styled(format!("Ensemble config, {}", config.label()), Style::default().fg(Color::Gray)));
+    }
+    match &zookeeper.brokers {
+        Some(Ok(brokers)) => {
+            items.push(Text::raw(""));
+            items.push(Text::raw(format!("Kafka brokers ({})", brokers.len())));
+            items.extend(brokers.iter().map(|b| if b.is_failing() {
+                Text::styled(format!("  {}", b.label()), Style::default().fg(Color::Red))
+            } else if b.under_replicated_partitions() > 0 {
+                Text::styled(format!("  {}", b.label()), Style::default().fg(Color::Yellow))
+            } else {
+                Text::raw(format!("  {}", b.label()))
+            }));
+        }
+        Some(Err(e)) => {
+            items.push(Text::raw(""));
+            items.push(Text::styled(format!("Kafka brokers: {}", e), Style::default().fg(Color::Red)));
+        }
+        None => {}
+    }
+    let (serving, voters) = zookeeper.voters();
+    let title = format!("Nodes, {}/{} voters serving (press <Up>/<Down> to select)", serving, voters);
+    let list = List::new(items)
+        .block(panel_block(&title, focused));
+    f.render_widget(list, area);
+}
+
+fn draw_zookeeper_console<B>(f: &mut Frame<B>, console: &ZookeeperConsole, focused: bool, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(area);
+    let prompt = match &console.input {
+        Some(input) => [Text::styled(format!("> {}_", input), Style::default().fg(Color::Yellow))],
+        None => [Text::raw("press : to type a four letter word command, e.g. stat, dump or envi")],
+    };
+    let p = Paragraph::new(prompt.iter())
+        .block(panel_block("Command", focused));
+    f.render_widget(p, chunks[0]);
+
+    let (title, text) = match &console.output {
+        Some((address, command, Ok(response))) =>
+            (format!("{} on {} (press <PageUp>/<PageDown> to scroll)", command, address), [Text::raw(response)]),
+        Some((address, command, Err(e))) =>
+            (format!("{} on {}", command, address), [Text::styled(e, Style::default().fg(Color::Red))]),
+        None => ("Response".to_owned(), [Text::raw("")]),
+    };
+    let p = Paragraph::new(text.iter())
+        .block(panel_block(&title, focused))
+        .wrap(true)
+        .scroll(console.scroll);
+    f.render_widget(p, chunks[1]);
+}
+
+fn draw_latency_tab<B>(f: &mut Frame<B>, latency: &PollLatency, area: Rect)
+    where B: Backend,
+{
+    if latency.sources.is_empty() {
+        let text = [Text::raw("No responses yet")];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("Latency"));
+        f.render_widget(p, area);
+        return;
+    }
+    let sparklines: Vec<(String, Vec<f64>)> = latency.sources.iter().zip(latency.values.iter())
+        .map(|(source, ms)| (
+            format!(
+                "{} round-trip: {}ms, max {}ms",
+                source,
+                ms.back().copied().unwrap_or(0),
+                ms.iter().max().copied().unwrap_or(0)
+            ),
+            ms.iter().map(|v| *v as f64).collect()
+        ))
+        .collect();
+    draw_sparkline_grid(f, &sparklines, area);
+}
+
+/// Rows of panels of the other tabs, sharing the height of the tab
+fn draw_dashboard<B>(f: &mut Frame<B>, app: &mut App, views: &ChartViews, focused: Panel, zoomed: bool, sort: Option<TableSort>, area: Rect)
+    where B: Backend,
+{
+    let dashboard = match app.dashboard.clone() {
+        Some(d) => d,
+        None => return,
+    };
+    let rows = Layout::default()
+        .constraints(vec![Constraint::Percentage((100 / dashboard.rows.len()) as u16); dashboard.rows.len()])
+        .split(area);
+    let mut layout = vec![];
+    for (panels, row) in dashboard.rows.iter().zip(rows) {
+        let cells = Layout::default()
+            .constraints(vec![Constraint::Percentage((100 / panels.len()) as u16); panels.len()])
+            .direction(Direction::Horizontal)
+            .split(row);
+        layout.extend(panels.iter().copied().zip(cells));
+    }
+
+    for (panel, area) in panel_areas(layout, focused, zoomed, area) {
+        draw_dashboard_panel(f, app, views, sort, panel, panel == focused, area);
+    }
+}
+
+/// Panel of another tab, or why it's empty when the tab isn't shown
+fn draw_dashboard_panel<B>(f: &mut Frame<B>, app: &mut App, views: &ChartViews, sort: Option<TableSort>, panel: Panel, is_focused: bool, area: Rect)
+    where B: Backend,
+{
+    let missing = |f: &mut Frame<B>, reason: &str| draw_placeholder(f, dashboard::panel_name(panel), reason, is_focused, area);
+    match panel {
+        Panel::Fibers | Panel::FiberCounts | Panel::FiberDump | Panel::FiberTree => match app.zmx.as_mut() {
+            Some(zmx) => draw_zio_panel(f, zmx, views, panel, is_focused, area),
+            None => missing(f, "No ZIO-ZMX address, see --zio-zmx"),
+        },
+        Panel::SlickThreads | Panel::SlickUtilization | Panel::SlickQueue | Panel::Hikari | Panel::PoolErrors | Panel::Statements => match &app.slick {
+            Some(slick) => draw_slick_panel(f, slick, views, sort, panel, is_focused, area),
+            None => missing(f, "No JMX address, see --jmx"),
+        },
+        Panel::JVMAttributes | Panel::JVMClasses | Panel::JVMThreads | Panel::JVMDaemonThreads => match &app.jvm {
+            Some(jvm) => match &jvm.error {
+                Some(e) => missing(f, e),
+                None => draw_jvm_panel(f, jvm, sort, panel, is_focused, area),
+            },
+            None => missing(f, "No JMX address, see --jmx"),
+        },
+        Panel::Watchlist => match &app.custom {
+            Some(custom) => draw_custom_tab(f, custom, area),
+            None => missing(f, "No JMX attributes to watch, see `watchlist` in the config file"),
+        },
+        Panel::Actors | Panel::ActorCount | Panel::ActorEvents | Panel::ActorRestarts | Panel::Sharding => match app.actor_tree.as_mut() {
+            Some(t) if panel == Panel::ActorRestarts && t.restarts.is_none() => {
+                missing(f, "No application log to count restarts from, see `actor_restarts` in the config file")
+            }
+            Some(t) => draw_actor_tree_panel(f, t, sort, panel, is_focused, area),
+            None => missing(f, "No Akka addresses, see --actor-tree"),
+        },
+        Panel::RedisMemory | Panel::RedisClients | Panel::RedisOps | Panel::RedisHitRate | Panel::RedisSlowlog => match &app.redis {
+            Some(redis) => draw_redis_panel(f, redis, sort, panel, is_focused, area),
+            None => missing(f, "No Redis address, see --redis"),
+        },
+        Panel::ConsulServices => match &app.consul {
+            Some(consul) => draw_consul_tab(f, consul, area),
+            None => missing(f, "No Consul address, see --consul"),
+        },
+        Panel::GcPauses | Panel::GcAllocationRate => match &app.gc_log {
+            Some(gc_log) => draw_gc_log_panel(f, gc_log, panel, is_focused, area),
+            None => missing(f, "No GC log, see --gc-log"),
+        },
+        Panel::PrometheusSeries => match app.prometheus.as_mut() {
+            Some(prometheus) => draw_prometheus_tab(f, prometheus, area),
+            None => missing(f, "No Prometheus endpoints, see `prometheus` in the config file"),
+        },
+        Panel::CompareFibers | Panel::CompareHikari | Panel::CompareActors => match &app.compare {
+            Some(compare) => draw_compare_panel(f, compare, views, panel, is_focused, area),
+            None => missing(f, "No environments to compare, see --compare"),
+        },
+        Panel::Derived => match &app.derived {
+            Some(derived) => draw_derived_tab(f, derived, area),
+            None => missing(f, "No derived series, see `derived` in the config file"),
+        },
+        Panel::Hooks | Panel::HookLog => match &app.hooks {
+            Some(hooks) => draw_hooks_panel(f, hooks, panel, is_focused, area),
+            None => missing(f, "No hooks, see --script"),
+        },
+        Panel::ZookeeperNodes | Panel::ZookeeperConsole | Panel::ZookeeperChart => match &app.zookeeper {
+            Some(zookeeper) => draw_zookeeper_panel(f, zookeeper, views, panel, is_focused, area),
+            None => missing(f, "No Zookeeper nodes, see --zookeeper"),
+        },
+        Panel::Latency => draw_latency_tab(f, &app.latency, area),
+        Panel::Log => draw_log_tab(f, &app.logger, area),
+        _ => {}
+    }
+}
+
+fn draw_log_tab<B>(f: &mut Frame<B>, logger: &Logger, area: Rect)
+    where B: Backend,
+{
+    let entries = logger.recent.iter().rev().map(|(level, line)| {
+        let style = match level {
+            LogLevel::Error => Style::default().fg(Color::Red),
+            LogLevel::Warn => Style::default().fg(Color::Yellow),
+            LogLevel::Info => Style::default(),
+            LogLevel::Debug => Style::default().fg(Color::DarkGray),
+        };
+        Text::styled(line, style)
+    });
+    let list = List::new(entries)
+        .block(panel_block("Log", true));
+    f.render_widget(list, area);
+}
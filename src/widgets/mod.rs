@@ -1 +1,4 @@
 pub mod tree;
+pub mod stale;
+pub mod table;
+pub mod stacked;
@@ -0,0 +1,168 @@
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    widgets::{Axis, Block, Chart, Dataset, Widget},
+};
+
+/// Series of a stacked area chart, with the color of its area
+pub struct Layer<'a> {
+    pub name: &'a str,
+    pub color: Color,
+    pub data: &'a [(f64, f64)],
+}
+
+///
+/// Chart of series stacked on top of each other, each filling the area between the sum of the series below it
+/// and its own value, to show how a whole splits into parts over time, e.g. fibers by status.
+/// The series are expected to be sampled at the same times. Lines, e.g. the cursor, are drawn over the areas.
+pub struct StackedAreaChart<'a> {
+    layers: &'a [Layer<'a>],
+    lines: Vec<Dataset<'a>>,
+    block: Option<Block<'a>>,
+    x_bounds: [f64; 2],
+    x_labels: &'a [String],
+    y_bounds: [f64; 2],
+    y_labels: &'a [String],
+}
+
+impl<'a> StackedAreaChart<'a> {
+    /// Layers from the bottom up
+    pub fn new(layers: &'a [Layer<'a>]) -> StackedAreaChart<'a> {
+        StackedAreaChart { layers, lines: vec![], block: None, x_bounds: [0.0, 0.0], x_labels: &[], y_bounds: [0.0, 0.0], y_labels: &[] }
+    }
+
+    pub fn lines(mut self, lines: Vec<Dataset<'a>>) -> StackedAreaChart<'a> {
+        self.lines = lines;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> StackedAreaChart<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn x_axis(mut self, bounds: [f64; 2], labels: &'a [String]) -> StackedAreaChart<'a> {
+        self.x_bounds = bounds;
+        self.x_labels = labels;
+        self
+    }
+
+    pub fn y_axis(mut self, bounds: [f64; 2], labels: &'a [String]) -> StackedAreaChart<'a> {
+        self.y_bounds = bounds;
+        self.y_labels = labels;
+        self
+    }
+}
+
+fn axis(bounds: [f64; 2], labels: &[String]) -> Axis<String> {
+    Axis::default()
+        .style(Style::default().fg(Color::Gray))
+        .labels_style(Style::default().modifier(Modifier::ITALIC))
+        .bounds(bounds)
+        .labels(labels)
+}
+
+impl<'a> Widget for StackedAreaChart<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let series: Vec<&[(f64, f64)]> = self.layers.iter().map(|l| l.data).collect();
+        let tops = stack(&series);
+        // braille cells are 2 dots wide and 4 dots high
+        let dots = (area.width as usize * 2, area.height as usize * 4);
+        let areas: Vec<Vec<(f64, f64)>> = tops.iter().enumerate()
+            .map(|(i, top)| {
+                let below = if i == 0 { None } else { Some(tops[i - 1].as_slice()) };
+                fill_between(below, top, self.x_bounds, self.y_bounds, dots)
+            })
+            .collect();
+        let mut datasets: Vec<Dataset> = self.layers.iter().zip(areas.iter())
+            .map(|(layer, points)| Dataset::default()
+                .name(layer.name)
+                .marker(Marker::Braille)
+                .style(Style::default().fg(layer.color))
+                .data(points))
+            .collect();
+        datasets.extend(self.lines);
+
+        let mut chart = Chart::default()
+            .x_axis(axis(self.x_bounds, self.x_labels))
+            .y_axis(axis(self.y_bounds, self.y_labels))
+            .datasets(&datasets);
+        if let Some(block) = self.block {
+            chart = chart.block(block);
+        }
+        chart.render(area, buf);
+    }
+}
+
+/// Top of each stacked series: its values added to the top of the series below, point by point
+pub fn stack(series: &[&[(f64, f64)]]) -> Vec<Vec<(f64, f64)>> {
+    let mut tops: Vec<Vec<(f64, f64)>> = vec![];
+    for s in series {
+        let top = match tops.last() {
+            Some(below) => s.iter().zip(below.iter()).map(|((x, y), (_, b))| (*x, y + b)).collect(),
+            None => s.to_vec(),
+        };
+        tops.push(top);
+    }
+    tops
+}
+
+///
+/// Points filling the area between two stacked series, or between zero and the series at the bottom,
+/// on a grid of the given number of columns and rows spanning the bounds.
+pub fn fill_between(lower: Option<&[(f64, f64)]>, upper: &[(f64, f64)], x_bounds: [f64; 2], y_bounds: [f64; 2], (columns, rows): (usize, usize)) -> Vec<(f64, f64)> {
+    let mut points = vec![];
+    if columns < 2 || rows < 2 {
+        return points;
+    }
+    let y_step = (y_bounds[1] - y_bounds[0]) / (rows - 1) as f64;
+    for i in 0..columns {
+        let x = x_bounds[0] + (x_bounds[1] - x_bounds[0]) * i as f64 / (columns - 1) as f64;
+        let top = match value_at(upper, x) {
+            Some(y) => y.min(y_bounds[1]),
+            None => continue,
+        };
+        let mut y = lower.and_then(|l| value_at(l, x)).unwrap_or(0.0).max(y_bounds[0]);
+        while y < top {
+            points.push((x, y));
+            y += y_step;
+        }
+        points.push((x, top));
+    }
+    points
+}
+
+/// Value of the series at the given time, interpolated between the points around it, `None` outside of the series
+fn value_at(series: &[(f64, f64)], x: f64) -> Option<f64> {
+    let i = series.iter().position(|p| p.0 >= x)?;
+    let (x1, y1) = series[i];
+    if x1 == x {
+        return Some(y1);
+    }
+    let (x0, y0) = *series.get(i.checked_sub(1)?)?;
+    Some(y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::stacked::{fill_between, stack};
+
+    #[test]
+    fn stacks_and_fills_series() {
+        let running = vec![(-2.0, 1.0), (0.0, 3.0)];
+        let suspended = vec![(-2.0, 2.0), (0.0, 2.0)];
+        let tops = stack(&[&running, &suspended]);
+        assert_eq!(tops, vec![running.clone(), vec![(-2.0, 3.0), (0.0, 5.0)]]);
+
+        // columns at -2, -1 and 0, rows every 1
+        let bottom = fill_between(None, &tops[0], [-2.0, 0.0], [0.0, 5.0], (3, 6));
+        assert_eq!(bottom, vec![(-2.0, 0.0), (-2.0, 1.0), (-1.0, 0.0), (-1.0, 1.0), (-1.0, 2.0), (0.0, 0.0), (0.0, 1.0), (0.0, 2.0), (0.0, 3.0)]);
+        let top = fill_between(Some(&tops[0]), &tops[1], [-2.0, 0.0], [0.0, 5.0], (3, 6));
+        assert_eq!(top, vec![(-2.0, 1.0), (-2.0, 2.0), (-2.0, 3.0), (-1.0, 2.0), (-1.0, 3.0), (-1.0, 4.0), (0.0, 3.0), (0.0, 4.0), (0.0, 5.0)]);
+
+        // nothing is charted before the first sample
+        assert!(fill_between(None, &[(0.0, 1.0)], [-2.0, 0.0], [0.0, 5.0], (3, 6)).iter().all(|p| p.0 == 0.0));
+    }
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+/// Grays out whatever was drawn in the area and writes a STALE watermark in its middle
+pub struct StaleOverlay {
+    /// Time since the source last delivered a sample
+    pub age: Duration,
+}
+
+impl Widget for StaleOverlay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf.get_mut(x, y).set_fg(Color::DarkGray);
+            }
+        }
+        let text = format!(" STALE - no data for {}s ", self.age.as_secs());
+        let width = text.chars().count() as u16;
+        if area.width < width || area.height == 0 {
+            return;
+        }
+        buf.set_string(
+            area.x + (area.width - width) / 2,
+            area.y + area.height / 2,
+            text,
+            Style::default().fg(Color::Black).bg(Color::Yellow).modifier(Modifier::BOLD),
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use tui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::{Block, Row, Table, Widget},
+};
+
+/// Column a table is sorted by, cycled with `s` and reversed with `S` on the focused table
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TableSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+impl TableSort {
+    /// Sort by the next column, or back to the order of the source after the last one
+    pub fn next(sort: Option<TableSort>, columns: usize) -> Option<TableSort> {
+        match sort {
+            None if columns > 0 => Some(TableSort { column: 0, descending: false }),
+            Some(s) if s.column + 1 < columns => Some(TableSort { column: s.column + 1, descending: s.descending }),
+            _ => None,
+        }
+    }
+
+    pub fn reversed(self) -> TableSort {
+        TableSort { descending: !self.descending, ..self }
+    }
+}
+
+///
+/// Rows sorted by a column, cells that are both numbers being compared by value.
+/// The sort is stable, so rows with equal cells keep the order of the source.
+pub fn sort_rows(rows: &mut [Vec<String>], sort: Option<TableSort>) {
+    let sort = match sort {
+        Some(s) => s,
+        None => return,
+    };
+    rows.sort_by(|a, b| {
+        let ordering = compare_cells(a.get(sort.column), b.get(sort.column));
+        if sort.descending { ordering.reverse() } else { ordering }
+    });
+}
+
+fn compare_cells(a: Option<&String>, b: Option<&String>) -> Ordering {
+    let number = |c: Option<&String>| c.and_then(|c| c.trim().parse::<f64>().ok());
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        // numbers before text, e.g. before `n/a`
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(&b),
+    }
+}
+
+/// Header with the sorted column marked by its direction, e.g. `Mean ms ▼`
+pub fn header(columns: &[&str], sort: Option<TableSort>) -> Vec<String> {
+    columns.iter().enumerate()
+        .map(|(i, c)| match sort {
+            Some(s) if s.column == i => format!("{} {}", c, if s.descending { "▼" } else { "▲" }),
+            _ => (*c).to_owned(),
+        })
+        .collect()
+}
+
+/// Table whose rows are sorted by the column of its sort, if any
+pub struct SortableTable<'a> {
+    columns: &'a [&'a str],
+    rows: Vec<Vec<String>>,
+    sort: Option<TableSort>,
+    block: Option<Block<'a>>,
+    widths: &'a [Constraint],
+}
+
+impl<'a> SortableTable<'a> {
+    pub fn new(columns: &'a [&'a str], rows: Vec<Vec<String>>) -> SortableTable<'a> {
+        SortableTable { columns, rows, sort: None, block: None, widths: &[] }
+    }
+
+    pub fn sort(mut self, sort: Option<TableSort>) -> SortableTable<'a> {
+        self.sort = sort;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> SortableTable<'a> {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn widths(mut self, widths: &'a [Constraint]) -> SortableTable<'a> {
+        self.widths = widths;
+        self
+    }
+}
+
+impl<'a> Widget for SortableTable<'a> {
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+        sort_rows(&mut self.rows, self.sort);
+        let header = header(self.columns, self.sort);
+        let mut table = Table::new(header.iter(), self.rows.iter().map(|r| Row::Data(r.iter())))
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(self.widths);
+        if let Some(block) = self.block {
+            table = table.block(block);
+        }
+        table.render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::table::{header, sort_rows, TableSort};
+
+    #[test]
+    fn sorts_rows_by_column() {
+        let row = |name: &str, mean: &str| vec![name.to_owned(), mean.to_owned()];
+        let mut rows = vec![row("select", "12.5"), row("insert", "n/a"), row("update", "3")];
+
+        let by_mean = TableSort::next(TableSort::next(None, 2), 2);
+        sort_rows(&mut rows, by_mean);
+        assert_eq!(rows, vec![row("update", "3"), row("select", "12.5"), row("insert", "n/a")]);
+
+        sort_rows(&mut rows, TableSort::next(None, 2).map(TableSort::reversed));
+        assert_eq!(rows, vec![row("update", "3"), row("select", "12.5"), row("insert", "n/a")]);
+
+        assert_eq!(header(&["Statement", "Mean ms"], by_mean.map(TableSort::reversed)), vec!["Statement", "Mean ms ▼"]);
+        assert_eq!(TableSort::next(by_mean, 2), None);
+    }
+}
@@ -1,4 +1,8 @@
 use std::collections::hash_map::HashMap;
+
+use serde::Deserialize;
+
+use crate::zio::label::NamedFiber;
 use crate::zio::model::Fiber;
 use crate::akka::model::ActorTreeNode;
 
@@ -6,6 +10,39 @@ pub trait TreeWidgetNode {
     fn id(&self) -> usize;
     fn parent_id(&self) -> Option<usize>;
     fn label(&self) -> String;
+    /// Time since the item was created, if known
+    fn age(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Column shown after the tree in fiber and actor lists
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    /// Status of a fiber or name of an actor
+    Label,
+    Id,
+    Children,
+    Age,
+}
+
+/// A line of a tree list: the tree drawing up to the item, the item itself and its number of children
+pub struct TreeRow<T> {
+    pub tree: String,
+    pub children: usize,
+    pub item: T,
+}
+
+impl<T: TreeWidgetNode> TreeRow<T> {
+    pub fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Label => self.item.label(),
+            Column::Id => format!("#{}", self.item.id()),
+            Column::Children => self.children.to_string(),
+            Column::Age => self.item.age().unwrap_or_else(|| "-".to_owned()),
+        }
+    }
 }
 
 impl TreeWidgetNode for Fiber {
@@ -20,6 +57,36 @@ impl TreeWidgetNode for Fiber {
     fn label(&self) -> String {
         format!("{:?}", self.status)
     }
+
+    /// Lifetime from the first line of the dump, e.g. `#4 (7h432m25965s25965835ms)`
+    fn age(&self) -> Option<String> {
+        let first = self.dump.lines().next()?;
+        let start = first.find('(')?;
+        let end = first[start..].find(')')?;
+        Some(first[start + 1..start + end].to_owned())
+    }
+}
+
+impl TreeWidgetNode for NamedFiber {
+    fn id(&self) -> usize {
+        self.fiber.id
+    }
+
+    fn parent_id(&self) -> Option<usize> {
+        self.fiber.parent_id
+    }
+
+    /// Status followed by the name, e.g. `Suspended com.example.Worker.run`
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {}", self.fiber.label(), name),
+            None => self.fiber.label(),
+        }
+    }
+
+    fn age(&self) -> Option<String> {
+        self.fiber.age()
+    }
 }
 
 impl TreeWidgetNode for ActorTreeNode {
@@ -37,7 +104,7 @@ impl TreeWidgetNode for ActorTreeNode {
 }
 
 ///
-/// Given a list of items returns a list of formatted labels ready to be printed as a tree.
+/// Given a list of items returns rows ready to be printed as a tree, see `format_rows`.
 ///
 /// eg. A list of items:
 ///       (id: 0, parent_id: None, label: Suspended)
@@ -48,7 +115,7 @@ impl TreeWidgetNode for ActorTreeNode {
 ///       (id: 5, parent_id: 4,    label: Running)
 ///       (id: 6, parent_id: None, label: Suspended)
 ///       (id: 7, parent_id: None, label: Running)
-/// should give:
+/// printed with the Label column should give:
 /// ├#0     Suspended
 /// │└#1    Running
 /// │ ├#2   Suspended
@@ -58,25 +125,102 @@ impl TreeWidgetNode for ActorTreeNode {
 /// ├#7     Running
 /// └#6     Suspended
 ///
-pub fn tree_list_widget<T: Clone + TreeWidgetNode>(items: Vec<T>, print_ids: bool) -> Vec<(String, T)> {
+pub fn tree_rows<T: Clone + TreeWidgetNode>(items: Vec<T>, print_ids: bool) -> Vec<TreeRow<T>> {
     let tree = &make_tree(items);
 
-    // get the initial printable tree
-    let temp: Vec<(String, T)> = match tree.get(&None) {
+    let nodes: Vec<(String, T)> = match tree.get(&None) {
         Some(v) => list_tree_nodes(v.to_vec(), 0, tree, "".to_string(), print_ids),
         None => vec![]
     };
 
-    // find the max length of the line to calculate padding
-    let max_len = &temp.iter()
-        .max_by_key(|i| i.0.chars().count())
-        .map_or(0, |i| i.0.chars().count());
+    nodes.into_iter()
+        .map(|(prefix, item)| TreeRow {
+            tree: prefix,
+            children: tree.get(&Some(item.id())).map_or(0, |c| c.len()),
+            item,
+        })
+        .collect()
+}
+
+///
+/// Formats rows as lines with the tree drawing followed by the given columns,
+/// each column padded to the width of its longest cell.
+///
+/// eg. with columns Label, Children:
+///   ├─#0   Suspended 1
+///   │└─#1  Running   0
+///   └─#6   Suspended 0
+pub fn format_rows<T: TreeWidgetNode>(rows: &[TreeRow<T>], columns: &[Column]) -> Vec<String> {
+    let cells: Vec<Vec<String>> = rows.iter()
+        .map(|r| {
+            let mut line = vec![r.tree.to_owned()];
+            line.extend(columns.iter().map(|c| r.cell(*c)));
+            line
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..=columns.len())
+        .map(|i| cells.iter().map(|l| l[i].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    cells.iter()
+        .map(|line| {
+            let last = line.len() - 1;
+            line.iter()
+                .enumerate()
+                .map(|(i, cell)| if i == last { cell.to_owned() } else { format!("{:width$}", cell, width = widths[i]) })
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Position of the item's parent in a list of items in tree order
+pub fn parent_index<T: TreeWidgetNode>(items: &[T], i: usize) -> Option<usize> {
+    let parent = items.get(i)?.parent_id()?;
+    items.iter().position(|x| x.id() == parent)
+}
+
+/// Position of the item's first child in a list of items in tree order, which is right after the item
+pub fn first_child_index<T: TreeWidgetNode>(items: &[T], i: usize) -> Option<usize> {
+    let id = items.get(i)?.id();
+    items.get(i + 1).filter(|x| x.parent_id() == Some(id)).map(|_| i + 1)
+}
 
+/// Position of the item's next sibling in a list of items in tree order
+pub fn next_sibling_index<T: TreeWidgetNode>(items: &[T], i: usize) -> Option<usize> {
+    let parent = items.get(i)?.parent_id();
+    items.iter().skip(i + 1).position(|x| x.parent_id() == parent).map(|n| i + 1 + n)
+}
+
+///
+/// Formats a list of items as a Graphviz DOT digraph with an edge from each parent to its children.
+///
+/// eg. items (id: 1, parent_id: None, label: Running), (id: 2, parent_id: 1, label: Suspended)
+/// with print_ids give:
+///   digraph fibers {
+///     n1 [label="#1 Running"];
+///     n2 [label="#2 Suspended"];
+///     n1 -> n2;
+///   }
+pub fn tree_to_dot<T: TreeWidgetNode>(name: &str, items: &[T], print_ids: bool) -> String {
+    let mut lines = vec![format!("digraph {} {{", name)];
+    for i in items {
+        let label = if print_ids { format!("#{} {}", i.id(), i.label()) } else { i.label() };
+        lines.push(format!("  n{} [label=\"{}\"];", i.id(), dot_escape(&label)));
+    }
+    for i in items {
+        if let Some(parent) = i.parent_id() {
+            lines.push(format!("  n{} -> n{};", parent, i.id()));
+        }
+    }
+    lines.push("}".to_owned());
+    lines.join("\n")
+}
 
-    // add label using padding
-    temp.iter().map(|i| {
-        (format!("{:width$} {}", i.0, i.1.label(), width = max_len), i.1.to_owned())
-    }).collect()
+/// Escapes quotes and backslashes of a DOT string, Graphviz reading any other character as is
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
 ///
@@ -0,0 +1,37 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::zio::model::{Fiber, FiberDumpArchive};
+
+///
+/// Fiber dumps polled back to back for a while, to catch short-lived fiber explosions
+/// that happen between regular polls. Every dump is saved to its own file in `dir`.
+pub struct BurstCapture {
+    pub dir: String,
+    pub until: Instant,
+    pub dumps: usize,
+    pub errors: usize,
+}
+
+impl BurstCapture {
+    /// Creates the directory the dumps are saved to
+    pub fn start(dir: String, duration: Duration) -> Result<BurstCapture, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("Couldn't create {}: {}", dir, e))?;
+        Ok(BurstCapture { dir, until: Instant::now() + duration, dumps: 0, errors: 0 })
+    }
+
+    pub fn is_running(&self, now: Instant) -> bool {
+        now < self.until
+    }
+
+    /// Saves the dump as `<number>-<millis since epoch>.json`, returning its path
+    pub fn save(&mut self, fibers: &[Fiber]) -> Result<String, String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let path = Path::new(&self.dir).join(format!("{:05}-{}.json", self.dumps + 1, now.as_millis()));
+        let json = FiberDumpArchive { exported_at: now.as_secs(), fibers }.to_json();
+        fs::write(&path, json).map_err(|e| format!("Couldn't write {}: {}", path.display(), e))?;
+        self.dumps += 1;
+        Ok(path.display().to_string())
+    }
+}
@@ -0,0 +1,47 @@
+/// Change of a line of the selected fiber dump since the previous snapshot
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineChange {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+///
+/// Lines of the current text with the ones removed since the previous text shown where they were,
+/// from the longest common subsequence of their lines, like `watch -d` does for a whole screen.
+///
+/// eg. previous `a\nb\nc` and current `a\nc\nd` give:
+///   (Unchanged, a), (Removed, b), (Unchanged, c), (Added, d)
+pub fn diff_lines(previous: &str, current: &str) -> Vec<(LineChange, String)> {
+    let old: Vec<&str> = previous.split('\n').collect();
+    let new: Vec<&str> = current.split('\n').collect();
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((LineChange::Unchanged, new[j].to_owned()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push((LineChange::Removed, old[i].to_owned()));
+            i += 1;
+        } else {
+            lines.push((LineChange::Added, new[j].to_owned()));
+            j += 1;
+        }
+    }
+    lines
+}
@@ -0,0 +1,66 @@
+use std::convert::TryFrom;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::zio::model::Fiber;
+
+///
+/// Extracts a name for each fiber from its dump, shown in the fiber list along with the status.
+///
+/// eg. with
+///   ```text
+///   { "pattern": "a future continuation at ([\\w.$]+)", "template": "$1" }
+///   ```
+/// a fiber suspended in `com.example.Worker.run` is listed as `Suspended com.example.Worker.run`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(try_from = "FiberLabelDef")]
+pub struct FiberLabel {
+    pub pattern: Regex,
+    /// Name made of the capture groups of the first match, e.g. `$1` or `$name`
+    pub template: String,
+}
+
+#[derive(Deserialize)]
+struct FiberLabelDef {
+    pattern: String,
+    #[serde(default = "default_template")]
+    template: String,
+}
+
+fn default_template() -> String {
+    "$1".to_owned()
+}
+
+impl TryFrom<FiberLabelDef> for FiberLabel {
+    type Error = String;
+
+    fn try_from(def: FiberLabelDef) -> Result<FiberLabel, String> {
+        let pattern = Regex::new(&def.pattern).map_err(|e| format!("invalid fiber label pattern: {}", e))?;
+        Ok(FiberLabel { pattern, template: def.template })
+    }
+}
+
+impl PartialEq for FiberLabel {
+    fn eq(&self, other: &FiberLabel) -> bool {
+        self.pattern.as_str() == other.pattern.as_str() && self.template == other.template
+    }
+}
+
+impl FiberLabel {
+    /// Name of the fiber, None if the pattern doesn't match its dump or the name would be empty
+    pub fn name(&self, dump: &str) -> Option<String> {
+        let captures = self.pattern.captures(dump)?;
+        let mut name = String::new();
+        captures.expand(&self.template, &mut name);
+        let name = name.trim();
+        if name.is_empty() { None } else { Some(name.to_owned()) }
+    }
+}
+
+/// A fiber of the fiber list, along with its name if a `FiberLabel` is configured
+#[derive(Clone, Debug)]
+pub struct NamedFiber {
+    pub fiber: Fiber,
+    pub name: Option<String>,
+}
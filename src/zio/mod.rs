@@ -1,4 +1,8 @@
+pub mod burst;
+pub mod diff;
+pub mod label;
 pub mod model;
+pub mod outline;
 pub mod zmx;
 mod dump_parser;
 mod tests;
@@ -1,6 +1,11 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+use serde::Serialize;
+
+use crate::history::Downsample;
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
 pub struct Fiber {
     pub id: usize,
     pub parent_id: Option<usize>,
@@ -14,7 +19,7 @@ impl Display for Fiber {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize)]
 pub enum FiberStatus {
     Done,
     Finishing,
@@ -22,13 +27,55 @@ pub enum FiberStatus {
     Suspended,
 }
 
+/// Order of the fiber list, cycled with `o`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FiberSort {
+    Tree,
+    Id,
+    /// Running fibers first, then suspended, finishing and done ones, each in the tree order
+    Status,
+    /// Suspended fibers first, the oldest at the top, then the others in the tree order.
+    /// Dumps don't tell since when a fiber is suspended, so its lifetime is used.
+    SuspendedAge,
+}
+
+impl FiberSort {
+    pub fn next(self) -> FiberSort {
+        match self {
+            FiberSort::Tree => FiberSort::Id,
+            FiberSort::Id => FiberSort::Status,
+            FiberSort::Status => FiberSort::SuspendedAge,
+            FiberSort::SuspendedAge => FiberSort::Tree,
+        }
+    }
+}
+
+impl Display for FiberSort {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            FiberSort::Tree => write!(f, "tree"),
+            FiberSort::Id => write!(f, "id"),
+            FiberSort::Status => write!(f, "status"),
+            FiberSort::SuspendedAge => write!(f, "suspended age"),
+        }
+    }
+}
+
+/// Lifetime (in ms) from the first line of a dump, e.g. 25965835 for `#4 (7h432m25965s25965835ms)`
+pub fn lifetime_ms(dump: &str) -> Option<u64> {
+    let first = dump.lines().next()?;
+    let end = first.find("ms)")?;
+    let start = first[..end].rfind(|c: char| !c.is_ascii_digit())? + 1;
+    first[start..end].parse().ok()
+}
+
 impl Display for FiberStatus {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "{:?}", self)
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
 pub struct FiberCount {
     pub done: i32,
     pub finishing: i32,
@@ -40,4 +87,174 @@ impl FiberCount {
     pub fn total(&self) -> i32 {
         self.done + self.finishing + self.running + self.suspended
     }
+
+    pub fn count(&self, status: &FiberStatus) -> i32 {
+        match status {
+            FiberStatus::Done => self.done,
+            FiberStatus::Finishing => self.finishing,
+            FiberStatus::Running => self.running,
+            FiberStatus::Suspended => self.suspended,
+        }
+    }
+}
+
+/// Fibers created and completed between two consecutive dumps
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+pub struct FiberLifecycle {
+    pub created: i32,
+    pub completed: i32,
+}
+
+impl FiberLifecycle {
+    ///
+    /// Compares fiber ids of two dumps.
+    ///
+    /// A fiber is created if its id wasn't in the previous dump, and completed
+    /// if it wasn't done in the previous dump but is done or gone in the current one.
+    pub fn between(prev: &[Fiber], current: &[Fiber]) -> FiberLifecycle {
+        let prev_ids: HashSet<usize> = prev.iter().map(|f| f.id).collect();
+        let live_ids: HashSet<usize> = current.iter()
+            .filter(|f| f.status != FiberStatus::Done)
+            .map(|f| f.id)
+            .collect();
+        FiberLifecycle {
+            created: current.iter().filter(|f| !prev_ids.contains(&f.id)).count() as i32,
+            completed: prev.iter()
+                .filter(|f| f.status != FiberStatus::Done && !live_ids.contains(&f.id))
+                .count() as i32,
+        }
+    }
+}
+
+impl Downsample for FiberCount {
+    fn merge(&mut self, later: FiberCount) {
+        self.done = self.done.max(later.done);
+        self.finishing = self.finishing.max(later.finishing);
+        self.running = self.running.max(later.running);
+        self.suspended = self.suspended.max(later.suspended);
+    }
+}
+
+impl Downsample for FiberLifecycle {
+    fn merge(&mut self, later: FiberLifecycle) {
+        self.created = self.created.max(later.created);
+        self.completed = self.completed.max(later.completed);
+    }
+}
+
+///
+/// Fibers of a dump too large to list one by one that share a status line, which tells where suspended fibers wait,
+/// e.g. `Suspended(interruptible, zio.Promise.await(Promise.scala:50))`. The count of asyncs is left out of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiberGroup {
+    pub status_line: String,
+    pub count: usize,
+    /// Fiber of the group with the lowest id, whose dump is shown for the group
+    pub sample: Fiber,
+}
+
+impl FiberGroup {
+    /// Groups of the fibers, the largest first
+    pub fn of(fibers: &[Fiber]) -> Vec<FiberGroup> {
+        let mut groups: HashMap<String, (usize, &Fiber)> = HashMap::new();
+        for fiber in fibers {
+            let line = fiber.dump.lines().nth(1).map_or("", str::trim).trim_start_matches("Status: ");
+            let line: Vec<&str> = line.split(", ").filter(|part| !part.ends_with(" asyncs")).collect();
+            let (count, sample) = groups.entry(line.join(", ")).or_insert((0, fiber));
+            *count += 1;
+            if fiber.id < sample.id {
+                *sample = fiber;
+            }
+        }
+        let mut groups: Vec<FiberGroup> = groups.into_iter()
+            .map(|(status_line, (count, sample))| FiberGroup { status_line, count, sample: sample.clone() })
+            .collect();
+        groups.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.status_line.cmp(&b.status_line)));
+        groups
+    }
+}
+
+/// Structure of the fiber tree of a dump, as runaway fiber nesting is a common cause of leaks
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct FiberTreeShape {
+    /// Most fibers on a path from a root, or an orphan, down to a leaf
+    pub max_depth: usize,
+    /// Most children of a single fiber
+    pub max_children: usize,
+    /// Number of fibers by their number of children, in the ranges of `CHILDREN_RANGES`
+    pub children: [usize; 6],
+    /// Fibers whose parent isn't in the dump, left out of the fiber list
+    pub orphans: usize,
+}
+
+impl FiberTreeShape {
+    pub const CHILDREN_RANGES: [&'static str; 6] = ["0", "1", "2-4", "5-9", "10-99", "100+"];
+
+    pub fn of(fibers: &[Fiber]) -> FiberTreeShape {
+        let parents: HashMap<usize, Option<usize>> = fibers.iter().map(|f| (f.id, f.parent_id)).collect();
+        let mut children: HashMap<usize, usize> = HashMap::new();
+        for parent in fibers.iter().filter_map(|f| f.parent_id) {
+            *children.entry(parent).or_insert(0) += 1;
+        }
+
+        let mut shape = FiberTreeShape::default();
+        let mut depths: HashMap<usize, usize> = HashMap::new();
+        for f in fibers {
+            let n = children.get(&f.id).copied().unwrap_or(0);
+            let range = match n {
+                0 => 0,
+                1 => 1,
+                2..=4 => 2,
+                5..=9 => 3,
+                10..=99 => 4,
+                _ => 5,
+            };
+            shape.children[range] += 1;
+            shape.max_children = shape.max_children.max(n);
+            if matches!(f.parent_id, Some(p) if !parents.contains_key(&p)) {
+                shape.orphans += 1;
+            }
+            shape.max_depth = shape.max_depth.max(depth(f.id, &parents, &mut depths));
+        }
+        shape
+    }
+}
+
+/// Depth of the fiber, 1 for a root or an orphan, memoizing the depths of its ancestors along the way
+fn depth(id: usize, parents: &HashMap<usize, Option<usize>>, depths: &mut HashMap<usize, usize>) -> usize {
+    let mut chain = vec![];
+    let mut visited = HashSet::new();
+    let mut base = 0;
+    let mut current = Some(id);
+    while let Some(i) = current {
+        if let Some(d) = depths.get(&i) {
+            base = *d;
+            break;
+        }
+        // the parent of an orphan, or a cycle in a dump taken while fibers were forked
+        if !parents.contains_key(&i) || !visited.insert(i) {
+            break;
+        }
+        chain.push(i);
+        current = parents[&i];
+    }
+    for (n, i) in chain.iter().rev().enumerate() {
+        depths.insert(*i, base + n + 1);
+    }
+    depths[&id]
+}
+
+/// Every fiber of a dump along with its stack trace, saved as a single JSON file for escalating issues upstream
+#[derive(Serialize)]
+pub struct FiberDumpArchive<'a> {
+    /// Seconds since the Unix epoch
+    pub exported_at: u64,
+    pub fibers: &'a [Fiber],
+}
+
+impl<'a> FiberDumpArchive<'a> {
+    pub fn to_json(&self) -> String {
+        // serializing plain strings and numbers can't fail
+        serde_json::to_string_pretty(self).unwrap()
+    }
 }
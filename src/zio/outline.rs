@@ -0,0 +1,89 @@
+///
+/// Fiber dump split into foldable sections.
+///
+/// Each section starts with a `Fiber:Id(..) ...:` heading, e.g. the continuation, the execution trace
+/// or the ancestry of the fiber, and lasts until the next heading:
+///
+///   ```text
+///   #2 (1m98s98260ms) waiting on #2
+///   Status: Suspended(interruptible, 18 asyncs, zio.Promise.await(Promise.scala:50))
+///   Fiber:Id(1588237280480,2) was supposed to continue to:
+///     a future continuation at zio.ZIO.zipWith(ZIO.scala:645)
+///   Fiber:Id(1588237280480,2) execution trace:
+///     at zio.Promise.await(Promise.scala:50)
+///   ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DumpOutline {
+    /// Lines before the first section, with the id and the status of the fiber
+    pub head: Vec<String>,
+    pub sections: Vec<DumpSection>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DumpSection {
+    pub heading: String,
+    pub lines: Vec<String>,
+    pub folded: bool,
+}
+
+/// Rendered dump along with the section shown on each line, if any
+pub struct RenderedDump {
+    pub text: String,
+    pub line_sections: Vec<Option<usize>>,
+}
+
+impl DumpOutline {
+    pub fn parse(dump: &str) -> DumpOutline {
+        let mut outline = DumpOutline::default();
+        for line in dump.lines() {
+            if line.trim_start().starts_with("Fiber:Id(") {
+                outline.sections.push(DumpSection { heading: line.to_owned(), lines: vec![], folded: false });
+            } else {
+                match outline.sections.last_mut() {
+                    Some(s) => s.lines.push(line.to_owned()),
+                    None => outline.head.push(line.to_owned()),
+                }
+            }
+        }
+        outline
+    }
+
+    pub fn toggle(&mut self, section: usize) {
+        if let Some(s) = self.sections.get_mut(section) {
+            s.folded = !s.folded;
+        }
+    }
+
+    /// Folds the same sections as the other outline, e.g. the one of the previous dump of the fiber
+    pub fn fold_like(&mut self, other: &DumpOutline) {
+        for (s, o) in self.sections.iter_mut().zip(other.sections.iter()) {
+            s.folded = o.folded;
+        }
+    }
+
+    /// Folds every section, or unfolds them all if they're already folded
+    pub fn toggle_all(&mut self) {
+        let fold = self.sections.iter().any(|s| !s.folded);
+        for s in self.sections.iter_mut() {
+            s.folded = fold;
+        }
+    }
+
+    /// Dump with the lines of folded sections replaced by their count
+    pub fn render(&self) -> RenderedDump {
+        let mut lines: Vec<String> = self.head.clone();
+        let mut line_sections = vec![None; lines.len()];
+        for (i, s) in self.sections.iter().enumerate() {
+            let body = s.lines.iter().filter(|l| !l.trim().is_empty()).count();
+            if s.folded {
+                lines.push(format!("▸ {} [{} lines]", s.heading.trim_start(), body));
+                line_sections.push(Some(i));
+            } else {
+                lines.push(format!("▾ {}", s.heading.trim_start()));
+                lines.extend(s.lines.iter().cloned());
+                line_sections.extend(vec![Some(i); s.lines.len() + 1]);
+            }
+        }
+        RenderedDump { text: lines.join("\n"), line_sections }
+    }
+}
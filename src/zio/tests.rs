@@ -84,3 +84,288 @@ fn dump_parser_not_enough_lines() {
     assert_eq!(parse_fiber_dump("#3 (1m96s96402ms)".to_owned()), None);
     assert_eq!(parse_fiber_dump("".to_owned()), None);
 }
+
+#[test]
+fn fiber_tree_as_dot() {
+    use crate::widgets::tree::tree_to_dot;
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let fibers = vec![
+        Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "".to_owned() },
+        Fiber { id: 2, parent_id: Some(1), status: FiberStatus::Suspended, dump: "".to_owned() },
+    ];
+    assert_eq!(tree_to_dot("fibers", &fibers, true), "digraph fibers {
+  n1 [label=\"#1 Running\"];
+  n2 [label=\"#2 Suspended\"];
+  n1 -> n2;
+}");
+}
+
+#[test]
+fn fiber_names_escaped_in_dot() {
+    use crate::widgets::tree::tree_to_dot;
+    use crate::zio::label::NamedFiber;
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let fiber = NamedFiber {
+        fiber: Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "".to_owned() },
+        name: Some("Café \"main\" C:\\app\ttab".to_owned()),
+    };
+    assert_eq!(tree_to_dot("fibers", &[fiber], true), "digraph fibers {
+  n1 [label=\"#1 Running Café \\\"main\\\" C:\\\\app\ttab\"];
+}");
+}
+
+#[test]
+fn fiber_lifecycle_between_dumps() {
+    use crate::zio::model::{Fiber, FiberLifecycle, FiberStatus};
+
+    let fiber = |id: usize, status: FiberStatus| Fiber { id, parent_id: None, status, dump: "".to_owned() };
+    let prev = vec![
+        fiber(1, FiberStatus::Running),
+        fiber(2, FiberStatus::Suspended),
+        fiber(3, FiberStatus::Done),
+        fiber(4, FiberStatus::Running),
+    ];
+    let current = vec![
+        fiber(1, FiberStatus::Running),
+        fiber(2, FiberStatus::Done),
+        fiber(5, FiberStatus::Running),
+        fiber(6, FiberStatus::Done),
+    ];
+
+    assert_eq!(FiberLifecycle::between(&prev, &current), FiberLifecycle { created: 2, completed: 2 });
+}
+
+#[test]
+fn fiber_tree_shape() {
+    use crate::zio::model::{Fiber, FiberStatus, FiberTreeShape};
+
+    let fiber = |id: usize, parent_id: Option<usize>| Fiber { id, parent_id, status: FiberStatus::Running, dump: "".to_owned() };
+    let mut fibers = vec![fiber(1, None), fiber(2, Some(1)), fiber(3, Some(2)), fiber(4, Some(42))];
+    fibers.extend((10..15).map(|id| fiber(id, Some(1))));
+
+    assert_eq!(FiberTreeShape::of(&fibers), FiberTreeShape {
+        max_depth: 3,
+        max_children: 6,
+        children: [7, 1, 0, 1, 0, 0],
+        orphans: 1,
+    });
+    assert_eq!(FiberTreeShape::of(&[fiber(1, Some(2)), fiber(2, Some(1))]).max_depth, 2);
+}
+
+#[test]
+fn fiber_groups_by_status_line() {
+    use crate::zio::model::{Fiber, FiberGroup, FiberStatus};
+
+    let fiber = |id: usize, status: FiberStatus, line: &str| Fiber { id, parent_id: None, status, dump: format!("#{} (1s)\n    Status: {}", id, line) };
+    let fibers = vec![
+        fiber(7, FiberStatus::Suspended, "Suspended(interruptible, 3 asyncs, zio.Promise.await(Promise.scala:50))"),
+        fiber(3, FiberStatus::Suspended, "Suspended(interruptible, 18 asyncs, zio.Promise.await(Promise.scala:50))"),
+        fiber(5, FiberStatus::Running, "Running()"),
+        fiber(9, FiberStatus::Suspended, "Suspended(interruptible, 1 asyncs, zio.ZIO.sleep)"),
+        fiber(8, FiberStatus::Suspended, "Suspended(interruptible, 2 asyncs, zio.ZIO.sleep)"),
+        fiber(4, FiberStatus::Suspended, "Suspended(interruptible, 5 asyncs, zio.Promise.await(Promise.scala:50))"),
+    ];
+
+    let groups = FiberGroup::of(&fibers);
+    let summary: Vec<(&str, usize, usize)> = groups.iter().map(|g| (g.status_line.as_str(), g.count, g.sample.id)).collect();
+    assert_eq!(summary, vec![
+        ("Suspended(interruptible, zio.Promise.await(Promise.scala:50))", 3, 3),
+        ("Suspended(interruptible, zio.ZIO.sleep)", 2, 8),
+        ("Running()", 1, 5),
+    ]);
+}
+
+#[test]
+fn fiber_dump_archive_json() {
+    use crate::zio::model::{Fiber, FiberDumpArchive, FiberStatus};
+
+    let fibers = vec![
+        Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "#1 Status: Running()".to_owned() },
+        Fiber { id: 2, parent_id: Some(1), status: FiberStatus::Suspended, dump: "#2 Status: Suspended()".to_owned() },
+    ];
+    let json: serde_json::Value = serde_json::from_str(&FiberDumpArchive { exported_at: 100, fibers: &fibers }.to_json()).unwrap();
+
+    assert_eq!(json["exported_at"], 100);
+    assert_eq!(json["fibers"][1]["parent_id"], 1);
+    assert_eq!(json["fibers"][1]["status"], "Suspended");
+    assert_eq!(json["fibers"][0]["dump"], "#1 Status: Running()");
+}
+
+#[test]
+fn fiber_tree_with_columns() {
+    use crate::widgets::tree::{Column, format_rows, tree_rows};
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let fibers = vec![
+        Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "#1 (12s)\nStatus: Running()".to_owned() },
+        Fiber { id: 2, parent_id: Some(1), status: FiberStatus::Suspended, dump: "#2 (3s)\nStatus: Suspended()".to_owned() },
+        Fiber { id: 4, parent_id: None, status: FiberStatus::Done, dump: "".to_owned() },
+    ];
+    let rows = tree_rows(fibers, true);
+
+    assert_eq!(format_rows(&rows, &[Column::Label, Column::Children, Column::Age]), vec![
+        "├─#1   Running   1 12s",
+        "│ └─#2 Suspended 0 3s",
+        "└─#4   Done      0 -",
+    ]);
+}
+
+#[test]
+fn fiber_tree_navigation() {
+    use crate::widgets::tree::{first_child_index, next_sibling_index, parent_index, tree_rows};
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let fiber = |id: usize, parent_id: Option<usize>| Fiber { id, parent_id, status: FiberStatus::Running, dump: "".to_owned() };
+    // in tree order: #1, #2 (child of #1), #3 (child of #1), #4
+    let fibers: Vec<Fiber> = tree_rows(vec![fiber(1, None), fiber(2, Some(1)), fiber(3, Some(1)), fiber(4, None)], true)
+        .into_iter()
+        .map(|r| r.item)
+        .collect();
+    let ids: Vec<usize> = fibers.iter().map(|f| f.id).collect();
+    assert_eq!(ids, vec![1, 2, 3, 4]);
+
+    assert_eq!(first_child_index(&fibers, 0), Some(1));
+    assert_eq!(first_child_index(&fibers, 1), None);
+    assert_eq!(next_sibling_index(&fibers, 1), Some(2));
+    assert_eq!(next_sibling_index(&fibers, 0), Some(3));
+    assert_eq!(next_sibling_index(&fibers, 3), None);
+    assert_eq!(parent_index(&fibers, 2), Some(0));
+    assert_eq!(parent_index(&fibers, 0), None);
+}
+
+#[test]
+fn burst_capture_saves_each_dump() {
+    use std::time::{Duration, Instant};
+    use crate::zio::burst::BurstCapture;
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let dir = std::env::temp_dir().join(format!("panopticon-burst-{}", std::process::id()));
+    let mut burst = BurstCapture::start(dir.display().to_string(), Duration::from_secs(10)).unwrap();
+    let fibers = vec![Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "#1".to_owned() }];
+
+    let first = burst.save(&fibers).unwrap();
+    burst.save(&[]).unwrap();
+
+    assert_eq!(burst.dumps, 2);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    assert!(std::fs::read_to_string(&first).unwrap().contains("\"dump\": \"#1\""));
+    assert!(burst.is_running(Instant::now()));
+    assert!(!burst.is_running(burst.until));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fiber_label_names_fibers_from_dump() {
+    use crate::widgets::tree::TreeWidgetNode;
+    use crate::zio::label::{FiberLabel, NamedFiber};
+    use crate::zio::model::{Fiber, FiberStatus};
+
+    let label: FiberLabel = serde_json::from_str(r#"{ "pattern": "continuation at (?P<method>[\\w.]+)\\(" }"#).unwrap();
+    let dump = "#4 (1s)\nStatus: Suspended()\na future continuation at com.example.Worker.run(Worker.scala:12)";
+    let named = |template: &str| FiberLabel { template: template.to_owned(), ..label.clone() }.name(dump);
+
+    assert_eq!(label.name(dump), Some("com.example.Worker.run".to_owned()));
+    assert_eq!(named("worker $method"), Some("worker com.example.Worker.run".to_owned()));
+    assert_eq!(named("$2"), None);
+    assert_eq!(label.name("#5 (1s)\nStatus: Running()"), None);
+
+    let fiber = Fiber { id: 4, parent_id: None, status: FiberStatus::Suspended, dump: dump.to_owned() };
+    let name = label.name(dump);
+    assert_eq!(NamedFiber { fiber: fiber.clone(), name }.label(), "Suspended com.example.Worker.run");
+    assert_eq!(NamedFiber { fiber, name: None }.label(), "Suspended");
+    assert!(serde_json::from_str::<FiberLabel>(r#"{ "pattern": "(" }"#).is_err());
+}
+
+#[test]
+fn diffs_dump_lines() {
+    use crate::zio::diff::{diff_lines, LineChange};
+
+    let line = |change: LineChange, text: &str| (change, text.to_owned());
+    assert_eq!(diff_lines("a\nb\nc", "a\nc\nd"), vec![
+        line(LineChange::Unchanged, "a"),
+        line(LineChange::Removed, "b"),
+        line(LineChange::Unchanged, "c"),
+        line(LineChange::Added, "d"),
+    ]);
+    assert_eq!(diff_lines("#2 (1s)\nat zio.ZIO", "#2 (3s)\nat zio.ZIO"), vec![
+        line(LineChange::Removed, "#2 (1s)"),
+        line(LineChange::Added, "#2 (3s)"),
+        line(LineChange::Unchanged, "at zio.ZIO"),
+    ]);
+    assert!(diff_lines("a\nb", "a\nb").iter().all(|(c, _)| *c == LineChange::Unchanged));
+}
+
+#[test]
+fn dump_outline_folds_sections() {
+    use crate::zio::outline::DumpOutline;
+
+    let dump = "#2 (1m98s98260ms)
+Status: Suspended(interruptible, 18 asyncs, zio.Promise.await(Promise.scala:50))
+Fiber:Id(1588237280480,2) was supposed to continue to:
+  a future continuation at zio.ZIO.zipWith(ZIO.scala:645)
+
+Fiber:Id(1588237280480,2) execution trace:
+  at zio.Promise.await(Promise.scala:50)
+  at zio.ZIO.zipWith(ZIO.scala:645)";
+
+    let mut outline = DumpOutline::parse(dump);
+    assert_eq!(outline.head.len(), 2);
+    assert_eq!(outline.sections.len(), 2);
+    assert_eq!(outline.render().text, dump.replace("Fiber:Id", "▾ Fiber:Id"));
+
+    outline.toggle(1);
+    let rendered = outline.render();
+    assert!(rendered.text.ends_with("▸ Fiber:Id(1588237280480,2) execution trace: [2 lines]"));
+    assert_eq!(rendered.line_sections, vec![None, None, Some(0), Some(0), Some(0), Some(1)]);
+
+    outline.toggle_all();
+    assert!(outline.sections.iter().all(|s| s.folded));
+    outline.toggle_all();
+    assert!(outline.sections.iter().all(|s| !s.folded));
+}
+
+#[cfg(unix)]
+#[test]
+fn zmx_client_reads_dump_from_unix_socket() {
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    use crate::zio::model::FiberStatus;
+    use crate::zio::zmx::{NetworkZMXClient, ZMXClient};
+
+    let dir = std::env::temp_dir().join(format!("panopticon-zmx-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("zmx.sock");
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let dumps = ["#1 (10s)\nStatus: Running()", "#2 (5s)\nStatus: Suspended(interruptible, 3 asyncs, zio.ZIO.sleep)"];
+    let mut response = format!("*{}\r\n", dumps.len());
+    for dump in dumps.iter() {
+        response.push_str(&format!("${}\r\n{}\r\n", dump.len(), dump));
+    }
+    let server = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 64];
+        while !request.ends_with(b"dump\r\n") {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0, "the client closed the connection before sending the dump command");
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+
+    let client = NetworkZMXClient::new(format!("unix://{}", path.display()));
+    let fibers = client.dump_fibers().unwrap();
+    server.join().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(fibers.iter().map(|f| (f.id, f.status.clone())).collect::<Vec<(usize, FiberStatus)>>(), vec![
+        (1, FiberStatus::Running),
+        (2, FiberStatus::Suspended),
+    ]);
+}
@@ -1,9 +1,12 @@
 use bytes::BytesMut;
 use redis_protocol::types::Frame;
 use std::error::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::io::AsyncWriteExt;
 use tokio::io::AsyncReadExt;
 use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 use crate::zio::dump_parser;
 use crate::zio::model::Fiber;
 
@@ -12,13 +15,38 @@ pub trait ZMXClient {
     fn dump_fibers(&self) -> Result<Vec<Fiber>, String>;
 }
 
+/// Client of a zio-zmx server listening on a TCP address, e.g. `localhost:6789`,
+/// or on a Unix domain socket, e.g. `unix:///var/run/zmx.sock`
 pub struct NetworkZMXClient {
     address: String
 }
 
 impl NetworkZMXClient {
+    const UNIX_SCHEME: &'static str = "unix://";
+
     pub fn new(address: String) -> NetworkZMXClient { NetworkZMXClient { address } }
 
+    /// Sends the request and reads the response until the server closes the connection
+    async fn exchange<S>(mut stream: S, request: &[u8]) -> Result<String, Box<dyn Error>>
+        where S: AsyncRead + AsyncWrite + Unpin {
+        let _ = stream.write(request).await;
+
+        let mut buffer = String::new();
+        stream.read_to_string(&mut buffer).await?;
+        Ok(buffer)
+    }
+
+    #[cfg(unix)]
+    async fn exchange_unix(path: &str, request: &[u8]) -> Result<String, Box<dyn Error>> {
+        let stream = UnixStream::connect(path).await?;
+        NetworkZMXClient::exchange(stream, request).await
+    }
+
+    #[cfg(not(unix))]
+    async fn exchange_unix(path: &str, _request: &[u8]) -> Result<String, Box<dyn Error>> {
+        Err(Box::from(format!("Unix domain sockets aren't supported on this platform: {}", path)))
+    }
+
     #[tokio::main]
     async fn get_dump(&self) -> Result<Vec<Fiber>, Box<dyn Error>> {
         let frame = Frame::Array(vec![Frame::BulkString("dump".into())]);
@@ -29,12 +57,12 @@ impl NetworkZMXClient {
             Err(e) => panic!("Error encoding frame: {:?}", e)
         };
 
-        let mut stream = TcpStream::connect(&self.address).await?;
-
-        let _ = stream.write(&buf).await;
-
-        let mut buffer = String::new();
-        stream.read_to_string(&mut buffer).await?;
+        let buffer = if self.address.starts_with(NetworkZMXClient::UNIX_SCHEME) {
+            NetworkZMXClient::exchange_unix(&self.address[NetworkZMXClient::UNIX_SCHEME.len()..], &buf).await?
+        } else {
+            let stream = TcpStream::connect(&self.address).await?;
+            NetworkZMXClient::exchange(stream, &buf).await?
+        };
 
         let buf: BytesMut = buffer.into();
 
@@ -0,0 +1,229 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::time::Duration;
+
+use native_tls::{Certificate, Identity, TlsConnector, TlsStream};
+
+use crate::http;
+use crate::process;
+use crate::zookeeper::model::{self, KafkaBroker, ZkAdminSettings, ZkEnsembleConfig, ZkKafkaSettings, ZkMonitor, ZkNodeState, ZkNodeStatus, ZkReply, ZkSshSettings, ZkTlsSettings};
+use crate::zookeeper::znode::ZnodeSession;
+
+///
+/// Probes each node of the ensemble with `ruok` and `isro`, and asks the ones that respond for their mode and zxid with `srvr`,
+/// for their load with `mntr` and for their view of the ensemble with `conf`, the last two left out if they aren't whitelisted.
+/// Commands that aren't whitelisted are sent to the AdminServer, if there's one.
+pub fn get_statuses(nodes: &[String], timeout: u64, tls: Option<&TlsConnector>, admin: Option<&ZkAdminSettings>, ssh: Option<&ZkSshSettings>) -> Vec<ZkNodeStatus> {
+    nodes.iter()
+        .map(|address| {
+            let uses_admin = admin.map_or(false, |a| a.is_transport_of(address));
+            let mut not_whitelisted = vec![];
+            let mut ask = |command: &str| {
+                let reply = run(address, command, timeout, tls, admin, ssh)?;
+                if !uses_admin && !matches!(reply, ZkReply::Text(_)) {
+                    not_whitelisted.push(command.to_owned());
+                }
+                match reply {
+                    // the node answered, it's serving requests even if it doesn't tell so
+                    ZkReply::NotWhitelisted if command == "ruok" => Ok("imok".to_owned()),
+                    r => r.text(command),
+                }
+            };
+            let ruok = ask("ruok");
+            let isro = if ruok.is_ok() { ask("isro") } else { Ok("".to_owned()) };
+            let mut status = ZkNodeStatus::from_responses(address, ruok, isro);
+            if status.state != ZkNodeState::NotResponding {
+                let srvr = ask("srvr").ok();
+                status.mode = srvr.as_deref().and_then(ZkNodeStatus::parse_mode);
+                status.zxid = srvr.as_deref().and_then(ZkNodeStatus::parse_zxid);
+                status.monitor = run(address, "mntr", timeout, tls, admin, ssh).ok().as_ref().and_then(ZkMonitor::from_reply);
+                let conf = run(address, "conf", timeout, tls, admin, ssh).and_then(|r| r.text("conf")).ok();
+                status.config = conf.as_deref().and_then(ZkEnsembleConfig::parse);
+                status.server_id = conf.as_deref().and_then(ZkNodeStatus::parse_server_id);
+            }
+            status.admin_fallback = admin.is_some() && !not_whitelisted.is_empty();
+            status.not_whitelisted = not_whitelisted;
+            status
+        })
+        .collect()
+}
+
+///
+/// Statuses of the nodes, or of the members of the ensemble they list when discovering them.
+/// The given nodes are probed if none lists the members, each telling why.
+pub fn probe_ensemble(nodes: &[String], discover: bool, timeout: u64, tls: Option<&TlsConnector>, admin: Option<&ZkAdminSettings>, ssh: Option<&ZkSshSettings>) -> Vec<ZkNodeStatus> {
+    let discovered = if discover { Some(discover_members(nodes, timeout, tls, admin, ssh)) } else { None };
+    let (nodes, discovery_error) = match discovered {
+        Some(Ok(members)) => (members, None),
+        Some(Err(e)) => (nodes.to_vec(), Some(e)),
+        None => (nodes.to_vec(), None),
+    };
+    let mut statuses = get_statuses(&nodes, timeout, tls, admin, ssh);
+    for status in statuses.iter_mut().filter(|s| s.error.is_none()) {
+        status.error = discovery_error.clone();
+    }
+    statuses
+}
+
+///
+/// Members of the ensemble from the `conf` of the first node listing them, which needs `conf` in `4lw.commands.whitelist`.
+/// The nodes are tried in turn, so that the ensemble is still found while some of its members are down.
+pub fn discover_members(nodes: &[String], timeout: u64, tls: Option<&TlsConnector>, admin: Option<&ZkAdminSettings>, ssh: Option<&ZkSshSettings>) -> Result<Vec<String>, String> {
+    let mut errors = vec![];
+    for address in nodes {
+        match run(address, "conf", timeout, tls, admin, ssh).and_then(|r| r.text("conf")) {
+            Ok(conf) => {
+                let members = model::ensemble_members(&conf);
+                if !members.is_empty() {
+                    return Ok(members);
+                }
+                errors.push(format!("{} lists no members in its conf", address));
+            }
+            Err(e) => errors.push(format!("{}: {}", address, e)),
+        }
+    }
+    Err(format!("Couldn't discover the ensemble ({})", errors.join(", ")))
+}
+
+///
+/// Brokers of the Kafka cluster registered in the ensemble, read from the first node that answers, each listener of
+/// the brokers being checked for connections. Nodes reached over the AdminServer or SSH are left out,
+/// as znodes are only read over the client port.
+pub fn get_brokers(nodes: &[String], kafka: &ZkKafkaSettings, timeout: u64, tls: Option<&TlsConnector>, admin: Option<&ZkAdminSettings>, ssh: Option<&ZkSshSettings>) -> Result<Vec<KafkaBroker>, String> {
+    let mut errors = vec![];
+    let reachable = nodes.iter()
+        .filter(|a| !admin.map_or(false, |s| s.is_transport_of(a)) && !ssh.map_or(false, |s| s.is_transport_of(a)));
+    for address in reachable {
+        let brokers = connect(address, timeout).and_then(|stream| match tls {
+            None => read_brokers(stream, kafka, timeout),
+            Some(connector) => read_brokers(tls_handshake(connector, address, stream)?, kafka, timeout),
+        });
+        match brokers {
+            Ok(mut brokers) => {
+                for listener in brokers.iter_mut().flat_map(|b| b.listeners.iter_mut()) {
+                    listener.error = connect(&listener.address, timeout).err();
+                }
+                return Ok(brokers);
+            }
+            Err(e) => errors.push(format!("{}: {}", address, e)),
+        }
+    }
+    if errors.is_empty() {
+        return Err("Kafka brokers are only read from nodes whose client port is reachable".to_owned());
+    }
+    Err(format!("Couldn't read the Kafka brokers ({})", errors.join(", ")))
+}
+
+/// Registrations of the live brokers, ordered by id
+fn read_brokers<S: Read + Write>(stream: S, kafka: &ZkKafkaSettings, timeout: u64) -> Result<Vec<KafkaBroker>, String> {
+    let mut session = ZnodeSession::open(stream, timeout)?;
+    let path = kafka.brokers_path();
+    let mut ids = session.children(&path)?;
+    ids.sort_by_key(|id| id.parse::<i64>().unwrap_or(i64::MAX));
+    ids.iter()
+        .map(|id| session.data(&format!("{}/{}", path, id)).and_then(|data| KafkaBroker::parse(id, &data)))
+        .collect()
+}
+
+///
+/// Sends a four letter word command, or the same command to the AdminServer if the node refuses it for not being whitelisted.
+/// Nodes using the AdminServer as their transport are only sent the command over HTTP, and the ones using SSH over `ssh`.
+pub fn run(address: &str, command_name: &str, timeout: u64, tls: Option<&TlsConnector>, admin: Option<&ZkAdminSettings>, ssh: Option<&ZkSshSettings>) -> Result<ZkReply, String> {
+    if let Some(admin) = admin.filter(|a| a.is_transport_of(address)) {
+        return admin_command(&admin.command_url(address, command_name), timeout).map(ZkReply::Admin);
+    }
+    let response = match ssh.filter(|s| s.is_transport_of(address)) {
+        Some(ssh) => ssh_command(ssh, address, command_name, timeout)?,
+        None => command(address, command_name, timeout, tls)?,
+    };
+    if !ZkReply::is_not_whitelisted(&response) {
+        return Ok(ZkReply::Text(response));
+    }
+    match admin {
+        Some(admin) => admin_command(&admin.command_url(address, command_name), timeout).map(ZkReply::Admin),
+        None => Ok(ZkReply::NotWhitelisted),
+    }
+}
+
+#[tokio::main]
+async fn admin_command(url: &str, timeout: u64) -> Result<serde_json::Value, String> {
+    let response = http::client(None)?
+        .get(url)
+        .timeout(Duration::from_millis(timeout))
+        .send().await
+        .map_err(|e| format!("AdminServer request {} failed: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("AdminServer request {} failed with status {}", url, response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Connector for the secure client port, reading the CA certificate and the client keystore of the settings
+pub fn tls_connector(settings: &ZkTlsSettings) -> Result<TlsConnector, String> {
+    let mut builder = TlsConnector::builder();
+    if let Some(path) = &settings.ca {
+        let pem = fs::read(path).map_err(|e| format!("Couldn't read Zookeeper CA {}: {}", path, e))?;
+        let ca = Certificate::from_pem(&pem).map_err(|e| format!("Invalid Zookeeper CA {}: {}", path, e))?;
+        builder.add_root_certificate(ca);
+    }
+    if let Some(path) = &settings.keystore {
+        let der = fs::read(path).map_err(|e| format!("Couldn't read Zookeeper keystore {}: {}", path, e))?;
+        let password = settings.keystore_password.as_deref().unwrap_or_default();
+        let identity = Identity::from_pkcs12(&der, password)
+            .map_err(|e| format!("Invalid Zookeeper keystore {}: {}", path, e))?;
+        builder.identity(identity);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Sends a four letter word command, the node closes the connection after responding
+pub fn command(address: &str, command: &str, timeout: u64, tls: Option<&TlsConnector>) -> Result<String, String> {
+    let stream = connect(address, timeout)?;
+    match tls {
+        None => exchange(stream, command),
+        Some(connector) => exchange(tls_handshake(connector, address, stream)?, command),
+    }
+}
+
+/// Connection whose reads and writes time out as well
+fn connect(address: &str, timeout: u64) -> Result<TcpStream, String> {
+    let timeout = Duration::from_millis(timeout);
+    let addr = address.to_socket_addrs()
+        .map_err(|e| e.to_string())?
+        .next()
+        .ok_or(format!("Couldn't resolve {}", address))?;
+    let stream = TcpStream::connect_timeout(&addr, timeout).map_err(|e| e.to_string())?;
+    stream.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+    Ok(stream)
+}
+
+fn tls_handshake(connector: &TlsConnector, address: &str, stream: TcpStream) -> Result<TlsStream<TcpStream>, String> {
+    let host = address.rsplitn(2, ':').last().unwrap_or(address);
+    connector.connect(host, stream).map_err(|e| format!("TLS handshake failed: {}", e))
+}
+
+///
+/// Sends a four letter word command from the host of the node over SSH.
+/// ssh is killed once the timeout is over, whether it's still connecting or the node doesn't respond.
+fn ssh_command(ssh: &ZkSshSettings, address: &str, command: &str, timeout: u64) -> Result<String, String> {
+    let output = process::output_within(Command::new("ssh").args(ssh.args(address, command, timeout / 1000)?), Duration::from_millis(timeout))
+        .map_err(|e| format!("Couldn't run ssh: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ssh failed with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Sends the command and reads the response, whose bytes that aren't UTF-8, e.g. in znode paths of `wchp`, are replaced
+pub(crate) fn exchange<S: Read + Write>(mut stream: S, command: &str) -> Result<String, String> {
+    stream.write_all(command.as_bytes()).map_err(|e| e.to_string())?;
+    let mut response = vec![];
+    // nodes may close a TLS connection without a close_notify once the response is sent
+    match stream.read_to_end(&mut response) {
+        Err(e) if response.is_empty() => Err(e.to_string()),
+        _ => Ok(String::from_utf8_lossy(&response).into_owned()),
+    }
+}
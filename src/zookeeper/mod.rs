@@ -0,0 +1,5 @@
+pub mod model;
+pub mod client;
+pub mod report;
+pub mod znode;
+mod tests;
@@ -0,0 +1,606 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::jmx::model::KafkaBrokerMetrics;
+
+///
+/// TLS settings of the ensemble's secure client port, set with `zookeeper_tls` in the config file.
+///
+/// eg.
+///   ```text
+///   { "ca": "zk-ca.pem", "keystore": "panopticon.p12" }
+///   ```
+/// The keystore password is read from the OS keyring for the `--profile`, or from `PANOPTICON_ZOOKEEPER_KEYSTORE_PASSWORD`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZkTlsSettings {
+    /// PEM file with the CA certificate of the ensemble, trusted along with the system ones
+    pub ca: Option<String>,
+    /// PKCS#12 file with the client certificate and key, for ensembles requiring client authentication
+    pub keystore: Option<String>,
+    /// Password of the keystore, never read from the config file
+    #[serde(skip)]
+    pub keystore_password: Option<String>,
+}
+
+/// State of a Zookeeper node as seen by the `ruok` and `isro` four letter word commands
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZkNodeState {
+    /// Answers `imok` and serves writes
+    Ok,
+    /// Answers `imok`, but is partitioned from the quorum and only serves reads
+    ReadOnly,
+    NotResponding,
+}
+
+impl fmt::Display for ZkNodeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkNodeState::Ok => write!(f, "imok"),
+            ZkNodeState::ReadOnly => write!(f, "read-only"),
+            ZkNodeState::NotResponding => write!(f, "not responding"),
+        }
+    }
+}
+
+///
+/// AdminServer of the nodes (Zookeeper 3.5+), set with `zookeeper_admin` in the config file.
+/// Commands that aren't in `4lw.commands.whitelist` are sent to it over HTTP instead,
+/// and the nodes listed in `nodes` are only queried through it.
+///
+/// eg.
+///   ```text
+///   { "port": 8080, "nodes": ["zk3:2181"] }
+///   ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZkAdminSettings {
+    /// `admin.serverPort` of the nodes, the AdminServer listening on the host of each node
+    pub port: u16,
+    /// Nodes sent every command over the AdminServer rather than as four letter words, as given with `--zookeeper`
+    pub nodes: Vec<String>,
+}
+
+impl Default for ZkAdminSettings {
+    fn default() -> ZkAdminSettings {
+        ZkAdminSettings { port: 8080, nodes: vec![] }
+    }
+}
+
+impl ZkAdminSettings {
+    /// Whether the AdminServer is the only transport of the node
+    pub fn is_transport_of(&self, address: &str) -> bool {
+        self.nodes.iter().any(|n| n == address)
+    }
+
+    /// URL of the command on the AdminServer of the node, which accepts four letter words as command names
+    pub fn command_url(&self, address: &str, command: &str) -> String {
+        let host = address.rsplitn(2, ':').last().unwrap_or(address);
+        format!("http://{}:{}/commands/{}", host, self.port, command)
+    }
+}
+
+///
+/// SSH transport of the nodes whose client port can only be reached from their own host, set with `zookeeper_ssh`
+/// in the config file. The four letter words are sent by bash on the host of the node, over `/dev/tcp`,
+/// to the node's address as resolved there. `nodes` maps each node, as given with `--zookeeper`, to its `ssh` destination.
+///
+/// eg.
+///   ```text
+///   { "nodes": { "zk3:2181": "ops@zk3.internal" }, "options": ["-i", "~/.ssh/zk"] }
+///   ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZkSshSettings {
+    pub nodes: HashMap<String, String>,
+    /// Arguments passed to `ssh` before the destination
+    pub options: Vec<String>,
+}
+
+impl ZkSshSettings {
+    /// Whether SSH is the transport of the node
+    pub fn is_transport_of(&self, address: &str) -> bool {
+        self.nodes.contains_key(address)
+    }
+
+    ///
+    /// Arguments of `ssh` sending the command to the node from its host, which never prompts
+    /// so that a poll doesn't hang on a password or an unknown host key.
+    pub fn args(&self, address: &str, command: &str, timeout_secs: u64) -> Result<Vec<String>, String> {
+        let destination = self.nodes.get(address).ok_or(format!("{} has no SSH destination", address))?;
+        let (host, port) = address.rsplit_once(':')
+            .and_then(|(h, p)| p.parse::<u16>().ok().map(|p| (h, p)))
+            .ok_or(format!("{} has no port", address))?;
+        // the host and the command end up in a remote shell
+        if !host.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-') {
+            return Err(format!("{} isn't a host name or an IPv4 address", host));
+        }
+        if !command.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err(format!("{} isn't a four letter word command", command));
+        }
+        let mut args = vec!["-o".to_owned(), "BatchMode=yes".to_owned(), "-o".to_owned(), format!("ConnectTimeout={}", timeout_secs.max(1))];
+        args.extend(self.options.iter().cloned());
+        args.push(destination.to_owned());
+        args.push(format!("bash -c 'exec 3<>/dev/tcp/{}/{} && printf {} >&3 && cat <&3'", host, port, command));
+        Ok(args)
+    }
+}
+
+///
+/// Kafka cluster registered in the ensemble, set with `zookeeper_kafka` in the config file.
+///
+/// eg.
+///   ```text
+///   { "chroot": "/kafka", "jmx": true }
+///   ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ZkKafkaSettings {
+    /// Path of the cluster's znodes, as in the `zookeeper.connect` of the brokers, the root unless set
+    pub chroot: String,
+    /// Whether the health of each broker is read over JMX, on the `jmx_port` it registered
+    pub jmx: bool,
+}
+
+impl ZkKafkaSettings {
+    /// Znode whose children are the ids of the live brokers
+    pub fn brokers_path(&self) -> String {
+        format!("{}/brokers/ids", self.chroot.trim_end_matches('/'))
+    }
+}
+
+/// Listener a broker advertises to clients
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct KafkaListener {
+    /// Name of the listener, e.g. `PLAINTEXT` or `SASL_SSL`
+    pub name: String,
+    pub address: String,
+    /// Why the listener couldn't be connected to
+    pub error: Option<String>,
+}
+
+/// Kafka broker registered under `/brokers/ids/<id>`, which the broker keeps while it's alive
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct KafkaBroker {
+    pub id: String,
+    /// Host of the broker, from the `host` of the registration or the first listener
+    pub host: Option<String>,
+    pub rack: Option<String>,
+    /// Advertised listeners, from the `endpoints` of the registration, or its `host` and `port` before Kafka 0.9
+    pub listeners: Vec<KafkaListener>,
+    /// Port of the broker's JMX connector, missing when JMX isn't enabled
+    pub jmx_port: Option<u16>,
+    /// Health of the broker read over JMX, if it's read
+    pub jmx: Option<Result<KafkaBrokerMetrics, String>>,
+}
+
+impl KafkaBroker {
+    ///
+    /// Broker from the JSON of its registration.
+    ///
+    /// eg.
+    ///   ```text
+    ///   {"endpoints":["PLAINTEXT://kafka1:9092"],"jmx_port":9999,"host":"kafka1","port":9092,"rack":"eu-west-1a","version":4}
+    ///   ```
+    pub fn parse(id: &str, registration: &[u8]) -> Result<KafkaBroker, String> {
+        #[derive(Deserialize)]
+        struct Registration {
+            #[serde(default)]
+            endpoints: Vec<String>,
+            host: Option<String>,
+            port: Option<i64>,
+            rack: Option<String>,
+            jmx_port: Option<i64>,
+        }
+
+        let r: Registration = serde_json::from_slice(registration)
+            .map_err(|e| format!("Invalid registration of broker {}: {}", id, e))?;
+        let listener = |name: &str, address: &str| KafkaListener { name: name.to_owned(), address: address.to_owned(), error: None };
+        let mut listeners: Vec<KafkaListener> = r.endpoints.iter()
+            .map(|e| match e.split_once("://") {
+                Some((name, address)) => listener(name, address),
+                None => listener("PLAINTEXT", e),
+            })
+            .collect();
+        if let (true, Some(host), Some(port)) = (listeners.is_empty(), &r.host, r.port) {
+            listeners.push(listener("PLAINTEXT", &format!("{}:{}", host, port)));
+        }
+        let host = r.host.filter(|h| !h.is_empty())
+            .or_else(|| listeners.first().and_then(|l| l.address.rsplit_once(':')).map(|(h, _)| h.to_owned()));
+        Ok(KafkaBroker {
+            id: id.to_owned(),
+            host,
+            rack: r.rack,
+            listeners,
+            // -1 when JMX isn't enabled
+            jmx_port: r.jmx_port.filter(|p| *p > 0 && *p <= u16::MAX as i64).map(|p| p as u16),
+            jmx: None,
+        })
+    }
+
+    /// Address of the broker's JMX connector
+    pub fn jmx_address(&self) -> Option<String> {
+        match (&self.host, self.jmx_port) {
+            (Some(host), Some(port)) => Some(format!("{}:{}", host, port)),
+            _ => None,
+        }
+    }
+
+    /// Whether every advertised listener accepts connections
+    pub fn is_reachable(&self) -> bool {
+        !self.listeners.is_empty() && self.listeners.iter().all(|l| l.error.is_none())
+    }
+
+    /// Whether the broker is registered but doesn't serve clients, or its health couldn't be read over JMX
+    pub fn is_failing(&self) -> bool {
+        !self.is_reachable() || matches!(self.jmx, Some(Err(_)))
+    }
+
+    /// Partitions led by the broker whose followers lag behind, as read over JMX
+    pub fn under_replicated_partitions(&self) -> u64 {
+        match &self.jmx {
+            Some(Ok(m)) => m.under_replicated_partitions,
+            _ => 0,
+        }
+    }
+
+    ///
+    /// e.g. `broker 1 rack eu-west-1a: PLAINTEXT kafka1:9092, SSL kafka1:9093 (Connection refused)`,
+    /// followed by its health when it's read over JMX, e.g. `, 2 under-replicated, 85% idle, 0.5 ISR shrinks/s`
+    pub fn label(&self) -> String {
+        let listeners: Vec<String> = self.listeners.iter()
+            .map(|l| match &l.error {
+                Some(e) => format!("{} {} ({})", l.name, l.address, e),
+                None => format!("{} {}", l.name, l.address),
+            })
+            .collect();
+        let rack = self.rack.as_ref().map_or("".to_owned(), |r| format!(" rack {}", r));
+        let listeners = if listeners.is_empty() { "no listeners".to_owned() } else { listeners.join(", ") };
+        let health = match &self.jmx {
+            Some(Ok(m)) => format!(
+                ", {} under-replicated, {:.0}% idle, {} ISR shrinks/s",
+                m.under_replicated_partitions, m.request_handler_idle * 100.0, (m.isr_shrinks_per_sec * 100.0).round() / 100.0,
+            ),
+            Some(Err(e)) => format!(", JMX failing: {}", e),
+            None => "".to_owned(),
+        };
+        format!("broker {}{}: {}{}", self.id, rack, listeners, health)
+    }
+}
+
+/// Response of a node to a command
+#[derive(Clone, Debug, PartialEq)]
+pub enum ZkReply {
+    /// Response to the four letter word
+    Text(String),
+    /// Response of the AdminServer, the command not being in `4lw.commands.whitelist` or the node using it as its transport
+    Admin(serde_json::Value),
+    /// The command isn't in `4lw.commands.whitelist` and there's no AdminServer to fall back to
+    NotWhitelisted,
+}
+
+impl ZkReply {
+    /// Zookeeper 3.5+ answers commands that aren't whitelisted with `<command> is not executed because it is not in the whitelist.`
+    pub fn is_not_whitelisted(response: &str) -> bool {
+        response.contains("is not in the whitelist")
+    }
+
+    ///
+    /// Response in the four letter word format, for the commands the status of a node is made of.
+    /// For the other ones, the JSON of the AdminServer is pretty-printed.
+    pub fn text(&self, command: &str) -> Result<String, String> {
+        match self {
+            ZkReply::Text(t) => Ok(t.to_owned()),
+            ZkReply::Admin(json) => Ok(match command {
+                "ruok" if json["error"].is_null() => "imok".to_owned(),
+                "isro" => match json["read_only"].as_bool() {
+                    Some(true) => "ro".to_owned(),
+                    Some(false) => "rw".to_owned(),
+                    None => "".to_owned(),
+                },
+                "srvr" => {
+                    let stats = &json["server_stats"];
+                    let mut lines = vec![];
+                    if let Some(state) = stats["server_state"].as_str() {
+                        lines.push(format!("Mode: {}", state));
+                    }
+                    if let Some(zxid) = stats["last_processed_zxid"].as_u64() {
+                        lines.push(format!("Zxid: 0x{:x}", zxid));
+                    }
+                    lines.join("\n")
+                }
+                _ => serde_json::to_string_pretty(json).unwrap_or_default(),
+            }),
+            ZkReply::NotWhitelisted =>
+                Err(format!("{} isn't in 4lw.commands.whitelist, set zookeeper_admin to send it to the AdminServer instead", command)),
+        }
+    }
+
+    /// Response as shown in the console, with the whole JSON of the AdminServer
+    pub fn output(&self, command: &str) -> Result<String, String> {
+        match self {
+            ZkReply::Admin(json) => Ok(format!(
+                "Response of the AdminServer to {}:\n{}",
+                command,
+                serde_json::to_string_pretty(json).unwrap_or_default(),
+            )),
+            _ => self.text(command),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ZkNodeStatus {
+    pub address: String,
+    pub state: ZkNodeState,
+    /// Role of the node from the `srvr` response, e.g. leader, follower or standalone
+    pub mode: Option<String>,
+    /// Id of the node in the ensemble, the `serverId` of its `conf` on Zookeeper 3.5+
+    pub server_id: Option<String>,
+    /// Last transaction the node processed, from the `srvr` response
+    pub zxid: Option<u64>,
+    pub error: Option<String>,
+    /// Load of the node from `mntr`, if it's whitelisted or answered by the AdminServer
+    pub monitor: Option<ZkMonitor>,
+    /// Commands of the status the node refused for not being in `4lw.commands.whitelist`
+    pub not_whitelisted: Vec<String>,
+    /// Whether the refused commands were answered by the AdminServer instead
+    pub admin_fallback: bool,
+    /// Ensemble as the node sees it, from its `conf` on Zookeeper 3.5+
+    pub config: Option<ZkEnsembleConfig>,
+}
+
+impl ZkNodeStatus {
+    ///
+    /// Status of a node given its responses to `ruok` and `isro`.
+    ///
+    /// A node that doesn't answer `ruok` with `imok` isn't serving requests.
+    /// Commands that aren't in `4lw.commands.whitelist` get an empty response.
+    pub fn from_responses(address: &str, ruok: Result<String, String>, isro: Result<String, String>) -> ZkNodeStatus {
+        let status = |state, error| ZkNodeStatus {
+            address: address.to_owned(),
+            state,
+            mode: None,
+            server_id: None,
+            zxid: None,
+            error,
+            monitor: None,
+            not_whitelisted: vec![],
+            admin_fallback: false,
+            config: None,
+        };
+        match ruok.as_deref().map(str::trim) {
+            Err(e) => status(ZkNodeState::NotResponding, Some(e.to_owned())),
+            Ok("imok") => match isro.as_deref().map(str::trim) {
+                Ok("ro") => status(ZkNodeState::ReadOnly, None),
+                Ok("rw") => status(ZkNodeState::Ok, None),
+                Ok(r) => status(ZkNodeState::Ok, Some(ZkNodeStatus::unexpected("isro", r))),
+                Err(e) => status(ZkNodeState::Ok, Some(format!("isro failed: {}", e))),
+            },
+            Ok(r) => status(ZkNodeState::NotResponding, Some(ZkNodeStatus::unexpected("ruok", r))),
+        }
+    }
+
+    /// Mode of the node from a `srvr` response, which has a `Mode: <mode>` line
+    pub fn parse_mode(srvr: &str) -> Option<String> {
+        srvr.lines()
+            .find(|l| l.starts_with("Mode:"))
+            .map(|l| l["Mode:".len()..].trim().to_owned())
+            .filter(|m| !m.is_empty())
+    }
+
+    /// Last processed zxid from a `srvr` response, which has a `Zxid: 0x<hex>` line
+    pub fn parse_zxid(srvr: &str) -> Option<u64> {
+        srvr.lines()
+            .find_map(|l| l.strip_prefix("Zxid:"))
+            .and_then(|z| u64::from_str_radix(z.trim().trim_start_matches("0x"), 16).ok())
+    }
+
+    /// Id of the node from a `conf` response, which has a `serverId=<id>` line
+    pub fn parse_server_id(conf: &str) -> Option<String> {
+        conf.lines()
+            .find_map(|l| l.trim().strip_prefix("serverId="))
+            .map(|id| id.trim().to_owned())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Whether the node is an observer, which serves clients without voting, so it doesn't count towards the quorum
+    pub fn is_observer(&self) -> bool {
+        self.mode.as_deref() == Some("observer")
+    }
+
+    /// Explains which commands the node refused, e.g. `isro, srvr not whitelisted, answered by the AdminServer`
+    pub fn whitelist_notice(&self) -> Option<String> {
+        if self.not_whitelisted.is_empty() {
+            return None;
+        }
+        let fallback = if self.admin_fallback { "answered by the AdminServer" } else { "set zookeeper_admin to use the AdminServer" };
+        Some(format!("{} not whitelisted, {}", self.not_whitelisted.join(", "), fallback))
+    }
+
+    fn unexpected(command: &str, response: &str) -> String {
+        if response.is_empty() {
+            format!("no response to {}, is it in 4lw.commands.whitelist?", command)
+        } else {
+            format!("unexpected response to {}: {}", command, response)
+        }
+    }
+}
+
+/// Member of the ensemble as listed in the `conf` of a node
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ZkMember {
+    /// Id of the server, from `server.<id>`
+    pub id: String,
+    /// Address clients reach the member on, see `ensemble_members`
+    pub client: String,
+    /// `participant`, voting in the quorum, or `observer`
+    pub role: String,
+}
+
+/// Dynamic configuration of the ensemble as a node sees it, which a reconfig that didn't reach every node leaves different
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ZkEnsembleConfig {
+    /// `version` of the dynamic config, missing from a static one
+    pub version: Option<String>,
+    pub members: Vec<ZkMember>,
+}
+
+impl ZkEnsembleConfig {
+    ///
+    /// Configuration listed in a `conf` response of Zookeeper 3.5+, or nothing if it lists no members.
+    /// Members listening on a wildcard address are reached on the host of their quorum address, and the ones without
+    /// a client address, as in a static config, on the `clientPort` of the node that responded.
+    ///
+    /// eg. `server.2=zk2:2888:3888:participant;0.0.0.0:2181` is the participant 2 reached on `zk2:2181`
+    pub fn parse(conf: &str) -> Option<ZkEnsembleConfig> {
+        let client_port = conf.lines().find_map(|l| l.trim().strip_prefix("clientPort="));
+        let mut members = vec![];
+        for line in conf.lines().map(str::trim).filter(|l| l.starts_with("server.")) {
+            let mut server = line["server.".len()..].splitn(2, '=');
+            let (id, spec) = match (server.next(), server.next()) {
+                (Some(id), Some(spec)) => (id.trim(), spec.trim()),
+                _ => continue,
+            };
+            let mut parts = spec.splitn(2, ';');
+            let quorum: Vec<&str> = parts.next().unwrap_or("").split(':').collect();
+            let (host, port) = match parts.next().map(str::trim) {
+                Some(client) => match client.rfind(':') {
+                    Some(i) => (&client[..i], &client[i + 1..]),
+                    None => ("", client),
+                },
+                None => match client_port {
+                    Some(p) => ("", p),
+                    None => continue,
+                },
+            };
+            let host = if ["", "0.0.0.0", "[::]", "::"].contains(&host) { quorum[0] } else { host };
+            if host.is_empty() {
+                continue;
+            }
+            members.push(ZkMember {
+                id: id.to_owned(),
+                client: format!("{}:{}", host, port),
+                role: quorum.get(3).map_or("participant", |r| r.trim()).to_owned(),
+            });
+        }
+        let version = conf.lines().find_map(|l| l.trim().strip_prefix("version=")).map(|v| v.to_owned());
+        if members.is_empty() { None } else { Some(ZkEnsembleConfig { version, members }) }
+    }
+
+    /// e.g. `version 100000000: 1 zk1:2181 participant, 2 zk2:2181 participant, 3 zk3:2181 observer`
+    pub fn label(&self) -> String {
+        let members: Vec<String> = self.members.iter().map(|m| format!("{} {} {}", m.id, m.client, m.role)).collect();
+        match &self.version {
+            Some(v) => format!("version {}: {}", v, members.join(", ")),
+            None => format!("static: {}", members.join(", ")),
+        }
+    }
+}
+
+/// Client addresses of the members of the ensemble listed in a `conf` response of Zookeeper 3.5+, in their order
+pub fn ensemble_members(conf: &str) -> Vec<String> {
+    let mut members: Vec<String> = vec![];
+    for member in ZkEnsembleConfig::parse(conf).map_or(vec![], |c| c.members) {
+        if !members.contains(&member.client) {
+            members.push(member.client);
+        }
+    }
+    members
+}
+
+///
+/// Nodes whose view of the ensemble differs from the one most nodes share, e.g. a node a reconfig didn't reach.
+/// Nodes that didn't tell their `conf` are left out, and there's no drift while the views are tied.
+pub fn config_drift(nodes: &[ZkNodeStatus]) -> Vec<&str> {
+    let configs: Vec<(&str, &ZkEnsembleConfig)> = nodes.iter()
+        .filter_map(|n| n.config.as_ref().map(|c| (n.address.as_str(), c)))
+        .collect();
+    let shared_by = |config: &ZkEnsembleConfig| configs.iter().filter(|(_, c)| *c == config).count();
+    let most_shared = configs.iter().map(|(_, c)| shared_by(c)).max().unwrap_or(0);
+    let majority: Vec<&ZkEnsembleConfig> = configs.iter().map(|(_, c)| *c).filter(|c| shared_by(c) == most_shared).collect();
+    let reference = match majority.first() {
+        Some(first) if majority.iter().all(|c| c == first) => *first,
+        _ => return vec![],
+    };
+    configs.iter().filter(|(_, c)| *c != reference).map(|(address, _)| *address).collect()
+}
+
+///
+/// Load of a node, from the `mntr` four letter word or the `monitor` command of the AdminServer.
+///
+/// eg. `mntr` responds with tab separated lines:
+///   ```text
+///   zk_avg_latency	0.4
+///   zk_outstanding_requests	0
+///   zk_server_state	follower
+///   ```
+/// and the AdminServer with the same keys without the `zk_` prefix in a JSON object.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ZkMonitor {
+    pub avg_latency: f64,
+    pub outstanding_requests: u64,
+    pub alive_connections: u64,
+    pub znode_count: u64,
+    pub watch_count: u64,
+}
+
+impl ZkMonitor {
+    pub fn from_reply(reply: &ZkReply) -> Option<ZkMonitor> {
+        match reply {
+            ZkReply::Text(t) => ZkMonitor::parse(t),
+            ZkReply::Admin(json) => ZkMonitor::from_json(json),
+            ZkReply::NotWhitelisted => None,
+        }
+    }
+
+    fn parse(mntr: &str) -> Option<ZkMonitor> {
+        let values: HashMap<&str, &str> = mntr.lines()
+            .filter_map(|l| {
+                let mut kv = l.splitn(2, '\t');
+                Some((kv.next()?.trim().trim_start_matches("zk_"), kv.next()?.trim()))
+            })
+            .collect();
+        ZkMonitor::from_values(|key| values.get(key).and_then(|v| v.parse().ok()))
+    }
+
+    fn from_json(json: &serde_json::Value) -> Option<ZkMonitor> {
+        // numbers may be serialized as strings, e.g. the average latency of Zookeeper 3.6
+        ZkMonitor::from_values(|key| match &json[key] {
+            serde_json::Value::String(s) => s.parse().ok(),
+            v => v.as_f64(),
+        })
+    }
+
+    fn from_values<F: Fn(&str) -> Option<f64>>(value: F) -> Option<ZkMonitor> {
+        Some(ZkMonitor {
+            avg_latency: value("avg_latency")?,
+            outstanding_requests: value("outstanding_requests")? as u64,
+            alive_connections: value("num_alive_connections")? as u64,
+            znode_count: value("znode_count")? as u64,
+            watch_count: value("watch_count")? as u64,
+        })
+    }
+}
+
+/// Four letter word command typed in the Zookeeper console, e.g. `stat`, `dump` or `envi`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ZkCommand([u8; 4]);
+
+impl ZkCommand {
+    pub fn parse(command: &str) -> Result<ZkCommand, String> {
+        let command = command.trim();
+        match command.as_bytes() {
+            [a, b, c, d] if command.bytes().all(|b| b.is_ascii_lowercase()) => Ok(ZkCommand([*a, *b, *c, *d])),
+            _ => Err(format!("{} isn't a four letter word command", command)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only lowercase ASCII letters get parsed
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::json;
+
+use crate::zookeeper::model::{ZkNodeState, ZkNodeStatus};
+
+/// Format the `zk-status` command prints the status of the ensemble in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ZkOutput {
+    /// Human readable table, with the state of each node colored unless colors are disabled
+    Table,
+    /// JSON array of the nodes, for scripts
+    Json,
+    /// Prometheus text exposition format, for scrapers such as the node exporter's textfile collector
+    Prometheus,
+}
+
+impl fmt::Display for ZkOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZkOutput::Table => write!(f, "table"),
+            ZkOutput::Json => write!(f, "json"),
+            ZkOutput::Prometheus => write!(f, "prometheus"),
+        }
+    }
+}
+
+impl FromStr for ZkOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ZkOutput, String> {
+        match s.to_lowercase().as_str() {
+            "table" => Ok(ZkOutput::Table),
+            "json" => Ok(ZkOutput::Json),
+            "prometheus" => Ok(ZkOutput::Prometheus),
+            _ => Err(format!("Unknown output '{}', expected json, table or prometheus", s)),
+        }
+    }
+}
+
+/// Status of the nodes in the given format, `color` telling whether the table may use ANSI colors
+pub fn render(nodes: &[ZkNodeStatus], output: ZkOutput, color: bool) -> String {
+    match output {
+        ZkOutput::Table => table(nodes, color),
+        ZkOutput::Json => json(nodes),
+        ZkOutput::Prometheus => prometheus(nodes),
+    }
+}
+
+///
+/// Whether the output may be colored: not with `--no-color`, nor when `NO_COLOR` is set to anything but an empty string
+/// (see https://no-color.org)
+pub fn use_color(no_color_flag: bool, no_color_env: Option<&str>) -> bool {
+    !no_color_flag && no_color_env.map_or(true, str::is_empty)
+}
+
+/// Zxids as Zookeeper prints them, the epoch in the high 32 bits and the counter in the low ones
+fn zxid(node: &ZkNodeStatus) -> Option<String> {
+    node.zxid.map(|z| format!("0x{:x}", z))
+}
+
+///
+/// eg.
+///   ```text
+///   NODE      ID  STATE           MODE      ZXID         LATENCY
+///   zk1:2181  1   imok            leader    0x100000002  0.4ms
+///   zk2:2181  -   not responding  -         -            -        Connection refused
+///   ```
+fn table(nodes: &[ZkNodeStatus], color: bool) -> String {
+    let rows: Vec<(ZkNodeState, [String; 6], Option<&str>)> = nodes.iter()
+        .map(|n| (n.state, [
+            n.address.to_owned(),
+            n.server_id.clone().unwrap_or_else(|| "-".to_owned()),
+            n.state.to_string(),
+            n.mode.clone().unwrap_or_else(|| "-".to_owned()),
+            zxid(n).unwrap_or_else(|| "-".to_owned()),
+            n.monitor.as_ref().map_or("-".to_owned(), |m| format!("{}ms", m.avg_latency)),
+        ], n.error.as_deref()))
+        .collect();
+    let header = ["NODE", "ID", "STATE", "MODE", "ZXID", "LATENCY"];
+    let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+    for (_, cells, _) in &rows {
+        for (w, c) in widths.iter_mut().zip(cells.iter()) {
+            *w = (*w).max(c.chars().count());
+        }
+    }
+    let pad = |cell: &str, i: usize| format!("{}{}", cell, " ".repeat(widths[i] - cell.chars().count()));
+    let mut lines = vec![header.iter().enumerate().map(|(i, h)| pad(h, i)).collect::<Vec<_>>().join("  ").trim_end().to_owned()];
+    for (state, cells, error) in rows {
+        let mut line: Vec<String> = cells.iter().enumerate().map(|(i, c)| pad(c, i)).collect();
+        if color {
+            let code = match state {
+                ZkNodeState::Ok => "32",
+                ZkNodeState::ReadOnly => "33",
+                ZkNodeState::NotResponding => "31",
+            };
+            line[2] = format!("\x1b[{}m{}\x1b[0m", code, line[2]);
+        }
+        if let Some(e) = error {
+            line.push(e.to_owned());
+        }
+        lines.push(line.join("  ").trim_end().to_owned());
+    }
+    lines.join("\n")
+}
+
+/// One object per node, `latency_ms` being the average latency from `mntr`
+fn json(nodes: &[ZkNodeStatus]) -> String {
+    let nodes: Vec<serde_json::Value> = nodes.iter()
+        .map(|n| json!({
+            "node": n.address,
+            "id": n.server_id,
+            "reachable": n.state != ZkNodeState::NotResponding,
+            "state": n.state,
+            "mode": n.mode,
+            "zxid": zxid(n),
+            "latency_ms": n.monitor.as_ref().map(|m| m.avg_latency),
+            "error": n.error,
+        }))
+        .collect();
+    serde_json::to_string_pretty(&nodes).unwrap_or_default()
+}
+
+fn prometheus(nodes: &[ZkNodeStatus]) -> String {
+    let labels = |n: &ZkNodeStatus| {
+        let mut labels = vec![format!("node=\"{}\"", escape(&n.address))];
+        if let Some(id) = &n.server_id {
+            labels.push(format!("id=\"{}\"", escape(id)));
+        }
+        labels.join(",")
+    };
+    let mut lines = vec![];
+    let mut metric = |name: &str, help: &str, values: Vec<(String, f64)>| {
+        if values.is_empty() {
+            return;
+        }
+        lines.push(format!("# HELP {} {}", name, help));
+        lines.push(format!("# TYPE {} gauge", name));
+        lines.extend(values.into_iter().map(|(labels, v)| format!("{}{{{}}} {}", name, labels, v)));
+    };
+    metric("zookeeper_up", "Whether the node answers ruok with imok",
+        nodes.iter().map(|n| (labels(n), if n.state == ZkNodeState::NotResponding { 0.0 } else { 1.0 })).collect());
+    metric("zookeeper_read_only", "Whether the node is partitioned from the quorum and only serves reads",
+        nodes.iter().map(|n| (labels(n), if n.state == ZkNodeState::ReadOnly { 1.0 } else { 0.0 })).collect());
+    metric("zookeeper_mode", "Role of the node from srvr, e.g. leader, follower or standalone",
+        nodes.iter().filter_map(|n| n.mode.as_ref().map(|m| (format!("{},mode=\"{}\"", labels(n), escape(m)), 1.0))).collect());
+    metric("zookeeper_zxid", "Last transaction the node processed",
+        nodes.iter().filter_map(|n| n.zxid.map(|z| (labels(n), z as f64))).collect());
+    metric("zookeeper_avg_latency_ms", "Average latency of the requests served by the node, from mntr",
+        nodes.iter().filter_map(|n| n.monitor.as_ref().map(|m| (labels(n), m.avg_latency))).collect());
+    lines.join("\n")
+}
+
+/// Label values escape backslashes, double quotes and line feeds
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,363 @@
+#[test]
+fn classifies_node_states() {
+    use crate::zookeeper::model::{ZkNodeState, ZkNodeStatus};
+
+    let ok = |s: &str| Ok(s.to_owned());
+    let status = |ruok, isro| ZkNodeStatus::from_responses("zk:2181", ruok, isro);
+
+    assert_eq!(status(ok("imok"), ok("rw")).state, ZkNodeState::Ok);
+    assert_eq!(status(ok("imok"), ok("ro\n")).state, ZkNodeState::ReadOnly);
+    assert_eq!(status(Err("Connection refused".to_owned()), ok("")), ZkNodeStatus {
+        address: "zk:2181".to_owned(),
+        state: ZkNodeState::NotResponding,
+        mode: None,
+        server_id: None,
+        zxid: None,
+        error: Some("Connection refused".to_owned()),
+        monitor: None,
+        not_whitelisted: vec![],
+        admin_fallback: false,
+        config: None,
+    });
+
+    let not_whitelisted = status(ok(""), ok(""));
+    assert_eq!(not_whitelisted.state, ZkNodeState::NotResponding);
+    assert_eq!(not_whitelisted.error, Some("no response to ruok, is it in 4lw.commands.whitelist?".to_owned()));
+
+    let isro_not_whitelisted = status(ok("imok"), ok(""));
+    assert_eq!(isro_not_whitelisted.state, ZkNodeState::Ok);
+    assert!(isro_not_whitelisted.error.is_some());
+}
+
+#[test]
+fn parses_mode_from_srvr_response() {
+    use crate::zookeeper::model::ZkNodeStatus;
+
+    let srvr = "Zookeeper version: 3.6.2--803c7f1a12f85978cb049af5e4ef23bd8b688715, built on 09/04/2020 12:44 GMT\n\
+        Latency min/avg/max: 0/0.0/0\n\
+        Received: 12\n\
+        Mode: leader\n\
+        Node count: 5\n";
+
+    assert_eq!(ZkNodeStatus::parse_mode(srvr), Some("leader".to_owned()));
+    assert_eq!(ZkNodeStatus::parse_mode(""), None);
+}
+
+#[test]
+fn parses_zxid_and_server_id() {
+    use serde_json::json;
+
+    use crate::zookeeper::model::{ZkNodeStatus, ZkReply};
+
+    assert_eq!(ZkNodeStatus::parse_zxid("Mode: leader\nZxid: 0x100000002\nNode count: 5\n"), Some(0x1_0000_0002));
+    assert_eq!(ZkNodeStatus::parse_zxid("Mode: leader\n"), None);
+    let srvr = ZkReply::Admin(json!({ "server_stats": { "server_state": "leader", "last_processed_zxid": 4294967298u64 } }));
+    assert_eq!(srvr.text("srvr").ok().as_deref().and_then(ZkNodeStatus::parse_zxid), Some(0x1_0000_0002));
+
+    assert_eq!(ZkNodeStatus::parse_server_id("clientPort=2181\nserverId=3\nserver.3=zk3:2888:3888:participant\n"), Some("3".to_owned()));
+    assert_eq!(ZkNodeStatus::parse_server_id("clientPort=2181\n"), None);
+}
+
+#[test]
+fn falls_back_to_admin_server_for_commands_not_whitelisted() {
+    use serde_json::json;
+
+    use crate::zookeeper::model::{ZkNodeState, ZkNodeStatus, ZkReply};
+
+    assert!(ZkReply::is_not_whitelisted("srvr is not executed because it is not in the whitelist.\n"));
+    assert!(!ZkReply::is_not_whitelisted("imok"));
+
+    let srvr = ZkReply::Admin(json!({ "command": "server_stats", "server_stats": { "server_state": "follower" }, "error": null }));
+    assert_eq!(srvr.text("srvr"), Ok("Mode: follower".to_owned()));
+    assert!(srvr.output("srvr").unwrap().contains("\"server_state\": \"follower\""));
+    assert_eq!(ZkReply::Admin(json!({ "command": "is_read_only", "read_only": true, "error": null })).text("isro"), Ok("ro".to_owned()));
+    assert_eq!(ZkReply::Admin(json!({ "command": "ruok", "error": null })).text("ruok"), Ok("imok".to_owned()));
+    assert!(ZkReply::NotWhitelisted.text("stat").unwrap_err().contains("zookeeper_admin"));
+
+    let mut status = ZkNodeStatus::from_responses("zk:2181", Ok("imok".to_owned()), Ok("rw".to_owned()));
+    assert_eq!(status.whitelist_notice(), None);
+    status.not_whitelisted = vec!["isro".to_owned(), "srvr".to_owned()];
+    assert_eq!(status.whitelist_notice(), Some("isro, srvr not whitelisted, set zookeeper_admin to use the AdminServer".to_owned()));
+    status.admin_fallback = true;
+    assert_eq!(status.whitelist_notice(), Some("isro, srvr not whitelisted, answered by the AdminServer".to_owned()));
+    assert_eq!(status.state, ZkNodeState::Ok);
+}
+
+#[test]
+fn reads_load_from_mntr_or_admin_server() {
+    use serde_json::json;
+
+    use crate::zookeeper::model::{ZkMonitor, ZkReply};
+
+    let mntr = "zk_version\t3.6.2--803c7f1a12f85978cb049af5e4ef23bd8b688715, built on 09/04/2020 12:44 GMT\n\
+        zk_avg_latency\t0.4\n\
+        zk_outstanding_requests\t2\n\
+        zk_server_state\tfollower\n\
+        zk_num_alive_connections\t12\n\
+        zk_znode_count\t5\n\
+        zk_watch_count\t3\n";
+    let expected = ZkMonitor { avg_latency: 0.4, outstanding_requests: 2, alive_connections: 12, znode_count: 5, watch_count: 3 };
+
+    assert_eq!(ZkMonitor::from_reply(&ZkReply::Text(mntr.to_owned())), Some(expected.clone()));
+    assert_eq!(ZkMonitor::from_reply(&ZkReply::Admin(json!({
+        "command": "monitor",
+        "avg_latency": "0.4",
+        "outstanding_requests": 2,
+        "server_state": "follower",
+        "num_alive_connections": 12,
+        "znode_count": 5,
+        "watch_count": 3,
+        "error": null
+    }))), Some(expected));
+    assert_eq!(ZkMonitor::from_reply(&ZkReply::Text("".to_owned())), None);
+    assert_eq!(ZkMonitor::from_reply(&ZkReply::NotWhitelisted), None);
+}
+
+/// Connection to a node answering with the given bytes, whatever it's sent
+#[cfg(test)]
+struct ScriptedNode {
+    sent: Vec<u8>,
+    response: std::io::Cursor<Vec<u8>>,
+}
+
+#[cfg(test)]
+impl ScriptedNode {
+    fn new(response: &[u8]) -> ScriptedNode {
+        ScriptedNode { sent: vec![], response: std::io::Cursor::new(response.to_vec()) }
+    }
+}
+
+#[cfg(test)]
+impl std::io::Read for ScriptedNode {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.response.read(buf)
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for ScriptedNode {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sent.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn reads_responses_that_are_not_utf8() {
+    use crate::zookeeper::client::exchange;
+
+    let mut node = ScriptedNode::new(b"0x1000000000\n\t/app/\xff\xfe\n");
+    assert_eq!(exchange(&mut node, "wchc"), Ok("0x1000000000\n\t/app/\u{fffd}\u{fffd}\n".to_owned()));
+    assert_eq!(node.sent, b"wchc");
+}
+
+/// Frame of the client protocol, made of big-endian integers and length-prefixed buffers
+#[cfg(test)]
+fn frame(fields: &[&[u8]]) -> Vec<u8> {
+    let body: Vec<u8> = fields.concat();
+    [&(body.len() as i32).to_be_bytes()[..], &body].concat()
+}
+
+#[test]
+fn reads_znodes_over_the_client_protocol() {
+    use crate::zookeeper::znode::ZnodeSession;
+
+    let buffer = |b: &[u8]| [&(b.len() as i32).to_be_bytes()[..], b].concat();
+    let reply_header = |xid: i32, err: i32| [&xid.to_be_bytes()[..], &7i64.to_be_bytes(), &err.to_be_bytes()].concat();
+    let session = frame(&[&0i32.to_be_bytes(), &30000i32.to_be_bytes(), &0x1234i64.to_be_bytes(), &buffer(&[0; 16]), &[0]]);
+    let children = frame(&[&reply_header(1, 0), &2i32.to_be_bytes(), &buffer(b"1"), &buffer(b"2")]);
+    let data = frame(&[&reply_header(2, 0), &buffer(b"{}"), &[0; 68]]);
+    let no_node = frame(&[&reply_header(3, -101)]);
+    let mut node = ScriptedNode::new(&[session, children, data, no_node].concat());
+
+    let mut znodes = ZnodeSession::open(&mut node, 30000).unwrap();
+    assert_eq!(znodes.children("/brokers/ids"), Ok(vec!["1".to_owned(), "2".to_owned()]));
+    assert_eq!(znodes.data("/brokers/ids/1"), Ok(b"{}".to_vec()));
+    assert_eq!(znodes.data("/brokers/ids/3"), Err("/brokers/ids/3 doesn't exist".to_owned()));
+    assert!(znodes.data("/brokers/ids/4").is_err());
+
+    let connect = frame(&[&0i32.to_be_bytes(), &0i64.to_be_bytes(), &30000i32.to_be_bytes(), &0i64.to_be_bytes(), &buffer(&[0; 16]), &[1]]);
+    let get_children = frame(&[&1i32.to_be_bytes(), &8i32.to_be_bytes(), &buffer(b"/brokers/ids"), &[0]]);
+    assert!(node.sent.starts_with(&[connect, get_children].concat()));
+
+    let mut refused = ScriptedNode::new(&frame(&[&0i32.to_be_bytes(), &0i32.to_be_bytes(), &0i64.to_be_bytes(), &buffer(&[0; 16])]));
+    assert!(ZnodeSession::open(&mut refused, 30000).is_err());
+}
+
+#[test]
+fn parses_kafka_broker_registrations() {
+    use crate::zookeeper::model::{KafkaBroker, KafkaListener};
+
+    let registration = br#"{"listener_security_protocol_map":{"PLAINTEXT":"PLAINTEXT","SSL":"SSL"},"endpoints":["PLAINTEXT://kafka1:9092","SSL://kafka1:9093"],"jmx_port":9999,"host":"kafka1","timestamp":"1600000000000","port":9092,"version":4,"rack":"eu-west-1a"}"#;
+    let mut broker = KafkaBroker::parse("1", registration).unwrap();
+    assert_eq!(broker.rack, Some("eu-west-1a".to_owned()));
+    assert_eq!(broker.jmx_port, Some(9999));
+    assert_eq!(broker.listeners[1], KafkaListener { name: "SSL".to_owned(), address: "kafka1:9093".to_owned(), error: None });
+    assert!(broker.is_reachable());
+    broker.listeners[1].error = Some("Connection refused".to_owned());
+    assert!(!broker.is_reachable());
+    assert_eq!(broker.label(), "broker 1 rack eu-west-1a: PLAINTEXT kafka1:9092, SSL kafka1:9093 (Connection refused)");
+
+    // before Kafka 0.9, and without JMX
+    let legacy = KafkaBroker::parse("2", br#"{"jmx_port":-1,"host":"kafka2","port":9092,"version":1}"#).unwrap();
+    assert_eq!(legacy.label(), "broker 2: PLAINTEXT kafka2:9092");
+    assert_eq!(legacy.jmx_port, None);
+    assert_eq!(legacy.jmx_address(), None);
+    assert!(KafkaBroker::parse("3", b"not json").unwrap_err().contains("broker 3"));
+}
+
+#[test]
+fn flags_kafka_brokers_failing_jmx() {
+    use crate::jmx::model::KafkaBrokerMetrics;
+    use crate::zookeeper::model::KafkaBroker;
+
+    let registration = br#"{"endpoints":["PLAINTEXT://kafka1:9092"],"jmx_port":9999,"host":null,"version":4}"#;
+    let mut broker = KafkaBroker::parse("1", registration).unwrap();
+    assert_eq!(broker.jmx_address(), Some("kafka1:9999".to_owned()));
+    assert!(!broker.is_failing());
+
+    broker.jmx = Some(Ok(KafkaBrokerMetrics { under_replicated_partitions: 2, request_handler_idle: 0.853, isr_shrinks_per_sec: 0.5 }));
+    assert!(!broker.is_failing());
+    assert_eq!(broker.under_replicated_partitions(), 2);
+    assert_eq!(broker.label(), "broker 1: PLAINTEXT kafka1:9092, 2 under-replicated, 85% idle, 0.5 ISR shrinks/s");
+
+    broker.jmx = Some(Err("Connection refused".to_owned()));
+    assert!(broker.is_failing());
+    assert_eq!(broker.under_replicated_partitions(), 0);
+    assert_eq!(broker.label(), "broker 1: PLAINTEXT kafka1:9092, JMX failing: Connection refused");
+}
+
+#[test]
+fn parses_four_letter_words() {
+    use crate::zookeeper::model::ZkCommand;
+
+    assert_eq!(ZkCommand::parse("stat").map(|c| c.as_str().to_owned()), Ok("stat".to_owned()));
+    assert_eq!(ZkCommand::parse(" envi\n").map(|c| c.as_str().to_owned()), Ok("envi".to_owned()));
+    assert!(ZkCommand::parse("st").is_err());
+    assert!(ZkCommand::parse("STAT").is_err());
+    assert!(ZkCommand::parse("stats").is_err());
+}
+
+#[test]
+fn lists_ensemble_members_from_conf() {
+    use crate::zookeeper::model::ensemble_members;
+
+    let dynamic = "clientPort=2181\n\
+        secureClientPort=-1\n\
+        dataDir=/data/version-2\n\
+        serverId=1\n\
+        membership: \n\
+        server.1=zk1.internal:2888:3888:participant;0.0.0.0:2181\n\
+        server.2=zk2.internal:2888:3888:participant;10.0.0.12:2182\n\
+        server.3=zk3.internal:2888:3888:observer;2181\n\
+        version=100000000\n";
+    assert_eq!(ensemble_members(dynamic), vec!["zk1.internal:2181", "10.0.0.12:2182", "zk3.internal:2181"]);
+
+    let static_config = "clientPort=2181\nserver.1=zk1:2888:3888:participant\nserver.2=zk2:2888:3888:participant\n";
+    assert_eq!(ensemble_members(static_config), vec!["zk1:2181", "zk2:2181"]);
+
+    // a standalone node or Zookeeper 3.4, which doesn't list the members
+    assert!(ensemble_members("clientPort=2181\ndataDir=/data\n").is_empty());
+}
+
+#[test]
+fn flags_nodes_whose_ensemble_config_drifted() {
+    use crate::zookeeper::model::{config_drift, ZkEnsembleConfig, ZkMember, ZkNodeStatus};
+
+    let conf = |version: &str, observer: &str| format!(
+        "server.1=zk1:2888:3888:participant;2181\nserver.2=zk2:2888:3888:participant;2181\nserver.3=zk3:2888:3888:{};2181\nversion={}\n",
+        observer, version,
+    );
+    let config = ZkEnsembleConfig::parse(&conf("100000000", "observer")).unwrap();
+    assert_eq!(config.version, Some("100000000".to_owned()));
+    assert_eq!(config.members[2], ZkMember { id: "3".to_owned(), client: "zk3:2181".to_owned(), role: "observer".to_owned() });
+    assert_eq!(config.label(), "version 100000000: 1 zk1:2181 participant, 2 zk2:2181 participant, 3 zk3:2181 observer");
+    let static_config = ZkEnsembleConfig::parse("clientPort=2181\nserver.1=zk1:2888:3888\n").unwrap();
+    assert_eq!(static_config.label(), "static: 1 zk1:2181 participant");
+
+    let node = |address: &str, conf: Option<String>| ZkNodeStatus {
+        config: conf.as_deref().and_then(ZkEnsembleConfig::parse),
+        ..ZkNodeStatus::from_responses(address, Ok("imok".to_owned()), Ok("rw".to_owned()))
+    };
+    let mut nodes = vec![
+        node("zk1:2181", Some(conf("100000000", "observer"))),
+        node("zk2:2181", Some(conf("100000000", "observer"))),
+        node("zk3:2181", Some(conf("0", "participant"))),
+    ];
+    assert_eq!(config_drift(&nodes), vec!["zk3:2181"]);
+
+    // no view most nodes agree on
+    nodes[1] = node("zk2:2181", None);
+    assert!(config_drift(&nodes).is_empty());
+    nodes[2] = node("zk3:2181", Some(conf("100000000", "observer")));
+    assert!(config_drift(&nodes).is_empty());
+}
+
+#[cfg(test)]
+fn probed_ensemble() -> Vec<crate::zookeeper::model::ZkNodeStatus> {
+    use crate::zookeeper::model::{ZkMonitor, ZkNodeStatus};
+
+    let leader = ZkNodeStatus {
+        mode: Some("leader".to_owned()),
+        server_id: Some("1".to_owned()),
+        zxid: Some(0x1_0000_0002),
+        monitor: Some(ZkMonitor { avg_latency: 0.4, ..ZkMonitor::default() }),
+        ..ZkNodeStatus::from_responses("zk1:2181", Ok("imok".to_owned()), Ok("rw".to_owned()))
+    };
+    let down = ZkNodeStatus::from_responses("zk2:2181", Err("Connection refused".to_owned()), Ok("".to_owned()));
+    vec![leader, down]
+}
+
+#[test]
+fn prints_status_as_json() {
+    use serde_json::json;
+
+    use crate::zookeeper::report::{self, ZkOutput};
+
+    let printed: serde_json::Value = serde_json::from_str(&report::render(&probed_ensemble(), ZkOutput::Json, true)).unwrap();
+    assert_eq!(printed, json!([
+        { "node": "zk1:2181", "id": "1", "reachable": true, "state": "ok", "mode": "leader", "zxid": "0x100000002", "latency_ms": 0.4, "error": null },
+        { "node": "zk2:2181", "id": null, "reachable": false, "state": "not_responding", "mode": null, "zxid": null, "latency_ms": null, "error": "Connection refused" },
+    ]));
+}
+
+#[test]
+fn prints_status_for_prometheus() {
+    use crate::zookeeper::report::{self, ZkOutput};
+
+    let printed = report::render(&probed_ensemble(), ZkOutput::Prometheus, true);
+    assert!(printed.contains("# TYPE zookeeper_up gauge\nzookeeper_up{node=\"zk1:2181\",id=\"1\"} 1\nzookeeper_up{node=\"zk2:2181\"} 0\n"));
+    assert!(printed.contains("zookeeper_mode{node=\"zk1:2181\",id=\"1\",mode=\"leader\"} 1\n"));
+    assert!(printed.contains("zookeeper_zxid{node=\"zk1:2181\",id=\"1\"} 4294967298\n"));
+    assert!(printed.ends_with("zookeeper_avg_latency_ms{node=\"zk1:2181\",id=\"1\"} 0.4"));
+}
+
+#[test]
+fn prints_status_as_table() {
+    use crate::zookeeper::report::{self, ZkOutput};
+
+    assert_eq!("table".parse(), Ok(ZkOutput::Table));
+    assert!("yaml".parse::<ZkOutput>().is_err());
+    let printed = report::render(&probed_ensemble(), ZkOutput::Table, true);
+    let lines: Vec<&str> = printed.lines().collect();
+    assert_eq!(lines[0], "NODE      ID  STATE           MODE    ZXID         LATENCY");
+    assert_eq!(lines[1], "zk1:2181  1   \x1b[32mimok          \x1b[0m  leader  0x100000002  0.4ms");
+    assert_eq!(lines[2], "zk2:2181  -   \x1b[31mnot responding\x1b[0m  -       -            -        Connection refused");
+}
+
+#[test]
+fn prints_table_without_colors() {
+    use crate::zookeeper::report::{self, ZkOutput};
+
+    assert!(report::use_color(false, None));
+    assert!(report::use_color(false, Some("")));
+    assert!(!report::use_color(false, Some("1")));
+    assert!(!report::use_color(true, None));
+
+    let printed = report::render(&probed_ensemble(), ZkOutput::Table, false);
+    assert!(!printed.contains('\x1b'));
+    assert_eq!(printed.lines().nth(2), Some("zk2:2181  -   not responding  -       -            -        Connection refused"));
+}
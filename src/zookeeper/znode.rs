@@ -0,0 +1,141 @@
+use std::convert::TryInto;
+use std::io::{Read, Write};
+
+/// `getData` request, see `ZooDefs.OpCode`
+const GET_DATA: i32 = 4;
+/// `getChildren` request
+const GET_CHILDREN: i32 = 8;
+/// `KeeperException.Code.NONODE`
+const NO_NODE: i32 = -101;
+/// Largest response accepted, well over the 1MB `jute.maxbuffer` of the nodes
+const MAX_RESPONSE_LEN: usize = 16 * 1024 * 1024;
+
+///
+/// Read-only session of the Zookeeper client protocol, for the znodes four letter words can't read.
+///
+/// Requests and responses are framed by their length, and encoded as Zookeeper's jute does:
+/// big-endian integers, and strings and buffers prefixed by their length.
+/// The session isn't kept alive with pings, it's meant to be dropped once the znodes are read.
+pub struct ZnodeSession<S: Read + Write> {
+    stream: S,
+    xid: i32,
+}
+
+impl<S: Read + Write> ZnodeSession<S> {
+    /// Starts a new session, accepting read-only nodes, which are partitioned from the quorum but still serve reads
+    pub fn open(mut stream: S, timeout: u64) -> Result<ZnodeSession<S>, String> {
+        let mut request = vec![];
+        put_i32(&mut request, 0); // protocol version
+        put_i64(&mut request, 0); // last zxid seen
+        put_i32(&mut request, timeout.min(i32::MAX as u64) as i32);
+        put_i64(&mut request, 0); // session id
+        put_buffer(&mut request, &[0; 16]); // password
+        request.push(1); // read-only
+        send(&mut stream, &request)?;
+        let mut response = Reply::new(receive(&mut stream)?);
+        let (_, _, session_id) = (response.i32()?, response.i32()?, response.i64()?);
+        if session_id == 0 {
+            return Err("Zookeeper refused the session".to_owned());
+        }
+        Ok(ZnodeSession { stream, xid: 0 })
+    }
+
+    /// Names of the children of the znode
+    pub fn children(&mut self, path: &str) -> Result<Vec<String>, String> {
+        let mut reply = self.call(GET_CHILDREN, path)?;
+        let count = reply.i32()?;
+        (0..count.max(0)).map(|_| reply.buffer().map(|b| String::from_utf8_lossy(&b).into_owned())).collect()
+    }
+
+    /// Data of the znode
+    pub fn data(&mut self, path: &str) -> Result<Vec<u8>, String> {
+        self.call(GET_DATA, path)?.buffer()
+    }
+
+    fn call(&mut self, op: i32, path: &str) -> Result<Reply, String> {
+        self.xid += 1;
+        let mut request = vec![];
+        put_i32(&mut request, self.xid);
+        put_i32(&mut request, op);
+        put_buffer(&mut request, path.as_bytes());
+        request.push(0); // no watch
+        send(&mut self.stream, &request)?;
+        let mut reply = Reply::new(receive(&mut self.stream)?);
+        let (xid, _zxid, err) = (reply.i32()?, reply.i64()?, reply.i32()?);
+        if xid != self.xid {
+            return Err(format!("Zookeeper replied {} to request {}", xid, self.xid));
+        }
+        match err {
+            0 => Ok(reply),
+            NO_NODE => Err(format!("{} doesn't exist", path)),
+            e => Err(format!("Reading {} failed with error {}", path, e)),
+        }
+    }
+}
+
+fn put_i32(buf: &mut Vec<u8>, v: i32) {
+    buf.extend_from_slice(&v.to_be_bytes());
+}
+
+fn put_i64(buf: &mut Vec<u8>, v: i64) {
+    buf.extend_from_slice(&v.to_be_bytes());
+}
+
+fn put_buffer(buf: &mut Vec<u8>, bytes: &[u8]) {
+    put_i32(buf, bytes.len() as i32);
+    buf.extend_from_slice(bytes);
+}
+
+fn send<S: Write>(stream: &mut S, request: &[u8]) -> Result<(), String> {
+    let mut framed = Vec::with_capacity(request.len() + 4);
+    put_buffer(&mut framed, request);
+    stream.write_all(&framed).and_then(|_| stream.flush()).map_err(|e| e.to_string())
+}
+
+fn receive<S: Read>(stream: &mut S) -> Result<Vec<u8>, String> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len).map_err(|e| e.to_string())?;
+    let len = i32::from_be_bytes(len);
+    if len < 0 || len as usize > MAX_RESPONSE_LEN {
+        return Err(format!("Invalid length of a Zookeeper response: {}", len));
+    }
+    let mut response = vec![0; len as usize];
+    stream.read_exact(&mut response).map_err(|e| e.to_string())?;
+    Ok(response)
+}
+
+/// Response being decoded, the fields read in order
+struct Reply {
+    bytes: Vec<u8>,
+    at: usize,
+}
+
+impl Reply {
+    fn new(bytes: Vec<u8>) -> Reply {
+        Reply { bytes, at: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        let end = self.at.checked_add(len).filter(|end| *end <= self.bytes.len()).ok_or("Truncated Zookeeper response")?;
+        let taken = &self.bytes[self.at..end];
+        self.at = end;
+        Ok(taken)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.take(4).map(|b| i32::from_be_bytes(b.try_into().unwrap_or_default()))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        self.take(8).map(|b| i64::from_be_bytes(b.try_into().unwrap_or_default()))
+    }
+
+    /// Buffer or string, a null one being empty
+    fn buffer(&mut self) -> Result<Vec<u8>, String> {
+        let len = self.i32()?;
+        if len < 0 {
+            return Ok(vec![]);
+        }
+        self.take(len as usize).map(|b| b.to_vec())
+    }
+}